@@ -132,6 +132,7 @@ pub enum DepNode<D: Clone + Debug> {
     // which would yield an overly conservative dep-graph.
     TraitItems(D),
     ReprHints(D),
+    ConstEval(D),
     TraitSelect(D, Vec<D>),
 }
 
@@ -162,6 +163,7 @@ impl<D: Clone + Debug> DepNode<D> {
             ImplItems,
             TraitImpls,
             ReprHints,
+            ConstEval,
         }
     }
 
@@ -232,6 +234,7 @@ impl<D: Clone + Debug> DepNode<D> {
             TraitImpls(ref d) => op(d).map(TraitImpls),
             TraitItems(ref d) => op(d).map(TraitItems),
             ReprHints(ref d) => op(d).map(ReprHints),
+            ConstEval(ref d) => op(d).map(ConstEval),
             TraitSelect(ref d, ref type_ds) => {
                 let d = try_opt!(op(d));
                 let type_ds = try_opt!(type_ds.iter().map(|d| op(d)).collect());
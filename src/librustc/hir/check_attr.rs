@@ -11,7 +11,7 @@
 use session::Session;
 
 use syntax::ast;
-use syntax::attr::AttrMetaMethods;
+use syntax::attr::{self, AttrMetaMethods};
 use syntax::visit;
 use syntax::visit::Visitor;
 
@@ -85,11 +85,28 @@ impl<'a> CheckAttrVisitor<'a> {
         }
     }
 
+    /// Reports a malformed `#[unwind]`; the passes that look it up later
+    /// take it as well-formed.
+    fn check_unwind(&self, attr: &ast::Attribute) {
+        attr::find_unwind_attr(Some(self.sess.diagnostic()), &[attr.clone()]);
+    }
+
+    /// Checks the `#[unwind]` attributes of a trait, impl or foreign item,
+    /// the only attribute checked on those.
+    fn check_unwind_attrs(&self, attrs: &[ast::Attribute]) {
+        for attr in attrs {
+            if attr.check_name("unwind") {
+                self.check_unwind(attr);
+            }
+        }
+    }
+
     fn check_attribute(&self, attr: &ast::Attribute, target: Target) {
         let name: &str = &attr.name();
         match name {
             "inline" => self.check_inline(attr, target),
             "repr" => self.check_repr(attr, target),
+            "unwind" => self.check_unwind(attr),
             _ => (),
         }
     }
@@ -103,6 +120,21 @@ impl<'a> Visitor for CheckAttrVisitor<'a> {
         }
         visit::walk_item(self, item);
     }
+
+    fn visit_trait_item(&mut self, trait_item: &ast::TraitItem) {
+        self.check_unwind_attrs(&trait_item.attrs);
+        visit::walk_trait_item(self, trait_item);
+    }
+
+    fn visit_impl_item(&mut self, impl_item: &ast::ImplItem) {
+        self.check_unwind_attrs(&impl_item.attrs);
+        visit::walk_impl_item(self, impl_item);
+    }
+
+    fn visit_foreign_item(&mut self, foreign_item: &ast::ForeignItem) {
+        self.check_unwind_attrs(&foreign_item.attrs);
+        visit::walk_foreign_item(self, foreign_item);
+    }
 }
 
 pub fn check_crate(sess: &Session, krate: &ast::Crate) {
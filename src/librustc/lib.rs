@@ -105,12 +105,15 @@ pub mod middle {
 
 pub mod mir {
     mod cache;
+    pub mod coverage;
     pub mod repr;
     pub mod tcx;
     pub mod visit;
     pub mod transform;
     pub mod traversal;
     pub mod mir_map;
+    pub mod provenance;
+    pub mod stats;
 }
 
 pub mod session;
@@ -58,6 +58,12 @@ declare_lint! {
     "detect assignments that will never be read"
 }
 
+declare_lint! {
+    pub UNUSED_MUST_USE,
+    Warn,
+    "unused result of a type flagged as #[must_use]"
+}
+
 declare_lint! {
     pub DEAD_CODE,
     Warn,
@@ -218,6 +224,7 @@ impl LintPass for HardwiredLints {
             UNKNOWN_LINTS,
             UNUSED_VARIABLES,
             UNUSED_ASSIGNMENTS,
+            UNUSED_MUST_USE,
             DEAD_CODE,
             UNREACHABLE_CODE,
             WARNINGS,
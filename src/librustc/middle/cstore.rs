@@ -251,6 +251,8 @@ pub trait CrateStore<'tcx> {
     fn maybe_get_item_mir<'a>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>, def: DefId)
                               -> Option<Mir<'tcx>>;
     fn is_item_mir_available(&self, def: DefId) -> bool;
+    // the inline hint score of the MIR of an item, without decoding it
+    fn item_mir_inline_hint(&self, def: DefId) -> Option<u32>;
 
     // This is basically a 1-based range of ints, which is a little
     // silly - I may fix that.
@@ -459,6 +461,9 @@ impl<'tcx> CrateStore<'tcx> for DummyCrateStore {
     fn is_item_mir_available(&self, def: DefId) -> bool {
         bug!("is_item_mir_available")
     }
+    fn item_mir_inline_hint(&self, def: DefId) -> Option<u32> {
+        bug!("item_mir_inline_hint")
+    }
 
     // This is basically a 1-based range of ints, which is a little
     // silly - I may fix that.
@@ -0,0 +1,53 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The table mapping the coverage counters inserted for
+//! `-Z instrument-coverage` back to the source regions they count.
+
+use rustc_serialize::json::as_pretty_json;
+use std::io::{self, Write};
+
+/// The source region whose executions a counter counts.
+#[derive(RustcEncodable)]
+pub struct CoverageRegion {
+    pub counter: u32,
+    pub file_name: String,
+    /// 1-based.
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 1-based, character offset.
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+/// The counters of one function. `function_hash` is the hash passed to
+/// `llvm.instrprof.increment`, under which the counts of the function are
+/// found in the raw profile.
+#[derive(RustcEncodable)]
+pub struct FunctionCoverage {
+    pub name: String,
+    pub function_hash: u64,
+    pub regions: Vec<CoverageRegion>,
+}
+
+#[derive(RustcEncodable)]
+pub struct CoverageMap {
+    pub functions: Vec<FunctionCoverage>,
+}
+
+impl CoverageMap {
+    pub fn new() -> CoverageMap {
+        CoverageMap { functions: vec![] }
+    }
+
+    pub fn write(&self, w: &mut Write) -> io::Result<()> {
+        writeln!(w, "{}", as_pretty_json(self))
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The table recording, for `-Z mir-provenance`, which pass created each
+//! basic block of every body, and which block and source span it was
+//! created as, so that dumps of optimized MIR can be traced back to the
+//! MIR that was built.
+
+use mir::repr::{BasicBlock, BasicBlockData, Mir, Promoted, SourceInfo};
+use mir::transform::MirSource;
+use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax::ast::NodeId;
+use syntax_pos::Span;
+use util::nodemap::FnvHashMap;
+
+/// The pass name recorded for the blocks of a body as it was built.
+pub const BUILT: &'static str = "build";
+
+/// Where a basic block comes from.
+#[derive(Clone, Debug)]
+pub struct BlockProvenance {
+    /// The pass that created the block, or `BUILT`.
+    pub pass: String,
+    /// The index of the block when it was created.
+    pub block: BasicBlock,
+    /// The span of the first statement of the block when it was created,
+    /// or of its terminator if it had no statements.
+    pub span: Span,
+}
+
+struct BodyProvenance {
+    blocks: IndexVec<BasicBlock, BlockProvenance>,
+    /// What identifies each block, see `block_key`.
+    keys: IndexVec<BasicBlock, (bool, SourceInfo)>,
+}
+
+/// Passes don't report the blocks they add, move or merge, so after each
+/// pass a block is assumed to be the block that had the same key before
+/// it: whether it is a cleanup block, and the source info of its first
+/// statement or, if it has none, of its terminator. Merging a block into
+/// its predecessor, the only way blocks are usually combined, keeps the
+/// key of the predecessor.
+fn block_key(data: &BasicBlockData) -> (bool, SourceInfo) {
+    let source_info = match data.statements.first() {
+        Some(statement) => statement.source_info,
+        None => data.terminator().source_info,
+    };
+    (data.is_cleanup, source_info)
+}
+
+fn body_key(src: MirSource) -> (NodeId, Option<Promoted>) {
+    match src {
+        MirSource::Promoted(id, promoted) => (id, Some(promoted)),
+        _ => (src.item_id(), None),
+    }
+}
+
+pub struct MirProvenance {
+    bodies: FnvHashMap<(NodeId, Option<Promoted>), BodyProvenance>,
+}
+
+impl MirProvenance {
+    pub fn new() -> MirProvenance {
+        MirProvenance { bodies: FnvHashMap() }
+    }
+
+    /// Whether the provenance of the blocks of `src` has been recorded.
+    pub fn has_body(&self, src: MirSource) -> bool {
+        self.bodies.contains_key(&body_key(src))
+    }
+
+    /// Updates the provenance of the blocks of `src` after the pass `pass`
+    /// ran over it: the blocks that match no block of the body before it
+    /// are recorded as created by `pass`. For a body not seen before none
+    /// match, and `pass` should be `BUILT`.
+    pub fn record(&mut self, src: MirSource, mir: &Mir, pass: &str) {
+        let keys: IndexVec<_, _> = mir.basic_blocks().iter().map(block_key).collect();
+        let key = body_key(src);
+        let mut blocks = IndexVec::with_capacity(keys.len());
+        {
+            let before = self.bodies.get(&key);
+            let mut used = vec![false; before.map_or(0, |before| before.keys.len())];
+            for (bb, block_key) in keys.iter_enumerated() {
+                let found = before.and_then(|before| {
+                    // A block that kept its index is the most likely match.
+                    let same_index = bb.index() < before.keys.len() &&
+                                     !used[bb.index()] &&
+                                     before.keys[bb] == *block_key;
+                    if same_index {
+                        return Some(bb);
+                    }
+                    before.keys.iter_enumerated().find(|&(old_bb, old_key)| {
+                        !used[old_bb.index()] && old_key == block_key
+                    }).map(|(old_bb, _)| old_bb)
+                });
+                blocks.push(match found {
+                    Some(old_bb) => {
+                        used[old_bb.index()] = true;
+                        before.unwrap().blocks[old_bb].clone()
+                    }
+                    None => BlockProvenance {
+                        pass: pass.to_string(),
+                        block: bb,
+                        span: block_key.1.span,
+                    },
+                });
+            }
+        }
+        self.bodies.insert(key, BodyProvenance { blocks: blocks, keys: keys });
+    }
+
+    /// The provenance of the block `bb` of `src`, if it was recorded.
+    pub fn block(&self, src: MirSource, bb: BasicBlock) -> Option<&BlockProvenance> {
+        self.bodies.get(&body_key(src)).and_then(|body| {
+            if bb.index() < body.blocks.len() {
+                Some(&body.blocks[bb])
+            } else {
+                None
+            }
+        })
+    }
+}
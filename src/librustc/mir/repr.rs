@@ -91,7 +91,7 @@ pub struct Mir<'tcx> {
 
     /// Names and capture modes of all the closure upvars, assuming
     /// the first argument is either the closure or a reference to it.
-    pub upvar_decls: Vec<UpvarDecl>,
+    pub upvar_decls: IndexVec<Field, UpvarDecl>,
 
     /// A span representing this MIR, for error reporting
     pub span: Span,
@@ -111,7 +111,7 @@ impl<'tcx> Mir<'tcx> {
                var_decls: IndexVec<Var, VarDecl<'tcx>>,
                arg_decls: IndexVec<Arg, ArgDecl<'tcx>>,
                temp_decls: IndexVec<Temp, TempDecl<'tcx>>,
-               upvar_decls: Vec<UpvarDecl>,
+               upvar_decls: IndexVec<Field, UpvarDecl>,
                span: Span) -> Self
     {
         Mir {
@@ -294,10 +294,16 @@ pub struct VarDecl<'tcx> {
 }
 
 /// A "temp" is a temporary that we place on the stack. They are
-/// anonymous, always mutable, and have only a type.
+/// anonymous, always mutable, and have only a type and the span of the
+/// expression they were created for.
 #[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
 pub struct TempDecl<'tcx> {
     pub ty: Ty<'tcx>,
+
+    /// The expression whose value, or part of whose evaluation, is stored
+    /// in the temp, shown next to it in dumps and diagnostics. `DUMMY_SP`
+    /// for temps added by passes.
+    pub span: Span,
 }
 
 /// A "arg" is one of the function's formal arguments. These are
@@ -413,6 +419,11 @@ pub enum TerminatorKind<'tcx> {
     /// continue. Emitted by build::scope::diverge_cleanup.
     Resume,
 
+    /// Indicates that the landing pad is finished and the process should
+    /// be aborted instead of unwinding out of the function. Replaces
+    /// `Resume` in functions marked with `#[unwind(aborts)]`.
+    Abort,
+
     /// Indicates a normal return. The ReturnPointer lvalue should
     /// have been filled in by now. This should occur at most once.
     Return,
@@ -477,6 +488,7 @@ impl<'tcx> TerminatorKind<'tcx> {
             Switch { targets: ref b, .. } => b[..].into_cow(),
             SwitchInt { targets: ref b, .. } => b[..].into_cow(),
             Resume => (&[]).into_cow(),
+            Abort => (&[]).into_cow(),
             Return => (&[]).into_cow(),
             Unreachable => (&[]).into_cow(),
             Call { destination: Some((_, t)), cleanup: Some(c), .. } => vec![t, c].into_cow(),
@@ -507,6 +519,7 @@ impl<'tcx> TerminatorKind<'tcx> {
             Switch { targets: ref mut b, .. } => b.iter_mut().collect(),
             SwitchInt { targets: ref mut b, .. } => b.iter_mut().collect(),
             Resume => Vec::new(),
+            Abort => Vec::new(),
             Return => Vec::new(),
             Unreachable => Vec::new(),
             Call { destination: Some((_, ref mut t)), cleanup: Some(ref mut c), .. } => vec![t, c],
@@ -587,6 +600,7 @@ impl<'tcx> TerminatorKind<'tcx> {
             SwitchInt { discr: ref lv, .. } => write!(fmt, "switchInt({:?})", lv),
             Return => write!(fmt, "return"),
             Resume => write!(fmt, "resume"),
+            Abort => write!(fmt, "abort"),
             Unreachable => write!(fmt, "unreachable"),
             Drop { ref location, .. } => write!(fmt, "drop({:?})", location),
             DropAndReplace { ref location, ref value, .. } =>
@@ -617,8 +631,17 @@ impl<'tcx> TerminatorKind<'tcx> {
                                "index out of bounds: the len is {} but the index is {}",
                                len, index)?;
                     }
-                    AssertMessage::Math(ref err) => {
+                    AssertMessage::Math { ref err, ref operands } => {
                         write!(fmt, "{:?}", err.description())?;
+                        for operand in operands {
+                            write!(fmt, ", {:?}", operand)?;
+                        }
+                    }
+                    AssertMessage::Misaligned => {
+                        write!(fmt, "{:?}", "misaligned pointer dereference")?;
+                    }
+                    AssertMessage::NullPointer => {
+                        write!(fmt, "{:?}", "null pointer dereference")?;
                     }
                 }
 
@@ -631,7 +654,7 @@ impl<'tcx> TerminatorKind<'tcx> {
     pub fn fmt_successor_labels(&self) -> Vec<Cow<'static, str>> {
         use self::TerminatorKind::*;
         match *self {
-            Return | Resume | Unreachable => vec![],
+            Return | Resume | Abort | Unreachable => vec![],
             Goto { .. } => vec!["".into()],
             If { .. } => vec!["true".into(), "false".into()],
             Switch { ref adt_def, .. } => {
@@ -674,7 +697,17 @@ pub enum AssertMessage<'tcx> {
         len: Operand<'tcx>,
         index: Operand<'tcx>
     },
-    Math(ConstMathErr)
+    /// An arithmetic operation that overflowed or divided by zero, with
+    /// its operands: the left one first, or only one for negation. They
+    /// are only evaluated if the assertion fails, to build the message.
+    Math { err: ConstMathErr, operands: Vec<Operand<'tcx>> },
+    /// A raw pointer that is dereferenced is not aligned for its
+    /// pointee type, checked with `-C debug-assertions`, or one that a
+    /// `Box` is made from, checked with `-Z check-box-from-raw`.
+    Misaligned,
+    /// A raw pointer that a `Box` is made from is null, checked with
+    /// `-Z check-box-from-raw`.
+    NullPointer
 }
 
 ///////////////////////////////////////////////////////////////////////////
@@ -690,6 +723,140 @@ pub struct Statement<'tcx> {
 pub enum StatementKind<'tcx> {
     Assign(Lvalue<'tcx>, Rvalue<'tcx>),
     SetDiscriminant{ lvalue: Lvalue<'tcx>, variant_index: usize },
+
+    /// Increment a coverage counter of the enclosing function, inserted by
+    /// `-Z instrument-coverage`. `function_hash` and `num_counters` are the
+    /// same for every counter of a function.
+    Coverage { function_hash: u64, num_counters: u32, index: u32 },
+
+    /// Check the access to `lvalue`, which goes through a raw pointer,
+    /// with the sanitizer runtime; inserted by `-Z sanitizer`.
+    SanitizerCheck { kind: SanitizerCheckKind, lvalue: Lvalue<'tcx> },
+
+    /// Record that the value just written to `lvalue` has type `ty`, a
+    /// subtype of the type of `lvalue`, for analyses that need to relate
+    /// the regions of both. Does nothing at runtime.
+    Subtype { lvalue: Lvalue<'tcx>, ty: Ty<'tcx> },
+
+    /// Mark the reference in `lvalue` as newly created, or as passed to
+    /// the function, for checkers of an aliasing model; inserted by
+    /// `-Z mir-retag`. Does nothing at runtime.
+    Retag { kind: RetagKind, lvalue: Lvalue<'tcx> },
+
+    /// An atomic access to the integer `ptr` points to, with the memory
+    /// ordering `ordering`, made from a call of one of the `atomic_*`
+    /// intrinsics. Loads and read-modify-writes write the value read to
+    /// `dest`; stores and read-modify-writes use `value`.
+    Atomic {
+        op: AtomicOp,
+        ordering: AtomicOrdering,
+        dest: Option<Lvalue<'tcx>>,
+        ptr: Operand<'tcx>,
+        value: Option<Operand<'tcx>>,
+    },
+
+    /// Call the function named by `-Z mir-instrument-calls` with
+    /// `function_id`, which identifies the enclosing function, and `kind`;
+    /// inserted by that option.
+    Probe { kind: ProbeKind, function_id: u64 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum AtomicOp {
+    Load,
+    Store,
+    Xchg,
+    Add,
+    Sub,
+    And,
+    Nand,
+    Or,
+    Xor,
+    Max,
+    Min,
+    UMax,
+    UMin,
+}
+
+impl AtomicOp {
+    /// The operation as named in the `atomic_*` intrinsics.
+    pub fn intrinsic_name(self) -> &'static str {
+        match self {
+            AtomicOp::Load => "load",
+            AtomicOp::Store => "store",
+            AtomicOp::Xchg => "xchg",
+            AtomicOp::Add => "xadd",
+            AtomicOp::Sub => "xsub",
+            AtomicOp::And => "and",
+            AtomicOp::Nand => "nand",
+            AtomicOp::Or => "or",
+            AtomicOp::Xor => "xor",
+            AtomicOp::Max => "max",
+            AtomicOp::Min => "min",
+            AtomicOp::UMax => "umax",
+            AtomicOp::UMin => "umin",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum AtomicOrdering {
+    Relaxed,
+    Acquire,
+    Release,
+    AcqRel,
+    SeqCst,
+}
+
+impl AtomicOrdering {
+    /// The ordering as named in the `atomic_*` intrinsics.
+    pub fn intrinsic_name(self) -> &'static str {
+        match self {
+            AtomicOrdering::Relaxed => "relaxed",
+            AtomicOrdering::Acquire => "acq",
+            AtomicOrdering::Release => "rel",
+            AtomicOrdering::AcqRel => "acqrel",
+            AtomicOrdering::SeqCst => "seqcst",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum ProbeKind {
+    /// On entry to the function.
+    Entry,
+    /// Right before the function returns.
+    Return,
+    /// Right before the function resumes unwinding from its cleanup code.
+    Unwind,
+}
+
+impl ProbeKind {
+    /// The event number the probe function is passed.
+    pub fn event(self) -> u32 {
+        match self {
+            ProbeKind::Entry => 0,
+            ProbeKind::Return => 1,
+            ProbeKind::Unwind => 2,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum SanitizerCheckKind {
+    Load,
+    Store,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum RetagKind {
+    /// A reference argument, on entry to the function.
+    FnEntry,
+    /// A reference just created by borrowing an lvalue.
+    Ref,
+    /// A reference just created by borrowing through another reference,
+    /// e.g. `&mut *r`.
+    Reborrow,
 }
 
 impl<'tcx> Debug for Statement<'tcx> {
@@ -700,10 +867,61 @@ impl<'tcx> Debug for Statement<'tcx> {
             SetDiscriminant{lvalue: ref lv, variant_index: index} => {
                 write!(fmt, "discriminant({:?}) = {:?}", lv, index)
             }
+            Coverage { num_counters, index, .. } => {
+                write!(fmt, "coverage_counter({} of {})", index, num_counters)
+            }
+            SanitizerCheck { kind: SanitizerCheckKind::Load, ref lvalue } => {
+                write!(fmt, "sanitizer_check_load({:?})", lvalue)
+            }
+            SanitizerCheck { kind: SanitizerCheckKind::Store, ref lvalue } => {
+                write!(fmt, "sanitizer_check_store({:?})", lvalue)
+            }
+            Subtype { ref lvalue, ty } => write!(fmt, "subtype({:?}: {:?})", lvalue, ty),
+            Retag { kind: RetagKind::FnEntry, ref lvalue } => {
+                write!(fmt, "retag_fn_entry({:?})", lvalue)
+            }
+            Retag { kind: RetagKind::Ref, ref lvalue } => write!(fmt, "retag({:?})", lvalue),
+            Retag { kind: RetagKind::Reborrow, ref lvalue } => {
+                write!(fmt, "retag_reborrow({:?})", lvalue)
+            }
+            Atomic { op, ordering, ref dest, ref ptr, ref value } => {
+                if let Some(ref dest) = *dest {
+                    write!(fmt, "{:?} = ", dest)?;
+                }
+                write!(fmt, "atomic_{}_{}({:?}", op.intrinsic_name(), ordering.intrinsic_name(),
+                       ptr)?;
+                if let Some(ref value) = *value {
+                    write!(fmt, ", {:?}", value)?;
+                }
+                write!(fmt, ")")
+            }
+            Probe { kind, function_id } => {
+                let name = match kind {
+                    ProbeKind::Entry => "probe_entry",
+                    ProbeKind::Return => "probe_return",
+                    ProbeKind::Unwind => "probe_unwind",
+                };
+                write!(fmt, "{}({:#x})", name, function_id)
+            }
         }
     }
 }
 
+/// A point in the MIR: the statement at `statement_index` within `block`,
+/// or the block's terminator if `statement_index` equals the number of
+/// statements.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    pub block: BasicBlock,
+    pub statement_index: usize,
+}
+
+impl fmt::Debug for Location {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:?}[{}]", self.block, self.statement_index)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////
 // Lvalues
 
@@ -807,6 +1025,16 @@ impl<'tcx> Lvalue<'tcx> {
         self.elem(ProjectionElem::Index(index))
     }
 
+    /// Returns the variable, temporary, argument, static or return pointer
+    /// that this lvalue is a projection of, or the lvalue itself.
+    pub fn base(&self) -> &Lvalue<'tcx> {
+        let mut lvalue = self;
+        while let Lvalue::Projection(ref proj) = *lvalue {
+            lvalue = &proj.base;
+        }
+        lvalue
+    }
+
     pub fn elem(self, elem: LvalueElem<'tcx>) -> Lvalue<'tcx> {
         Lvalue::Projection(Box::new(LvalueProjection {
             base: self,
@@ -902,6 +1130,10 @@ pub enum Rvalue<'tcx> {
     /// &x or &mut x
     Ref(Region, BorrowKind, Lvalue<'tcx>),
 
+    /// The address of x as a *const or *mut pointer, e.g. for `&x as *const _`,
+    /// without creating a reference in between.
+    RawPtr(Mutability, Lvalue<'tcx>),
+
     /// length of a [X] or [X;n] value
     Len(Lvalue<'tcx>),
 
@@ -922,6 +1154,12 @@ pub enum Rvalue<'tcx> {
     /// away after type-checking and before lowering.
     Aggregate(AggregateKind<'tcx>, Vec<Operand<'tcx>>),
 
+    /// An operation on `#[repr(simd)]` vectors, lowered from the
+    /// `simd_extract`, `simd_insert` and `simd_shuffleN` platform
+    /// intrinsics so that passes can see through them. Vectors are
+    /// built with `Aggregate` like any other struct.
+    Simd(SimdOp<'tcx>, Vec<Operand<'tcx>>),
+
     InlineAsm {
         asm: InlineAsm,
         outputs: Vec<Lvalue<'tcx>>,
@@ -931,8 +1169,23 @@ pub enum Rvalue<'tcx> {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum CastKind {
+    /// Any other cast between numbers, C-like enums, `bool`, `char` and
+    /// pointers, e.g. widening or same-width integer casts, float casts
+    /// and casts from one pointer to another.
     Misc,
 
+    /// Convert a thin raw pointer or a fn pointer to an integer, which
+    /// exposes its address to code that reasons about the integer.
+    PointerExposeAddress,
+
+    /// Convert an integer to a thin raw pointer, whose address is the
+    /// integer and which may point to anything whose address was exposed.
+    PointerFromExposedAddress,
+
+    /// Convert an integer to a narrower integer, discarding its high bits.
+    /// Widths of `isize` and `usize` are those of the target.
+    IntTruncate,
+
     /// Convert unique, zero-sized type for a fn to fn()
     ReifyFnPointer,
 
@@ -955,6 +1208,19 @@ pub enum AggregateKind<'tcx> {
     Closure(DefId, ClosureSubsts<'tcx>),
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum SimdOp<'tcx> {
+    /// `vector[index]`, with operands `[vector, index]`
+    Extract,
+    /// `vector` with `vector[index]` replaced by `element`, with operands
+    /// `[vector, index, element]`
+    Insert,
+    /// A vector of the given type with the elements of the two input
+    /// vectors picked by the constant indices, with operands
+    /// `[first, second, indices]`
+    Shuffle(Ty<'tcx>),
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum BinOp {
     /// The `+` operator (addition)
@@ -1016,6 +1282,8 @@ impl<'tcx> Debug for Rvalue<'tcx> {
         match *self {
             Use(ref lvalue) => write!(fmt, "{:?}", lvalue),
             Repeat(ref a, ref b) => write!(fmt, "[{:?}; {:?}]", a, b),
+            RawPtr(Mutability::Not, ref lv) => write!(fmt, "&raw const {:?}", lv),
+            RawPtr(Mutability::Mut, ref lv) => write!(fmt, "&raw mut {:?}", lv),
             Len(ref a) => write!(fmt, "Len({:?})", a),
             Cast(ref kind, ref lv, ref ty) => write!(fmt, "{:?} as {:?} ({:?})", lv, ty, kind),
             BinaryOp(ref op, ref a, ref b) => write!(fmt, "{:?}({:?}, {:?})", op, a, b),
@@ -1024,6 +1292,21 @@ impl<'tcx> Debug for Rvalue<'tcx> {
             }
             UnaryOp(ref op, ref a) => write!(fmt, "{:?}({:?})", op, a),
             Box(ref t) => write!(fmt, "Box({:?})", t),
+            Simd(op, ref operands) => {
+                let name = match op {
+                    SimdOp::Extract => "simd_extract",
+                    SimdOp::Insert => "simd_insert",
+                    SimdOp::Shuffle(_) => "simd_shuffle",
+                };
+                write!(fmt, "{}(", name)?;
+                for (i, operand) in operands.iter().enumerate() {
+                    if i > 0 {
+                        write!(fmt, ", ")?;
+                    }
+                    write!(fmt, "{:?}", operand)?;
+                }
+                write!(fmt, ")")
+            }
             InlineAsm { ref asm, ref outputs, ref inputs } => {
                 write!(fmt, "asm!({:?} : {:?} : {:?})", asm, outputs, inputs)
             }
@@ -1174,7 +1457,7 @@ impl<'tcx> Debug for Literal<'tcx> {
 }
 
 /// Write a `ConstVal` in a way closer to the original source code than the `Debug` output.
-fn fmt_const_val<W: Write>(fmt: &mut W, const_val: &ConstVal) -> fmt::Result {
+pub fn fmt_const_val<W: Write>(fmt: &mut W, const_val: &ConstVal) -> fmt::Result {
     use middle::const_val::ConstVal::*;
     match *const_val {
         Float(f) => write!(fmt, "{:?}", f),
@@ -0,0 +1,270 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Statistics about the size of MIR before and after each pass, gathered
+//! with `-Z mir-stats`, and about how much of it is cleanup code, printed
+//! with `-Z mir-unwind-stats`, the inline hint score recorded for each body
+//! encoded into metadata, and the size budget of `-Z mir-size-limit`.
+
+use mir::mir_map::MirMap;
+use mir::repr::{Mir, TerminatorKind};
+use ty::TyCtxt;
+
+use std::u32;
+
+/// The size of one or more MIR bodies.
+#[derive(Copy, Clone, Default)]
+pub struct MirSize {
+    pub bodies: usize,
+    pub blocks: usize,
+    pub statements: usize,
+    pub terminators: usize,
+    pub locals: usize,
+}
+
+impl MirSize {
+    pub fn of(mir: &Mir) -> MirSize {
+        let blocks = mir.basic_blocks();
+        MirSize {
+            bodies: 1,
+            blocks: blocks.len(),
+            statements: blocks.iter().map(|data| data.statements.len()).sum(),
+            terminators: blocks.iter().filter(|data| data.terminator.is_some()).count(),
+            locals: mir.arg_decls.len() + mir.var_decls.len() + mir.temp_decls.len(),
+        }
+    }
+
+    fn add(&mut self, other: MirSize) {
+        self.bodies += other.bodies;
+        self.blocks += other.blocks;
+        self.statements += other.statements;
+        self.terminators += other.terminators;
+        self.locals += other.locals;
+    }
+}
+
+struct PassStats {
+    pass: String,
+    before: MirSize,
+    after: MirSize,
+}
+
+/// The total size of all bodies before and after every pass run, in the
+/// order the passes ran in.
+pub struct MirStats {
+    passes: Vec<PassStats>,
+}
+
+impl MirStats {
+    pub fn new() -> MirStats {
+        MirStats { passes: vec![] }
+    }
+
+    /// Records the size of one body before or after the pass `pass`. As a
+    /// pass is run over all bodies before the next pass starts, the sizes
+    /// are added to the totals of the last pass recorded if it has the same
+    /// name; otherwise a new row is started.
+    pub fn record(&mut self, pass: &str, is_after: bool, size: MirSize) {
+        let is_same_pass = self.passes.last().map_or(false, |last| last.pass == pass);
+        if !is_same_pass {
+            self.passes.push(PassStats {
+                pass: pass.to_string(),
+                before: MirSize::default(),
+                after: MirSize::default(),
+            });
+        }
+        let last = self.passes.last_mut().unwrap();
+        if is_after {
+            last.after.add(size);
+        } else {
+            last.before.add(size);
+        }
+    }
+
+    pub fn print(&self) {
+        fn change(before: usize, after: usize) -> String {
+            format!("{} -> {}", before, after)
+        }
+
+        println!("--- MIR stats ---");
+        println!("{:<30} {:>7} {:>17} {:>17} {:>17} {:>17}",
+                 "pass", "bodies", "blocks", "statements", "terminators", "locals");
+        for stats in &self.passes {
+            let (before, after) = (&stats.before, &stats.after);
+            println!("{:<30} {:>7} {:>17} {:>17} {:>17} {:>17}",
+                     stats.pass,
+                     after.bodies,
+                     change(before.blocks, after.blocks),
+                     change(before.statements, after.statements),
+                     change(before.terminators, after.terminators),
+                     change(before.locals, after.locals));
+        }
+    }
+}
+
+/// Bodies with an inline hint score above this are too large to be worth
+/// inlining into another crate.
+pub const INLINE_HINT_THRESHOLD: u32 = 200;
+
+/// A rough estimate of how costly it is to inline `mir`, recorded in the
+/// metadata next to the body so that other crates can skip decoding bodies
+/// that are obviously too large. Every statement counts once and calls,
+/// which are rarely removed after inlining, count more than other
+/// terminators; the bodies of promoted constants are counted as well, as
+/// they are decoded along with `mir`.
+pub fn inline_hint_score(mir: &Mir) -> u32 {
+    let mut score = 0usize;
+    for data in mir.basic_blocks() {
+        score += data.statements.len();
+        score += match data.terminator().kind {
+            TerminatorKind::Goto { .. } |
+            TerminatorKind::Return |
+            TerminatorKind::Unreachable => 0,
+            TerminatorKind::Call { .. } => 5,
+            TerminatorKind::Drop { .. } |
+            TerminatorKind::DropAndReplace { .. } => 2,
+            _ => 1,
+        };
+    }
+    for promoted in &mir.promoted {
+        score += inline_hint_score(promoted) as usize;
+    }
+    if score > u32::MAX as usize {
+        u32::MAX
+    } else {
+        score as u32
+    }
+}
+
+/// The number of bodies listed by `print_unwind_stats`.
+const UNWIND_STATS_BODIES: usize = 10;
+
+/// The number of blocks and statements of one or more bodies, and how
+/// many of them are cleanup blocks and their statements. Terminators count
+/// as statements.
+#[derive(Copy, Clone, Default)]
+struct UnwindSize {
+    blocks: usize,
+    cleanup_blocks: usize,
+    statements: usize,
+    cleanup_statements: usize,
+}
+
+impl UnwindSize {
+    fn of(mir: &Mir) -> UnwindSize {
+        let mut size = UnwindSize::default();
+        for data in mir.basic_blocks() {
+            let statements = data.statements.len() + 1;
+            size.blocks += 1;
+            size.statements += statements;
+            if data.is_cleanup {
+                size.cleanup_blocks += 1;
+                size.cleanup_statements += statements;
+            }
+        }
+        size
+    }
+
+    fn add(&mut self, other: UnwindSize) {
+        self.blocks += other.blocks;
+        self.cleanup_blocks += other.cleanup_blocks;
+        self.statements += other.statements;
+        self.cleanup_statements += other.cleanup_statements;
+    }
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+/// Prints how much of the MIR of the crate is cleanup code, in total and
+/// for the bodies with the most cleanup statements.
+pub fn print_unwind_stats<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir_map: &MirMap<'tcx>) {
+    let mut def_ids = mir_map.map.keys();
+    def_ids.sort();
+
+    let mut total = UnwindSize::default();
+    let mut bodies: Vec<_> = def_ids.into_iter().map(|def_id| {
+        let size = UnwindSize::of(mir_map.map.get(&def_id).unwrap());
+        total.add(size);
+        (def_id, size)
+    }).collect();
+    // This sort is stable, so bodies with as many cleanup statements stay
+    // in `DefId` order.
+    bodies.sort_by(|a, b| b.1.cleanup_statements.cmp(&a.1.cleanup_statements));
+
+    println!("--- MIR unwind stats ---");
+    println!("{} bodies: {} of {} blocks ({:.1}%) and {} of {} statements ({:.1}%) are cleanup",
+             bodies.len(),
+             total.cleanup_blocks, total.blocks,
+             percent(total.cleanup_blocks, total.blocks),
+             total.cleanup_statements, total.statements,
+             percent(total.cleanup_statements, total.statements));
+    println!("{:>17} {:>17} {:>8}  {}", "cleanup blocks", "cleanup stmts", "share", "body");
+    for &(def_id, size) in bodies.iter().take(UNWIND_STATS_BODIES) {
+        if size.cleanup_statements == 0 {
+            break;
+        }
+        println!("{:>17} {:>17} {:>7.1}%  {}",
+                 format!("{} / {}", size.cleanup_blocks, size.blocks),
+                 format!("{} / {}", size.cleanup_statements, size.statements),
+                 percent(size.cleanup_statements, size.statements),
+                 tcx.item_path_str(def_id));
+    }
+}
+
+/// The number of bodies listed when `-Z mir-size-limit` is exceeded.
+const SIZE_LIMIT_BODIES: usize = 5;
+
+/// The number of statements of `mir` and of its promoted constants,
+/// counting terminators as statements.
+fn statement_count(mir: &Mir) -> usize {
+    let own: usize = mir.basic_blocks().iter().map(|data| data.statements.len() + 1).sum();
+    own + mir.promoted.iter().map(statement_count).sum::<usize>()
+}
+
+/// Warns if a body of `mir_map`, or all of them together, have more than
+/// `limit` statements, for `-Z mir-size-limit`. The warning points at the
+/// bodies over the limit and lists the largest bodies of the crate.
+pub fn check_size_limit<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  mir_map: &MirMap<'tcx>,
+                                  limit: usize) {
+    let mut def_ids = mir_map.map.keys();
+    def_ids.sort();
+
+    let mut bodies: Vec<_> = def_ids.into_iter().map(|def_id| {
+        (def_id, statement_count(mir_map.map.get(&def_id).unwrap()))
+    }).collect();
+    let total: usize = bodies.iter().map(|&(_, statements)| statements).sum();
+    // This sort is stable, so bodies of the same size stay in `DefId` order.
+    bodies.sort_by(|a, b| b.1.cmp(&a.1));
+
+    // A body over the limit puts the crate over it as well.
+    if total <= limit {
+        return;
+    }
+    let mut err = tcx.sess.struct_warn(&format!("MIR exceeds the size limit of {} statements",
+                                                limit));
+    for &(def_id, statements) in bodies.iter().take_while(|&&(_, n)| n > limit) {
+        err.span_note(mir_map.map.get(&def_id).unwrap().span,
+                      &format!("`{}` has {} statements",
+                               tcx.item_path_str(def_id), statements));
+    }
+    err.note(&format!("the crate has {} statements in {} bodies", total, bodies.len()));
+    let largest: Vec<_> = bodies.iter().take(SIZE_LIMIT_BODIES).map(|&(def_id, statements)| {
+        format!("`{}` ({})", tcx.item_path_str(def_id), statements)
+    }).collect();
+    err.note(&format!("the largest bodies are {}", largest.join(", ")));
+    err.emit();
+}
@@ -153,6 +153,10 @@ impl<'tcx> Rvalue<'tcx> {
                     }
                 ))
             }
+            &Rvalue::RawPtr(mutbl, ref lv) => {
+                let lv_ty = lv.ty(mir, tcx).to_ty(tcx);
+                Some(tcx.mk_ptr(ty::TypeAndMut { ty: lv_ty, mutbl: mutbl.to_hir() }))
+            }
             &Rvalue::Len(..) => Some(tcx.types.usize),
             &Rvalue::Cast(_, _, ty) => Some(ty),
             &Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
@@ -196,6 +200,13 @@ impl<'tcx> Rvalue<'tcx> {
                     }
                 }
             }
+            &Rvalue::Simd(op, ref ops) => {
+                match op {
+                    SimdOp::Extract => Some(ops[0].ty(mir, tcx).simd_type(tcx)),
+                    SimdOp::Insert => Some(ops[0].ty(mir, tcx)),
+                    SimdOp::Shuffle(ty) => Some(ty),
+                }
+            }
             &Rvalue::InlineAsm { .. } => None
         }
     }
@@ -234,6 +245,15 @@ impl<'tcx> BinOp {
     }
 }
 
+impl Mutability {
+    pub fn to_hir(self) -> hir::Mutability {
+        match self {
+            Mutability::Mut => hir::MutMutable,
+            Mutability::Not => hir::MutImmutable,
+        }
+    }
+}
+
 impl BorrowKind {
     pub fn to_mutbl_lossy(self) -> hir::Mutability {
         match self {
@@ -14,6 +14,7 @@ use hir::map::DefPathData;
 use mir::mir_map::MirMap;
 use mir::repr::{Mir, Promoted};
 use ty::TyCtxt;
+use util::common::time_rss_delta;
 use syntax::ast::NodeId;
 
 use std::fmt;
@@ -115,7 +116,11 @@ impl<'tcx, T: MirPass<'tcx>> MirMapPass<'tcx> for T {
                     map: &mut MirMap<'tcx>,
                     hooks: &mut [Box<for<'s> MirPassHook<'s>>])
     {
-        let def_ids = map.map.keys();
+        // Run on the bodies in the order of their `DefId`s, not that of the
+        // map, so that reruns number the bodies and blocks they add, and
+        // report errors, the same way.
+        let mut def_ids = map.map.keys();
+        def_ids.sort();
         for def_id in def_ids {
             let _task = tcx.dep_graph.in_task(DepNode::Mir(def_id));
             let mir = map.map.get_mut(&def_id).unwrap();
@@ -161,12 +166,17 @@ impl<'a, 'tcx> Passes {
         passes
     }
 
+    /// Runs the plugin passes, then the built-in ones. With
+    /// `-Z time-passes`, each of them is timed on its own.
     pub fn run_passes(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, map: &mut MirMap<'tcx>) {
-        for pass in &mut self.plugin_passes {
-            pass.run_pass(tcx, map, &mut self.pass_hooks);
-        }
-        for pass in &mut self.passes {
-            pass.run_pass(tcx, map, &mut self.pass_hooks);
+        let time_passes = tcx.sess.time_passes();
+        let hooks = &mut self.pass_hooks;
+        for pass in self.plugin_passes.iter_mut().chain(self.passes.iter_mut()) {
+            let what = match pass.disambiguator() {
+                Some(disambiguator) => format!("MIR pass {}-{}", pass.name(), disambiguator),
+                None => format!("MIR pass {}", pass.name()),
+            };
+            time_rss_delta(time_passes, &what, || pass.run_pass(tcx, map, hooks));
         }
     }
 
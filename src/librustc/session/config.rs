@@ -44,7 +44,7 @@ pub struct Config {
     pub uint_type: UintTy,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Hash)]
 pub enum OptLevel {
     No, // -O0
     Less, // -O1
@@ -69,6 +69,8 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    MirJson,
+    MirHtml,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -87,7 +89,9 @@ impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
             OutputType::Exe |
-            OutputType::DepInfo => true,
+            OutputType::DepInfo |
+            OutputType::MirJson |
+            OutputType::MirHtml => true,
             OutputType::Bitcode |
             OutputType::Assembly |
             OutputType::LlvmAssembly |
@@ -95,7 +99,7 @@ impl OutputType {
         }
     }
 
-    fn shorthand(&self) -> &'static str {
+    pub fn shorthand(&self) -> &'static str {
         match *self {
             OutputType::Bitcode => "llvm-bc",
             OutputType::Assembly => "asm",
@@ -103,6 +107,8 @@ impl OutputType {
             OutputType::Object => "obj",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::MirJson => "mir-json",
+            OutputType::MirHtml => "mir-html",
         }
     }
 
@@ -113,6 +119,8 @@ impl OutputType {
             OutputType::LlvmAssembly => "ll",
             OutputType::Object => "o",
             OutputType::DepInfo => "d",
+            OutputType::MirJson => "mir.json",
+            OutputType::MirHtml => "mir.html",
             OutputType::Exe => "",
         }
     }
@@ -391,6 +399,23 @@ impl PanicStrategy {
     }
 }
 
+/// The runtime that the checks inserted for `-Z sanitizer` call into.
+#[derive(Copy, Clone, PartialEq, Hash, Debug)]
+pub enum Sanitizer {
+    Address,
+    Memory,
+}
+
+/// Which crate's overflow check setting generic functions follow, for
+/// `-Z overflow-checks-policy`.
+#[derive(Copy, Clone, PartialEq, Hash, Debug)]
+pub enum OverflowChecksPolicy {
+    /// The setting of the crate that instantiates them.
+    Caller,
+    /// The setting of the crate that defines them.
+    Definition,
+}
+
 /// Declare a macro that will define all CodegenOptions/DebuggingOptions fields and parsers all
 /// at once. The goal of this macro is to define an interface that can be
 /// programmatically used by the option parser in order to initialize the struct
@@ -480,11 +505,16 @@ macro_rules! options {
             Some("a number");
         pub const parse_panic_strategy: Option<&'static str> =
             Some("either `panic` or `abort`");
+        pub const parse_sanitizer: Option<&'static str> =
+            Some("one of: `address`, `memory`");
+        pub const parse_overflow_checks_policy: Option<&'static str> =
+            Some("either `caller` or `definition`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy};
+        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy, Sanitizer,
+                    OverflowChecksPolicy};
 
         $(
             pub fn $opt(cg: &mut $struct_name, v: Option<&str>) -> bool {
@@ -616,6 +646,25 @@ macro_rules! options {
             }
             true
         }
+
+        fn parse_sanitizer(slot: &mut Option<Sanitizer>, v: Option<&str>) -> bool {
+            match v {
+                Some("address") => *slot = Some(Sanitizer::Address),
+                Some("memory") => *slot = Some(Sanitizer::Memory),
+                _ => return false
+            }
+            true
+        }
+
+        fn parse_overflow_checks_policy(slot: &mut OverflowChecksPolicy,
+                                        v: Option<&str>) -> bool {
+            match v {
+                Some("caller") => *slot = OverflowChecksPolicy::Caller,
+                Some("definition") => *slot = OverflowChecksPolicy::Definition,
+                _ => return false
+            }
+            true
+        }
     }
 ) }
 
@@ -700,6 +749,37 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "gather statistics about the input"),
     trans_stats: bool = (false, parse_bool,
         "gather trans statistics"),
+    mir_stats: bool = (false, parse_bool,
+        "print the size of the MIR before and after each MIR pass"),
+    mir_unwind_stats: bool = (false, parse_bool,
+        "print how much of the MIR of the crate is cleanup code, and the bodies with the most"),
+    mir_match_stats: bool = (false, parse_bool,
+        "report the number of candidates, tests and blocks of the MIR of every match"),
+    mir_match_stats_threshold: usize = (1000, parse_uint,
+        "the number of blocks above which -Z mir-match-stats warns about a match"),
+    mir_size_limit: Option<usize> = (None, parse_opt_uint,
+        "warn if the optimized MIR of a body, or of the whole crate, has more than this \
+         many statements, terminators included"),
+    short_scrutinee_temps: bool = (false, parse_bool,
+        "drop the temporaries of a match scrutinee when an arm is entered, rather than at the \
+         end of the enclosing statement, if no binding of the arms can borrow from them"),
+    instrument_coverage: bool = (false, parse_bool,
+        "count the executions of every basic block with LLVM's profiling intrinsics and \
+         write the counters' source regions to a `.covmap.json` file; the resulting binary \
+         must be linked against the LLVM profiler runtime"),
+    sanitizer: Option<Sanitizer> = (None, parse_sanitizer,
+        "check the loads and stores through raw pointers with the given sanitizer's runtime \
+         (`address` or `memory`); the resulting binary must be linked against that runtime"),
+    mir_retag: bool = (false, parse_bool,
+        "mark the references that are created or passed to functions with `Retag` \
+         statements in MIR, for checkers of an aliasing model; they do nothing at runtime"),
+    mir_instrument_calls: Option<String> = (None, parse_opt_string,
+        "call the `extern \"C\" fn(function_id: u64, event: u32)` with the given symbol on \
+         entry to every function (event 0), and right before it returns (1) or resumes \
+         unwinding (2)"),
+    check_box_from_raw: bool = (false, parse_bool,
+        "assert that the raw pointers boxes are made from, e.g. by `Box::from_raw`, are \
+         non-null and aligned before the boxes are dereferenced"),
     asm_comments: bool = (false, parse_bool,
         "generate comments into the assembly (may change behavior)"),
     no_verify: bool = (false, parse_bool,
@@ -763,6 +843,10 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "adds unstable command line options to rustc interface"),
     force_overflow_checks: Option<bool> = (None, parse_opt_bool,
           "force overflow checks on or off"),
+    overflow_checks_policy: OverflowChecksPolicy = (OverflowChecksPolicy::Definition,
+          parse_overflow_checks_policy,
+          "whether generic functions have overflow checks if the crate instantiating them \
+           (`caller`) or the crate defining them (`definition`) has them"),
     force_dropflag_checks: Option<bool> = (None, parse_opt_bool,
           "force drop flag checks on or off"),
     trace_macros: bool = (false, parse_bool,
@@ -777,12 +861,33 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "show spans for compiler debugging (expr|pat|ty)"),
     print_trans_items: Option<String> = (None, parse_opt_string,
           "print the result of the translation item collection pass"),
+    print_mono_mir: Option<String> = (None, parse_opt_string,
+          "print the MIR of an instance, named as by `-Z print-trans-items`, for \
+           example `krate::foo<u32>`, with its type parameters substituted"),
     mir_opt_level: Option<usize> = (None, parse_opt_uint,
           "set the MIR optimization level (0-3)"),
+    mir_deaggregate: bool = (false, parse_bool,
+          "lower aggregates of structs and enum variants in all bodies to assignments \
+           of their fields, followed by a `SetDiscriminant` for enums"),
     dump_mir: Option<String> = (None, parse_opt_string,
-          "dump MIR state at various points in translation"),
+          "dump MIR state before and after each pass; filter by pass and \
+           item name substrings, or `all`, joined with `&` (all must match) \
+           and `|` (any group may match), e.g. `SimplifyCfg&main|mir_map`"),
     dump_mir_dir: Option<String> = (None, parse_opt_string,
           "the directory the MIR is dumped into"),
+    dump_mir_graphviz: bool = (false, parse_bool,
+          "in addition to `.mir` files, create graphviz `.dot` files"),
+    dump_mir_callgraph: bool = (false, parse_bool,
+          "write the crate's call graph, built from its MIR, to `rustc.callgraph.dot` \
+           in the MIR dump directory"),
+    dump_mir_on_ice: bool = (false, parse_bool,
+          "when building the MIR of a body panics, write the MIR built so far, and the \
+           scopes that were still open, to the MIR dump directory"),
+    mir_pretty_source: bool = (false, parse_bool,
+          "print the originating source line above the MIR statements built from it"),
+    mir_provenance: bool = (false, parse_bool,
+          "track which pass created each MIR block, and which block and span it was \
+           created as, and print it in MIR dumps"),
     orbit: bool = (true, parse_all_bool,
           "get MIR where it belongs - everywhere; most importantly, in orbit"),
 }
@@ -1025,7 +1130,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
                "NAME"),
         opt::multi_s("", "emit", "Comma separated list of types of output for \
                               the compiler to emit",
-                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info]"),
+                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info|mir-json|mir-html]"),
         opt::multi_s("", "print", "Comma separated list of compiler information to \
                                print on stdout",
                  "[crate-name|file-names|sysroot|cfg|target-list|target-cpus|\
@@ -1204,6 +1309,8 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
                     "obj" => OutputType::Object,
                     "link" => OutputType::Exe,
                     "dep-info" => OutputType::DepInfo,
+                    "mir-json" => OutputType::MirJson,
+                    "mir-html" => OutputType::MirHtml,
                     part => {
                         early_error(error_format, &format!("unknown emission type: `{}`",
                                                     part))
@@ -1217,6 +1324,15 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
     if output_types.is_empty() {
         output_types.insert(OutputType::Exe, None);
     }
+    if !debugging_opts.unstable_options {
+        for ot in &[OutputType::MirJson, OutputType::MirHtml] {
+            if output_types.contains_key(ot) {
+                early_error(error_format, &format!("`--emit={}` is unstable; \
+                                                    pass `-Z unstable-options` to enable it",
+                                                   ot.shorthand()));
+            }
+        }
+    }
 
     let mut cg = build_codegen_options(matches, error_format);
 
@@ -17,7 +17,10 @@ use middle::dependency_format;
 use session::search_paths::PathKind;
 use session::config::{DebugInfoLevel, PanicStrategy};
 use ty::tls;
-use util::nodemap::{NodeMap, FnvHashMap};
+use util::nodemap::{NodeMap, NodeSet, FnvHashMap};
+use mir::coverage::CoverageMap;
+use mir::provenance::MirProvenance;
+use mir::stats::MirStats;
 use mir::transform as mir_pass;
 
 use syntax::ast::{NodeId, Name};
@@ -71,6 +74,16 @@ pub struct Session {
     pub lints: RefCell<NodeMap<Vec<(lint::LintId, Span, String)>>>,
     pub plugin_llvm_passes: RefCell<Vec<String>>,
     pub mir_passes: RefCell<mir_pass::Passes>,
+    /// The MIR size statistics collected for `-Z mir-stats`.
+    pub mir_stats: RefCell<MirStats>,
+    /// The origin of every basic block, tracked for `-Z mir-provenance`.
+    pub mir_provenance: RefCell<MirProvenance>,
+    /// The coverage counters inserted for `-Z instrument-coverage`.
+    pub coverage_map: RefCell<CoverageMap>,
+    /// The bodies for which MIR construction or the MIR passes buffered a
+    /// lint or reported broken MIR. The MIR cache leaves their items out,
+    /// as those diagnostics would be lost once their MIR is reused.
+    pub mir_diagnostic_nodes: RefCell<NodeSet>,
     pub plugin_attributes: RefCell<Vec<(String, AttributeType)>>,
     pub crate_types: RefCell<Vec<config::CrateType>>,
     pub dependency_formats: RefCell<dependency_format::Dependencies>,
@@ -435,6 +448,10 @@ pub fn build_session_(sopts: config::Options,
         lints: RefCell::new(NodeMap()),
         plugin_llvm_passes: RefCell::new(Vec::new()),
         mir_passes: RefCell::new(mir_pass::Passes::new()),
+        mir_stats: RefCell::new(MirStats::new()),
+        mir_provenance: RefCell::new(MirProvenance::new()),
+        coverage_map: RefCell::new(CoverageMap::new()),
+        mir_diagnostic_nodes: RefCell::new(NodeSet()),
         plugin_attributes: RefCell::new(Vec::new()),
         crate_types: RefCell::new(Vec::new()),
         dependency_formats: RefCell::new(FnvHashMap()),
@@ -444,6 +444,10 @@ pub struct GlobalCtxt<'tcx> {
     /// Caches the representation hints for struct definitions.
     repr_hint_cache: RefCell<DepTrackingMap<maps::ReprHints<'tcx>>>,
 
+    /// Caches the values of constants referenced from type-checked
+    /// expressions; see `rustc_const_eval::eval_const_expr_partial`.
+    pub const_eval_cache: RefCell<DepTrackingMap<maps::ConstEvals<'tcx>>>,
+
     /// Maps Expr NodeId's to their constant qualification.
     pub const_qualif_map: RefCell<NodeMap<middle::const_qualif::ConstQualif>>,
 
@@ -490,6 +494,10 @@ pub struct GlobalCtxt<'tcx> {
 
     /// Cache for layouts computed from types.
     pub layout_cache: RefCell<FnvHashMap<Ty<'tcx>, &'tcx Layout>>,
+
+    /// Cache for `type_needs_drop_given_env`, of the types which, like
+    /// those in `layout_cache`, mention no type parameters.
+    pub needs_drop_cache: RefCell<FnvHashMap<Ty<'tcx>, bool>>,
 }
 
 impl<'tcx> GlobalCtxt<'tcx> {
@@ -737,6 +745,7 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
             selection_cache: traits::SelectionCache::new(),
             evaluation_cache: traits::EvaluationCache::new(),
             repr_hint_cache: RefCell::new(DepTrackingMap::new(dep_graph.clone())),
+            const_eval_cache: RefCell::new(DepTrackingMap::new(dep_graph.clone())),
             const_qualif_map: RefCell::new(NodeMap()),
             custom_coerce_unsized_kinds: RefCell::new(DefIdMap()),
             cast_kinds: RefCell::new(NodeMap()),
@@ -744,6 +753,7 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
             crate_name: token::intern_and_get_ident(crate_name),
             data_layout: data_layout,
             layout_cache: RefCell::new(FnvHashMap()),
+            needs_drop_cache: RefCell::new(FnvHashMap()),
        }, f)
     }
 }
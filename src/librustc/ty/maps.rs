@@ -10,7 +10,9 @@
 
 use dep_graph::{DepNode, DepTrackingMapConfig};
 use hir::def_id::DefId;
+use middle::const_val::{ConstVal, EvalHint};
 use ty;
+use ty::subst::Substs;
 use std::marker::PhantomData;
 use std::rc::Rc;
 use syntax::{attr, ast};
@@ -43,3 +45,20 @@ dep_map_ty! { ImplItems: ImplItems(DefId) -> Vec<ty::ImplOrTraitItemId> }
 dep_map_ty! { TraitItems: TraitItems(DefId) -> Rc<Vec<ty::ImplOrTraitItem<'tcx>>> }
 dep_map_ty! { ReprHints: ReprHints(DefId) -> Rc<Vec<attr::ReprAttr>> }
 dep_map_ty! { InlinedClosures: Hir(DefId) -> ast::NodeId }
+
+/// Successfully evaluated constants, keyed by the constant's `DefId`, the
+/// substitutions it was referenced with, if they are known, and the hint
+/// it was evaluated with, which can change the value of an untyped
+/// literal. This is a true cache, so the entries are filled from within a
+/// `ConstEval` task.
+pub struct ConstEvals<'tcx> {
+    data: PhantomData<&'tcx ()>
+}
+
+impl<'tcx> DepTrackingMapConfig for ConstEvals<'tcx> {
+    type Key = (DefId, Option<&'tcx Substs<'tcx>>, EvalHint<'tcx>);
+    type Value = ConstVal;
+    fn to_dep_node(key: &(DefId, Option<&'tcx Substs<'tcx>>, EvalHint<'tcx>)) -> DepNode<DefId> {
+        DepNode::ConstEval(key.0)
+    }
+}
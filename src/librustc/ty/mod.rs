@@ -2566,9 +2566,50 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
     ///
     /// (Note that this implies that if `ty` has a destructor attached,
     /// then `type_needs_drop` will definitely return `true` for `ty`.)
+    ///
+    /// The results are cached for types that mention no type parameters,
+    /// which need to be dropped in every parameter environment or in none.
+    /// Whether a type parameter needs to be dropped depends on the caller
+    /// bounds, so the others are not cached.
     pub fn type_needs_drop_given_env(self,
                                      ty: Ty<'gcx>,
                                      param_env: &ty::ParameterEnvironment<'gcx>) -> bool {
+        let tcx = self.global_tcx();
+        if ty.has_param_types() || ty.has_self_ty() {
+            return self.type_needs_drop_uncached(ty, param_env);
+        }
+        if let Some(&cached) = tcx.needs_drop_cache.borrow().get(&ty) {
+            return cached;
+        }
+        let result = self.type_needs_drop_uncached(ty, param_env);
+        tcx.needs_drop_cache.borrow_mut().insert(ty, result);
+        result
+    }
+
+    /// Like `type_needs_drop_given_env`, but returns `None` rather than
+    /// ICE-ing if `ty` is not fully known, i.e. has inference variables.
+    pub fn try_type_needs_drop_given_env(self,
+                                         ty: Ty<'tcx>,
+                                         param_env: &ty::ParameterEnvironment<'gcx>)
+                                         -> Option<bool> {
+        if ty.needs_infer() {
+            return None;
+        }
+        self.lift_to_global(&ty).map(|ty| self.type_needs_drop_given_env(ty, param_env))
+    }
+
+    fn type_needs_drop_uncached(self,
+                                ty: Ty<'gcx>,
+                                param_env: &ty::ParameterEnvironment<'gcx>) -> bool {
+        // The layout of an empty array or of an enum without variants
+        // holds no value, so there is nothing to drop, whatever the types
+        // it is made of.
+        match ty.sty {
+            TyArray(_, 0) => return false,
+            TyEnum(def, _) if def.variants.is_empty() => return false,
+            _ => {}
+        }
+
         // Issue #22536: We first query type_moves_by_default.  It sees a
         // normalized version of the type, and therefore will definitely
         // know whether the type implements Copy (and thus needs no
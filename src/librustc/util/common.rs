@@ -33,6 +33,20 @@ pub struct ErrorReported;
 
 pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
     F: FnOnce() -> T,
+{
+    time_impl(do_it, what, false, f)
+}
+
+/// Like `time`, but also reports by how much the resident set size grew,
+/// or shrank, while running `f`.
+pub fn time_rss_delta<T, F>(do_it: bool, what: &str, f: F) -> T where
+    F: FnOnce() -> T,
+{
+    time_impl(do_it, what, true, f)
+}
+
+fn time_impl<T, F>(do_it: bool, what: &str, rss_delta: bool, f: F) -> T where
+    F: FnOnce() -> T,
 {
     thread_local!(static DEPTH: Cell<usize> = Cell::new(0));
     if !do_it { return f(); }
@@ -43,6 +57,7 @@ pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
         r
     });
 
+    let start_rss = if rss_delta { get_resident() } else { None };
     let start = Instant::now();
     let rv = f();
     let dur = start.elapsed();
@@ -56,7 +71,13 @@ pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
     let mem_string = match get_resident() {
         Some(n) => {
             let mb = n as f64 / 1_000_000.0;
-            format!("; rss: {}MB", mb.round() as usize)
+            match start_rss {
+                Some(start) => {
+                    let delta = (n as f64 - start as f64) / 1_000_000.0;
+                    format!("; rss: {}MB ({:+}MB)", mb.round() as usize, delta.round() as isize)
+                }
+                None => format!("; rss: {}MB", mb.round() as usize),
+            }
         }
         None => "".to_owned(),
     };
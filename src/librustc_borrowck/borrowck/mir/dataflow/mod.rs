@@ -450,6 +450,7 @@ impl<'a, 'tcx: 'a, D> DataflowAnalysis<'a, 'tcx, D>
         match bb_data.terminator().kind {
             repr::TerminatorKind::Return |
             repr::TerminatorKind::Resume |
+            repr::TerminatorKind::Abort |
             repr::TerminatorKind::Unreachable => {}
             repr::TerminatorKind::Goto { ref target } |
             repr::TerminatorKind::Assert { ref target, cleanup: None, .. } |
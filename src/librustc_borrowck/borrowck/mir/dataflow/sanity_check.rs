@@ -15,6 +15,7 @@ use syntax_pos::Span;
 use rustc::ty::{self, TyCtxt};
 use rustc::mir::repr::{self, Mir};
 use rustc_data_structures::indexed_vec::Idx;
+use rustc_mir::pretty::describe_lvalue;
 
 use super::super::gather_moves::{MovePathIndex};
 use super::super::MoveDataParamEnv;
@@ -107,6 +108,23 @@ fn each_block<'a, 'tcx, O>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
             repr::StatementKind::SetDiscriminant{ .. } =>
                 span_bug!(stmt.source_info.span,
                           "sanity_check should run before Deaggregator inserts SetDiscriminant"),
+            repr::StatementKind::Coverage { .. } =>
+                span_bug!(stmt.source_info.span,
+                          "sanity_check should run before coverage counters are inserted"),
+            repr::StatementKind::SanitizerCheck { .. } =>
+                span_bug!(stmt.source_info.span,
+                          "sanity_check should run before sanitizer checks are inserted"),
+            repr::StatementKind::Retag { .. } =>
+                span_bug!(stmt.source_info.span,
+                          "sanity_check should run before retags are inserted"),
+            repr::StatementKind::Atomic { .. } =>
+                span_bug!(stmt.source_info.span,
+                          "sanity_check should run before atomic intrinsics are lowered"),
+            repr::StatementKind::Probe { .. } =>
+                span_bug!(stmt.source_info.span,
+                          "sanity_check should run before probes are inserted"),
+            // Subtyping has no effect on the dataflow.
+            repr::StatementKind::Subtype { .. } => continue,
         };
 
         if lvalue == peek_arg_lval {
@@ -119,7 +137,8 @@ fn each_block<'a, 'tcx, O>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                 debug!("rustc_peek({:?} = &{:?}) bit_state: {}",
                        lvalue, peeking_at_lval, bit_state);
                 if !bit_state {
-                    tcx.sess.span_err(span, &format!("rustc_peek: bit not set"));
+                    let peeked = describe_lvalue(tcx, mir, peeking_at_lval);
+                    tcx.sess.span_err(span, &format!("rustc_peek: bit not set for {}", peeked));
                 }
                 return;
             } else {
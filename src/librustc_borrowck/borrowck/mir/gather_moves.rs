@@ -34,17 +34,18 @@ mod indexes {
     macro_rules! new_index {
         ($Index:ident) => {
             #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-            pub struct $Index(NonZero<usize>);
+            pub struct $Index(NonZero<u32>);
 
             impl $Index {
             }
 
             impl Idx for $Index {
                 fn new(idx: usize) -> Self {
-                    unsafe { $Index(NonZero::new(idx + 1)) }
+                    assert!(idx < ::std::u32::MAX as usize);
+                    unsafe { $Index(NonZero::new(idx as u32 + 1)) }
                 }
                 fn index(self) -> usize {
-                    *self.0 - 1
+                    (*self.0 - 1) as usize
                 }
             }
         }
@@ -62,7 +63,7 @@ pub use self::indexes::MoveOutIndex;
 
 impl self::indexes::MoveOutIndex {
     pub fn move_path_index(&self, move_data: &MoveData) -> MovePathIndex {
-        move_data.moves[self.index()].path
+        move_data.moves[*self].path
     }
 }
 
@@ -138,7 +139,7 @@ impl<'tcx> fmt::Debug for MovePath<'tcx> {
 #[derive(Debug)]
 pub struct MoveData<'tcx> {
     pub move_paths: MovePathData<'tcx>,
-    pub moves: Vec<MoveOut>,
+    pub moves: IndexVec<MoveOutIndex, MoveOut>,
     pub loc_map: LocMap,
     pub path_map: PathMap,
     pub rev_lookup: MovePathLookup<'tcx>,
@@ -153,15 +154,15 @@ pub struct LocMap {
     /// of executing the code at `l`. (There can be multiple MoveOut's
     /// for a given `l` because each MoveOut is associated with one
     /// particular path being moved.)
-    map: Vec<Vec<Vec<MoveOutIndex>>>,
+    map: IndexVec<BasicBlock, Vec<Vec<MoveOutIndex>>>,
 }
 
 impl Index<Location> for LocMap {
     type Output = [MoveOutIndex];
     fn index(&self, index: Location) -> &Self::Output {
         assert!(index.block.index() < self.map.len());
-        assert!(index.index < self.map[index.block.index()].len());
-        &self.map[index.block.index()][index.index]
+        assert!(index.statement_index < self.map[index.block].len());
+        &self.map[index.block][index.statement_index]
     }
 }
 
@@ -170,13 +171,13 @@ pub struct PathMap {
     /// Path-indexed map to list of MoveOutIndex's.
     ///
     /// Each Path `p` is mapped to the MoveOut's that move out of `p`.
-    map: Vec<Vec<MoveOutIndex>>,
+    map: IndexVec<MovePathIndex, Vec<MoveOutIndex>>,
 }
 
 impl Index<MovePathIndex> for PathMap {
     type Output = [MoveOutIndex];
     fn index(&self, index: MovePathIndex) -> &Self::Output {
-        &self.map[index.index()]
+        &self.map[index]
     }
 }
 
@@ -200,24 +201,9 @@ impl fmt::Debug for MoveOut {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Location {
-    /// block where action is located
-    pub block: BasicBlock,
-    /// index within above block; statement when < statments.len) or
-    /// the terminator (when = statements.len).
-    pub index: usize,
-}
-
-impl fmt::Debug for Location {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:?}[{}]", self.block, self.index)
-    }
-}
-
 #[derive(Debug)]
 pub struct MovePathData<'tcx> {
-    move_paths: Vec<MovePath<'tcx>>,
+    move_paths: IndexVec<MovePathIndex, MovePath<'tcx>>,
 }
 
 impl<'tcx> MovePathData<'tcx> {
@@ -227,13 +213,13 @@ impl<'tcx> MovePathData<'tcx> {
 impl<'tcx> Index<MovePathIndex> for MovePathData<'tcx> {
     type Output = MovePath<'tcx>;
     fn index(&self, i: MovePathIndex) -> &MovePath<'tcx> {
-        &self.move_paths[i.index()]
+        &self.move_paths[i]
     }
 }
 
 struct MovePathDataBuilder<'a, 'tcx: 'a> {
     mir: &'a Mir<'tcx>,
-    pre_move_paths: Vec<PreMovePath<'tcx>>,
+    pre_move_paths: IndexVec<MovePathIndex, PreMovePath<'tcx>>,
     rev_lookup: MovePathLookup<'tcx>,
 }
 
@@ -259,7 +245,7 @@ pub struct MovePathLookup<'tcx> {
     /// subsequent search so that it is solely relative to that
     /// base-lvalue). For the remaining lookup, we map the projection
     /// elem to the associated MovePathIndex.
-    projections: Vec<FnvHashMap<AbstractElem<'tcx>, MovePathIndex>>,
+    projections: IndexVec<MovePathIndex, FnvHashMap<AbstractElem<'tcx>, MovePathIndex>>,
 
     /// Tracks the next index to allocate during construction of the
     /// MovePathData. Unused after MovePathData is fully constructed.
@@ -284,6 +270,12 @@ impl<T:Clone> FillTo for Vec<T> {
         debug_assert!(idx < self.len());
     }
 }
+impl<I: Idx, T: Clone> FillTo for IndexVec<I, T> {
+    type T = T;
+    fn fill_to_with(&mut self, idx: usize, x: T) {
+        self.raw.fill_to_with(idx, x)
+    }
+}
 
 #[derive(Clone, Debug)]
 enum LookupKind { Generate, Reuse }
@@ -302,7 +294,7 @@ impl<'tcx> MovePathLookup<'tcx> {
             args: IndexVec::from_elem(None, &mir.arg_decls),
             statics: None,
             return_ptr: None,
-            projections: vec![],
+            projections: IndexVec::new(),
             next_index: MovePathIndex::new(0),
         }
     }
@@ -380,7 +372,7 @@ impl<'tcx> MovePathLookup<'tcx> {
         let MovePathLookup { ref mut projections,
                              ref mut next_index, .. } = *self;
         projections.fill_to(base.index());
-        match projections[base.index()].entry(proj.elem.lift()) {
+        match projections[base].entry(proj.elem.lift()) {
             Entry::Occupied(ent) => {
                 Lookup(LookupKind::Reuse, *ent.get())
             }
@@ -406,7 +398,7 @@ impl<'tcx> MovePathLookup<'tcx> {
             Lvalue::ReturnPointer => self.return_ptr.unwrap(),
             Lvalue::Projection(ref proj) => {
                 let base_index = self.find(&proj.base);
-                self.projections[base_index.index()][&proj.elem.lift()]
+                self.projections[base_index][&proj.elem.lift()]
             }
         }
     }
@@ -477,7 +469,7 @@ impl<'a, 'tcx> MovePathDataBuilder<'a, 'tcx> {
                     let idx = self.move_path_for(&proj.base);
                     parent = Some(idx);
 
-                    let parent_move_path = &mut self.pre_move_paths[idx.index()];
+                    let parent_move_path = &mut self.pre_move_paths[idx];
 
                     // At last: Swap in the new first_child.
                     sibling = parent_move_path.first_child.get();
@@ -513,23 +505,22 @@ impl<'a, 'tcx> MoveData<'tcx> {
 #[derive(Debug)]
 enum StmtKind {
     Use, Repeat, Cast, BinaryOp, UnaryOp, Box,
-    Aggregate, Drop, CallFn, CallArg, Return, If,
+    Aggregate, Simd, Drop, CallFn, CallArg, Return, If,
 }
 
 fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveData<'tcx> {
     use self::StmtKind as SK;
 
-    let bb_count = mir.basic_blocks().len();
-    let mut moves = vec![];
-    let mut loc_map: Vec<_> = iter::repeat(Vec::new()).take(bb_count).collect();
-    let mut path_map = Vec::new();
+    let mut moves = IndexVec::new();
+    let mut loc_map = IndexVec::from_elem(Vec::new(), mir.basic_blocks());
+    let mut path_map = IndexVec::new();
 
     // this is mutable only because we will move it to and fro' the
     // BlockContexts constructed on each iteration. (Moving is more
     // straight-forward than mutable borrows in this instance.)
     let mut builder = MovePathDataBuilder {
         mir: mir,
-        pre_move_paths: Vec::new(),
+        pre_move_paths: IndexVec::new(),
         rev_lookup: MovePathLookup::new(mir),
     };
 
@@ -555,7 +546,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
     }
 
     for (bb, bb_data) in mir.basic_blocks().iter_enumerated() {
-        let loc_map_bb = &mut loc_map[bb.index()];
+        let loc_map_bb = &mut loc_map[bb];
 
         debug_assert!(loc_map_bb.len() == 0);
         let len = bb_data.statements.len();
@@ -571,7 +562,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
         };
 
         for (i, stmt) in bb_data.statements.iter().enumerate() {
-            let source = Location { block: bb, index: i };
+            let source = Location { block: bb, statement_index: i };
             match stmt.kind {
                 StatementKind::Assign(ref lval, ref rval) => {
                     bb_ctxt.builder.create_move_path(lval);
@@ -611,7 +602,13 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
                                 bb_ctxt.on_operand(SK::Aggregate, operand, source);
                             }
                         }
+                        Rvalue::Simd(_, ref operands) => {
+                            for operand in operands {
+                                bb_ctxt.on_operand(SK::Simd, operand, source);
+                            }
+                        }
                         Rvalue::Ref(..) |
+                        Rvalue::RawPtr(..) |
                         Rvalue::Len(..) |
                         Rvalue::InlineAsm { .. } => {}
                     }
@@ -620,6 +617,27 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
                     span_bug!(stmt.source_info.span,
                               "SetDiscriminant should not exist during borrowck");
                 }
+                StatementKind::Coverage { .. } => {
+                    span_bug!(stmt.source_info.span,
+                              "Coverage should not exist during borrowck");
+                }
+                StatementKind::SanitizerCheck { .. } => {
+                    span_bug!(stmt.source_info.span,
+                              "SanitizerCheck should not exist during borrowck");
+                }
+                StatementKind::Retag { .. } => {
+                    span_bug!(stmt.source_info.span,
+                              "Retag should not exist during borrowck");
+                }
+                StatementKind::Atomic { .. } => {
+                    span_bug!(stmt.source_info.span,
+                              "Atomic should not exist during borrowck");
+                }
+                StatementKind::Probe { .. } => {
+                    span_bug!(stmt.source_info.span,
+                              "Probe should not exist during borrowck");
+                }
+                StatementKind::Subtype { .. } => {}
             }
         }
 
@@ -627,11 +645,12 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
         match bb_data.terminator().kind {
             TerminatorKind::Goto { target: _ } |
             TerminatorKind::Resume |
+            TerminatorKind::Abort |
             TerminatorKind::Unreachable => { }
 
             TerminatorKind::Return => {
                 let source = Location { block: bb,
-                                        index: bb_data.statements.len() };
+                                        statement_index: bb_data.statements.len() };
                 if let FnOutput::FnConverging(_) = bb_ctxt.builder.mir.return_ty {
                     debug!("gather_moves Return on_move_out_lval return {:?}", source);
                     bb_ctxt.on_move_out_lval(SK::Return, &Lvalue::ReturnPointer, source);
@@ -643,7 +662,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
 
             TerminatorKind::If { ref cond, targets: _ } => {
                 let source = Location { block: bb,
-                                        index: bb_data.statements.len() };
+                                        statement_index: bb_data.statements.len() };
                 bb_ctxt.on_operand(SK::If, cond, source);
             }
 
@@ -659,7 +678,12 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
                         // Same for the usize length and index in bounds-checking.
                         let _ = (len, index);
                     }
-                    AssertMessage::Math(_) => {}
+                    AssertMessage::Math { ref operands, .. } => {
+                        // And for the integer operands of arithmetic.
+                        let _ = operands;
+                    }
+                    AssertMessage::Misaligned |
+                    AssertMessage::NullPointer => {}
                 }
             }
 
@@ -674,7 +698,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
 
             TerminatorKind::Drop { ref location, target: _, unwind: _ } => {
                 let source = Location { block: bb,
-                                        index: bb_data.statements.len() };
+                                        statement_index: bb_data.statements.len() };
                 bb_ctxt.on_move_out_lval(SK::Drop, location, source);
             }
             TerminatorKind::DropAndReplace { ref location, ref value, .. } => {
@@ -682,12 +706,12 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
                 bb_ctxt.path_map.fill_to(assigned_path.index());
 
                 let source = Location { block: bb,
-                                        index: bb_data.statements.len() };
+                                        statement_index: bb_data.statements.len() };
                 bb_ctxt.on_operand(SK::Use, value, source);
             }
             TerminatorKind::Call { ref func, ref args, ref destination, cleanup: _ } => {
                 let source = Location { block: bb,
-                                        index: bb_data.statements.len() };
+                                        statement_index: bb_data.statements.len() };
                 bb_ctxt.on_operand(SK::CallFn, func, source);
                 for arg in args {
                     debug!("gather_moves Call on_operand {:?} {:?}", arg, source);
@@ -721,7 +745,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
     assert_eq!(path_map.len(), builder.pre_move_paths.len());
 
     let pre_move_paths = builder.pre_move_paths;
-    let move_paths: Vec<_> = pre_move_paths.into_iter()
+    let move_paths: IndexVec<_, _> = pre_move_paths.into_iter()
         .map(|p| p.into_move_path())
         .collect();
 
@@ -729,7 +753,7 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
         let mut seen: Vec<_> = move_paths.iter().map(|_| false).collect();
         for (j, &MoveOut { ref path, ref source }) in moves.iter().enumerate() {
             debug!("MovePathData moves[{}]: MoveOut {{ path: {:?} = {:?}, source: {:?} }}",
-                   j, path, move_paths[path.index()], source);
+                   j, path, move_paths[*path], source);
             seen[path.index()] = true;
         }
         for (j, path) in move_paths.iter().enumerate() {
@@ -751,9 +775,9 @@ fn gather_moves<'a, 'tcx>(mir: &Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MoveD
 
 struct BlockContext<'b, 'a: 'b, 'tcx: 'a> {
     _tcx: TyCtxt<'b, 'tcx, 'tcx>,
-    moves: &'b mut Vec<MoveOut>,
+    moves: &'b mut IndexVec<MoveOutIndex, MoveOut>,
     builder: MovePathDataBuilder<'a, 'tcx>,
-    path_map: &'b mut Vec<Vec<MoveOutIndex>>,
+    path_map: &'b mut IndexVec<MovePathIndex, Vec<MoveOutIndex>>,
     loc_map_bb: &'b mut Vec<Vec<MoveOutIndex>>,
 }
 
@@ -762,7 +786,7 @@ impl<'b, 'a: 'b, 'tcx: 'a> BlockContext<'b, 'a, 'tcx> {
                         stmt_kind: StmtKind,
                         lval: &Lvalue<'tcx>,
                         source: Location) {
-        let i = source.index;
+        let i = source.statement_index;
         let index = MoveOutIndex::new(self.moves.len());
 
         let path = self.builder.move_path_for(lval);
@@ -779,8 +803,8 @@ impl<'b, 'a: 'b, 'tcx: 'a> BlockContext<'b, 'a, 'tcx> {
         // this is actually a questionable assert; at the very
         // least, incorrect input code can probably cause it to
         // fire.
-        assert!(self.path_map[path.index()].iter().find(|idx| **idx == index).is_none());
-        self.path_map[path.index()].push(index);
+        assert!(self.path_map[path].iter().find(|idx| **idx == index).is_none());
+        self.path_map[path].push(index);
 
         debug_assert!(i < self.loc_map_bb.len());
         debug_assert!(self.loc_map_bb[i].iter().find(|idx| **idx == index).is_none());
@@ -19,7 +19,7 @@ use rustc::hir;
 use rustc::hir::intravisit::{FnKind};
 
 use rustc::mir::repr;
-use rustc::mir::repr::{BasicBlock, BasicBlockData, Mir, Statement, Terminator};
+use rustc::mir::repr::{BasicBlock, BasicBlockData, Location, Mir, Statement, Terminator};
 use rustc::session::Session;
 use rustc::ty::{self, TyCtxt};
 
@@ -27,7 +27,6 @@ mod abs_domain;
 pub mod elaborate_drops;
 mod dataflow;
 mod gather_moves;
-mod patch;
 // mod graphviz;
 
 use self::dataflow::{BitDenotation};
@@ -35,7 +34,7 @@ use self::dataflow::{DataflowOperator};
 use self::dataflow::{Dataflow, DataflowAnalysis, DataflowResults};
 use self::dataflow::{MaybeInitializedLvals, MaybeUninitializedLvals};
 use self::dataflow::{DefinitelyInitializedLvals};
-use self::gather_moves::{MoveData, MovePathIndex, Location};
+use self::gather_moves::{MoveData, MovePathIndex};
 use self::gather_moves::{MovePathContent, MovePathData};
 
 fn has_rustc_mir_with(attrs: &[ast::Attribute], name: &str) -> Option<P<MetaItem>> {
@@ -81,12 +80,20 @@ pub fn borrowck_mir<'a, 'tcx: 'a>(
     let move_data = MoveData::gather_moves(mir, tcx);
     let param_env = ty::ParameterEnvironment::for_item(tcx, id);
     let mdpe = MoveDataParamEnv { move_data: move_data, param_env: param_env };
+    let analyses = [MaybeInitializedLvals::name(),
+                    MaybeUninitializedLvals::name(),
+                    DefinitelyInitializedLvals::name()];
+    let analysis = graphviz_analysis(tcx.sess, attributes, &analyses);
+    let analysis = analysis.as_ref().map(|s| &s[..]);
     let flow_inits =
-        do_dataflow(tcx, mir, id, attributes, &mdpe, MaybeInitializedLvals::new(tcx, mir));
+        do_dataflow(tcx, mir, id, attributes, analysis, &mdpe,
+                    MaybeInitializedLvals::new(tcx, mir));
     let flow_uninits =
-        do_dataflow(tcx, mir, id, attributes, &mdpe, MaybeUninitializedLvals::new(tcx, mir));
+        do_dataflow(tcx, mir, id, attributes, analysis, &mdpe,
+                    MaybeUninitializedLvals::new(tcx, mir));
     let flow_def_inits =
-        do_dataflow(tcx, mir, id, attributes, &mdpe, DefinitelyInitializedLvals::new(tcx, mir));
+        do_dataflow(tcx, mir, id, attributes, analysis, &mdpe,
+                    DefinitelyInitializedLvals::new(tcx, mir));
 
     if has_rustc_mir_with(attributes, "rustc_peek_maybe_init").is_some() {
         dataflow::sanity_check_via_rustc_peek(bcx.tcx, mir, id, attributes, &mdpe, &flow_inits);
@@ -118,10 +125,42 @@ pub fn borrowck_mir<'a, 'tcx: 'a>(
     debug!("borrowck_mir done");
 }
 
+/// Returns the analysis that `borrowck_graphviz_analysis` restricts the
+/// rendering to, reporting an error if it does not name one of `analyses`.
+/// Nothing is rendered after such an error.
+fn graphviz_analysis(sess: &Session,
+                     attributes: &[ast::Attribute],
+                     analyses: &[&str]) -> Option<String> {
+    let item = match has_rustc_mir_with(attributes, "borrowck_graphviz_analysis") {
+        Some(item) => item,
+        None => return None,
+    };
+    let expected = analyses.iter()
+                           .map(|analysis| format!("`{}`", analysis))
+                           .collect::<Vec<_>>()
+                           .join(", ");
+    match item.value_str() {
+        Some(ref analysis) if analyses.contains(&&analysis[..]) => Some(analysis.to_string()),
+        Some(analysis) => {
+            sess.span_err(item.span,
+                          &format!("unknown analysis `{}` in borrowck_graphviz_analysis, \
+                                    expected one of {}", analysis, expected));
+            Some(analysis.to_string())
+        }
+        None => {
+            sess.span_err(item.span,
+                          &format!("borrowck_graphviz_analysis attribute requires \
+                                    one of {}", expected));
+            Some(String::new())
+        }
+    }
+}
+
 fn do_dataflow<'a, 'tcx, BD>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                              mir: &Mir<'tcx>,
                              node_id: ast::NodeId,
                              attributes: &[ast::Attribute],
+                             analysis: Option<&str>,
                              ctxt: &BD::Ctxt,
                              bd: BD) -> DataflowResults<BD>
     where BD: BitDenotation<Idx=MovePathIndex, Ctxt=MoveDataParamEnv<'tcx>> + DataflowOperator
@@ -142,10 +181,13 @@ fn do_dataflow<'a, 'tcx, BD>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         return None;
     };
 
-    let print_preflow_to =
-        name_found(tcx.sess, attributes, "borrowck_graphviz_preflow");
-    let print_postflow_to =
-        name_found(tcx.sess, attributes, "borrowck_graphviz_postflow");
+    let is_selected = analysis.map_or(true, |analysis| analysis == BD::name());
+    let (print_preflow_to, print_postflow_to) = if is_selected {
+        (name_found(tcx.sess, attributes, "borrowck_graphviz_preflow"),
+         name_found(tcx.sess, attributes, "borrowck_graphviz_postflow"))
+    } else {
+        (None, None)
+    };
 
     let mut mbcx = MirBorrowckCtxtPreDataflow {
         node_id: node_id,
@@ -367,11 +409,28 @@ fn drop_flag_effects_for_location<'a, 'tcx, F>(
     }
 
     let block = &mir[loc.block];
-    match block.statements.get(loc.index) {
+    match block.statements.get(loc.statement_index) {
         Some(stmt) => match stmt.kind {
             repr::StatementKind::SetDiscriminant{ .. } => {
                 span_bug!(stmt.source_info.span, "SetDiscrimant should not exist during borrowck");
             }
+            repr::StatementKind::Coverage { .. } => {
+                span_bug!(stmt.source_info.span, "Coverage should not exist during borrowck");
+            }
+            repr::StatementKind::SanitizerCheck { .. } => {
+                span_bug!(stmt.source_info.span,
+                          "SanitizerCheck should not exist during borrowck");
+            }
+            repr::StatementKind::Retag { .. } => {
+                span_bug!(stmt.source_info.span, "Retag should not exist during borrowck");
+            }
+            repr::StatementKind::Atomic { .. } => {
+                span_bug!(stmt.source_info.span, "Atomic should not exist during borrowck");
+            }
+            repr::StatementKind::Probe { .. } => {
+                span_bug!(stmt.source_info.span, "Probe should not exist during borrowck");
+            }
+            repr::StatementKind::Subtype { .. } => {}
             repr::StatementKind::Assign(ref lvalue, _) => {
                 debug!("drop_flag_effects: assignment {:?}", stmt);
                  on_all_children_bits(tcx, mir, move_data,
@@ -1194,7 +1194,9 @@ impl<'a, 'gcx, 'tcx> Delegate<'tcx> for MutationChecker<'a, 'gcx> {
 
 /// Forbids bindings in `@` patterns. This is necessary for memory safety,
 /// because of the way rvalues are handled in the borrow check. (See issue
-/// #14587.)
+/// #14587.) With `#![feature(bindings_after_at)]`, they are allowed when
+/// they and the `@` binding all bind by reference, and the MIR builder
+/// reports the `ref mut` bindings that alias another binding.
 fn check_legality_of_bindings_in_at_patterns(cx: &MatchCheckCtxt, pat: &Pat) {
     AtBindingPatternVisitor { cx: cx, bindings_allowed: true }.visit_pat(pat);
 }
@@ -1215,7 +1217,13 @@ impl<'a, 'b, 'tcx, 'v> Visitor<'v> for AtBindingPatternVisitor<'a, 'b, 'tcx> {
 
                 if subpat.is_some() {
                     let bindings_were_allowed = self.bindings_allowed;
-                    self.bindings_allowed = false;
+                    let mut by_ref = self.cx.tcx.sess.features.borrow().bindings_after_at;
+                    pat_bindings(pat, |bm, _, _, _| {
+                        if let hir::BindByValue(..) = bm {
+                            by_ref = false;
+                        }
+                    });
+                    self.bindings_allowed = bindings_were_allowed && by_ref;
                     intravisit::walk_pat(self, pat);
                     self.bindings_allowed = bindings_were_allowed;
                 }
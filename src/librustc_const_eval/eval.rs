@@ -12,9 +12,11 @@
 
 use rustc::middle::const_val::ConstVal::*;
 use rustc::middle::const_val::ConstVal;
+pub use rustc::middle::const_val::EvalHint;
 use self::ErrKind::*;
 use self::EvalHint::*;
 
+use rustc::dep_graph::DepNode;
 use rustc::hir::map as ast_map;
 use rustc::hir::map::blocks::FnLikeNode;
 use rustc::middle::cstore::InlinedItem;
@@ -547,44 +549,34 @@ pub type CastResult = Result<ConstVal, ErrKind>;
 // disaster.  That said, it's not clear how to fix ast_ty_to_ty
 // to avoid the ordering issue.
 
-/// Hint to determine how to evaluate constant expressions which
-/// might not be type-checked.
-#[derive(Copy, Clone, Debug)]
-pub enum EvalHint<'tcx> {
-    /// We have a type-checked expression.
-    ExprTypeChecked,
-    /// We have an expression which hasn't been type-checked, but we have
-    /// an idea of what the type will be because of the context. For example,
-    /// the length of an array is always `usize`. (This is referred to as
-    /// a hint because it isn't guaranteed to be consistent with what
-    /// type-checking would compute.)
-    UncheckedExprHint(Ty<'tcx>),
-    /// We have an expression which has not yet been type-checked, and
-    /// and we have no clue what the type will be.
-    UncheckedExprNoHint,
-}
-
-impl<'tcx> EvalHint<'tcx> {
-    fn erase_hint(&self) -> EvalHint<'tcx> {
-        match *self {
-            ExprTypeChecked => ExprTypeChecked,
-            UncheckedExprHint(_) | UncheckedExprNoHint => UncheckedExprNoHint,
-        }
-    }
-    fn checked_or(&self, ty: Ty<'tcx>) -> EvalHint<'tcx> {
-        match *self {
-            ExprTypeChecked => ExprTypeChecked,
-            _ => UncheckedExprHint(ty),
-        }
-    }
-}
-
 macro_rules! signal {
     ($e:expr, $exn:expr) => {
         return Err(ConstEvalErr { span: $e.span, kind: $exn })
     }
 }
 
+/// Evaluates the body `expr` of the constant `def_id`, referenced with
+/// `ty_hint`. A successful result is recorded in `tcx.const_eval_cache`
+/// and reused by later references with the same substitutions and hint
+/// instead of evaluating the body again.
+fn eval_referenced_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   def_id: DefId,
+                                   substs: Option<&'tcx subst::Substs<'tcx>>,
+                                   ty_hint: EvalHint<'tcx>,
+                                   expr: &Expr,
+                                   item_hint: EvalHint<'tcx>) -> EvalResult {
+    let result = {
+        let _task = tcx.dep_graph.in_task(DepNode::ConstEval(def_id));
+        let result = eval_const_expr_partial(tcx, expr, item_hint, None);
+        if let Ok(ref val) = result {
+            tcx.const_eval_cache.borrow_mut().insert((def_id, substs, ty_hint), val.clone());
+        }
+        result
+    };
+    tcx.dep_graph.read(DepNode::ConstEval(def_id));
+    result
+}
+
 /// Evaluate a constant expression in a context where the expression isn't
 /// guaranteed to be evaluatable. `ty_hint` is usually ExprTypeChecked,
 /// but a few places need to evaluate constants during type-checking, like
@@ -790,12 +782,16 @@ pub fn eval_const_expr_partial<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                   } else {
                       None
                   };
-                  if let Some((expr, ty)) = lookup_const_by_id(tcx, def_id, substs) {
+                  let cached = tcx.const_eval_cache.borrow()
+                                  .get(&(def_id, substs, ty_hint)).cloned();
+                  if let Some(val) = cached {
+                      val
+                  } else if let Some((expr, ty)) = lookup_const_by_id(tcx, def_id, substs) {
                       let item_hint = match ty {
                           Some(ty) => ty_hint.checked_or(ty),
                           None => ty_hint,
                       };
-                      match eval_const_expr_partial(tcx, expr, item_hint, None) {
+                      match eval_referenced_const(tcx, def_id, substs, ty_hint, expr, item_hint) {
                           Ok(val) => val,
                           Err(err) => {
                               debug!("bad reference: {:?}, {:?}", err.description(), err.span);
@@ -1159,11 +1155,11 @@ fn cast_const_float<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
             cast_const_int(tcx, i, ty)
         }
         ty::TyFloat(ast::FloatTy::F64) => Ok(Float(F64(match val {
-            F32(f) => f as f64,
+            F32(f) => f32_to_f64(f),
             FInfer { f64: f, .. } | F64(f) => f
         }))),
         ty::TyFloat(ast::FloatTy::F32) => Ok(Float(F32(match val {
-            F64(f) => f as f32,
+            F64(f) => f64_to_f32(f),
             FInfer { f32: f, .. } | F32(f) => f
         }))),
         _ => Err(CannotCast),
@@ -13,7 +13,41 @@ use std::hash;
 use std::mem::transmute;
 
 use super::err::*;
+use super::softfloat::{SINGLE, DOUBLE};
 
+/// Applies `op` to the bits of `a` and `b` in the binary32 format.
+fn with_f32_bits<F: FnOnce(u64, u64) -> u64>(a: f32, b: f32, op: F) -> f32 {
+    let (a, b) = unsafe { (transmute::<_, u32>(a), transmute::<_, u32>(b)) };
+    unsafe { transmute::<u32, f32>(op(a as u64, b as u64) as u32) }
+}
+
+/// Applies `op` to the bits of `a` and `b` in the binary64 format.
+fn with_f64_bits<F: FnOnce(u64, u64) -> u64>(a: f64, b: f64, op: F) -> f64 {
+    let (a, b) = unsafe { (transmute::<_, u64>(a), transmute::<_, u64>(b)) };
+    unsafe { transmute::<u64, f64>(op(a, b)) }
+}
+
+/// Converts `f` to `f32`, rounding to nearest and canonicalizing NaNs,
+/// independently of the host's floating-point unit.
+pub fn f64_to_f32(f: f64) -> f32 {
+    with_f32_bits(0.0, 0.0, |_, _| {
+        DOUBLE.convert(unsafe { transmute::<_, u64>(f) }, SINGLE)
+    })
+}
+
+/// Converts `f` to `f64`, canonicalizing NaNs.
+pub fn f32_to_f64(f: f32) -> f64 {
+    with_f64_bits(0.0, 0.0, |_, _| {
+        SINGLE.convert(unsafe { transmute::<_, u32>(f) } as u64, DOUBLE)
+    })
+}
+
+/// A floating-point constant.
+///
+/// All arithmetic on these is implemented in software (see the
+/// `softfloat` module), so the results are bit-for-bit identical on
+/// every host; in particular, any operation producing a NaN produces
+/// the canonical quiet NaN, with positive sign and no payload.
 #[derive(Copy, Clone, Debug, RustcEncodable, RustcDecodable)]
 pub enum ConstFloat {
     F32(f32),
@@ -47,31 +81,22 @@ impl ConstFloat {
 
     /// Compares the values if they are of the same type
     pub fn try_cmp(self, rhs: Self) -> Result<Ordering, ConstMathErr> {
+        // This is pretty bad but it is the existing behavior:
+        // NaN compares greater than everything.
         match (self, rhs) {
             (F64(a), F64(b)) |
             (F64(a), FInfer { f64: b, .. }) |
             (FInfer { f64: a, .. }, F64(b)) |
             (FInfer { f64: a, .. }, FInfer { f64: b, .. })  => {
-                // This is pretty bad but it is the existing behavior.
-                Ok(if a == b {
-                    Ordering::Equal
-                } else if a < b {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                })
+                let (a, b) = unsafe { (transmute::<_, u64>(a), transmute::<_, u64>(b)) };
+                Ok(DOUBLE.partial_cmp(a, b).unwrap_or(Ordering::Greater))
             }
 
             (F32(a), F32(b)) |
             (F32(a), FInfer { f32: b, .. }) |
             (FInfer { f32: a, .. }, F32(b)) => {
-                Ok(if a == b {
-                    Ordering::Equal
-                } else if a < b {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                })
+                let (a, b) = unsafe { (transmute::<_, u32>(a), transmute::<_, u32>(b)) };
+                Ok(SINGLE.partial_cmp(a as u64, b as u64).unwrap_or(Ordering::Greater))
             }
 
             _ => Err(CmpBetweenUnequalTypes),
@@ -130,19 +155,21 @@ macro_rules! derive_binop {
         impl ::std::ops::$op for ConstFloat {
             type Output = Result<Self, ConstMathErr>;
             fn $func(self, rhs: Self) -> Result<Self, ConstMathErr> {
+                let op32 = |a, b| with_f32_bits(a, b, |a, b| SINGLE.$func(a, b));
+                let op64 = |a, b| with_f64_bits(a, b, |a, b| DOUBLE.$func(a, b));
                 match (self, rhs) {
                     (F32(a), F32(b)) |
                     (F32(a), FInfer { f32: b, .. }) |
-                    (FInfer { f32: a, .. }, F32(b)) => Ok(F32(a.$func(b))),
+                    (FInfer { f32: a, .. }, F32(b)) => Ok(F32(op32(a, b))),
 
                     (F64(a), F64(b)) |
                     (FInfer { f64: a, .. }, F64(b)) |
-                    (F64(a), FInfer { f64: b, .. }) => Ok(F64(a.$func(b))),
+                    (F64(a), FInfer { f64: b, .. }) => Ok(F64(op64(a, b))),
 
                     (FInfer { f32: a32, f64: a64 },
                      FInfer { f32: b32, f64: b64 }) => Ok(FInfer {
-                        f32: a32.$func(b32),
-                        f64: a64.$func(b64)
+                        f32: op32(a32, b32),
+                        f64: op64(a64, b64)
                     }),
 
                     _ => Err(UnequalTypes(Op::$op)),
@@ -161,12 +188,14 @@ derive_binop!(Rem, rem);
 impl ::std::ops::Neg for ConstFloat {
     type Output = Self;
     fn neg(self) -> Self {
+        let neg32 = |f| with_f32_bits(f, f, |f, _| SINGLE.neg(f));
+        let neg64 = |f| with_f64_bits(f, f, |f, _| DOUBLE.neg(f));
         match self {
-            F32(f) => F32(-f),
-            F64(f) => F64(-f),
+            F32(f) => F32(neg32(f)),
+            F64(f) => F64(neg64(f)),
             FInfer { f32, f64 } => FInfer {
-                f32: -f32,
-                f64: -f64
+                f32: neg32(f32),
+                f64: neg64(f64)
             }
         }
     }
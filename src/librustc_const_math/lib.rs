@@ -33,6 +33,7 @@
 extern crate serialize as rustc_serialize; // used by deriving
 
 mod float;
+mod softfloat;
 mod int;
 mod us;
 mod is;
@@ -0,0 +1,394 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Software implementation of IEEE 754 binary32/binary64 arithmetic.
+//!
+//! Constant evaluation must not depend on the floating-point unit of
+//! the host the compiler happens to run on (e.g. x87 keeps intermediate
+//! results in extended precision), so all arithmetic on `ConstFloat` is
+//! done here, on the raw bit patterns, using only integer operations.
+//!
+//! All operations round to nearest, ties to even, and never raise
+//! exceptions. NaN results are canonicalized:
+//!
+//! - every operation that produces a NaN, including operations on NaN
+//!   inputs and negation of a NaN, produces the canonical quiet NaN:
+//!   positive sign, only the most significant mantissa bit set
+//!   (`0x7fc00000` for `f32`, `0x7ff8000000000000` for `f64`);
+//! - NaN payloads and signs of the inputs are never propagated.
+//!
+//! This means the bits of a constant like `0.0 / 0.0` are the same on
+//! every host, even though they may differ from what the same
+//! expression produces at runtime on a given target.
+
+use std::cmp::Ordering;
+
+/// Layout of an IEEE 754 binary interchange format.
+#[derive(Copy, Clone)]
+pub struct Format {
+    /// Number of explicitly stored mantissa bits.
+    mant_bits: u32,
+    /// Number of exponent bits.
+    exp_bits: u32,
+}
+
+pub const SINGLE: Format = Format { mant_bits: 23, exp_bits: 8 };
+pub const DOUBLE: Format = Format { mant_bits: 52, exp_bits: 11 };
+
+/// An unpacked, finite, non-zero value `sig * 2^exp`.
+#[derive(Copy, Clone)]
+struct Finite {
+    sig: u64,
+    exp: i32,
+}
+
+enum Class {
+    Nan,
+    Inf,
+    Zero,
+    Finite(Finite),
+}
+
+impl Format {
+    fn bias(self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    fn sign_bit(self) -> u64 {
+        1 << (self.mant_bits + self.exp_bits)
+    }
+
+    fn exp_mask(self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    fn mant_mask(self) -> u64 {
+        (1 << self.mant_bits) - 1
+    }
+
+    /// The exponent of the smallest subnormal, i.e. the exponent
+    /// of the least significant mantissa bit of a subnormal.
+    fn min_exp(self) -> i32 {
+        1 - self.bias() - self.mant_bits as i32
+    }
+
+    pub fn canonical_nan(self) -> u64 {
+        (self.exp_mask() << self.mant_bits) | (1 << (self.mant_bits - 1))
+    }
+
+    fn inf(self, sign: bool) -> u64 {
+        self.signed(sign, self.exp_mask() << self.mant_bits)
+    }
+
+    fn zero(self, sign: bool) -> u64 {
+        self.signed(sign, 0)
+    }
+
+    fn signed(self, sign: bool, bits: u64) -> u64 {
+        if sign { bits | self.sign_bit() } else { bits }
+    }
+
+    fn sign(self, bits: u64) -> bool {
+        bits & self.sign_bit() != 0
+    }
+
+    fn classify(self, bits: u64) -> Class {
+        let exp = (bits >> self.mant_bits) & self.exp_mask();
+        let mant = bits & self.mant_mask();
+        if exp == self.exp_mask() {
+            if mant == 0 { Class::Inf } else { Class::Nan }
+        } else if exp == 0 {
+            if mant == 0 {
+                Class::Zero
+            } else {
+                Class::Finite(Finite { sig: mant, exp: self.min_exp() })
+            }
+        } else {
+            Class::Finite(Finite {
+                sig: mant | (1 << self.mant_bits),
+                exp: self.min_exp() + exp as i32 - 1,
+            })
+        }
+    }
+
+    pub fn is_nan(self, bits: u64) -> bool {
+        match self.classify(bits) {
+            Class::Nan => true,
+            _ => false,
+        }
+    }
+
+    /// Rounds `(sig + ε) * 2^exp` to the nearest representable value,
+    /// where `ε` is some value in `(0, 1)` if `sticky` is set and 0
+    /// otherwise. If `sticky` is set, `sig` must have at least two bits
+    /// more than the precision of the format, so that `ε` only ever
+    /// affects the direction of ties.
+    fn round(self, sign: bool, sig: u64, exp: i32, sticky: bool) -> u64 {
+        if sig == 0 {
+            assert!(!sticky);
+            return self.zero(sign);
+        }
+        let msb = 63 - sig.leading_zeros() as i32;
+        let mant_bits = self.mant_bits as i32;
+
+        // The exponent of the least significant bit of the result.
+        let quantum = ::std::cmp::max(msb + exp - mant_bits, self.min_exp());
+        let shift = quantum - exp;
+
+        let (mut result, mut quantum) = if shift <= 0 {
+            assert!(!sticky);
+            (sig << -shift as u32, quantum)
+        } else if shift > 64 {
+            // Less than half of the smallest subnormal.
+            (0, quantum)
+        } else {
+            let (result, rem) = if shift == 64 {
+                (0, sig)
+            } else {
+                (sig >> shift as u32, sig & ((1 << shift as u32) - 1))
+            };
+            let half = 1 << (shift - 1) as u32;
+            let round_up = match rem.cmp(&half) {
+                Ordering::Less => false,
+                Ordering::Greater => true,
+                Ordering::Equal => sticky || result & 1 == 1,
+            };
+            if round_up { (result + 1, quantum) } else { (result, quantum) }
+        };
+
+        // Rounding may carry into a new bit.
+        if result == 1 << (mant_bits + 1) {
+            result >>= 1;
+            quantum += 1;
+        }
+
+        if result < 1 << mant_bits {
+            // Subnormal or zero; the exponent field stays 0.
+            return self.signed(sign, result);
+        }
+        let biased_exp = quantum - self.min_exp() + 1;
+        if biased_exp >= self.exp_mask() as i32 {
+            return self.inf(sign);
+        }
+        self.signed(sign, ((biased_exp as u64) << self.mant_bits) |
+                          (result & self.mant_mask()))
+    }
+
+    pub fn neg(self, a: u64) -> u64 {
+        match self.classify(a) {
+            Class::Nan => self.canonical_nan(),
+            _ => a ^ self.sign_bit(),
+        }
+    }
+
+    pub fn add(self, a: u64, b: u64) -> u64 {
+        let (sa, sb) = (self.sign(a), self.sign(b));
+        let (fa, fb) = match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) => return self.canonical_nan(),
+            (Class::Inf, Class::Inf) => {
+                return if sa == sb { a } else { self.canonical_nan() };
+            }
+            (Class::Inf, _) => return a,
+            (_, Class::Inf) => return b,
+            (Class::Zero, Class::Zero) => return self.zero(sa && sb),
+            (Class::Zero, _) => return b,
+            (_, Class::Zero) => return a,
+            (Class::Finite(fa), Class::Finite(fb)) => (fa, fb),
+        };
+
+        // Move the most significant bit of both to bit 62, leaving
+        // room for the carry of the addition.
+        let (fa, fb) = (normalize(fa, 62), normalize(fb, 62));
+
+        // Make `a` the operand with the larger magnitude.
+        let (sa, fa, sb, fb) = if (fa.exp, fa.sig) < (fb.exp, fb.sig) {
+            (sb, fb, sa, fa)
+        } else {
+            (sa, fa, sb, fb)
+        };
+
+        let (b_sig, sticky) = shift_right_sticky(fb.sig, (fa.exp - fb.exp) as u32);
+        if sa == sb {
+            self.round(sa, fa.sig + b_sig, fa.exp, sticky)
+        } else if sticky {
+            // The bits shifted out of `b` borrow one from the result.
+            self.round(sa, fa.sig - b_sig - 1, fa.exp, true)
+        } else if fa.sig == b_sig {
+            // Exact cancellation always gives +0 when rounding to nearest.
+            self.zero(false)
+        } else {
+            self.round(sa, fa.sig - b_sig, fa.exp, false)
+        }
+    }
+
+    pub fn sub(self, a: u64, b: u64) -> u64 {
+        if self.is_nan(b) {
+            return self.canonical_nan();
+        }
+        self.add(a, b ^ self.sign_bit())
+    }
+
+    pub fn mul(self, a: u64, b: u64) -> u64 {
+        let sign = self.sign(a) != self.sign(b);
+        let (fa, fb) = match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) |
+            (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf) => {
+                return self.canonical_nan();
+            }
+            (Class::Inf, _) | (_, Class::Inf) => return self.inf(sign),
+            (Class::Zero, _) | (_, Class::Zero) => return self.zero(sign),
+            (Class::Finite(fa), Class::Finite(fb)) => (fa, fb),
+        };
+
+        let (hi, lo) = mul_wide(fa.sig, fb.sig);
+        let exp = fa.exp + fb.exp;
+        if hi == 0 {
+            self.round(sign, lo, exp, false)
+        } else {
+            // Keep the 64 most significant bits, and whether any
+            // of the remaining ones were set.
+            let extra = 64 - hi.leading_zeros();
+            let sig = (hi << (64 - extra)) | (lo >> extra);
+            let sticky = lo & ((1 << extra) - 1) != 0;
+            self.round(sign, sig, exp + extra as i32, sticky)
+        }
+    }
+
+    pub fn div(self, a: u64, b: u64) -> u64 {
+        let sign = self.sign(a) != self.sign(b);
+        let (fa, fb) = match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) |
+            (Class::Inf, Class::Inf) | (Class::Zero, Class::Zero) => {
+                return self.canonical_nan();
+            }
+            (Class::Inf, _) | (_, Class::Zero) => return self.inf(sign),
+            (_, Class::Inf) | (Class::Zero, _) => return self.zero(sign),
+            (Class::Finite(fa), Class::Finite(fb)) => (fa, fb),
+        };
+
+        // With both significands in `[2^52, 2^53)`, the quotient of
+        // the significands is in `(1/2, 2)`, and each step of the long
+        // division below produces exactly one bit of it.
+        let (fa, fb) = (normalize(fa, 52), normalize(fb, 52));
+        const QUOTIENT_BITS: u32 = 62;
+        let mut rem = fa.sig;
+        let mut quot = 0u64;
+        for _ in 0..QUOTIENT_BITS {
+            quot <<= 1;
+            if rem >= fb.sig {
+                rem -= fb.sig;
+                quot |= 1;
+            }
+            rem <<= 1;
+        }
+        self.round(sign, quot, fa.exp - fb.exp - (QUOTIENT_BITS - 1) as i32, rem != 0)
+    }
+
+    /// The remainder of truncating division, like C's `fmod`.
+    /// The result is always exact and has the sign of `a`.
+    pub fn rem(self, a: u64, b: u64) -> u64 {
+        let sign = self.sign(a);
+        let (fa, fb) = match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) |
+            (Class::Inf, _) | (_, Class::Zero) => return self.canonical_nan(),
+            (Class::Zero, _) | (_, Class::Inf) => return a,
+            (Class::Finite(fa), Class::Finite(fb)) => (fa, fb),
+        };
+
+        let (fa, fb) = (normalize(fa, 52), normalize(fb, 52));
+        if (fa.exp, fa.sig) < (fb.exp, fb.sig) {
+            return a;
+        }
+
+        // Here `fa.exp >= fb.exp`, so `a` is `fa.sig * 2^(fa.exp - fb.exp)`
+        // in units of `2^fb.exp`. Reduce it modulo `fb.sig` a few bits at
+        // a time, keeping the remainder below 2^53.
+        let mut rem = fa.sig % fb.sig;
+        let mut shift = (fa.exp - fb.exp) as u32;
+        while shift > 0 {
+            let step = ::std::cmp::min(shift, 10);
+            rem = (rem << step) % fb.sig;
+            shift -= step;
+        }
+        self.round(sign, rem, fb.exp, false)
+    }
+
+    /// Converts `bits` in this format to the format `to`.
+    pub fn convert(self, bits: u64, to: Format) -> u64 {
+        let sign = self.sign(bits);
+        match self.classify(bits) {
+            Class::Nan => to.canonical_nan(),
+            Class::Inf => to.inf(sign),
+            Class::Zero => to.zero(sign),
+            Class::Finite(f) => {
+                // Leave enough headroom below the precision of `to`
+                // for the rounding bits.
+                let f = normalize(f, 62);
+                to.round(sign, f.sig, f.exp, false)
+            }
+        }
+    }
+
+    /// Compares two values, returning `None` if either is NaN.
+    pub fn partial_cmp(self, a: u64, b: u64) -> Option<Ordering> {
+        let (sa, sb) = (self.sign(a), self.sign(b));
+        let (mag_a, mag_b) = (a & !self.sign_bit(), b & !self.sign_bit());
+        match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) => None,
+            (Class::Zero, Class::Zero) => Some(Ordering::Equal),
+            // For values of the same sign, the order of the bit patterns
+            // is the order of the magnitudes.
+            _ if sa == sb && !sa => Some(mag_a.cmp(&mag_b)),
+            _ if sa == sb => Some(mag_b.cmp(&mag_a)),
+            _ if sa => Some(Ordering::Less),
+            _ => Some(Ordering::Greater),
+        }
+    }
+}
+
+/// Shifts `f` so that its most significant bit is at bit `msb`.
+fn normalize(f: Finite, msb: i32) -> Finite {
+    let shift = msb - (63 - f.sig.leading_zeros() as i32);
+    if shift >= 0 {
+        Finite { sig: f.sig << shift as u32, exp: f.exp - shift }
+    } else {
+        assert!(f.sig.trailing_zeros() as i32 >= -shift);
+        Finite { sig: f.sig >> -shift as u32, exp: f.exp - shift }
+    }
+}
+
+/// Shifts `x` right by `shift` bits, also returning whether
+/// any of the bits shifted out were set.
+fn shift_right_sticky(x: u64, shift: u32) -> (u64, bool) {
+    if shift == 0 {
+        (x, false)
+    } else if shift >= 64 {
+        (0, x != 0)
+    } else {
+        (x >> shift, x & ((1 << shift) - 1) != 0)
+    }
+}
+
+/// Full 128-bit product of `a` and `b`, as `(high, low)` halves.
+fn mul_wide(a: u64, b: u64) -> (u64, u64) {
+    const MASK: u64 = 0xffff_ffff;
+    let (a_hi, a_lo) = (a >> 32, a & MASK);
+    let (b_hi, b_lo) = (b >> 32, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 32) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (lo_lo & MASK) | (mid << 32);
+    let hi = hi_hi + (hi_lo >> 32) + (lo_hi >> 32) + (mid >> 32);
+    (hi, lo)
+}
@@ -11,6 +11,7 @@
 use rustc::hir;
 use rustc::hir::{map as hir_map, FreevarMap, TraitMap};
 use rustc::hir::def::DefMap;
+use rustc::hir::def_id::DefId;
 use rustc::hir::lowering::lower_crate;
 use rustc_mir as mir;
 use rustc::mir::mir_map::MirMap;
@@ -202,7 +203,8 @@ pub fn compile_input(sess: &Session,
             }
             let trans = phase_4_translate_to_llvm(tcx,
                                                   mir_map.unwrap(),
-                                                  analysis);
+                                                  analysis,
+                                                  &outputs);
 
             if log_enabled!(::log::INFO) {
                 println!("Post-trans");
@@ -902,24 +904,42 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
              "rvalue checking",
              || rvalues::check_crate(tcx));
 
+        // The MIR of items that MIR construction or the MIR passes buffer
+        // lints for must not be cached, the lints would be lost.
+        let lints_before_mir = rustc_incremental::buffered_lints(sess);
+
+        let cached_mir_items =
+            time(time_passes,
+                 "looking up cached MIR",
+                 || rustc_incremental::cached_mir_items(tcx));
+
         let mut mir_map =
             time(time_passes,
                  "MIR dump",
-                 || mir::mir_map::build_mir_for_crate(tcx));
+                 || mir::mir_map::build_mir_for_crate(tcx, &cached_mir_items));
 
         time(time_passes, "MIR passes", || {
             let mut passes = sess.mir_passes.borrow_mut();
             // Push all the built-in passes.
-            passes.push_hook(box mir::transform::dump_mir::DumpMir);
+            if sess.opts.debugging_opts.mir_provenance {
+                passes.push_hook(box mir::transform::mir_provenance::TrackProvenance);
+            }
+            passes.push_hook(box mir::transform::dump_mir::DumpMir::new());
+            if sess.opts.debugging_opts.mir_stats {
+                passes.push_hook(box mir::transform::mir_stats::CollectStats);
+            }
             passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("initial"));
             passes.push_pass(box mir::transform::qualify_consts::QualifyAndPromoteConstants);
+            passes.push_pass(box mir::transform::simd_intrinsics::SimdIntrinsics);
             passes.push_pass(box mir::transform::type_check::TypeckMir);
+            passes.push_pass(box mir::transform::unused_must_use::UnusedMustUse);
             passes.push_pass(
                 box mir::transform::simplify_branches::SimplifyBranches::new("initial"));
             passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("qualify-consts"));
             // And run everything.
             passes.run_passes(tcx, &mut mir_map);
         });
+        rustc_incremental::record_mir_lints(sess, &lints_before_mir);
 
         time(time_passes,
              "borrow checking",
@@ -970,7 +990,8 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
 /// Run the translation phase to LLVM, after which the AST and analysis can
 pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                            mut mir_map: MirMap<'tcx>,
-                                           analysis: ty::CrateAnalysis)
+                                           analysis: ty::CrateAnalysis,
+                                           outputs: &OutputFilenames)
                                            -> trans::CrateTranslation {
     let time_passes = tcx.sess.time_passes();
 
@@ -982,7 +1003,13 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     // to LLVM code.
     time(time_passes, "Prepare MIR codegen passes", || {
         let mut passes = ::rustc::mir::transform::Passes::new();
-        passes.push_hook(box mir::transform::dump_mir::DumpMir);
+        if tcx.sess.opts.debugging_opts.mir_provenance {
+            passes.push_hook(box mir::transform::mir_provenance::TrackProvenance);
+        }
+        passes.push_hook(box mir::transform::dump_mir::DumpMir::new());
+        if tcx.sess.opts.debugging_opts.mir_stats {
+            passes.push_hook(box mir::transform::mir_stats::CollectStats);
+        }
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
         passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("no-landing-pads"));
 
@@ -991,16 +1018,94 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
         passes.push_pass(box borrowck::ElaborateDrops);
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
+        passes.push_pass(box mir::transform::atomic_intrinsics::AtomicIntrinsics);
+        passes.push_pass(box mir::transform::fold_layout_calls::FoldLayoutCalls);
+        passes.push_pass(box mir::transform::thread_switches::ThreadSwitches);
         passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("elaborate-drops"));
+        if tcx.sess.opts.debugging_opts.check_box_from_raw {
+            passes.push_pass(box mir::transform::check_box_from_raw::CheckBoxFromRaw);
+        }
+        passes.push_pass(box mir::transform::abort_unwinding::AbortUnwinding);
 
         passes.push_pass(box mir::transform::deaggregator::Deaggregator);
 
+        if tcx.sess.opts.debugging_opts.instrument_coverage {
+            passes.push_pass(box mir::transform::instrument_coverage::InstrumentCoverage);
+        }
+
+        if tcx.sess.opts.debugging_opts.sanitizer.is_some() {
+            passes.push_pass(box mir::transform::instrument_sanitizer::InstrumentSanitizer);
+        }
+
+        if tcx.sess.opts.debugging_opts.mir_retag {
+            passes.push_pass(box mir::transform::instrument_retag::InstrumentRetag);
+        }
+
+        if tcx.sess.opts.debugging_opts.mir_instrument_calls.is_some() {
+            passes.push_pass(box mir::transform::instrument_calls::InstrumentCalls);
+        }
+
         passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
         passes.push_pass(box mir::transform::dump_mir::Marker("PreTrans"));
 
         passes.run_passes(tcx, &mut mir_map);
     });
 
+    let cached_mir_items =
+        time(time_passes,
+             "loading cached MIR",
+             || rustc_incremental::load_cached_mir(tcx, &mut mir_map));
+
+    time(time_passes,
+         "serialize MIR cache",
+         || rustc_incremental::save_mir_cache(tcx, &mir_map, &cached_mir_items));
+
+    if let Some(limit) = tcx.sess.opts.debugging_opts.mir_size_limit {
+        ::rustc::mir::stats::check_size_limit(tcx, &mir_map, limit);
+    }
+
+    if tcx.sess.opts.debugging_opts.mir_stats {
+        tcx.sess.mir_stats.borrow().print();
+    }
+
+    if tcx.sess.opts.debugging_opts.mir_unwind_stats {
+        ::rustc::mir::stats::print_unwind_stats(tcx, &mir_map);
+    }
+
+    if tcx.sess.opts.debugging_opts.instrument_coverage {
+        let path = outputs.with_extension("covmap.json");
+        let result = fs::File::create(&path).and_then(|mut file| {
+            tcx.sess.coverage_map.borrow().write(&mut file)
+        });
+        if let Err(e) = result {
+            tcx.sess.err(&format!("error writing coverage map to {}: {}", path.display(), e));
+        }
+    }
+
+    if tcx.sess.opts.debugging_opts.dump_mir_callgraph {
+        time(time_passes,
+             "MIR call graph dump",
+             || mir::callgraph::dump_callgraph(tcx, &mir_map));
+    }
+
+    if tcx.sess.opts.output_types.contains_key(&OutputType::MirJson) {
+        time(time_passes, "MIR json output", || {
+            write_mir_output(tcx, &mir_map, outputs, OutputType::MirJson, |def_ids, path| {
+                fs::File::create(path).and_then(|mut file| {
+                    mir::json::write_mir_json(tcx, def_ids.into_iter(), &mir_map, &mut file)
+                })
+            })
+        });
+    }
+
+    if tcx.sess.opts.output_types.contains_key(&OutputType::MirHtml) {
+        time(time_passes, "MIR html output", || {
+            write_mir_output(tcx, &mir_map, outputs, OutputType::MirHtml, |def_ids, path| {
+                mir::html::write_mir_html(tcx, def_ids.into_iter(), &mir_map, path)
+            })
+        });
+    }
+
     let translation =
         time(time_passes,
              "translation",
@@ -1069,6 +1174,25 @@ fn escape_dep_filename(filename: &str) -> String {
     filename.replace(" ", "\\ ")
 }
 
+/// Write the MIR of every body in the crate, sorted by `DefId`, to the
+/// output path for `output_type` with `write`.
+fn write_mir_output<'a, 'tcx, F>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 mir_map: &MirMap<'tcx>,
+                                 outputs: &OutputFilenames,
+                                 output_type: OutputType,
+                                 write: F)
+    where F: FnOnce(Vec<DefId>, &Path) -> io::Result<()>
+{
+    let path = outputs.path(output_type);
+    let mut def_ids = mir_map.map.keys();
+    def_ids.sort();
+    let result = write(def_ids, &path);
+    if let Err(e) = result {
+        tcx.sess.fatal(&format!("error writing --emit={} output to {}: {}",
+                                output_type.shorthand(), path.display(), e));
+    }
+}
+
 fn write_out_deps(sess: &Session, outputs: &OutputFilenames, crate_name: &str) {
     let mut out_filenames = Vec::new();
     for output_type in sess.opts.output_types.keys() {
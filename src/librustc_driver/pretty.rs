@@ -965,13 +965,15 @@ fn print_with_analysis<'tcx, 'a: 'tcx>(sess: &'a Session,
                             _ => unreachable!(),
                         }?;
                     } else {
+                        let mut def_ids = mir_map.map.keys();
+                        def_ids.sort();
                         match ppm {
                             PpmMir => write_mir_pretty(tcx,
-                                                       mir_map.map.keys().into_iter(),
+                                                       def_ids.into_iter(),
                                                        &mir_map,
                                                        &mut out),
                             PpmMirCFG => write_mir_graphviz(tcx,
-                                                            mir_map.map.keys().into_iter(),
+                                                            def_ids.into_iter(),
                                                             &mir_map,
                                                             &mut out),
                             _ => unreachable!(),
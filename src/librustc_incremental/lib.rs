@@ -27,6 +27,7 @@ extern crate graphviz;
 extern crate rbml;
 #[macro_use] extern crate rustc;
 extern crate rustc_data_structures;
+extern crate rustc_metadata;
 extern crate serialize as rustc_serialize;
 
 #[macro_use] extern crate log;
@@ -39,7 +40,12 @@ mod persist;
 
 pub use assert_dep_graph::assert_dep_graph;
 pub use calculate_svh::SvhCalculate;
+pub use persist::buffered_lints;
+pub use persist::cached_mir_items;
+pub use persist::load_cached_mir;
 pub use persist::load_dep_graph;
+pub use persist::record_mir_lints;
+pub use persist::save_mir_cache;
 pub use persist::save_dep_graph;
 pub use persist::save_trans_partition;
 pub use persist::save_work_products;
@@ -11,7 +11,7 @@
 //! The data that we will serialize and deserialize.
 
 use rustc::dep_graph::{DepNode, WorkProduct, WorkProductId};
-use rustc::hir::def_id::DefIndex;
+use rustc::hir::def_id::{DefId, DefIndex};
 use std::sync::Arc;
 
 use super::directory::DefPathIndex;
@@ -105,3 +105,63 @@ pub struct SerializedMetadataHash {
     /// the hash itself, computed by `calculate_item_hash`
     pub hash: u64,
 }
+
+/// Data for reusing the MIR of items in the **current crate** when it
+/// gets recompiled, see `mir_cache.rs`. The encoded MIR itself follows
+/// this in the same file.
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct SerializedMirCache {
+    /// Every def-id that the encoded MIR refers to, as it was numbered in
+    /// the compilation that encoded it, along with its def-path. Since
+    /// def-ids are encoded as they are, they have to be mapped to the
+    /// def-ids that these paths retrace to before the MIR can be used.
+    pub def_ids: Vec<(DefId, DefPathIndex)>,
+
+    /// The source files that the spans in the encoded MIR point into.
+    pub files: Vec<SerializedFileMap>,
+
+    pub entries: Vec<SerializedMirEntry>,
+
+    /// A hash of the encoded MIR. It goes into the input hash of the
+    /// work-product of every entry, so that an entry is only reused
+    /// together with the MIR it was saved with.
+    pub data_hash: u64,
+}
+
+/// The MIR of all the bodies of one item: the item itself, its methods
+/// if it is an impl or a trait, and the closures and constants nested in
+/// them.
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct SerializedMirEntry {
+    /// The work-product whose dep-node says whether the entry is still
+    /// clean.
+    pub id: Arc<WorkProductId>,
+
+    pub item: DefPathIndex,
+
+    /// The def-paths of everything that the entry refers to, all of
+    /// which must still exist for it to be reused.
+    pub def_ids: Vec<DefPathIndex>,
+
+    /// Indices into `SerializedMirCache::files` of the files that the
+    /// spans of the entry point into, all of which must be unchanged
+    /// for it to be reused.
+    pub files: Vec<usize>,
+
+    /// The offset within the encoded MIR at which the bodies of the entry
+    /// start.
+    pub position: usize,
+}
+
+/// A source file as it was when the MIR was encoded. Spans are encoded
+/// as byte positions within the whole codemap, so they have to be moved
+/// to where the file starts in the compilation that decodes them.
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct SerializedFileMap {
+    pub name: String,
+    pub start_pos: u32,
+    pub end_pos: u32,
+
+    /// A hash of the source of the file.
+    pub src_hash: u64,
+}
@@ -72,7 +72,7 @@ fn load_dep_graph_if_exists<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
     }
 }
 
-fn load_data(sess: &Session, path: &Path) -> Option<Vec<u8>> {
+pub fn load_data(sess: &Session, path: &Path) -> Option<Vec<u8>> {
     if !path.exists() {
         return None;
     }
@@ -0,0 +1,843 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reusing the MIR of unchanged items across compilation sessions.
+//!
+//! Once the MIR passes have run, the MIR of every item whose bodies can
+//! be cached is encoded into a single file, and each item gets a
+//! work-product that depends on everything its MIR was built from. In the
+//! next session, the items whose work-product is still clean are skipped
+//! when building MIR (see `cached_mir_items`), and their MIR is decoded
+//! from the file after the MIR passes have run on everything else (see
+//! `load_cached_mir`).
+//!
+//! Def-ids and spans are specific to a session, so the MIR is encoded with
+//! the def-ids of the session that saved it, together with the def-paths
+//! they stand for and the source files its spans point into. An item is
+//! only reused if all of those still exist unchanged, and its def-ids and
+//! spans are translated to the current session when it is decoded. Spans
+//! lose their expansion info on the way, which only affects diagnostics
+//! that trans reports for cached items.
+//!
+//! MIR that refers to the HIR by `NodeId`, i.e. aggregate constants and
+//! inline assembly, is not cached.
+
+use rbml::Error;
+use rbml::opaque::{Decoder, Encoder};
+use rustc::dep_graph::{DepNode, WorkProduct, WorkProductId};
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::hir::intravisit;
+use rustc::hir::map as hir_map;
+use rustc::hir::map::DefPathData;
+use rustc::middle::const_val::ConstVal;
+use rustc::middle::cstore::{tls, LOCAL_CRATE};
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr::*;
+use rustc::mir::transform::MirSource;
+use rustc::mir::visit::{LvalueContext, MutVisitor, Visitor};
+use rustc::session::Session;
+use rustc::ty::{self, ClosureSubsts, Ty, TyCtxt};
+use rustc::ty::subst::Substs;
+use rustc::util::nodemap::{DefIdMap, DefIdSet, NodeMap};
+use rustc_data_structures::fnv::FnvHashMap;
+use rustc_metadata::encoder::def_to_string;
+use rustc_metadata::tydecode::TyDecoder;
+use rustc_metadata::tyencode::{self, abbrev_map};
+use rustc_serialize::{Decodable, Encodable};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher, SipHasher};
+use std::io::{self, Cursor};
+use std::rc::Rc;
+use std::sync::Arc;
+use syntax::ast;
+use syntax::attr::{self, AttrMetaMethods};
+use syntax_pos::{BytePos, FileMap, Span, DUMMY_SP};
+
+use super::data::*;
+use super::directory::*;
+use super::load::load_data;
+use super::save::save_in;
+use super::util::*;
+
+const MIR_REUSED: &'static str = "rustc_mir_reused";
+const MIR_REBUILT: &'static str = "rustc_mir_rebuilt";
+const CFG: &'static str = "cfg";
+
+/// The items whose MIR was saved by the previous session and is still
+/// clean. Their MIR does not have to be built, `load_cached_mir` loads it
+/// from the cache instead.
+pub fn cached_mir_items<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> DefIdSet {
+    let _ignore = tcx.dep_graph.in_ignore();
+    match load_previous_cache(tcx) {
+        Some(previous) => {
+            previous.reusable_entries(tcx).into_iter().map(|(_, item)| item).collect()
+        }
+        None => DefIdSet(),
+    }
+}
+
+/// The number of lints buffered for each node so far. Taken before MIR is
+/// built, it lets `record_mir_lints` tell which lints MIR construction and
+/// the MIR passes buffered.
+pub fn buffered_lints(sess: &Session) -> NodeMap<usize> {
+    sess.lints.borrow().iter().map(|(&id, lints)| (id, lints.len())).collect()
+}
+
+/// Add the nodes that lints have been buffered for since `before` was
+/// taken to the `mir_diagnostic_nodes` of the session, so that the MIR of
+/// their items is not cached.
+pub fn record_mir_lints(sess: &Session, before: &NodeMap<usize>) {
+    let mut nodes = sess.mir_diagnostic_nodes.borrow_mut();
+    for (&id, lints) in sess.lints.borrow().iter() {
+        if lints.len() > before.get(&id).cloned().unwrap_or(0) {
+            nodes.insert(id);
+        }
+    }
+}
+
+/// Decode the MIR of the items returned by `cached_mir_items` into
+/// `mir_map`. The cached MIR has already been through the MIR passes, so
+/// this has to happen after they have run on the MIR that was built.
+/// Returns the items whose MIR was loaded.
+pub fn load_cached_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 mir_map: &mut MirMap<'tcx>)
+                                 -> DefIdSet {
+    let _ignore = tcx.dep_graph.in_ignore();
+    let loaded = decode_cached_mir(tcx, mir_map);
+    check_reuse_annotations(tcx, &loaded);
+    loaded
+}
+
+fn decode_cached_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               mir_map: &mut MirMap<'tcx>)
+                               -> DefIdSet {
+    let mut loaded = DefIdSet();
+    let previous = match load_previous_cache(tcx) {
+        Some(previous) => previous,
+        None => return loaded,
+    };
+
+    let def_ids: DefIdMap<DefId> =
+        previous.cache.def_ids.iter()
+                              .filter_map(|&(def_id, index)| {
+                                  previous.retraced.def_id(index).map(|new| (def_id, new))
+                              })
+                              .collect();
+    let dcx = DecodingContext {
+        tcx: tcx,
+        data: &previous.data,
+        def_ids: &def_ids,
+    };
+    let mut translator = MirTranslator {
+        def_ids: &def_ids,
+        files: &previous.cache.files,
+        file_starts: &previous.file_starts,
+    };
+
+    for (entry, item) in previous.reusable_entries(tcx) {
+        let mut decoder = Decoder::new(&previous.data, entry.position);
+        let result = tls::enter_decoding_context(&dcx, &mut decoder, |_, decoder| {
+            <Vec<(DefPathIndex, Mir<'tcx>)>>::decode(decoder)
+        });
+        let bodies = match result {
+            Ok(bodies) => bodies,
+            Err(err) => {
+                // The item has not been built, so there is no way to go on
+                // without its MIR.
+                tcx.sess.fatal(&format!("decoding error in cached MIR of `{}`: {}",
+                                        tcx.item_path_str(item),
+                                        err));
+            }
+        };
+
+        debug!("load_cached_mir: loading {:?}", entry.id);
+        for (index, mut mir) in bodies {
+            let def_id = previous.retraced.def_id(index).unwrap();
+            translator.translate_body(&mut mir);
+
+            let _task = tcx.dep_graph.in_task(DepNode::Mir(def_id));
+            tcx.dep_graph.read(DepNode::WorkProduct(entry.id.clone()));
+            mir_map.map.insert(def_id, mir);
+        }
+        loaded.insert(item);
+    }
+
+    loaded
+}
+
+/// Save the MIR of every item whose bodies can all be cached, and create
+/// a work-product for each of them. `reused` are the items that were
+/// loaded by `load_cached_mir`: their work-products keep the edges that
+/// were loaded along with the dep-graph.
+pub fn save_mir_cache<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                mir_map: &MirMap<'tcx>,
+                                reused: &DefIdSet) {
+    debug!("save_mir_cache()");
+    if !mir_cache_enabled(tcx.sess) {
+        return;
+    }
+    let _ignore = tcx.dep_graph.in_ignore();
+
+    let mut items = BTreeMap::new();
+    let mut def_ids = mir_map.map.keys();
+    def_ids.sort();
+    for def_id in def_ids {
+        if let Some(item) = enclosing_item(tcx, def_id) {
+            items.entry(item).or_insert(vec![]).push(def_id);
+        }
+    }
+
+    let mut builder = DefIdDirectoryBuilder::new(tcx);
+    let mut wr = Cursor::new(Vec::new());
+    let result = encode_mir(tcx, mir_map, &items, &mut builder, &mut Encoder::new(&mut wr));
+    let (mut cache, saved_items) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            tcx.sess.err(&format!("could not encode MIR cache: {}", err));
+            return;
+        }
+    };
+    let data = wr.into_inner();
+    let mut state = SipHasher::new();
+    data.hash(&mut state);
+    cache.data_hash = state.finish();
+
+    save_in(tcx.sess, mir_cache_path(tcx), |e| {
+        try!(builder.directory().encode(e));
+        try!(cache.encode(e));
+        data.encode(e)
+    });
+
+    let input_hash = input_hash(tcx.sess, cache.data_hash);
+    for (entry, item) in cache.entries.iter().zip(saved_items) {
+        if !reused.contains(&item) {
+            let _task = tcx.dep_graph.in_task(DepNode::WorkProduct(entry.id.clone()));
+            for &def_id in &items[&item] {
+                tcx.dep_graph.read(DepNode::Mir(def_id));
+            }
+        }
+        tcx.dep_graph.insert_work_product(&entry.id, WorkProduct {
+            input_hash: input_hash,
+            saved_files: vec![],
+        });
+    }
+}
+
+/// Encode the bodies of each of `items` that can be cached. Returns the
+/// cache without its `data_hash`, along with the item of each entry.
+fn encode_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        mir_map: &MirMap<'tcx>,
+                        items: &BTreeMap<DefId, Vec<DefId>>,
+                        builder: &mut DefIdDirectoryBuilder,
+                        encoder: &mut Encoder)
+                        -> io::Result<(SerializedMirCache, Vec<DefId>)> {
+    let ecx = EncodingContext {
+        tcx: tcx,
+        type_abbrevs: RefCell::new(FnvHashMap()),
+    };
+    let mut def_ids = FnvHashMap();
+    let mut files = BTreeMap::new();
+    let mut entries = vec![];
+    let mut saved_items = vec![];
+
+    let reported_items: DefIdSet =
+        tcx.sess.mir_diagnostic_nodes.borrow()
+                                     .iter()
+                                     .filter_map(|&id| enclosing_item_of_node(tcx, id))
+                                     .collect();
+
+    for (&item, bodies) in items {
+        if reported_items.contains(&item) || has_rustc_mir_attrs(tcx, item) ||
+           !bodies.iter().all(|&def_id| is_cacheable_body(tcx, def_id)) {
+            continue;
+        }
+
+        let mut references = MirReferences {
+            tcx: tcx,
+            def_ids: DefIdSet(),
+            files: BTreeMap::new(),
+            cacheable: true,
+        };
+        references.def_ids.insert(item);
+        for &def_id in bodies {
+            references.def_ids.insert(def_id);
+            references.add_body(&mir_map.map[&def_id]);
+        }
+        if !references.cacheable {
+            debug!("encode_mir: not caching {:?}", item);
+            continue;
+        }
+
+        let position = encoder.position();
+        let encoded_bodies: Vec<_> =
+            bodies.iter()
+                  .map(|&def_id| (builder.add(def_id), &mir_map.map[&def_id]))
+                  .collect();
+        try!(tls::enter_encoding_context(&ecx, encoder, |_, encoder| {
+            encoded_bodies.encode(encoder)
+        }));
+
+        let mut entry_def_ids: Vec<_> =
+            references.def_ids.iter()
+                              .map(|&def_id| {
+                                  let index = builder.add(def_id);
+                                  def_ids.insert(def_id, index);
+                                  index
+                              })
+                              .collect();
+        entry_def_ids.sort();
+        let entry_files: Vec<_> = references.files.keys().cloned().collect();
+        files.extend(references.files);
+
+        entries.push((SerializedMirEntry {
+            id: work_product_id(tcx, item),
+            item: builder.add(item),
+            def_ids: entry_def_ids,
+            files: vec![],
+            position: position,
+        }, entry_files));
+        saved_items.push(item);
+    }
+
+    // Number the files in the order they are in the codemap, which lets
+    // `MirTranslator` binary search them.
+    let file_indices: FnvHashMap<_, _> =
+        files.keys().enumerate().map(|(index, &start_pos)| (start_pos, index)).collect();
+    let entries = entries.into_iter().map(|(mut entry, entry_files)| {
+        entry.files = entry_files.iter().map(|start_pos| file_indices[start_pos]).collect();
+        entry
+    }).collect();
+    let files = files.values().map(|filemap| {
+        SerializedFileMap {
+            name: filemap.name.clone(),
+            start_pos: filemap.start_pos.0,
+            end_pos: filemap.end_pos.0,
+            src_hash: src_hash(filemap),
+        }
+    }).collect();
+    let mut def_ids: Vec<_> = def_ids.into_iter().collect();
+    def_ids.sort();
+
+    Ok((SerializedMirCache {
+        def_ids: def_ids,
+        files: files,
+        entries: entries,
+        data_hash: 0,
+    }, saved_items))
+}
+
+/// Items loaded from the cache skip MIR construction and all of the MIR
+/// passes, including the ones that report diagnostics, such as the
+/// `unused_must_use` lint and `TypeckMir`. The cache may therefore only
+/// stand in for MIR whose construction and passes reported nothing:
+///
+/// - items whose bodies had a lint buffered (see `record_mir_lints`) or
+///   broken MIR reported by `TypeckMir` are left out of the cache, so they
+///   are rebuilt and report it again in every session;
+/// - errors stop compilation before the cache is saved;
+/// - and the cache is only used when nothing but trans looks at the MIR,
+///   as checked here.
+fn mir_cache_enabled(sess: &Session) -> bool {
+    sess.opts.incremental.is_some() &&
+        !sess.opts.no_trans &&
+        sess.opts.debugging_opts.dump_mir.is_none() &&
+        !sess.opts.debugging_opts.mir_stats &&
+        !sess.opts.debugging_opts.mir_provenance &&
+        !sess.opts.debugging_opts.mir_unwind_stats &&
+        !sess.opts.debugging_opts.mir_match_stats &&
+        !sess.opts.debugging_opts.instrument_coverage &&
+        !sess.opts.debugging_opts.mir_retag &&
+        sess.opts.debugging_opts.mir_instrument_calls.is_none() &&
+        sess.opts.debugging_opts.sanitizer.is_none()
+}
+
+/// The input hash of the work-products of a cache whose encoded MIR
+/// hashes to `data_hash`. Every option that MIR construction or the MIR
+/// passes read goes into it as well, so that changing one invalidates the
+/// whole cache. Options that only dump or print MIR are left out.
+fn input_hash(sess: &Session, data_hash: u64) -> u64 {
+    let mut state = SipHasher::new();
+    data_hash.hash(&mut state);
+    sess.opts.debug_assertions.hash(&mut state);
+    sess.opts.optimize.hash(&mut state);
+    sess.opts.debugging_opts.force_overflow_checks.hash(&mut state);
+    sess.opts.debugging_opts.overflow_checks_policy.hash(&mut state);
+    sess.no_landing_pads().hash(&mut state);
+    sess.opts.debugging_opts.check_box_from_raw.hash(&mut state);
+    sess.opts.debugging_opts.mir_deaggregate.hash(&mut state);
+    sess.opts.debugging_opts.short_scrutinee_temps.hash(&mut state);
+    sess.opts.debugging_opts.mir_opt_level.hash(&mut state);
+
+    // The cache is disabled while any of these is set, but MIR built with
+    // them must not be reused once that changes.
+    sess.opts.debugging_opts.instrument_coverage.hash(&mut state);
+    sess.opts.debugging_opts.sanitizer.hash(&mut state);
+    sess.opts.debugging_opts.mir_retag.hash(&mut state);
+    sess.opts.debugging_opts.mir_instrument_calls.hash(&mut state);
+    sess.opts.debugging_opts.mir_provenance.hash(&mut state);
+    state.finish()
+}
+
+/// Check the `#[rustc_mir_reused(cfg="foo")]` and
+/// `#[rustc_mir_rebuilt(cfg="foo")]` annotations of the items of the
+/// crate, which say whether their MIR is loaded from the cache in the
+/// revision `foo`. These are only used in tests.
+fn check_reuse_annotations<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, loaded: &DefIdSet) {
+    if !tcx.sess.features.borrow().rustc_attrs {
+        return;
+    }
+    tcx.map.krate().visit_all_items(&mut ReuseAnnotationVisitor {
+        tcx: tcx,
+        loaded: loaded,
+    });
+}
+
+struct ReuseAnnotationVisitor<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    loaded: &'a DefIdSet,
+}
+
+impl<'a, 'tcx> ReuseAnnotationVisitor<'a, 'tcx> {
+    /// Whether the `cfg="foo"` field of `attr` names a cfg flag that is set.
+    fn check_config(&self, attr: &ast::Attribute) -> bool {
+        let config = &self.tcx.map.krate().config;
+        for item in attr.meta_item_list().unwrap_or(&[]) {
+            if item.check_name(CFG) {
+                let value = match item.value_str() {
+                    Some(value) => value,
+                    None => {
+                        self.tcx.sess.span_fatal(
+                            item.span,
+                            &format!("associated value expected for `{}`", CFG));
+                    }
+                };
+                return config.iter().any(|c| c.check_name(&value[..]));
+            }
+        }
+        self.tcx.sess.span_fatal(attr.span, "no cfg attribute");
+    }
+}
+
+impl<'a, 'tcx> intravisit::Visitor<'tcx> for ReuseAnnotationVisitor<'a, 'tcx> {
+    fn visit_item(&mut self, item: &'tcx hir::Item) {
+        let def_id = self.tcx.map.local_def_id(item.id);
+        for attr in &item.attrs {
+            let expected = if attr.check_name(MIR_REUSED) {
+                true
+            } else if attr.check_name(MIR_REBUILT) {
+                false
+            } else {
+                continue;
+            };
+            if !self.check_config(attr) {
+                continue;
+            }
+            let reused = self.loaded.contains(&def_id);
+            if reused != expected {
+                self.tcx.sess.span_err(
+                    item.span,
+                    &format!("expected the MIR of `{}` to be {} but it was {}",
+                             self.tcx.item_path_str(def_id),
+                             if expected { "reused" } else { "rebuilt" },
+                             if reused { "reused" } else { "rebuilt" }));
+            }
+        }
+    }
+}
+
+fn work_product_id<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, item: DefId) -> Arc<WorkProductId> {
+    Arc::new(WorkProductId(format!("mir-{}", tcx.def_path(item).to_string(tcx))))
+}
+
+fn src_hash(filemap: &FileMap) -> u64 {
+    let mut state = SipHasher::new();
+    filemap.src.hash(&mut state);
+    state.finish()
+}
+
+/// The item in whose dep-graph task the MIR of `def_id` is built: the
+/// closest enclosing item that is not an associated item.
+fn enclosing_item<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> Option<DefId> {
+    enclosing_item_of_node(tcx, tcx.map.as_local_node_id(def_id).unwrap())
+}
+
+fn enclosing_item_of_node<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    mut id: ast::NodeId)
+                                    -> Option<DefId> {
+    loop {
+        if let Some(hir_map::NodeItem(_)) = tcx.map.find(id) {
+            return Some(tcx.map.local_def_id(id));
+        }
+        let parent = tcx.map.get_parent(id);
+        if parent == id {
+            return None;
+        }
+        id = parent;
+    }
+}
+
+/// The MIR of items with these attributes is looked at before trans.
+fn has_rustc_mir_attrs<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> bool {
+    let attrs = tcx.map.attrs(tcx.map.as_local_node_id(def_id).unwrap());
+    attr::contains_name(attrs, "rustc_mir") || attr::contains_name(attrs, "rustc_mir_borrowck")
+}
+
+/// Whether the MIR of `def_id` can be loaded from the cache instead of
+/// being built. Only functions, closures and anonymous constants qualify:
+/// the MIR of named constants and statics is evaluated by the MIR passes
+/// of the items that use them.
+fn is_cacheable_body<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> bool {
+    if has_rustc_mir_attrs(tcx, def_id) {
+        return false;
+    }
+    match MirSource::from_node(tcx, tcx.map.as_local_node_id(def_id).unwrap()) {
+        MirSource::Fn(_) => true,
+        MirSource::Const(_) => {
+            tcx.def_key(def_id).disambiguated_data.data == DefPathData::Initializer
+        }
+        MirSource::Static(..) | MirSource::Promoted(..) => false,
+    }
+}
+
+/// The MIR cache saved by the previous session.
+struct PreviousMirCache {
+    retraced: RetracedDefIdDirectory,
+    cache: SerializedMirCache,
+    data: Vec<u8>,
+
+    /// Where each of `cache.files` starts in the current session, if it is
+    /// unchanged.
+    file_starts: Vec<Option<BytePos>>,
+}
+
+impl PreviousMirCache {
+    /// The entries that are still clean and whose def-paths and source
+    /// files all still exist, along with the current def-id of their item.
+    fn reusable_entries<'a, 'tcx>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>)
+                                  -> Vec<(&SerializedMirEntry, DefId)> {
+        let input_hash = input_hash(tcx.sess, self.cache.data_hash);
+        self.cache.entries.iter().filter_map(|entry| {
+            match tcx.dep_graph.previous_work_product(&entry.id) {
+                Some(ref work_product) if work_product.input_hash == input_hash => {}
+                _ => {
+                    debug!("reusable_entries: {:?} is dirty", entry.id);
+                    return None;
+                }
+            }
+            if !entry.def_ids.iter().all(|&index| self.retraced.def_id(index).is_some()) {
+                debug!("reusable_entries: {:?} refers to a removed def-path", entry.id);
+                return None;
+            }
+            if !entry.files.iter().all(|&file| self.file_starts[file].is_some()) {
+                debug!("reusable_entries: {:?} points into a changed file", entry.id);
+                return None;
+            }
+            self.retraced.def_id(entry.item).map(|item| (entry, item))
+        }).collect()
+    }
+}
+
+fn load_previous_cache<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Option<PreviousMirCache> {
+    if !mir_cache_enabled(tcx.sess) {
+        return None;
+    }
+    let path = mir_cache_path(tcx).unwrap();
+    let data = match load_data(tcx.sess, &path) {
+        Some(data) => data,
+        None => return None // no file
+    };
+    match decode_previous_cache(tcx, &data) {
+        Ok(previous) => Some(previous),
+        Err(err) => {
+            tcx.sess.warn(
+                &format!("decoding error in MIR cache from `{}`: {}",
+                         path.display(), err));
+            None
+        }
+    }
+}
+
+fn decode_previous_cache<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   data: &[u8])
+                                   -> Result<PreviousMirCache, Error> {
+    let mut decoder = Decoder::new(data, 0);
+    let directory = try!(DefIdDirectory::decode(&mut decoder));
+    let cache = try!(SerializedMirCache::decode(&mut decoder));
+    let data = try!(<Vec<u8>>::decode(&mut decoder));
+
+    let file_starts = cache.files.iter().map(|file| {
+        tcx.sess.codemap().get_filemap(&file.name).and_then(|filemap| {
+            let unchanged = filemap.end_pos.0 - filemap.start_pos.0 ==
+                                file.end_pos - file.start_pos &&
+                            src_hash(&filemap) == file.src_hash;
+            if unchanged { Some(filemap.start_pos) } else { None }
+        })
+    }).collect();
+
+    Ok(PreviousMirCache {
+        retraced: directory.retrace(tcx),
+        cache: cache,
+        data: data,
+        file_starts: file_starts,
+    })
+}
+
+fn translate_def_id(def_ids: &DefIdMap<DefId>, def_id: DefId) -> DefId {
+    match def_ids.get(&def_id) {
+        Some(&def_id) => def_id,
+        None => bug!("cached MIR refers to {:?}, which has no def-path", def_id),
+    }
+}
+
+struct EncodingContext<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    type_abbrevs: abbrev_map<'tcx>,
+}
+
+impl<'a, 'tcx> EncodingContext<'a, 'tcx> {
+    fn ty_str_ctxt<'b>(&'b self) -> tyencode::ctxt<'b, 'tcx> {
+        tyencode::ctxt {
+            diag: self.tcx.sess.diagnostic(),
+            ds: def_to_string,
+            tcx: self.tcx,
+            abbrevs: &self.type_abbrevs
+        }
+    }
+}
+
+impl<'a, 'tcx> tls::EncodingContext<'tcx> for EncodingContext<'a, 'tcx> {
+    fn tcx<'s>(&'s self) -> TyCtxt<'s, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn encode_ty(&self, encoder: &mut Encoder, t: Ty<'tcx>) {
+        tyencode::enc_ty(encoder.cursor, &self.ty_str_ctxt(), t);
+    }
+
+    fn encode_substs(&self, encoder: &mut Encoder, substs: &Substs<'tcx>) {
+        tyencode::enc_substs(encoder.cursor, &self.ty_str_ctxt(), substs);
+    }
+}
+
+/// Types are decoded straight from the encoded MIR, which is why it is
+/// kept in a single buffer: `TyDecoder` caches abbreviated types by their
+/// position, as if they were in the metadata of the local crate.
+struct DecodingContext<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    data: &'a [u8],
+    def_ids: &'a DefIdMap<DefId>,
+}
+
+impl<'a, 'tcx> tls::DecodingContext<'tcx> for DecodingContext<'a, 'tcx> {
+    fn tcx<'s>(&'s self) -> TyCtxt<'s, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn decode_ty(&self, decoder: &mut Decoder) -> Ty<'tcx> {
+        let def_id_convert = &mut |def_id| translate_def_id(self.def_ids, def_id);
+        let starting_position = decoder.position();
+        let mut ty_decoder = TyDecoder::new(self.data,
+                                            LOCAL_CRATE,
+                                            starting_position,
+                                            self.tcx,
+                                            def_id_convert);
+        let ty = ty_decoder.parse_ty();
+        let end_position = ty_decoder.position();
+        decoder.advance(end_position - starting_position);
+        ty
+    }
+
+    fn decode_substs(&self, decoder: &mut Decoder) -> Substs<'tcx> {
+        let def_id_convert = &mut |def_id| translate_def_id(self.def_ids, def_id);
+        let starting_position = decoder.position();
+        let mut ty_decoder = TyDecoder::new(self.data,
+                                            LOCAL_CRATE,
+                                            starting_position,
+                                            self.tcx,
+                                            def_id_convert);
+        let substs = ty_decoder.parse_substs();
+        let end_position = ty_decoder.position();
+        decoder.advance(end_position - starting_position);
+        substs
+    }
+
+    fn translate_def_id(&self, def_id: DefId) -> DefId {
+        translate_def_id(self.def_ids, def_id)
+    }
+}
+
+/// Collects the def-ids and source files that MIR refers to, which have
+/// to be translated when the MIR is used in another session.
+struct MirReferences<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    def_ids: DefIdSet,
+    files: BTreeMap<BytePos, Rc<FileMap>>,
+
+    /// Cleared if the MIR refers to something that cannot be translated.
+    cacheable: bool,
+}
+
+impl<'a, 'tcx> MirReferences<'a, 'tcx> {
+    fn add_body(&mut self, mir: &Mir<'tcx>) {
+        self.visit_mir(mir);
+        for promoted in &mir.promoted {
+            self.visit_mir(promoted);
+        }
+    }
+
+    /// Add the def-ids that `tyencode` writes out for `ty`.
+    fn add_ty(&mut self, ty: Ty<'tcx>) {
+        for ty in ty.walk() {
+            match ty.sty {
+                ty::TyEnum(def, _) | ty::TyStruct(def, _) => {
+                    self.def_ids.insert(def.did);
+                }
+                ty::TyFnDef(def_id, _, _) |
+                ty::TyClosure(def_id, _) |
+                ty::TyAnon(def_id, _) => {
+                    self.def_ids.insert(def_id);
+                }
+                ty::TyTrait(ref data) => {
+                    self.def_ids.insert(data.principal.def_id());
+                    for bound in &data.bounds.projection_bounds {
+                        self.def_ids.insert(bound.0.projection_ty.trait_ref.def_id);
+                    }
+                }
+                ty::TyProjection(ref data) => {
+                    self.def_ids.insert(data.trait_ref.def_id);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for MirReferences<'a, 'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        match *rvalue {
+            Rvalue::Aggregate(AggregateKind::Adt(adt_def, _, _), _) => {
+                self.def_ids.insert(adt_def.did);
+            }
+            Rvalue::InlineAsm { .. } => {
+                self.cacheable = false;
+            }
+            _ => {}
+        }
+        self.super_rvalue(rvalue, location);
+    }
+
+    fn visit_projection_elem(&mut self,
+                             elem: &LvalueElem<'tcx>,
+                             context: LvalueContext,
+                             location: Location) {
+        if let ProjectionElem::Downcast(adt_def, _) = *elem {
+            self.def_ids.insert(adt_def.did);
+        }
+        self.super_projection_elem(elem, context, location);
+    }
+
+    fn visit_def_id(&mut self, def_id: &DefId, _: Location) {
+        self.def_ids.insert(*def_id);
+    }
+
+    fn visit_const_val(&mut self, const_val: &ConstVal) {
+        match *const_val {
+            ConstVal::Function(def_id) => {
+                self.def_ids.insert(def_id);
+            }
+            ConstVal::Struct(_) |
+            ConstVal::Tuple(_) |
+            ConstVal::Array(..) |
+            ConstVal::Repeat(..) => {
+                self.cacheable = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_span(&mut self, span: &Span) {
+        if !span.source_equal(&DUMMY_SP) {
+            let filemap = self.tcx.sess.codemap().lookup_byte_offset(span.lo).fm;
+            self.files.insert(filemap.start_pos, filemap);
+        }
+    }
+
+    fn visit_ty(&mut self, ty: &Ty<'tcx>) {
+        self.add_ty(*ty);
+    }
+
+    fn visit_substs(&mut self, substs: &&'tcx Substs<'tcx>) {
+        for &ty in substs.types.iter() {
+            self.add_ty(ty);
+        }
+    }
+
+    fn visit_closure_substs(&mut self, substs: &ClosureSubsts<'tcx>) {
+        self.visit_substs(&substs.func_substs);
+        for &ty in substs.upvar_tys {
+            self.add_ty(ty);
+        }
+    }
+}
+
+/// Moves the def-ids and spans of MIR loaded from the cache to where they
+/// are in the current session.
+struct MirTranslator<'a> {
+    def_ids: &'a DefIdMap<DefId>,
+    files: &'a [SerializedFileMap],
+    file_starts: &'a [Option<BytePos>],
+}
+
+impl<'a> MirTranslator<'a> {
+    fn translate_body(&mut self, mir: &mut Mir) {
+        self.visit_mir(mir);
+        for promoted in mir.promoted.iter_mut() {
+            self.visit_mir(promoted);
+        }
+    }
+
+    fn translate_span(&self, span: Span) -> Span {
+        if span.source_equal(&DUMMY_SP) {
+            return span;
+        }
+        let index = match self.files.binary_search_by(|file| file.start_pos.cmp(&span.lo.0)) {
+            Ok(index) => index,
+            Err(0) => return DUMMY_SP,
+            Err(index) => index - 1,
+        };
+        let file = &self.files[index];
+        match self.file_starts[index] {
+            Some(start_pos) if span.hi.0 <= file.end_pos => Span {
+                lo: BytePos(span.lo.0 - file.start_pos + start_pos.0),
+                hi: BytePos(span.hi.0 - file.start_pos + start_pos.0),
+                expn_id: span.expn_id,
+            },
+            _ => DUMMY_SP,
+        }
+    }
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for MirTranslator<'a> {
+    fn visit_def_id(&mut self, def_id: &mut DefId, _: Location) {
+        *def_id = translate_def_id(self.def_ids, *def_id);
+    }
+
+    fn visit_const_val(&mut self, const_val: &mut ConstVal) {
+        if let ConstVal::Function(ref mut def_id) = *const_val {
+            *def_id = translate_def_id(self.def_ids, *def_id);
+        }
+    }
+
+    fn visit_span(&mut self, span: &mut Span) {
+        *span = self.translate_span(*span);
+    }
+}
@@ -17,12 +17,15 @@ mod directory;
 mod dirty_clean;
 mod hash;
 mod load;
+mod mir_cache;
 mod preds;
 mod save;
 mod util;
 mod work_product;
 
 pub use self::load::load_dep_graph;
+pub use self::mir_cache::{buffered_lints, cached_mir_items, load_cached_mir, record_mir_lints};
+pub use self::mir_cache::save_mir_cache;
 pub use self::save::save_dep_graph;
 pub use self::save::save_work_products;
 pub use self::work_product::save_trans_partition;
@@ -25,6 +25,10 @@ pub fn metadata_hash_path(tcx: TyCtxt, cnum: ast::CrateNum) -> Option<PathBuf> {
     tcx_path(tcx, cnum, "metadata")
 }
 
+pub fn mir_cache_path(tcx: TyCtxt) -> Option<PathBuf> {
+    tcx_path(tcx, LOCAL_CRATE, "mir")
+}
+
 pub fn tcx_work_products_path(tcx: TyCtxt) -> Option<PathBuf> {
     let crate_name = tcx.crate_name(LOCAL_CRATE);
     sess_work_products_path(tcx.sess, &crate_name)
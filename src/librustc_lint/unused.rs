@@ -14,6 +14,7 @@ use rustc::ty::adjustment;
 use util::nodemap::FnvHashMap;
 use lint::{LateContext, EarlyContext, LintContext, LintArray};
 use lint::{LintPass, EarlyLintPass, LateLintPass};
+use lint::builtin::UNUSED_MUST_USE;
 
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 
@@ -98,12 +99,6 @@ impl LateLintPass for UnusedMut {
     }
 }
 
-declare_lint! {
-    pub UNUSED_MUST_USE,
-    Warn,
-    "unused result of a type flagged as #[must_use]"
-}
-
 declare_lint! {
     pub UNUSED_RESULTS,
     Allow,
@@ -115,7 +110,7 @@ pub struct UnusedResults;
 
 impl LintPass for UnusedResults {
     fn get_lints(&self) -> LintArray {
-        lint_array!(UNUSED_MUST_USE, UNUSED_RESULTS)
+        lint_array!(UNUSED_RESULTS)
     }
 }
 
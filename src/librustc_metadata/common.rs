@@ -243,6 +243,9 @@ pub const tag_items_data_item_defaultness: usize = 0xa8;
 
 pub const tag_items_data_parent_impl: usize = 0xa9;
 
+// The inline hint score of the MIR of an item, see `mir::stats::inline_hint_score`.
+pub const tag_items_data_item_mir_inline_hint: usize = 0xaa;
+
 pub const tag_rustc_version: usize = 0x10f;
 pub fn rustc_version() -> String {
     format!(
@@ -639,6 +639,12 @@ impl<'tcx> CrateStore<'tcx> for cstore::CStore {
         decoder::is_item_mir_available(&cdata, def.index)
     }
 
+    fn item_mir_inline_hint(&self, def: DefId) -> Option<u32> {
+        self.dep_graph.read(DepNode::MetaData(def));
+        let cdata = self.get_crate_data(def.krate);
+        decoder::get_item_mir_inline_hint(&cdata, def.index)
+    }
+
     fn crates(&self) -> Vec<ast::CrateNum>
     {
         let mut result = vec![];
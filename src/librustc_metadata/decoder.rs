@@ -813,6 +813,12 @@ pub fn is_item_mir_available<'tcx>(cdata: Cmd, id: DefIndex) -> bool {
     false
 }
 
+pub fn get_item_mir_inline_hint(cdata: Cmd, id: DefIndex) -> Option<u32> {
+    cdata.get_item(id).and_then(|item_doc| {
+        reader::maybe_get_doc(item_doc, tag_items_data_item_mir_inline_hint)
+    }).map(reader::doc_as_u32)
+}
+
 pub fn maybe_get_item_mir<'a, 'tcx>(cdata: Cmd,
                                     tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                     id: DefIndex)
@@ -857,7 +863,7 @@ pub fn maybe_get_item_mir<'a, 'tcx>(cdata: Cmd,
     impl<'v, 'cdata, 'codemap> mir::visit::MutVisitor<'v>
         for MirDefIdAndSpanTranslator<'cdata, 'codemap>
     {
-        fn visit_def_id(&mut self, def_id: &mut DefId) {
+        fn visit_def_id(&mut self, def_id: &mut DefId, _: mir::repr::Location) {
             *def_id = translate_def_id(self.crate_metadata, *def_id);
         }
 
@@ -33,6 +33,8 @@ use rustc::ty::util::IntTypeExt;
 
 use rustc::hir::svh::Svh;
 use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr::{Mir, StatementKind};
+use rustc::mir::stats as mir_stats;
 use rustc::session::config::{self, PanicStrategy};
 use rustc::util::nodemap::{FnvHashMap, NodeSet};
 
@@ -745,6 +747,8 @@ fn encode_repr_attrs(rbml_w: &mut Encoder,
 fn encode_mir(ecx: &EncodeContext, rbml_w: &mut Encoder, node_id: NodeId) {
     let def_id = ecx.tcx.map.local_def_id(node_id);
     if let Some(mir) = ecx.mir_map.map.get(&def_id) {
+        let stripped = strip_instrumentation(mir);
+        let mir = stripped.as_ref().unwrap_or(mir);
         rbml_w.start_tag(tag_mir as usize);
         rbml_w.emit_opaque(|opaque_encoder| {
             tls::enter_encoding_context(ecx, opaque_encoder, |_, opaque_encoder| {
@@ -752,7 +756,35 @@ fn encode_mir(ecx: &EncodeContext, rbml_w: &mut Encoder, node_id: NodeId) {
             })
         }).unwrap();
         rbml_w.end_tag();
+
+        rbml_w.wr_tagged_u32(tag_items_data_item_mir_inline_hint,
+                             mir_stats::inline_hint_score(mir));
+    }
+}
+
+/// Returns a copy of `mir` without its `Probe` and `SanitizerCheck`
+/// statements, if it has any. They only mean something under the
+/// `-Z mir-instrument-calls` and `-Z sanitizer` of this crate, while the
+/// crates that translate the body from metadata may be built without them.
+fn strip_instrumentation<'tcx>(mir: &Mir<'tcx>) -> Option<Mir<'tcx>> {
+    fn is_instrumentation(kind: &StatementKind) -> bool {
+        match *kind {
+            StatementKind::Probe { .. } | StatementKind::SanitizerCheck { .. } => true,
+            _ => false,
+        }
+    }
+
+    let instrumented = mir.basic_blocks().iter().any(|data| {
+        data.statements.iter().any(|statement| is_instrumentation(&statement.kind))
+    });
+    if !instrumented {
+        return None;
+    }
+    let mut mir = mir.clone();
+    for data in mir.basic_blocks_mut() {
+        data.statements.retain(|statement| !is_instrumentation(&statement.kind));
     }
+    Some(mir)
 }
 
 const FN_FAMILY: char = 'f';
@@ -84,7 +84,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             } else if dest_is_unit {
                 // FIXME(#31472)
                 let source_info = this.source_info(span);
-                this.cfg.push_assign_unit(block, source_info, destination);
+                this.push_assign_unit(block, source_info, destination);
             }
             // Finally, we pop all the let scopes before exiting out from the scope of block
             // itself.
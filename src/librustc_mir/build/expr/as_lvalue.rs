@@ -13,7 +13,10 @@
 use build::{BlockAnd, BlockAndExtension, Builder};
 use build::expr::category::Category;
 use hair::*;
+use patch::alignment_test;
 use rustc::mir::repr::*;
+use rustc::ty;
+use syntax_pos::Span;
 
 use rustc_data_structures::indexed_vec::Idx;
 
@@ -26,41 +29,87 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         where M: Mirror<'tcx, Output=Expr<'tcx>>
     {
         let expr = self.hir.mirror(expr);
-        self.expr_as_lvalue(block, expr)
+        self.expr_as_lvalue(block, expr, true)
     }
 
+    /// Compile `expr`, yielding an lvalue that is borrowed or whose address
+    /// is taken, but which is not read or written. Unlike `as_lvalue`, the
+    /// raw pointers the lvalue goes through are not checked for alignment,
+    /// so that e.g. `&*p as *const T` does not assert anything about `p`.
+    pub fn as_address_lvalue<M>(&mut self,
+                                block: BasicBlock,
+                                expr: M)
+                                -> BlockAnd<Lvalue<'tcx>>
+        where M: Mirror<'tcx, Output=Expr<'tcx>>
+    {
+        let expr = self.hir.mirror(expr);
+        self.expr_as_lvalue(block, expr, false)
+    }
+
+    /// Whether `expr` is an lvalue, which `as_lvalue` does not copy into
+    /// a temporary.
+    pub fn is_lvalue_expr(&mut self, expr: &ExprRef<'tcx>) -> bool {
+        let expr = self.hir.mirror(expr.clone());
+        match expr.kind {
+            ExprKind::Scope { ref value, .. } => self.is_lvalue_expr(value),
+            ref kind => Category::of(kind) == Some(Category::Lvalue),
+        }
+    }
+
+    /// `check_alignment` is false if the lvalue is only borrowed or has
+    /// its address taken, in which case the raw pointers dereferenced by
+    /// its outer projections are not checked for alignment. Pointers that
+    /// are themselves read from memory, as `*p` is in `&**p`, still are.
     fn expr_as_lvalue(&mut self,
                       mut block: BasicBlock,
-                      expr: Expr<'tcx>)
+                      expr: Expr<'tcx>,
+                      check_alignment: bool)
                       -> BlockAnd<Lvalue<'tcx>> {
-        debug!("expr_as_lvalue(block={:?}, expr={:?})", block, expr);
+        debug!("expr_as_lvalue(block={:?}, expr={:?}, check_alignment={:?})",
+               block, expr, check_alignment);
 
         let this = self;
         let expr_span = expr.span;
         let source_info = this.source_info(expr_span);
         match expr.kind {
             ExprKind::Scope { extent, value } => {
-                this.in_scope(extent, block, |this| this.as_lvalue(block, value))
+                this.in_scope(extent, block, |this| {
+                    let value = this.hir.mirror(value);
+                    this.expr_as_lvalue(block, value, check_alignment)
+                })
             }
             ExprKind::Field { lhs, name } => {
-                let lvalue = unpack!(block = this.as_lvalue(block, lhs));
+                let lhs = this.hir.mirror(lhs);
+                let lvalue = unpack!(block = this.expr_as_lvalue(block, lhs, check_alignment));
                 let lvalue = lvalue.field(name, expr.ty);
                 block.and(lvalue)
             }
             ExprKind::Deref { arg } => {
+                let arg = this.hir.mirror(arg);
+                let align = match arg.ty.sty {
+                    ty::TyRawPtr(mt) if check_alignment && this.hir.check_alignment() => {
+                        this.hir.align_of(mt.ty)
+                    }
+                    _ => None,
+                };
                 let lvalue = unpack!(block = this.as_lvalue(block, arg));
+                if let Some(align) = align {
+                    block = this.check_alignment(block, &lvalue, align, expr_span);
+                }
                 let lvalue = lvalue.deref();
                 block.and(lvalue)
             }
             ExprKind::Index { lhs, index } => {
                 let (usize_ty, bool_ty) = (this.hir.usize_ty(), this.hir.bool_ty());
 
-                let slice = unpack!(block = this.as_lvalue(block, lhs));
+                let lhs = this.hir.mirror(lhs);
+                let slice = unpack!(block = this.expr_as_lvalue(block, lhs, check_alignment));
 
                 let idx = unpack!(block = this.as_operand(block, index));
 
                 // bounds check:
-                let (len, lt) = (this.temp(usize_ty.clone()), this.temp(bool_ty));
+                let (len, lt) = (this.temp(usize_ty.clone(), expr_span),
+                                 this.temp(bool_ty, expr_span));
                 this.cfg.push_assign(block, source_info, // len = len(slice)
                                      &len, Rvalue::Len(slice.clone()));
                 this.cfg.push_assign(block, source_info, // lt = idx < len
@@ -101,6 +150,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             ExprKind::Unsize { .. } |
             ExprKind::Repeat { .. } |
             ExprKind::Borrow { .. } |
+            ExprKind::AddressOf { .. } |
             ExprKind::If { .. } |
             ExprKind::Match { .. } |
             ExprKind::Loop { .. } |
@@ -122,4 +172,36 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             }
         }
     }
+
+    /// Asserts that the raw pointer `ptr` is aligned to `align` bytes.
+    fn check_alignment(&mut self,
+                       block: BasicBlock,
+                       ptr: &Lvalue<'tcx>,
+                       align: u64,
+                       span: Span)
+                       -> BasicBlock {
+        if align == 1 {
+            return block;
+        }
+        let source_info = self.source_info(span);
+        let (usize_ty, bool_ty) = (self.hir.usize_ty(), self.hir.bool_ty());
+
+        let (addr, low_bits, aligned) = (self.temp(usize_ty, span),
+                                         self.temp(usize_ty, span),
+                                         self.temp(bool_ty, span));
+        self.cfg.push_assign(block, source_info, // addr = ptr as usize
+                             &addr, Rvalue::Cast(CastKind::PointerExposeAddress,
+                                                 Operand::Consume(ptr.clone()),
+                                                 usize_ty));
+        let mask = self.hir.usize_literal(span, align - 1);
+        let mask = self.literal_operand(span, usize_ty, mask);
+        let zero = self.zero_literal(span, usize_ty);
+        for (lvalue, rvalue) in alignment_test(Operand::Consume(addr), mask, zero,
+                                               low_bits, aligned.clone()) {
+            self.cfg.push_assign(block, source_info, &lvalue, rvalue);
+        }
+
+        self.assert(block, Operand::Consume(aligned), true,
+                    AssertMessage::Misaligned, span)
+    }
 }
@@ -22,6 +22,7 @@ use hair::*;
 use rustc_const_math::{ConstInt, ConstIsize};
 use rustc::middle::const_val::ConstVal;
 use rustc::ty;
+use rustc::ty::cast::CastTy;
 use rustc::mir::repr::*;
 use syntax::ast;
 use syntax_pos::Span;
@@ -65,13 +66,29 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 })
             }
             ExprKind::Repeat { value, count } => {
+                let value = this.hir.mirror(value);
+                let elem_ty = value.ty;
                 let value_operand = unpack!(block = this.as_operand(block, value));
-                block.and(Rvalue::Repeat(value_operand, count))
+                // An element that needs dropping is not `Copy`, so it is
+                // repeated at most once. Build an array of the elements
+                // instead, so that an element repeated zero times is still
+                // dropped.
+                let len = count.value.as_u64(this.hir.tcx().sess.target.uint_type);
+                if len <= 1 && this.hir.needs_drop(elem_ty) {
+                    let elems = if len == 1 { vec![value_operand] } else { vec![] };
+                    block.and(Rvalue::Aggregate(AggregateKind::Vec, elems))
+                } else {
+                    block.and(Rvalue::Repeat(value_operand, count))
+                }
             }
             ExprKind::Borrow { region, borrow_kind, arg } => {
-                let arg_lvalue = unpack!(block = this.as_lvalue(block, arg));
+                let arg_lvalue = unpack!(block = this.as_address_lvalue(block, arg));
                 block.and(Rvalue::Ref(region, borrow_kind, arg_lvalue))
             }
+            ExprKind::AddressOf { mutability, arg } => {
+                let arg_lvalue = unpack!(block = this.as_address_lvalue(block, arg));
+                block.and(Rvalue::RawPtr(mutability, arg_lvalue))
+            }
             ExprKind::Binary { op, lhs, rhs } => {
                 let lhs = unpack!(block = this.as_operand(block, lhs));
                 let rhs = unpack!(block = this.as_operand(block, rhs));
@@ -85,20 +102,22 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     let bool_ty = this.hir.bool_ty();
 
                     let minval = this.minval_literal(expr_span, expr.ty);
-                    let is_min = this.temp(bool_ty);
+                    let is_min = this.temp(bool_ty, expr_span);
 
                     this.cfg.push_assign(block, source_info, &is_min,
                                          Rvalue::BinaryOp(BinOp::Eq, arg.clone(), minval));
 
-                    let err = ConstMathErr::Overflow(Op::Neg);
-                    block = this.assert(block, Operand::Consume(is_min), false,
-                                        AssertMessage::Math(err), expr_span);
+                    let msg = AssertMessage::Math {
+                        err: ConstMathErr::Overflow(Op::Neg),
+                        operands: vec![arg.clone()]
+                    };
+                    block = this.assert(block, Operand::Consume(is_min), false, msg, expr_span);
                 }
                 block.and(Rvalue::UnaryOp(op, arg))
             }
             ExprKind::Box { value, value_extents } => {
                 let value = this.hir.mirror(value);
-                let result = this.temp(expr.ty);
+                let result = this.temp(expr.ty, expr_span);
                 // to start, malloc some memory of suitable type (thus far, uninitialized):
                 this.cfg.push_assign(block, source_info, &result, Rvalue::Box(value.ty));
                 this.in_scope(value_extents, block, |this| {
@@ -112,8 +131,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             ExprKind::Cast { source } => {
                 let source = this.hir.mirror(source);
 
+                let kind = this.cast_kind(source.ty, expr.ty);
                 let source = unpack!(block = this.as_operand(block, source));
-                block.and(Rvalue::Cast(CastKind::Misc, source, expr.ty))
+                block.and(Rvalue::Cast(kind, source, expr.ty))
             }
             ExprKind::ReifyFnPointer { source } => {
                 let source = unpack!(block = this.as_operand(block, source));
@@ -250,7 +270,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         let bool_ty = self.hir.bool_ty();
         if self.hir.check_overflow() && op.is_checkable() && ty.is_integral() {
             let result_tup = self.hir.tcx().mk_tup(vec![ty, bool_ty]);
-            let result_value = self.temp(result_tup);
+            let result_value = self.temp(result_tup, span);
+            let operands = vec![lhs.clone(), rhs.clone()];
 
             self.cfg.push_assign(block, source_info,
                                  &result_value, Rvalue::CheckedBinaryOp(op,
@@ -273,8 +294,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 }
             });
 
-            block = self.assert(block, Operand::Consume(of), false,
-                                AssertMessage::Math(err), span);
+            let msg = AssertMessage::Math { err: err, operands: operands };
+            block = self.assert(block, Operand::Consume(of), false, msg, span);
 
             block.and(Rvalue::Use(Operand::Consume(val)))
         } else {
@@ -291,13 +312,16 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 };
 
                 // Check for / 0
-                let is_zero = self.temp(bool_ty);
+                let is_zero = self.temp(bool_ty, span);
                 let zero = self.zero_literal(span, ty);
                 self.cfg.push_assign(block, source_info, &is_zero,
                                      Rvalue::BinaryOp(BinOp::Eq, rhs.clone(), zero));
 
-                block = self.assert(block, Operand::Consume(is_zero), false,
-                                    AssertMessage::Math(zero_err), span);
+                let msg = AssertMessage::Math {
+                    err: zero_err,
+                    operands: vec![lhs.clone(), rhs.clone()]
+                };
+                block = self.assert(block, Operand::Consume(is_zero), false, msg, span);
 
                 // We only need to check for the overflow in one case:
                 // MIN / -1, and only for signed values.
@@ -305,9 +329,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     let neg_1 = self.neg_1_literal(span, ty);
                     let min = self.minval_literal(span, ty);
 
-                    let is_neg_1 = self.temp(bool_ty);
-                    let is_min   = self.temp(bool_ty);
-                    let of       = self.temp(bool_ty);
+                    let is_neg_1 = self.temp(bool_ty, span);
+                    let is_min   = self.temp(bool_ty, span);
+                    let of       = self.temp(bool_ty, span);
 
                     // this does (rhs == -1) & (lhs == MIN). It could short-circuit instead
 
@@ -321,8 +345,11 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     self.cfg.push_assign(block, source_info, &of,
                                          Rvalue::BinaryOp(BinOp::BitAnd, is_neg_1, is_min));
 
-                    block = self.assert(block, Operand::Consume(of), false,
-                                        AssertMessage::Math(overflow_err), span);
+                    let msg = AssertMessage::Math {
+                        err: overflow_err,
+                        operands: vec![lhs.clone(), rhs.clone()]
+                    };
+                    block = self.assert(block, Operand::Consume(of), false, msg, span);
                 }
             }
 
@@ -387,4 +414,30 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
         self.literal_operand(span, ty, literal)
     }
+
+    // Helper to get the kind of a non-coercion cast from `source_ty` to `target_ty`
+    fn cast_kind(&mut self, source_ty: ty::Ty<'tcx>, target_ty: ty::Ty<'tcx>) -> CastKind {
+        match (CastTy::from_ty(source_ty), CastTy::from_ty(target_ty)) {
+            (Some(CastTy::Ptr(_)), Some(CastTy::Int(_))) |
+            (Some(CastTy::FnPtr), Some(CastTy::Int(_))) => CastKind::PointerExposeAddress,
+            (Some(CastTy::Int(_)), Some(CastTy::Ptr(_))) => CastKind::PointerFromExposedAddress,
+            _ => match (self.int_bit_width(source_ty), self.int_bit_width(target_ty)) {
+                (Some(source), Some(target)) if target < source => CastKind::IntTruncate,
+                _ => CastKind::Misc,
+            }
+        }
+    }
+
+    // Helper to get the width of an integer type on the target
+    fn int_bit_width(&mut self, ty: ty::Ty<'tcx>) -> Option<usize> {
+        let tcx = self.hir.tcx();
+        let target = &tcx.sess.target;
+        match ty.sty {
+            ty::TyInt(ast::IntTy::Is) => target.int_type.bit_width(),
+            ty::TyInt(ity) => ity.bit_width(),
+            ty::TyUint(ast::UintTy::Us) => target.uint_type.bit_width(),
+            ty::TyUint(uty) => uty.bit_width(),
+            _ => None,
+        }
+    }
 }
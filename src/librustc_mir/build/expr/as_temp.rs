@@ -22,21 +22,38 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         where M: Mirror<'tcx, Output = Expr<'tcx>>
     {
         let expr = self.hir.mirror(expr);
-        self.expr_as_temp(block, expr)
+        self.expr_as_temp(block, expr, true)
     }
 
-    fn expr_as_temp(&mut self, mut block: BasicBlock, expr: Expr<'tcx>) -> BlockAnd<Lvalue<'tcx>> {
+    /// Compile `expr` into a fresh temporary, like `as_temp`, but without
+    /// scheduling a drop of it. The caller must make sure that nothing is
+    /// left in the temporary to drop, on every path out of its scope.
+    pub fn as_undropped_temp<M>(&mut self, block: BasicBlock, expr: M) -> BlockAnd<Lvalue<'tcx>>
+        where M: Mirror<'tcx, Output = Expr<'tcx>>
+    {
+        let expr = self.hir.mirror(expr);
+        self.expr_as_temp(block, expr, false)
+    }
+
+    fn expr_as_temp(&mut self,
+                    mut block: BasicBlock,
+                    expr: Expr<'tcx>,
+                    schedule_drop: bool)
+                    -> BlockAnd<Lvalue<'tcx>> {
         debug!("expr_as_temp(block={:?}, expr={:?})", block, expr);
         let this = self;
 
         if let ExprKind::Scope { extent, value } = expr.kind {
-            return this.in_scope(extent, block, |this| this.as_temp(block, value));
+            return this.in_scope(extent, block, |this| {
+                let value = this.hir.mirror(value);
+                this.expr_as_temp(block, value, schedule_drop)
+            });
         }
 
         let expr_ty = expr.ty.clone();
-        let temp = this.temp(expr_ty.clone());
         let temp_lifetime = expr.temp_lifetime;
         let expr_span = expr.span;
+        let temp = this.temp(expr_ty.clone(), expr_span);
 
         // Careful here not to cause an infinite cycle. If we always
         // called `into`, then for lvalues like `x.f`, it would
@@ -50,7 +67,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 let lvalue = unpack!(block = this.as_lvalue(block, expr));
                 let rvalue = Rvalue::Use(Operand::Consume(lvalue));
                 let source_info = this.source_info(expr_span);
-                this.cfg.push_assign(block, source_info, &temp, rvalue);
+                this.push_assign(block, source_info, &temp, rvalue);
             }
             _ => {
                 unpack!(block = this.into(&temp, block, expr));
@@ -61,7 +78,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         // anything because no values with a destructor can be created in
         // a constant at this time, even if the type may need dropping.
         if let Some(temp_lifetime) = temp_lifetime {
-            this.schedule_drop(expr_span, temp_lifetime, &temp, expr_ty);
+            if schedule_drop {
+                this.schedule_drop(expr_span, temp_lifetime, &temp, expr_ty);
+            }
         }
 
         block.and(temp)
@@ -72,6 +72,7 @@ impl Category {
             ExprKind::Unsize { .. } |
             ExprKind::Repeat { .. } |
             ExprKind::Borrow { .. } |
+            ExprKind::AddressOf { .. } |
             ExprKind::Assign { .. } |
             ExprKind::AssignOp { .. } |
             ExprKind::InlineAsm { .. } =>
@@ -33,6 +33,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         // just use the name `this` uniformly
         let this = self;
         let expr_span = expr.span;
+        let expr_ty = expr.ty;
         let source_info = this.source_info(expr_span);
 
         match expr.kind {
@@ -42,8 +43,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             ExprKind::Block { body: ast_block } => {
                 this.ast_block(destination, expr.ty.is_nil(), block, ast_block)
             }
-            ExprKind::Match { discriminant, arms } => {
-                this.match_expr(destination, expr_span, block, discriminant, arms)
+            ExprKind::Match { discriminant, arms, loop_desugar } => {
+                this.match_expr(destination, expr_span, block, discriminant, arms, loop_desugar)
             }
             ExprKind::If { condition: cond_expr, then: then_expr, otherwise: else_expr } => {
                 let operand = unpack!(block = this.as_operand(block, cond_expr));
@@ -61,7 +62,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 } else {
                     // Body of the `if` expression without an `else` clause must return `()`, thus
                     // we implicitly generate a `else {}` if it is not specified.
-                    this.cfg.push_assign_unit(else_block, source_info, destination);
+                    this.push_assign_unit(else_block, source_info, destination);
                     else_block
                 };
 
@@ -183,7 +184,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 // If the loop may reach its exit_block, we assign an empty tuple to the
                 // destination to keep the MIR well-formed.
                 if might_break {
-                    this.cfg.push_assign_unit(exit_block, source_info, destination);
+                    this.push_assign_unit(exit_block, source_info, destination);
                 }
                 exit_block.unit()
             }
@@ -200,6 +201,24 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                         .map(|arg| unpack!(block = this.as_operand(block, arg)))
                         .collect();
 
+                // The destination may be a variable that is already
+                // initialized, when assigning to it. If the call is passed
+                // (a part of) that variable, it has to return into a
+                // temporary, as it might read the argument after writing
+                // to the destination.
+                let base = destination.base();
+                let overlaps = Some(&fun).into_iter().chain(&args).any(|operand| {
+                    match *operand {
+                        Operand::Consume(ref lvalue) => lvalue.base() == base,
+                        Operand::Constant(_) => false,
+                    }
+                });
+                let call_destination = if overlaps {
+                    this.temp(expr.ty, expr_span)
+                } else {
+                    destination.clone()
+                };
+
                 let success = this.cfg.start_new_block();
                 let cleanup = this.diverge_cleanup();
                 this.cfg.terminate(block, source_info, TerminatorKind::Call {
@@ -209,9 +228,16 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     destination: if diverges {
                         None
                     } else {
-                        Some ((destination.clone(), success))
+                        Some ((call_destination.clone(), success))
                     }
                 });
+                if overlaps && !diverges {
+                    this.cfg.push_assign(success, source_info, destination,
+                                         Rvalue::Use(Operand::Consume(call_destination)));
+                }
+                if !diverges {
+                    this.push_subtype(success, source_info, destination, expr_ty);
+                }
                 success.unit()
             }
 
@@ -234,6 +260,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             ExprKind::Unsize { .. } |
             ExprKind::Repeat { .. } |
             ExprKind::Borrow { .. } |
+            ExprKind::AddressOf { .. } |
             ExprKind::VarRef { .. } |
             ExprKind::SelfRef |
             ExprKind::StaticRef { .. } |
@@ -252,7 +279,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 });
 
                 let rvalue = unpack!(block = this.as_rvalue(block, expr));
-                this.cfg.push_assign(block, source_info, destination, rvalue);
+                this.push_assign(block, source_info, destination, rvalue);
+                this.push_subtype(block, source_info, destination, expr_ty);
                 block.unit()
             }
         }
@@ -32,6 +32,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 let lhs = this.hir.mirror(lhs);
                 let rhs = this.hir.mirror(rhs);
                 let lhs_span = lhs.span;
+                let rhs_ty = rhs.ty;
 
                 // Note: we evaluate assignments right-to-left. This
                 // is better for borrowck interaction with overloaded
@@ -43,13 +44,23 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     let rhs = unpack!(block = this.as_operand(block, rhs));
                     let lhs = unpack!(block = this.as_lvalue(block, lhs));
                     unpack!(block = this.build_drop_and_replace(
-                        block, lhs_span, lhs, rhs
+                        block, lhs_span, lhs.clone(), rhs
                     ));
+                    this.push_subtype(block, source_info, &lhs, rhs_ty);
+                    block.unit()
+                } else if this.is_call(&rhs) && this.is_direct_destination(&lhs) {
+                    // Let the call return straight into `lhs`, instead of
+                    // into a temporary that is then copied. Evaluating `lhs`
+                    // first has no side effects, and nothing can observe it
+                    // being written to while the call runs.
+                    let lhs = unpack!(block = this.as_lvalue(block, lhs));
+                    unpack!(block = this.into(&lhs, block, rhs));
                     block.unit()
                 } else {
                     let rhs = unpack!(block = this.as_rvalue(block, rhs));
                     let lhs = unpack!(block = this.as_lvalue(block, lhs));
-                    this.cfg.push_assign(block, source_info, &lhs, rhs);
+                    this.push_assign(block, source_info, &lhs, rhs);
+                    this.push_subtype(block, source_info, &lhs, rhs_ty);
                     block.unit()
                 }
             }
@@ -103,7 +114,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             }
             _ => {
                 let expr_ty = expr.ty;
-                let temp = this.temp(expr.ty.clone());
+                let temp = this.temp(expr.ty.clone(), expr_span);
                 unpack!(block = this.into(&temp, block, expr));
                 unpack!(block = this.build_drop(block, expr_span, temp, expr_ty));
                 block.unit()
@@ -111,6 +122,31 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         }
     }
 
+    fn is_call(&mut self, expr: &Expr<'tcx>) -> bool {
+        match expr.kind {
+            ExprKind::Scope { ref value, .. } => {
+                let value = self.hir.mirror(value.clone());
+                self.is_call(&value)
+            }
+            ExprKind::Call { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// True if `expr` is a variable or a field of one, and the variable
+    /// is never borrowed.
+    fn is_direct_destination(&mut self, expr: &Expr<'tcx>) -> bool {
+        match expr.kind {
+            ExprKind::Scope { ref value, .. } |
+            ExprKind::Field { lhs: ref value, .. } => {
+                let value = self.hir.mirror(value.clone());
+                self.is_direct_destination(&value)
+            }
+            ExprKind::VarRef { id } => !self.borrowed_vars.contains(&id),
+            _ => false,
+        }
+    }
+
     fn break_or_continue<F>(&mut self,
                             span: Span,
                             label: Option<CodeExtent>,
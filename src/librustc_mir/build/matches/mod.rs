@@ -17,12 +17,14 @@ use build::{BlockAnd, BlockAndExtension, Builder};
 use rustc_data_structures::fnv::FnvHashMap;
 use rustc_data_structures::bitvec::BitVector;
 use rustc::middle::const_val::ConstVal;
-use rustc::ty::{AdtDef, Ty};
+use rustc::ty::{self, AdtDef, Ty, TypeFoldable};
 use rustc::mir::repr::*;
 use hair::*;
 use syntax::ast::{Name, NodeId};
 use syntax_pos::Span;
 
+use std::cmp;
+
 // helper functions, broken out by category:
 mod simplify;
 mod test;
@@ -34,10 +36,44 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                       span: Span,
                       mut block: BasicBlock,
                       discriminant: ExprRef<'tcx>,
-                      arms: Vec<Arm<'tcx>>)
+                      arms: Vec<Arm<'tcx>>,
+                      loop_desugar: bool)
                       -> BlockAnd<()> {
-        let discriminant_lvalue = unpack!(block = self.as_lvalue(block, discriminant));
+        // With `-Z short-scrutinee-temps`, the temporaries of the
+        // discriminant that live past the match are dropped as soon as an
+        // arm is entered, if nothing bound by the arms can borrow from them.
+        let drop_counts = if self.hir.short_scrutinee_temps() &&
+                             arms.iter().all(|arm| arm.patterns.iter().all(cannot_borrow)) {
+            Some(self.scheduled_drop_counts())
+        } else {
+            None
+        };
 
+        // The matches of loops are built once per iteration, so rather
+        // than dropping their temporary discriminant at the end of every
+        // iteration, and on the exits of the loop, after its contents have
+        // been moved out, it is not dropped at all if the arms always move
+        // out whatever needs to be dropped. Those contents are then dropped
+        // where the bindings they are moved into are.
+        //
+        // This is also what gives the iterator of a `for` loop a scope of
+        // its own: the loop is desugared to `match into_iter(head) { mut
+        // iter => loop { .. } }`, so the iterator is moved out into `iter`,
+        // whose arm encloses the whole loop. It is dropped once, when that
+        // arm is left, which every exit edge of the loop does.
+        let discriminant_lvalue = if loop_desugar &&
+                                     !self.is_lvalue_expr(&discriminant) &&
+                                     self.arms_move_out_drops(&arms) {
+            unpack!(block = self.as_undropped_temp(block, discriminant))
+        } else {
+            unpack!(block = self.as_lvalue(block, discriminant))
+        };
+        let scrutinee_drops = match drop_counts {
+            Some(counts) => self.drops_scheduled_since(&counts),
+            None => vec![],
+        };
+
+        let (blocks_before, tests_before) = (self.cfg.basic_blocks.len(), self.match_tests);
         let mut arm_blocks = ArmBlocks {
             blocks: arms.iter()
                         .map(|_| self.cfg.start_new_block())
@@ -73,11 +109,18 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     }
                 })
                 .collect();
+        let num_candidates = candidates.len();
 
         // this will generate code to test discriminant_lvalue and
         // branch to the appropriate arm block
         let otherwise = self.match_candidates(span, &mut arm_blocks, candidates, block);
 
+        if self.hir.tcx().sess.opts.debugging_opts.mir_match_stats {
+            let num_tests = self.match_tests - tests_before;
+            let num_blocks = self.cfg.basic_blocks.len() - blocks_before;
+            self.report_match_stats(span, num_candidates, num_tests, num_blocks);
+        }
+
         if !otherwise.is_empty() {
             // All matches are exhaustive. However, because some matches
             // only have exponentially-large exhaustive decision trees, we
@@ -101,6 +144,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         let outer_source_info = self.source_info(span);
         for (arm_index, (body, visibility_scope)) in arm_bodies.into_iter().enumerate() {
             let mut arm_block = arm_blocks.blocks[arm_index];
+            unpack!(arm_block = self.build_scheduled_drops(arm_block, &scrutinee_drops));
             // Re-enter the visibility scope we created the bindings in.
             self.visibility_scope = visibility_scope;
             unpack!(arm_block = self.into(destination, arm_block, body));
@@ -112,6 +156,96 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         end_block.unit()
     }
 
+    /// Whether every part of the discriminant that needs to be dropped is
+    /// moved out by the bindings of whichever of `arms` matches it.
+    fn arms_move_out_drops(&mut self, arms: &[Arm<'tcx>]) -> bool {
+        // The variants that are matched in full by an earlier arm, which a
+        // wildcard does not match.
+        let mut matched_variants = vec![];
+        for arm in arms {
+            if arm.guard.is_some() {
+                return false;
+            }
+            for pattern in &arm.patterns {
+                let moves_out = match *pattern.kind {
+                    PatternKind::Wild => self.other_variants_need_no_drop(pattern.ty,
+                                                                          &matched_variants),
+                    _ => self.pattern_moves_out_drops(pattern),
+                };
+                if !moves_out {
+                    return false;
+                }
+                if let PatternKind::Variant { variant_index, ref subpatterns, .. } =
+                        *pattern.kind {
+                    if subpatterns.iter().all(|p| is_plain_binding(&p.pattern)) {
+                        matched_variants.push(variant_index);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether the bindings of `pattern` move out every part of the value
+    /// it matches that needs to be dropped.
+    fn pattern_moves_out_drops(&mut self, pattern: &Pattern<'tcx>) -> bool {
+        match (&*pattern.kind, &pattern.ty.sty) {
+            (&PatternKind::Binding { mode: BindingMode::ByValue, subpattern: None, .. }, _) => {
+                true
+            }
+            (&PatternKind::Variant { adt_def, variant_index, ref subpatterns },
+             &ty::TyEnum(_, substs)) if !adt_def.has_dtor() => {
+                let field_tys: Vec<_> = adt_def.variants[variant_index].fields.iter()
+                    .map(|field| field.ty(self.hir.tcx(), substs))
+                    .collect();
+                self.fields_move_out_drops(&field_tys, subpatterns)
+            }
+            (&PatternKind::Leaf { ref subpatterns }, &ty::TyStruct(adt_def, substs))
+                    if !adt_def.has_dtor() => {
+                let field_tys: Vec<_> = adt_def.struct_variant().fields.iter()
+                    .map(|field| field.ty(self.hir.tcx(), substs))
+                    .collect();
+                self.fields_move_out_drops(&field_tys, subpatterns)
+            }
+            (&PatternKind::Leaf { ref subpatterns }, &ty::TyTuple(tys)) => {
+                self.fields_move_out_drops(tys, subpatterns)
+            }
+            _ => !self.hir.needs_drop(pattern.ty),
+        }
+    }
+
+    fn fields_move_out_drops(&mut self,
+                             field_tys: &[Ty<'tcx>],
+                             subpatterns: &[FieldPattern<'tcx>])
+                             -> bool {
+        field_tys.iter().enumerate().all(|(index, &field_ty)| {
+            match subpatterns.iter().find(|p| p.field.index() == index) {
+                Some(subpattern) => self.pattern_moves_out_drops(&subpattern.pattern),
+                None => !self.hir.needs_drop(field_ty),
+            }
+        })
+    }
+
+    /// Whether the values of `ty` that are not of one of `matched_variants`
+    /// need no drop.
+    fn other_variants_need_no_drop(&mut self,
+                                   ty: Ty<'tcx>,
+                                   matched_variants: &[usize])
+                                   -> bool {
+        match ty.sty {
+            ty::TyEnum(adt_def, substs) if !adt_def.has_dtor() => {
+                adt_def.variants.iter().enumerate().all(|(index, variant)| {
+                    matched_variants.contains(&index) ||
+                    variant.fields.iter().all(|field| {
+                        let field_ty = field.ty(self.hir.tcx(), substs);
+                        !self.hir.needs_drop(field_ty)
+                    })
+                })
+            }
+            _ => !self.hir.needs_drop(ty),
+        }
+    }
+
     pub fn expr_into_pattern(&mut self,
                              mut block: BasicBlock,
                              irrefutable_pat: Pattern<'tcx>,
@@ -208,6 +342,36 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
     }
 }
 
+/// Whether `pattern` matches anything without a test, e.g. `x` or `_`.
+fn is_plain_binding(pattern: &Pattern) -> bool {
+    match *pattern.kind {
+        PatternKind::Wild | PatternKind::Binding { subpattern: None, .. } => true,
+        _ => false,
+    }
+}
+
+/// Whether no binding of `pattern` can borrow from the value it matches:
+/// none binds by reference, or has a type with regions, which could be
+/// that of a reference into it.
+fn cannot_borrow(pattern: &Pattern) -> bool {
+    match *pattern.kind {
+        PatternKind::Binding { mode: BindingMode::ByRef(..), .. } => false,
+        PatternKind::Binding { ty, ref subpattern, .. } => {
+            !ty.has_erasable_regions() && subpattern.as_ref().map_or(true, cannot_borrow)
+        }
+        PatternKind::Wild | PatternKind::Constant { .. } | PatternKind::Range { .. } => true,
+        PatternKind::Deref { ref subpattern } => cannot_borrow(subpattern),
+        PatternKind::Leaf { ref subpatterns } |
+        PatternKind::Variant { ref subpatterns, .. } => {
+            subpatterns.iter().all(|subpattern| cannot_borrow(&subpattern.pattern))
+        }
+        PatternKind::Array { ref prefix, ref slice, ref suffix } |
+        PatternKind::Slice { ref prefix, ref slice, ref suffix } => {
+            prefix.iter().chain(slice).chain(suffix).all(cannot_borrow)
+        }
+    }
+}
+
 /// List of blocks for each arm (and potentially other metadata in the
 /// future).
 struct ArmBlocks {
@@ -264,6 +428,7 @@ enum TestKind<'tcx> {
     // test the branches of enum
     Switch {
         adt_def: AdtDef<'tcx>,
+        enum_ty: Ty<'tcx>,
         variants: BitVector,
     },
 
@@ -388,6 +553,28 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         self.match_candidates(span, arm_blocks, untested_candidates, join_block)
     }
 
+    /// Reports the size of the decision tree of the match at `span`, for
+    /// `-Z mir-match-stats`, warning if it has more blocks than the
+    /// threshold.
+    fn report_match_stats(&mut self,
+                          span: Span,
+                          num_candidates: usize,
+                          num_tests: usize,
+                          num_blocks: usize) {
+        let sess = self.hir.tcx().sess;
+        let threshold = sess.opts.debugging_opts.mir_match_stats_threshold;
+        let msg = format!("match has {} candidates, performs {} tests and generates {} blocks",
+                          num_candidates, num_tests, num_blocks);
+        if num_blocks > threshold {
+            sess.struct_span_warn(span, &msg)
+                .note(&format!("more than the {} blocks of -Z mir-match-stats-threshold",
+                               threshold))
+                .emit();
+        } else {
+            sess.span_note_without_error(span, &msg);
+        }
+    }
+
     fn join_otherwise_blocks(&mut self,
                              span: Span,
                              mut otherwise: Vec<BasicBlock>)
@@ -546,7 +733,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     }
                 }
             }
-            TestKind::Switch { adt_def: _, ref mut variants} => {
+            TestKind::Switch { ref mut variants, .. } => {
                 for candidate in candidates.iter() {
                     if !self.add_variants_to_switch(&match_pair.lvalue,
                                                     candidate,
@@ -564,6 +751,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         // apply if the test has that particular outcome.
         debug!("match_candidates: test={:?} match_pair={:?}", test, match_pair);
         let target_blocks = self.perform_test(block, &match_pair.lvalue, &test);
+        self.match_tests += 1;
         let mut target_candidates: Vec<_> = (0..target_blocks.len()).map(|_| vec![]).collect();
 
         // Sort the candidates into the appropriate vector in
@@ -650,6 +838,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         debug!("bind_matched_candidate(block={:?}, bindings={:?})",
                block, bindings);
 
+        self.check_binding_aliases(&bindings);
+
         // Assign each of the bindings. This may trigger moves out of the candidate.
         for binding in bindings {
             // Find the variable for the `var_id` being bound. It
@@ -670,6 +860,37 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         }
     }
 
+    /// Reports the `ref mut` bindings of a candidate whose lvalue overlaps
+    /// that of another of its bindings, as they would alias. This is only
+    /// possible with `@` patterns, as in `ref mut a @ Some(ref b)`.
+    fn check_binding_aliases(&mut self, bindings: &[Binding<'tcx>]) {
+        for (i, a) in bindings.iter().enumerate() {
+            for b in &bindings[i + 1..] {
+                let (mutable, other) = match (a.binding_mode, b.binding_mode) {
+                    (BindingMode::ByRef(_, BorrowKind::Mut), _) => (a, b),
+                    (_, BindingMode::ByRef(_, BorrowKind::Mut)) => (b, a),
+                    _ => continue,
+                };
+                if !lvalues_overlap(&mutable.source, &other.source) {
+                    continue;
+                }
+                // The same candidate is bound once per guard it is tested
+                // against, so only report each pair once.
+                if !self.aliasing_bindings.insert((mutable.var_id, other.var_id)) {
+                    continue;
+                }
+                struct_span_err!(self.hir.tcx().sess, mutable.span, E0566,
+                                 "cannot bind `{}` by mutable reference because it \
+                                  overlaps the binding `{}`",
+                                 mutable.name, other.name)
+                    .span_label(mutable.span, &format!("mutable borrow of `{}` here",
+                                                       mutable.name))
+                    .span_label(other.span, &format!("`{}` bound here", other.name))
+                    .emit();
+            }
+        }
+    }
+
     fn declare_binding(&mut self,
                        source_info: SourceInfo,
                        mutability: Mutability,
@@ -696,3 +917,57 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         var
     }
 }
+
+/// Returns true if `a` and `b` may refer to overlapping memory, that is if
+/// they project from the same lvalue along paths that don't select disjoint
+/// parts of a value, like different fields, variants or slice elements.
+fn lvalues_overlap<'tcx>(a: &Lvalue<'tcx>, b: &Lvalue<'tcx>) -> bool {
+    let (base_a, elems_a) = lvalue_elems(a);
+    let (base_b, elems_b) = lvalue_elems(b);
+    base_a == base_b &&
+        elems_a.iter().zip(&elems_b).all(|(a, b)| !elems_disjoint(a, b))
+}
+
+/// Splits `lvalue` into its base and the projections applied to it,
+/// outermost last.
+fn lvalue_elems<'a, 'tcx>(mut lvalue: &'a Lvalue<'tcx>)
+                          -> (&'a Lvalue<'tcx>, Vec<&'a LvalueElem<'tcx>>) {
+    let mut elems = vec![];
+    while let Lvalue::Projection(ref proj) = *lvalue {
+        elems.push(&proj.elem);
+        lvalue = &proj.base;
+    }
+    elems.reverse();
+    (lvalue, elems)
+}
+
+/// Returns true if the projections `a` and `b` of the same lvalue are
+/// known to select disjoint parts of it.
+fn elems_disjoint<'tcx>(a: &LvalueElem<'tcx>, b: &LvalueElem<'tcx>) -> bool {
+    match (a, b) {
+        (&ProjectionElem::Field(a, _), &ProjectionElem::Field(b, _)) => a != b,
+        (&ProjectionElem::Downcast(_, a), &ProjectionElem::Downcast(_, b)) => a != b,
+        (&ProjectionElem::ConstantIndex { offset: a, min_length: min_a, from_end: end_a },
+         &ProjectionElem::ConstantIndex { offset: b, min_length: min_b, from_end: end_b }) => {
+            if end_a == end_b {
+                a != b
+            } else {
+                // One counts from the start and the other from the end, so
+                // they are the same element only if the slice is `a + b`
+                // long, which is shorter than the pattern requires.
+                a + b < cmp::max(min_a, min_b)
+            }
+        }
+        (&ProjectionElem::ConstantIndex { offset, from_end, .. },
+         &ProjectionElem::Subslice { from, to }) |
+        (&ProjectionElem::Subslice { from, to },
+         &ProjectionElem::ConstantIndex { offset, from_end, .. }) => {
+            if from_end {
+                offset <= to
+            } else {
+                offset < from
+            }
+        }
+        _ => false,
+    }
+}
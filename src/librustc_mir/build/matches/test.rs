@@ -17,6 +17,7 @@
 
 use build::Builder;
 use build::matches::{Candidate, MatchPair, Test, TestKind};
+use cost_model;
 use hair::*;
 use rustc_data_structures::fnv::FnvHashMap;
 use rustc_data_structures::bitvec::BitVector;
@@ -37,6 +38,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     span: match_pair.pattern.span,
                     kind: TestKind::Switch {
                         adt_def: adt_def.clone(),
+                        enum_ty: match_pair.pattern.ty,
                         variants: BitVector::new(self.hir.num_variants(adt_def)),
                     },
                 }
@@ -179,7 +181,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                         -> Vec<BasicBlock> {
         let source_info = self.source_info(test.span);
         match test.kind {
-            TestKind::Switch { adt_def, ref variants } => {
+            TestKind::Switch { adt_def, enum_ty, ref variants } => {
                 let num_enum_variants = self.hir.num_variants(adt_def);
                 let mut otherwise_block = None;
                 let target_blocks: Vec<_> = (0..num_enum_variants).map(|i| {
@@ -194,6 +196,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 }).collect();
                 debug!("num_enum_variants: {}, num tested variants: {}, variants: {:?}",
                        num_enum_variants, variants.iter().count(), variants);
+                if self.niche_test(block, lvalue, enum_ty, test.span, &target_blocks) {
+                    return target_blocks;
+                }
                 self.cfg.terminate(block, source_info, TerminatorKind::Switch {
                     discr: lvalue.clone(),
                     adt_def: adt_def,
@@ -262,7 +267,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     if let ty::TyRef(region, mt) = ty.sty {
                         if let ty::TyArray(_, _) = mt.ty.sty {
                             ty = tcx.mk_imm_ref(region, tcx.mk_slice(tcx.types.u8));
-                            let val_slice = self.temp(ty);
+                            let val_slice = self.temp(ty, test.span);
                             self.cfg.push_assign(block, source_info, &val_slice,
                                                  Rvalue::Cast(CastKind::Unsize, val, ty));
                             val = Operand::Consume(val_slice);
@@ -277,7 +282,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                         value: value.clone()
                     });
 
-                    let slice = self.temp(ty);
+                    let slice = self.temp(ty, test.span);
                     self.cfg.push_assign(block, source_info, &slice,
                                          Rvalue::Cast(CastKind::Unsize, array, ty));
                     Operand::Consume(slice)
@@ -296,7 +301,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     let (mty, method) = self.hir.trait_method(eq_def_id, "eq", ty, vec![ty]);
 
                     let bool_ty = self.hir.bool_ty();
-                    let eq_result = self.temp(bool_ty);
+                    let eq_result = self.temp(bool_ty, test.span);
                     let eq_block = self.cfg.start_new_block();
                     let cleanup = self.diverge_cleanup();
                     self.cfg.terminate(block, source_info, TerminatorKind::Call {
@@ -339,7 +344,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
             TestKind::Len { len, op } => {
                 let (usize_ty, bool_ty) = (self.hir.usize_ty(), self.hir.bool_ty());
-                let (actual, result) = (self.temp(usize_ty), self.temp(bool_ty));
+                let (actual, result) = (self.temp(usize_ty, test.span),
+                                         self.temp(bool_ty, test.span));
 
                 // actual = len(lvalue)
                 self.cfg.push_assign(block, source_info,
@@ -367,6 +373,65 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         }
     }
 
+    /// Tells the variants of `lvalue`, of type `ty`, apart by comparing its
+    /// niche field with zero instead of reading its discriminant, and
+    /// returns true, if the layout of `ty` allows it and the cost model of
+    /// the session prefers it.
+    fn niche_test(&mut self,
+                  block: BasicBlock,
+                  lvalue: &Lvalue<'tcx>,
+                  ty: Ty<'tcx>,
+                  span: Span,
+                  target_blocks: &[BasicBlock])
+                  -> bool {
+        // Pointers cannot be compared with integers in constants.
+        if self.hir.is_const() {
+            return false;
+        }
+        if !cost_model::prefers_niche_test(self.hir.tcx().sess) {
+            return false;
+        }
+        let (nonnull_variant, field, field_ty) = match self.hir.niche_field(lvalue, ty) {
+            Some(niche) => niche,
+            None => return false,
+        };
+        let source_info = self.source_info(span);
+        let (value, value_ty) = match field_ty.sty {
+            ty::TyInt(_) | ty::TyUint(_) => (Operand::Consume(field), field_ty),
+            // A reference read as such is assumed to be non-null by trans,
+            // which would fold the comparison away, so pointers are read
+            // as a `usize` through a raw pointer to the field instead.
+            ty::TyRef(..) | ty::TyRawPtr(_) | ty::TyFnPtr(_) => {
+                let tcx = self.hir.tcx();
+                let usize_ty = self.hir.usize_ty();
+                let field_ptr_ty = tcx.mk_imm_ptr(field_ty);
+                let field_ptr = self.temp(field_ptr_ty, span);
+                self.cfg.push_assign(block, source_info, &field_ptr,
+                                     Rvalue::RawPtr(Mutability::Not, field));
+                let addr_ptr_ty = tcx.mk_imm_ptr(usize_ty);
+                let addr_ptr = self.temp(addr_ptr_ty, span);
+                self.cfg.push_assign(block, source_info, &addr_ptr,
+                                     Rvalue::Cast(CastKind::Misc,
+                                                  Operand::Consume(field_ptr),
+                                                  addr_ptr_ty));
+                (Operand::Consume(addr_ptr.deref()), usize_ty)
+            }
+            // Other fields, such as boxes, would be moved by reading them.
+            _ => return false,
+        };
+
+        let zero = self.zero_literal(span, value_ty);
+        let bool_ty = self.hir.bool_ty();
+        let is_null = self.temp(bool_ty, span);
+        self.cfg.push_assign(block, source_info, &is_null,
+                             Rvalue::BinaryOp(BinOp::Eq, value, zero));
+        self.cfg.terminate(block, source_info, TerminatorKind::If {
+            cond: Operand::Consume(is_null),
+            targets: (target_blocks[1 - nonnull_variant], target_blocks[nonnull_variant])
+        });
+        true
+    }
+
     fn compare(&mut self,
                block: BasicBlock,
                fail_block: BasicBlock,
@@ -375,7 +440,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                left: Operand<'tcx>,
                right: Operand<'tcx>) -> BasicBlock {
         let bool_ty = self.hir.bool_ty();
-        let result = self.temp(bool_ty);
+        let result = self.temp(bool_ty, span);
 
         // result = op(left, right)
         let source_info = self.source_info(span);
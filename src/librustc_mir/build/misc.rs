@@ -13,22 +13,20 @@
 
 use build::Builder;
 
-use rustc_const_math::{ConstInt, ConstUsize, ConstIsize};
 use rustc::middle::const_val::ConstVal;
 use rustc::ty::{self, Ty};
 
 use rustc::mir::repr::*;
-use syntax::ast;
 use syntax_pos::Span;
 
 impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
     /// Add a new temporary value of type `ty` storing the result of
-    /// evaluating `expr`.
+    /// evaluating the expression at `span`.
     ///
     /// NB: **No cleanup is scheduled for this temporary.** You should
     /// call `schedule_drop` once the temporary is initialized.
-    pub fn temp(&mut self, ty: Ty<'tcx>) -> Lvalue<'tcx> {
-        let temp = self.temp_decls.push(TempDecl { ty: ty });
+    pub fn temp(&mut self, ty: Ty<'tcx>, span: Span) -> Lvalue<'tcx> {
+        let temp = self.temp_decls.push(TempDecl { ty: ty, span: span });
         let lvalue = Lvalue::Temp(temp);
         debug!("temp: created temp {:?} with type {:?}",
                lvalue, self.temp_decls[temp].ty);
@@ -60,36 +58,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 self.hir.false_literal()
             }
             ty::TyChar => Literal::Value { value: ConstVal::Char('\0') },
-            ty::TyUint(ity) => {
-                let val = match ity {
-                    ast::UintTy::U8  => ConstInt::U8(0),
-                    ast::UintTy::U16 => ConstInt::U16(0),
-                    ast::UintTy::U32 => ConstInt::U32(0),
-                    ast::UintTy::U64 => ConstInt::U64(0),
-                    ast::UintTy::Us => {
-                        let uint_ty = self.hir.tcx().sess.target.uint_type;
-                        let val = ConstUsize::new(0, uint_ty).unwrap();
-                        ConstInt::Usize(val)
-                    }
-                };
-
-                Literal::Value { value: ConstVal::Integral(val) }
-            }
-            ty::TyInt(ity) => {
-                let val = match ity {
-                    ast::IntTy::I8  => ConstInt::I8(0),
-                    ast::IntTy::I16 => ConstInt::I16(0),
-                    ast::IntTy::I32 => ConstInt::I32(0),
-                    ast::IntTy::I64 => ConstInt::I64(0),
-                    ast::IntTy::Is => {
-                        let int_ty = self.hir.tcx().sess.target.int_type;
-                        let val = ConstIsize::new(0, int_ty).unwrap();
-                        ConstInt::Isize(val)
-                    }
-                };
-
-                Literal::Value { value: ConstVal::Integral(val) }
-            }
+            ty::TyUint(_) | ty::TyInt(_) => self.hir.int_literal(span, 0, ty),
             _ => {
                 span_bug!(span, "Invalid type for zero_literal: `{:?}`", ty)
             }
@@ -98,20 +67,99 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         self.literal_operand(span, ty, literal)
     }
 
+    /// Pushes the assignment of `rvalue` to `lvalue`, unless it would do
+    /// nothing: when `lvalue` is a variable or temporary of a zero-sized
+    /// type and `rvalue` only uses operands. The assignments of types that
+    /// need to be dropped are kept, as drop elaboration tracks which values
+    /// are initialized from them, and so are those of constants and const
+    /// fns, whose evaluation checks that every temporary read is assigned.
+    pub fn push_assign(&mut self,
+                       block: BasicBlock,
+                       source_info: SourceInfo,
+                       lvalue: &Lvalue<'tcx>,
+                       rvalue: Rvalue<'tcx>) {
+        if !self.is_elidable_assign(lvalue, &rvalue) {
+            self.cfg.push_assign(block, source_info, lvalue, rvalue);
+        }
+    }
+
+    pub fn push_assign_unit(&mut self,
+                            block: BasicBlock,
+                            source_info: SourceInfo,
+                            lvalue: &Lvalue<'tcx>) {
+        let rvalue = self.unit_rvalue();
+        self.push_assign(block, source_info, lvalue, rvalue);
+    }
+
+    fn is_elidable_assign(&mut self, lvalue: &Lvalue<'tcx>, rvalue: &Rvalue<'tcx>) -> bool {
+        if self.hir.is_const() {
+            return false;
+        }
+        match *rvalue {
+            Rvalue::Use(..) | Rvalue::Repeat(..) | Rvalue::Aggregate(..) => {}
+            _ => return false,
+        }
+        let ty = match *lvalue {
+            Lvalue::Var(var) => self.var_decls[var].ty,
+            Lvalue::Temp(temp) => self.temp_decls[temp].ty,
+            _ => return false,
+        };
+        self.hir.is_zero_sized(ty) && !self.hir.needs_drop(ty)
+    }
+
     pub fn push_usize(&mut self,
                       block: BasicBlock,
                       source_info: SourceInfo,
                       value: u64)
                       -> Lvalue<'tcx> {
         let usize_ty = self.hir.usize_ty();
-        let temp = self.temp(usize_ty);
+        let temp = self.temp(usize_ty, source_info.span);
         self.cfg.push_assign_constant(
             block, source_info, &temp,
             Constant {
                 span: source_info.span,
                 ty: self.hir.usize_ty(),
-                literal: self.hir.usize_literal(value),
+                literal: self.hir.usize_literal(source_info.span, value),
             });
         temp
     }
+
+    /// Records that the value of type `ty` just written to `lvalue` is
+    /// coerced to the type of `lvalue` by subtyping, if the types differ.
+    /// Writes to temporaries, which are created with the type of the value
+    /// written to them, are not recorded, and neither are writes through a
+    /// dereference or an index, as the builder does not know their types.
+    pub fn push_subtype(&mut self,
+                        block: BasicBlock,
+                        source_info: SourceInfo,
+                        lvalue: &Lvalue<'tcx>,
+                        ty: Ty<'tcx>) {
+        let mut base = lvalue;
+        while let Lvalue::Projection(ref proj) = *base {
+            match proj.elem {
+                ProjectionElem::Field(..) => base = &proj.base,
+                _ => return,
+            }
+        }
+        if let Lvalue::Temp(_) = *base {
+            return;
+        }
+        let lvalue_ty = match *lvalue {
+            Lvalue::Var(var) => self.var_decls[var].ty,
+            Lvalue::ReturnPointer => match self.return_ty {
+                Some(return_ty) => return_ty,
+                None => return,
+            },
+            Lvalue::Projection(box Projection { elem: ProjectionElem::Field(_, field_ty), .. }) => {
+                field_ty
+            }
+            _ => return,
+        };
+        if lvalue_ty != ty {
+            self.cfg.push(block, Statement {
+                source_info: source_info,
+                kind: StatementKind::Subtype { lvalue: lvalue.clone(), ty: ty },
+            });
+        }
+    }
 }
@@ -9,18 +9,24 @@
 // except according to those terms.
 
 use hair::cx::Cx;
+use pretty;
 use rustc::middle::region::{CodeExtent, CodeExtentData, ROOT_CODE_EXTENT};
 use rustc::ty::{self, Ty};
 use rustc::mir::repr::*;
-use rustc::util::nodemap::NodeMap;
+use rustc::util::nodemap::{NodeMap, NodeSet};
 use rustc::hir;
 use syntax::abi::Abi;
 use syntax::ast;
+use syntax::errors::FatalError;
 use syntax::parse::token::keywords;
-use syntax_pos::Span;
+use syntax_pos::{Span, DUMMY_SP};
 
+use rustc_data_structures::fnv::FnvHashSet;
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
 
+use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::u32;
 
 pub struct Builder<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
@@ -51,8 +57,19 @@ pub struct Builder<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
 
     var_decls: IndexVec<Var, VarDecl<'tcx>>,
     var_indices: NodeMap<Var>,
+    /// the variables that are borrowed somewhere in the function, which
+    /// calls cannot return into directly
+    borrowed_vars: NodeSet,
     temp_decls: IndexVec<Temp, TempDecl<'tcx>>,
     unit_temp: Option<Lvalue<'tcx>>,
+    /// the type of the return pointer, if it is not diverging
+    return_ty: Option<Ty<'tcx>>,
+    /// the number of tests the matches built so far perform, for
+    /// `-Z mir-match-stats`
+    match_tests: usize,
+    /// the pairs of bindings, a `ref mut` one first, that have been
+    /// reported to alias, as candidates can be bound in several places
+    aliasing_bindings: FnvHashSet<(ast::NodeId, ast::NodeId)>,
 
     /// cached block with the RESUME terminator; this is created
     /// when first set of cleanups are built.
@@ -101,16 +118,6 @@ pub struct ScopeAuxiliary {
     pub postdoms: Vec<Location>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-pub struct Location {
-    /// the location is within this block
-    pub block: BasicBlock,
-
-    /// the location is the start of the this statement; or, if `statement_index`
-    /// == num-statements, then the start of the terminator.
-    pub statement_index: usize,
-}
-
 pub type ScopeAuxiliaryVec = IndexVec<ScopeId, ScopeAuxiliary>;
 
 ///////////////////////////////////////////////////////////////////////////
@@ -163,6 +170,7 @@ pub fn construct_fn<'a, 'gcx, 'tcx, A>(hir: Cx<'a, 'gcx, 'tcx>,
                                        fn_id: ast::NodeId,
                                        arguments: A,
                                        return_ty: ty::FnOutput<'gcx>,
+                                       fn_decl: &'gcx hir::FnDecl,
                                        ast_block: &'gcx hir::Block)
                                        -> (Mir<'tcx>, ScopeAuxiliaryVec)
     where A: Iterator<Item=(Ty<'gcx>, Option<&'gcx hir::Pat>)>
@@ -170,6 +178,10 @@ pub fn construct_fn<'a, 'gcx, 'tcx, A>(hir: Cx<'a, 'gcx, 'tcx>,
     let tcx = hir.tcx();
     let span = tcx.map.span(fn_id);
     let mut builder = Builder::new(hir, span);
+    builder.borrowed_vars = builder.hir.borrowed_vars(fn_decl, ast_block);
+    if let ty::FnConverging(ty) = return_ty {
+        builder.return_ty = Some(ty);
+    }
 
     let body_id = ast_block.id;
     let call_site_extent =
@@ -179,18 +191,20 @@ pub fn construct_fn<'a, 'gcx, 'tcx, A>(hir: Cx<'a, 'gcx, 'tcx>,
         tcx.region_maps.lookup_code_extent(
             CodeExtentData::ParameterScope { fn_id: fn_id, body_id: body_id });
     let mut block = START_BLOCK;
-    let mut arg_decls = unpack!(block = builder.in_scope(call_site_extent, block, |builder| {
-        let arg_decls = unpack!(block = builder.in_scope(arg_extent, block, |builder| {
-            builder.args_and_body(block, return_ty, arguments, arg_extent, ast_block)
-        }));
-
-        let source_info = builder.source_info(span);
-        let return_block = builder.return_block();
-        builder.cfg.terminate(block, source_info,
-                              TerminatorKind::Goto { target: return_block });
-        builder.cfg.terminate(return_block, source_info,
-                              TerminatorKind::Return);
-        return_block.and(arg_decls)
+    let mut arg_decls = unpack!(block = builder.build_body(|builder| {
+        builder.in_scope(call_site_extent, block, |builder| {
+            let arg_decls = unpack!(block = builder.in_scope(arg_extent, block, |builder| {
+                builder.args_and_body(block, return_ty, arguments, arg_extent, ast_block)
+            }));
+
+            let source_info = builder.source_info(span);
+            let return_block = builder.return_block();
+            builder.cfg.terminate(block, source_info,
+                                  TerminatorKind::Goto { target: return_block });
+            builder.cfg.terminate(return_block, source_info,
+                                  TerminatorKind::Return);
+            return_block.and(arg_decls)
+        })
     }));
     assert_eq!(block, builder.return_block());
 
@@ -205,7 +219,7 @@ pub fn construct_fn<'a, 'gcx, 'tcx, A>(hir: Cx<'a, 'gcx, 'tcx>,
     }
 
     // Gather the upvars of a closure, if any.
-    let upvar_decls: Vec<_> = tcx.with_freevars(fn_id, |freevars| {
+    let upvar_decls: IndexVec<_, _> = tcx.with_freevars(fn_id, |freevars| {
         freevars.iter().map(|fv| {
             let by_ref = tcx.upvar_capture(ty::UpvarId {
                 var_id: fv.def.var_id(),
@@ -237,25 +251,28 @@ pub fn construct_const<'a, 'gcx, 'tcx>(hir: Cx<'a, 'gcx, 'tcx>,
     let tcx = hir.tcx();
     let span = tcx.map.span(item_id);
     let mut builder = Builder::new(hir, span);
+    let ty = tcx.expr_ty_adjusted(ast_expr);
+    builder.return_ty = Some(ty);
 
     let extent = ROOT_CODE_EXTENT;
     let mut block = START_BLOCK;
-    let _ = builder.in_scope(extent, block, |builder| {
-        let expr = builder.hir.mirror(ast_expr);
-        unpack!(block = builder.into(&Lvalue::ReturnPointer, block, expr));
-
-        let source_info = builder.source_info(span);
-        let return_block = builder.return_block();
-        builder.cfg.terminate(block, source_info,
-                              TerminatorKind::Goto { target: return_block });
-        builder.cfg.terminate(return_block, source_info,
-                              TerminatorKind::Return);
-
-        return_block.unit()
+    let _ = builder.build_body(|builder| {
+        builder.in_scope(extent, block, |builder| {
+            let expr = builder.hir.mirror(ast_expr);
+            unpack!(block = builder.into(&Lvalue::ReturnPointer, block, expr));
+
+            let source_info = builder.source_info(span);
+            let return_block = builder.return_block();
+            builder.cfg.terminate(block, source_info,
+                                  TerminatorKind::Goto { target: return_block });
+            builder.cfg.terminate(return_block, source_info,
+                                  TerminatorKind::Return);
+
+            return_block.unit()
+        })
     });
 
-    let ty = tcx.expr_ty_adjusted(ast_expr);
-    builder.finish(vec![], IndexVec::new(), ty::FnConverging(ty))
+    builder.finish(IndexVec::new(), IndexVec::new(), ty::FnConverging(ty))
 }
 
 impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
@@ -272,7 +289,11 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             temp_decls: IndexVec::new(),
             var_decls: IndexVec::new(),
             var_indices: NodeMap(),
+            borrowed_vars: NodeSet(),
             unit_temp: None,
+            return_ty: None,
+            match_tests: 0,
+            aliasing_bindings: FnvHashSet(),
             cached_resume_block: None,
             cached_return_block: None
         };
@@ -285,16 +306,10 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
     }
 
     fn finish(self,
-              upvar_decls: Vec<UpvarDecl>,
+              upvar_decls: IndexVec<Field, UpvarDecl>,
               arg_decls: IndexVec<Arg, ArgDecl<'tcx>>,
               return_ty: ty::FnOutput<'tcx>)
               -> (Mir<'tcx>, ScopeAuxiliaryVec) {
-        for (index, block) in self.cfg.basic_blocks.iter().enumerate() {
-            if block.terminator.is_none() {
-                span_bug!(self.fn_span, "no terminator on block {:?}", index);
-            }
-        }
-
         (Mir::new(self.cfg.basic_blocks,
                   self.visibility_scopes,
                   IndexVec::new(),
@@ -307,6 +322,101 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         ), self.scope_auxiliary)
     }
 
+    /// Builds the body with `f`, checking that every block ends up with a
+    /// terminator. If that panics with a bug, rather than a fatal error,
+    /// and `-Z dump-mir-on-ice` or `#[rustc_mir_dump_on_ice]` ask for it,
+    /// the MIR built so far is dumped before the panic goes on.
+    fn build_body<F, R>(&mut self, f: F) -> R
+        where F: FnOnce(&mut Builder<'a, 'gcx, 'tcx>) -> R
+    {
+        if !self.hir.dump_on_ice() {
+            let rv = f(self);
+            self.check_terminators();
+            return rv;
+        }
+
+        let result = {
+            let builder = &mut *self;
+            panic::catch_unwind(AssertUnwindSafe(move || {
+                let rv = f(builder);
+                builder.check_terminators();
+                rv
+            }))
+        };
+        match result {
+            Ok(rv) => rv,
+            Err(payload) => {
+                if !payload.is::<FatalError>() {
+                    self.dump_partial_mir();
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    fn check_terminators(&self) {
+        for (index, block) in self.cfg.basic_blocks.iter().enumerate() {
+            if block.terminator.is_none() {
+                span_bug!(self.fn_span, "no terminator on block {:?}", index);
+            }
+        }
+    }
+
+    /// Writes the declarations and blocks built so far, and the scopes
+    /// that were still open, to `rustc.node<id>.build.ice.mir` in the MIR
+    /// dump directory.
+    fn dump_partial_mir(&self) {
+        let tcx = self.hir.tcx().global_tcx();
+        let src = self.hir.src();
+        let file_path = match pretty::dump_path(tcx, "build", &"ice", src, "mir") {
+            Some(file_path) => file_path,
+            None => return,
+        };
+        let node_path = tcx.item_path_str(tcx.map.local_def_id(src.item_id()));
+        let result = fs::File::create(&file_path).and_then(|mut file| {
+            try!(writeln!(file, "// partially built MIR for `{}`", node_path));
+            try!(writeln!(file, "// node_id = {}", src.item_id()));
+            try!(writeln!(file, ""));
+            self.write_partial_mir(&mut file)
+        });
+        match result {
+            Ok(()) => {
+                tcx.sess.note_without_error(&format!("partially built MIR written to `{}`",
+                                                     file_path.display()));
+            }
+            Err(e) => {
+                tcx.sess.note_without_error(&format!("could not write partially built MIR \
+                                                      to `{}`: {}",
+                                                     file_path.display(), e));
+            }
+        }
+    }
+
+    fn write_partial_mir(&self, w: &mut Write) -> io::Result<()> {
+        for (var, decl) in self.var_decls.iter_enumerated() {
+            let mut_str = if decl.mutability == Mutability::Mut { "mut " } else { "" };
+            try!(writeln!(w, "let {}{:?}: {}; // {}", mut_str, var, decl.ty, decl.name));
+        }
+        for (temp, decl) in self.temp_decls.iter_enumerated() {
+            try!(writeln!(w, "let mut {:?}: {};", temp, decl.ty));
+        }
+        for (block, data) in self.cfg.basic_blocks.iter_enumerated() {
+            try!(writeln!(w, ""));
+            let cleanup_text = if data.is_cleanup { " // cleanup" } else { "" };
+            try!(writeln!(w, "{:?}: {{{}", block, cleanup_text));
+            for statement in &data.statements {
+                try!(writeln!(w, "    {:?};", statement));
+            }
+            match data.terminator {
+                Some(ref terminator) => try!(writeln!(w, "    {:?};", terminator.kind)),
+                None => try!(writeln!(w, "    // no terminator yet")),
+            }
+            try!(writeln!(w, "}}"));
+        }
+        try!(writeln!(w, ""));
+        self.write_open_scopes(w)
+    }
+
     fn args_and_body<A>(&mut self,
                         mut block: BasicBlock,
                         return_ty: ty::FnOutput<'tcx>,
@@ -349,6 +459,12 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             self.visibility_scope = visibility_scope;
         }
 
+        // `#[rustc_mir_build_bug]` stops here, once the arguments are bound,
+        // so that there are open scopes to dump.
+        if self.hir.force_build_bug() {
+            span_bug!(ast_block.span, "forced by #[rustc_mir_build_bug]");
+        }
+
         // FIXME(#32959): temporary hack for the issue at hand
         let return_is_unit = if let ty::FnConverging(t) = return_ty {
             t.is_nil()
@@ -366,7 +482,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             Some(ref tmp) => tmp.clone(),
             None => {
                 let ty = self.hir.unit_ty();
-                let tmp = self.temp(ty);
+                let tmp = self.temp(ty, DUMMY_SP);
                 self.unit_temp = Some(tmp.clone());
                 tmp
             }
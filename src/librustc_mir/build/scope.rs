@@ -95,6 +95,7 @@ use rustc::mir::repr::*;
 use syntax_pos::Span;
 use rustc_data_structures::indexed_vec::Idx;
 use rustc_data_structures::fnv::FnvHashMap;
+use std::io::{self, Write};
 
 pub struct Scope<'tcx> {
     /// the scope-id within the scope_auxiliary
@@ -130,6 +131,14 @@ pub struct Scope<'tcx> {
 
     /// The cache for drop chain on “normal” exit into a particular BasicBlock.
     cached_exits: FnvHashMap<(BasicBlock, CodeExtent), BasicBlock>,
+
+    /// The cleanup block that the diverge paths of the scopes nested in
+    /// this one branch to once they are done with their own drops. It
+    /// holds a `Goto` to the first of the drops of this scope, which is
+    /// updated as more drops are scheduled, so that all of these paths
+    /// share a single cleanup ladder. Values that are not initialized yet
+    /// on some of them are taken care of by drop elaboration.
+    cached_entry: Option<BasicBlock>,
 }
 
 struct DropData<'tcx> {
@@ -181,6 +190,7 @@ impl<'tcx> Scope<'tcx> {
     /// larger extent of code.
     fn invalidate_cache(&mut self) {
         self.cached_exits = FnvHashMap();
+        self.cached_entry = None;
         for dropdata in &mut self.drops {
             dropdata.cached_block = None;
         }
@@ -189,6 +199,11 @@ impl<'tcx> Scope<'tcx> {
         }
     }
 
+    /// Returns true if the scope has no cleanup to do.
+    fn is_empty(&self) -> bool {
+        self.drops.is_empty() && self.free.is_none()
+    }
+
     /// Returns the cached entrypoint for diverging exit from this scope.
     ///
     /// Precondition: the caches must be fully filled (i.e. diverge_cleanup is called) in order for
@@ -267,7 +282,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             extent: extent,
             drops: vec![],
             free: None,
-            cached_exits: FnvHashMap()
+            cached_exits: FnvHashMap(),
+            cached_entry: None
         });
         self.scope_auxiliary.push(ScopeAuxiliary {
             extent: extent,
@@ -418,6 +434,12 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         if !self.hir.needs_drop(lvalue_ty) {
             return
         }
+        // The diverge paths of the inner scopes go through the entry of the
+        // scope, which will branch to the just-scheduled drop, unless there
+        // was nothing to drop in the scope and they skipped it.
+        let was_empty = self.scopes.iter().rev()
+                                     .find(|scope| scope.extent == extent)
+                                     .map_or(true, |scope| scope.is_empty());
         for scope in self.scopes.iter_mut().rev() {
             if scope.extent == extent {
                 // No need to invalidate any caches here. The just-scheduled drop will branch into
@@ -428,15 +450,59 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                     cached_block: None
                 });
                 return;
-            } else {
+            } else if was_empty {
                 // We must invalidate all the cached_blocks leading up to the scope we’re
                 // looking for, because all of the blocks in the chain will become incorrect.
                 scope.invalidate_cache()
+            } else {
+                // The drops on “normal” exits only include the drops scheduled so far.
+                scope.cached_exits = FnvHashMap();
             }
         }
         span_bug!(span, "extent {:?} not in scope to drop {:?}", extent, lvalue);
     }
 
+    /// The number of drops scheduled so far in each of the current scopes,
+    /// to be passed to `drops_scheduled_since`.
+    pub fn scheduled_drop_counts(&self) -> Vec<usize> {
+        self.scopes.iter().map(|scope| scope.drops.len()).collect()
+    }
+
+    /// The drops scheduled in the scopes that were current when
+    /// `scheduled_drop_counts` returned `counts`, since then, from the
+    /// outermost scope to the innermost one, in the order they were
+    /// scheduled in each.
+    pub fn drops_scheduled_since(&self, counts: &[usize]) -> Vec<(Span, Lvalue<'tcx>)> {
+        self.scopes.iter().zip(counts).flat_map(|(scope, &count)| {
+            scope.drops[count..].iter().map(|drop_data| {
+                (drop_data.span, drop_data.location.clone())
+            })
+        }).collect()
+    }
+
+    /// Builds the drops in `drops`, as returned by `drops_scheduled_since`,
+    /// last scheduled first, ahead of the exit of their scopes. They stay
+    /// scheduled there, and drop elaboration removes the later drops of
+    /// the values these already dropped.
+    pub fn build_scheduled_drops(&mut self,
+                                 mut block: BasicBlock,
+                                 drops: &[(Span, Lvalue<'tcx>)])
+                                 -> BlockAnd<()> {
+        for &(span, ref location) in drops.iter().rev() {
+            let source_info = self.source_info(span);
+            let next_target = self.cfg.start_new_block();
+            let diverge_target = self.diverge_cleanup();
+            self.cfg.terminate(block, source_info,
+                               TerminatorKind::Drop {
+                                   location: location.clone(),
+                                   target: next_target,
+                                   unwind: diverge_target,
+                               });
+            block = next_target;
+        }
+        block.unit()
+    }
+
     /// Schedule dropping of a not-yet-fully-initialised box.
     ///
     /// This cleanup will only be translated into unwind branch.
@@ -478,7 +544,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
     /// See module comment for more details. None indicates there’s no
     /// cleanup to do at this point.
     pub fn diverge_cleanup(&mut self) -> Option<BasicBlock> {
-        if self.scopes.iter().all(|scope| scope.drops.is_empty() && scope.free.is_none()) {
+        if self.scopes.iter().all(|scope| scope.is_empty()) {
             return None;
         }
         assert!(!self.scopes.is_empty()); // or `all` above would be true
@@ -490,13 +556,15 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         // Build up the drops in **reverse** order. The end result will
         // look like:
         //
-        //    scopes[n] -> scopes[n-1] -> ... -> scopes[0]
+        //    scopes[n] -> entry[n-1] -> scopes[n-1] -> ... -> entry[0] -> scopes[0]
         //
         // However, we build this in **reverse order**. That is, we
         // process scopes[0], then scopes[1], etc, pointing each one at
-        // the result generates from the one before. Along the way, we
-        // store caches. If everything is cached, we'll just walk right
-        // to left reading the cached results but never created anything.
+        // the entry of the one before. Along the way, we store caches.
+        // If everything is cached, we'll just walk right to left reading
+        // the cached results but never created anything. The innermost
+        // scope is entered directly, so that the drops scheduled in it
+        // later do not run on this path.
 
         // To start, create the resume terminator.
         let mut target = if let Some(target) = *cached_resume_block {
@@ -510,8 +578,12 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             resumeblk
         };
 
-        for scope in scopes.iter_mut().filter(|s| !s.drops.is_empty() || s.free.is_some()) {
+        let mut with_cleanup = scopes.iter_mut().filter(|s| !s.is_empty()).peekable();
+        while let Some(scope) = with_cleanup.next() {
             target = build_diverge_scope(hir.tcx(), cfg, &unit_temp, scope, target);
+            if with_cleanup.peek().is_some() {
+                target = build_diverge_entry(cfg, scope, self.fn_span, target);
+            }
         }
         Some(target)
     }
@@ -581,6 +653,29 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
         success_block
     }
+
+    /// Writes the scopes and loops the builder is in, outermost first,
+    /// with the drops scheduled in each scope, for `-Z dump-mir-on-ice`.
+    /// As the HAIR is built recursively, they are what remained to be
+    /// built when it panicked.
+    pub fn write_open_scopes(&self, w: &mut Write) -> io::Result<()> {
+        try!(writeln!(w, "// open scopes, outermost first:"));
+        for scope in &self.scopes {
+            try!(writeln!(w, "//     {:?}: {:?}", scope.id, scope.extent));
+            for drop_data in &scope.drops {
+                try!(writeln!(w, "//         drop({:?})", drop_data.location));
+            }
+            if let Some(ref free_data) = scope.free {
+                try!(writeln!(w, "//         free({:?})", free_data.value));
+            }
+        }
+        try!(writeln!(w, "// open loops, outermost first:"));
+        for loop_scope in &self.loop_scopes {
+            try!(writeln!(w, "//     {:?}: continue to {:?}, break to {:?}",
+                          loop_scope.extent, loop_scope.continue_block, loop_scope.break_block));
+        }
+        Ok(())
+    }
 }
 
 /// Builds drops for pop_scope and exit_scope.
@@ -671,6 +766,25 @@ fn build_diverge_scope<'a, 'gcx, 'tcx>(tcx: TyCtxt<'a, 'gcx, 'tcx>,
     target
 }
 
+/// Returns the entry of `scope`, pointing it at `target`, the first of the
+/// drops of the scope.
+fn build_diverge_entry<'tcx>(cfg: &mut CFG<'tcx>,
+                             scope: &mut Scope<'tcx>,
+                             span: Span,
+                             target: BasicBlock)
+                             -> BasicBlock {
+    let goto = TerminatorKind::Goto { target: target };
+    if let Some(entry) = scope.cached_entry {
+        cfg.block_data_mut(entry).terminator_mut().kind = goto;
+        entry
+    } else {
+        let entry = cfg.start_new_cleanup_block();
+        cfg.terminate(entry, scope.source_info(span), goto);
+        scope.cached_entry = Some(entry);
+        entry
+    }
+}
+
 fn build_free<'a, 'gcx, 'tcx>(tcx: TyCtxt<'a, 'gcx, 'tcx>,
                               unit_temp: &Lvalue<'tcx>,
                               data: &FreeData<'tcx>,
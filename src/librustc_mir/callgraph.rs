@@ -0,0 +1,260 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A crate-level call graph, built from the `Call` terminators of the MIR
+//! of every body in the crate.
+//!
+//! A call whose callee is known statically becomes a direct edge to that
+//! callee. This includes calls to trait methods with a concrete `Self`
+//! type, which point at the trait method rather than the impl it resolves
+//! to. Calls through a trait object become indirect edges to the trait
+//! method. Calls through function pointers have no known target and are
+//! left out.
+//!
+//! Calls of the `Fn*` traits on function items and closures are edges to
+//! the function or closure itself, as they only go through a shim, if
+//! anything (see `shim::fn_trait_shim`).
+
+use dot;
+use rustc::hir::def_id::DefId;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr::*;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::DefIdMap;
+use rustc_data_structures::fnv::FnvHashSet;
+use rustc_data_structures::graph::{Graph, NodeIndex, INCOMING, OUTGOING};
+use std::cmp;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// The callee is known statically.
+    Direct,
+    /// The call is dispatched through a trait object's vtable.
+    Indirect,
+}
+
+pub struct CallGraph {
+    node_map: DefIdMap<NodeIndex>,
+    graph: Graph<DefId, CallKind>,
+}
+
+impl CallGraph {
+    /// Build the call graph of all bodies in `mir_map`. Every body becomes a
+    /// node, as does every item called from one of them, even if it is not
+    /// defined in this crate. Several calls from one body to the same callee
+    /// share a single edge.
+    pub fn build<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir_map: &MirMap<'tcx>) -> CallGraph {
+        let mut callgraph = CallGraph {
+            node_map: DefIdMap(),
+            graph: Graph::new(),
+        };
+
+        let mut def_ids = mir_map.map.keys();
+        def_ids.sort();
+        let mut seen = FnvHashSet();
+        for def_id in def_ids {
+            let caller = callgraph.add_node(def_id);
+            let mir = &mir_map.map[&def_id];
+            let bodies = Some(mir).into_iter().chain(mir.promoted.iter());
+            for body in bodies {
+                for data in body.basic_blocks() {
+                    if let Some((callee, kind)) = callee(tcx, &data.terminator().kind) {
+                        let callee = callgraph.add_node(callee);
+                        if seen.insert((caller, callee, kind)) {
+                            callgraph.graph.add_edge(caller, callee, kind);
+                        }
+                    }
+                }
+            }
+        }
+
+        callgraph
+    }
+
+    fn add_node(&mut self, def_id: DefId) -> NodeIndex {
+        let graph = &mut self.graph;
+        *self.node_map.entry(def_id).or_insert_with(|| graph.add_node(def_id))
+    }
+
+    /// The items called from `def_id`, with the kind of each call.
+    pub fn callees(&self, def_id: DefId) -> Vec<(DefId, CallKind)> {
+        self.adjacent(def_id, false)
+    }
+
+    /// The items calling `def_id`, with the kind of each call.
+    pub fn callers(&self, def_id: DefId) -> Vec<(DefId, CallKind)> {
+        self.adjacent(def_id, true)
+    }
+
+    fn adjacent(&self, def_id: DefId, incoming: bool) -> Vec<(DefId, CallKind)> {
+        let node = match self.node_map.get(&def_id) {
+            Some(&node) => node,
+            None => return vec![],
+        };
+        let direction = if incoming { INCOMING } else { OUTGOING };
+        let mut adjacent: Vec<_> = self.graph.adjacent_edges(node, direction).map(|(_, edge)| {
+            let other = if incoming { edge.source() } else { edge.target() };
+            (*self.graph.node_data(other), edge.data)
+        }).collect();
+        adjacent.reverse();
+        adjacent
+    }
+
+    /// The strongly connected components of the graph, i.e. the sets of
+    /// mutually recursive items, ordered bottom-up: every component comes
+    /// after all of the components it calls into. An inliner can work
+    /// through them in this order so that callees are optimized before
+    /// their callers.
+    pub fn bottom_up_sccs(&self) -> Vec<Vec<DefId>> {
+        let mut tarjan = Tarjan {
+            graph: &self.graph,
+            index: vec![None; self.graph.len_nodes()],
+            lowlink: vec![0; self.graph.len_nodes()],
+            stack: vec![],
+            on_stack: vec![false; self.graph.len_nodes()],
+            next_index: 0,
+            sccs: vec![],
+        };
+        for node in 0..self.graph.len_nodes() {
+            if tarjan.index[node].is_none() {
+                tarjan.visit(NodeIndex(node));
+            }
+        }
+        tarjan.sccs
+    }
+
+    /// Write the call graph as a graphviz DOT graph, drawing indirect calls
+    /// dashed.
+    pub fn write_graphviz<'a, 'tcx, W>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>, w: &mut W)
+                                       -> io::Result<()>
+        where W: Write
+    {
+        writeln!(w, "digraph CallGraph {{")?;
+        writeln!(w, r#"    node [fontname="monospace", shape="box"];"#)?;
+        for (index, node) in self.graph.all_nodes().iter().enumerate() {
+            let style = if node.data.is_local() { "" } else { r#", style="dotted""# };
+            writeln!(w, r#"    n{} [label="{}"{}];"#,
+                     index, dot::escape_html(&tcx.item_path_str(node.data)), style)?;
+        }
+        for edge in self.graph.all_edges() {
+            let style = match edge.data {
+                CallKind::Direct => "",
+                CallKind::Indirect => r#" [style="dashed"]"#,
+            };
+            writeln!(w, "    n{} -> n{}{};", edge.source().0, edge.target().0, style)?;
+        }
+        writeln!(w, "}}")
+    }
+}
+
+/// The item called by `kind`, if it is a call with a known target.
+fn callee<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, kind: &TerminatorKind<'tcx>)
+                    -> Option<(DefId, CallKind)> {
+    let func = match *kind {
+        TerminatorKind::Call { ref func, .. } => func,
+        _ => return None,
+    };
+    let (def_id, substs) = match *func {
+        Operand::Constant(Constant {
+            literal: Literal::Item { def_id, substs }, ..
+        }) => (def_id, substs),
+        _ => return None,
+    };
+    if tcx.trait_of_item(def_id).and_then(|t| tcx.lang_items.fn_trait_kind(t)).is_some() {
+        match substs.self_ty().map(|self_ty| &self_ty.sty) {
+            Some(&ty::TyFnDef(fn_def_id, _, _)) |
+            Some(&ty::TyClosure(fn_def_id, _)) => {
+                return Some((fn_def_id, CallKind::Direct));
+            }
+            _ => {}
+        }
+    }
+    let is_object_call = tcx.trait_of_item(def_id).is_some() && match substs.self_ty() {
+        Some(self_ty) => match self_ty.sty {
+            ty::TyTrait(..) => true,
+            _ => false,
+        },
+        None => false,
+    };
+    Some((def_id, if is_object_call { CallKind::Indirect } else { CallKind::Direct }))
+}
+
+struct Tarjan<'g> {
+    graph: &'g Graph<DefId, CallKind>,
+    index: Vec<Option<usize>>,
+    lowlink: Vec<usize>,
+    stack: Vec<NodeIndex>,
+    on_stack: Vec<bool>,
+    next_index: usize,
+    sccs: Vec<Vec<DefId>>,
+}
+
+impl<'g> Tarjan<'g> {
+    fn visit(&mut self, node: NodeIndex) {
+        self.index[node.0] = Some(self.next_index);
+        self.lowlink[node.0] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node.0] = true;
+
+        let graph = self.graph;
+        for succ in graph.successor_nodes(node) {
+            match self.index[succ.0] {
+                None => {
+                    self.visit(succ);
+                    self.lowlink[node.0] = cmp::min(self.lowlink[node.0], self.lowlink[succ.0]);
+                }
+                Some(index) if self.on_stack[succ.0] => {
+                    self.lowlink[node.0] = cmp::min(self.lowlink[node.0], index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.lowlink[node.0]) == self.index[node.0] {
+            let mut scc = vec![];
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack[member.0] = false;
+                scc.push(*graph.node_data(member));
+                if member == node {
+                    break;
+                }
+            }
+            self.sccs.push(scc);
+        }
+    }
+}
+
+/// Write the call graph of the crate to `rustc.callgraph.dot` in the MIR
+/// dump directory, for `-Z dump-mir-callgraph`.
+pub fn dump_callgraph<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir_map: &MirMap<'tcx>) {
+    let mut path = PathBuf::new();
+    if let Some(ref dir) = tcx.sess.opts.debugging_opts.dump_mir_dir {
+        path.push(dir);
+        if let Err(e) = fs::create_dir_all(&path) {
+            tcx.sess.err(&format!("could not create MIR dump directory `{}`: {}",
+                                  path.display(), e));
+            return;
+        }
+    }
+    path.push("rustc.callgraph.dot");
+
+    let callgraph = CallGraph::build(tcx, mir_map);
+    let result = fs::File::create(&path).and_then(|mut file| {
+        callgraph.write_graphviz(tcx, &mut file)
+    });
+    if let Err(e) = result {
+        tcx.sess.err(&format!("error writing call graph to `{}`: {}", path.display(), e));
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The choices MIR construction makes between smaller and faster code,
+//! which favour size under `-C opt-level=s` and `-C opt-level=z`, and
+//! speed otherwise.
+//!
+//! For now, the only such choice is how matches tell apart the variants of
+//! enums with a niche (see `Builder::niche_test`). There is no MIR inliner
+//! or outliner to consult a cost model yet, so rather than a cost model
+//! with a single answer, this is a function of the optimization level. A
+//! `MirCostModel` trait, with size and speed implementations estimating
+//! the cost of a body, should come with the first pass that needs one.
+//!
+//! Nothing is estimated here: each choice follows from the optimization
+//! level alone, so the same options always give the same MIR, and there
+//! is no need to log the choices or replay them.
+
+use rustc::session::Session;
+use rustc::session::config::OptLevel;
+
+/// Whether a match on an enum whose variants are told apart by a non-null
+/// field should compare that field with zero, which needs a few
+/// statements, rather than switch on the discriminant, which trans lowers
+/// to the same comparison.
+pub fn prefers_niche_test(sess: &Session) -> bool {
+    match sess.opts.optimize {
+        OptLevel::Size | OptLevel::SizeMin => false,
+        OptLevel::No | OptLevel::Less | OptLevel::Default | OptLevel::Aggressive => true,
+    }
+}
@@ -384,4 +384,5 @@ static A : &'static u32 = &S.a; // ok!
 
 register_diagnostics! {
     E0526, // shuffle indices are not constant
+    E0566, // `ref mut` binding overlaps another binding of the same pattern
 }
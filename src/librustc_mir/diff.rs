@@ -0,0 +1,150 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A line-based unified diff, used to show what a MIR pass changed.
+
+use std::cmp;
+use std::fmt::Write;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Number of unchanged lines shown around every change.
+const CONTEXT: usize = 3;
+
+/// Returns a unified diff turning `before` into `after`, or `None` if the
+/// two texts are identical.
+pub fn unified_diff(before_name: &str, before: &str,
+                    after_name: &str, after: &str) -> Option<String> {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+    let edits = edit_script(&a, &b);
+
+    // Pair every edit with the line numbers (0-based) it applies at.
+    let mut lines = Vec::with_capacity(edits.len());
+    let (mut i, mut j) = (0, 0);
+    for &edit in &edits {
+        lines.push((edit, i, j));
+        match edit {
+            Edit::Keep => { i += 1; j += 1; }
+            Edit::Delete => i += 1,
+            Edit::Insert => j += 1,
+        }
+    }
+
+    let changes: Vec<usize> = lines.iter()
+                                   .enumerate()
+                                   .filter(|&(_, &(edit, _, _))| edit != Edit::Keep)
+                                   .map(|(index, _)| index)
+                                   .collect();
+    if changes.is_empty() {
+        return None;
+    }
+
+    let mut out = String::new();
+    writeln!(out, "--- {}", before_name).unwrap();
+    writeln!(out, "+++ {}", after_name).unwrap();
+
+    let mut next = 0;
+    while next < changes.len() {
+        // Changes separated by at most twice the context share a hunk.
+        let start = changes[next].saturating_sub(CONTEXT);
+        let mut last = changes[next];
+        next += 1;
+        while next < changes.len() && changes[next] <= last + 2 * CONTEXT + 1 {
+            last = changes[next];
+            next += 1;
+        }
+        let end = cmp::min(lines.len(), last + CONTEXT + 1);
+
+        let hunk = &lines[start..end];
+        let a_len = hunk.iter().filter(|&&(edit, _, _)| edit != Edit::Insert).count();
+        let b_len = hunk.iter().filter(|&&(edit, _, _)| edit != Edit::Delete).count();
+        let (_, a_start, b_start) = hunk[0];
+        writeln!(out, "@@ -{},{} +{},{} @@",
+                 if a_len == 0 { a_start } else { a_start + 1 }, a_len,
+                 if b_len == 0 { b_start } else { b_start + 1 }, b_len).unwrap();
+        for &(edit, i, j) in hunk {
+            match edit {
+                Edit::Keep => writeln!(out, " {}", a[i]),
+                Edit::Delete => writeln!(out, "-{}", a[i]),
+                Edit::Insert => writeln!(out, "+{}", b[j]),
+            }.unwrap();
+        }
+    }
+
+    Some(out)
+}
+
+/// Computes a shortest edit script turning `a` into `b`, using the greedy
+/// algorithm from Myers' "An O(ND) Difference Algorithm and Its Variations".
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = n + m;
+    let offset = |k: isize| (k + max) as usize;
+
+    // `v[offset(k)]` is the furthest x reached on diagonal `k = x - y`; a
+    // copy is kept for every edit distance `d` so that the path can be
+    // recovered afterwards.
+    let mut v = vec![0isize; 2 * max as usize + 2];
+    let mut trace = vec![];
+    'search: for d in 0..max + 1 {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && v[offset(k - 1)] < v[offset(k + 1)]) {
+                v[offset(k + 1)]
+            } else {
+                v[offset(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[offset(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[offset(k - 1)] < v[offset(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[offset(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x { Edit::Insert } else { Edit::Delete });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
@@ -30,29 +30,56 @@ pub fn write_mir_graphviz<'a, 'b, 'tcx, W, I>(tcx: TyCtxt<'b, 'tcx, 'tcx>,
     for def_id in iter {
         let nodeid = tcx.map.as_local_node_id(def_id).unwrap();
         let mir = &mir_map.map[&def_id];
+        write_mir_fn_graphviz(tcx, nodeid, mir, w)?;
+    }
+    Ok(())
+}
 
-        writeln!(w, "digraph Mir_{} {{", nodeid)?;
+/// Write a graphviz DOT graph of a single MIR. Cleanup blocks are grouped
+/// into their own cluster and unwind edges are drawn dashed, so that the
+/// unwinding paths can be told apart from normal control flow.
+pub fn write_mir_fn_graphviz<'a, 'tcx, W>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                          nodeid: NodeId,
+                                          mir: &Mir,
+                                          w: &mut W)
+                                          -> io::Result<()>
+    where W: Write
+{
+    writeln!(w, "digraph Mir_{} {{", nodeid)?;
 
-        // Global graph properties
-        writeln!(w, r#"    graph [fontname="monospace"];"#)?;
-        writeln!(w, r#"    node [fontname="monospace"];"#)?;
-        writeln!(w, r#"    edge [fontname="monospace"];"#)?;
+    // Global graph properties
+    writeln!(w, r#"    graph [fontname="monospace"];"#)?;
+    writeln!(w, r#"    node [fontname="monospace"];"#)?;
+    writeln!(w, r#"    edge [fontname="monospace"];"#)?;
 
-        // Graph label
-        write_graph_label(tcx, nodeid, mir, w)?;
+    // Graph label
+    write_graph_label(tcx, nodeid, mir, w)?;
 
-        // Nodes
-        for (block, _) in mir.basic_blocks().iter_enumerated() {
+    // Nodes
+    for (block, data) in mir.basic_blocks().iter_enumerated() {
+        if !data.is_cleanup {
             write_node(block, mir, w)?;
         }
+    }
 
-        // Edges
-        for (source, _) in mir.basic_blocks().iter_enumerated() {
-            write_edges(source, mir, w)?;
+    // Cleanup nodes
+    if mir.basic_blocks().iter().any(|data| data.is_cleanup) {
+        writeln!(w, "    subgraph cluster_cleanup {{")?;
+        writeln!(w, r#"    label="cleanup";"#)?;
+        writeln!(w, r#"    style="dashed";"#)?;
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            if data.is_cleanup {
+                write_node(block, mir, w)?;
+            }
         }
-        writeln!(w, "}}")?
+        writeln!(w, "    }}")?;
     }
-    Ok(())
+
+    // Edges
+    for (source, _) in mir.basic_blocks().iter_enumerated() {
+        write_edges(source, mir, w)?;
+    }
+    writeln!(w, "}}")
 }
 
 /// Write a graphviz HTML-styled label for the given basic block, with
@@ -119,7 +146,8 @@ fn write_edges<W: Write>(source: BasicBlock, mir: &Mir, w: &mut W) -> io::Result
     let labels = terminator.kind.fmt_successor_labels();
 
     for (&target, label) in terminator.successors().iter().zip(labels) {
-        writeln!(w, r#"    {} -> {} [label="{}"];"#, node(source), node(target), label)?;
+        let style = if label == "unwind" { r#", style="dashed""# } else { "" };
+        writeln!(w, r#"    {} -> {} [label="{}"{}];"#, node(source), node(target), label, style)?;
     }
 
     Ok(())
@@ -24,6 +24,7 @@ use rustc::ty::cast::CastKind as TyCastKind;
 use rustc::mir::repr::*;
 use rustc::hir;
 use syntax::ptr::P;
+use syntax_pos::Span;
 
 impl<'tcx> Mirror<'tcx> for &'tcx hir::Expr {
     type Output = Expr<'tcx>;
@@ -39,137 +40,15 @@ impl<'tcx> Mirror<'tcx> for &'tcx hir::Expr {
         debug!("make_mirror: unadjusted-expr={:?} applying adjustments={:?}",
                expr, cx.tcx.tables.borrow().adjustments.get(&self.id));
 
-        // Now apply adjustments, if any.
-        match cx.tcx.tables.borrow().adjustments.get(&self.id) {
-            None => {}
-            Some(&ty::adjustment::AdjustReifyFnPointer) => {
-                let adjusted_ty = cx.tcx.expr_ty_adjusted(self);
-                expr = Expr {
-                    temp_lifetime: temp_lifetime,
-                    ty: adjusted_ty,
-                    span: self.span,
-                    kind: ExprKind::ReifyFnPointer { source: expr.to_ref() },
-                };
-            }
-            Some(&ty::adjustment::AdjustUnsafeFnPointer) => {
-                let adjusted_ty = cx.tcx.expr_ty_adjusted(self);
-                expr = Expr {
-                    temp_lifetime: temp_lifetime,
-                    ty: adjusted_ty,
-                    span: self.span,
-                    kind: ExprKind::UnsafeFnPointer { source: expr.to_ref() },
-                };
-            }
-            Some(&ty::adjustment::AdjustMutToConstPointer) => {
-                let adjusted_ty = cx.tcx.expr_ty_adjusted(self);
-                expr = Expr {
-                    temp_lifetime: temp_lifetime,
-                    ty: adjusted_ty,
-                    span: self.span,
-                    kind: ExprKind::Cast { source: expr.to_ref() },
-                };
-            }
-            Some(&ty::adjustment::AdjustDerefRef(ref adj)) => {
-                for i in 0..adj.autoderefs {
-                    let i = i as u32;
-                    let adjusted_ty =
-                        expr.ty.adjust_for_autoderef(
-                            cx.tcx,
-                            self.id,
-                            self.span,
-                            i,
-                            |mc| cx.tcx.tables.borrow().method_map.get(&mc).map(|m| m.ty));
-                    debug!("make_mirror: autoderef #{}, adjusted_ty={:?}", i, adjusted_ty);
-                    let method_key = ty::MethodCall::autoderef(self.id, i);
-                    let meth_ty =
-                        cx.tcx.tables.borrow().method_map.get(&method_key).map(|m| m.ty);
-                    let kind = if let Some(meth_ty) = meth_ty {
-                        debug!("make_mirror: overloaded autoderef (meth_ty={:?})", meth_ty);
-
-                        let ref_ty = cx.tcx.no_late_bound_regions(&meth_ty.fn_ret());
-                        let (region, mutbl) = match ref_ty {
-                            Some(ty::FnConverging(&ty::TyS {
-                                sty: ty::TyRef(region, mt), ..
-                            })) => (region, mt.mutbl),
-                            _ => span_bug!(expr.span, "autoderef returned bad type")
-                        };
-
-                        expr = Expr {
-                            temp_lifetime: temp_lifetime,
-                            ty: cx.tcx.mk_ref(
-                                region, ty::TypeAndMut { ty: expr.ty, mutbl: mutbl }),
-                            span: expr.span,
-                            kind: ExprKind::Borrow {
-                                region: *region,
-                                borrow_kind: to_borrow_kind(mutbl),
-                                arg: expr.to_ref()
-                            }
-                        };
-
-                        overloaded_lvalue(cx, self, method_key,
-                                          PassArgs::ByRef, expr.to_ref(), vec![])
-                    } else {
-                        debug!("make_mirror: built-in autoderef");
-                        ExprKind::Deref { arg: expr.to_ref() }
-                    };
-                    expr = Expr {
-                        temp_lifetime: temp_lifetime,
-                        ty: adjusted_ty,
-                        span: self.span,
-                        kind: kind,
-                    };
-                }
-
-                if let Some(autoref) = adj.autoref {
-                    let adjusted_ty = expr.ty.adjust_for_autoref(cx.tcx, Some(autoref));
-                    match autoref {
-                        ty::adjustment::AutoPtr(r, m) => {
-                            expr = Expr {
-                                temp_lifetime: temp_lifetime,
-                                ty: adjusted_ty,
-                                span: self.span,
-                                kind: ExprKind::Borrow {
-                                    region: *r,
-                                    borrow_kind: to_borrow_kind(m),
-                                    arg: expr.to_ref(),
-                                },
-                            };
-                        }
-                        ty::adjustment::AutoUnsafe(m) => {
-                            // Convert this to a suitable `&foo` and
-                            // then an unsafe coercion. Limit the region to be just this
-                            // expression.
-                            let region = ty::ReScope(expr_extent);
-                            let region = cx.tcx.mk_region(region);
-                            expr = Expr {
-                                temp_lifetime: temp_lifetime,
-                                ty: cx.tcx.mk_ref(region, ty::TypeAndMut { ty: expr.ty, mutbl: m }),
-                                span: self.span,
-                                kind: ExprKind::Borrow {
-                                    region: *region,
-                                    borrow_kind: to_borrow_kind(m),
-                                    arg: expr.to_ref(),
-                                },
-                            };
-                            expr = Expr {
-                                temp_lifetime: temp_lifetime,
-                                ty: adjusted_ty,
-                                span: self.span,
-                                kind: ExprKind::Cast { source: expr.to_ref() },
-                            };
-                        }
-                    }
-                }
-
-                if let Some(target) = adj.unsize {
-                    expr = Expr {
-                        temp_lifetime: temp_lifetime,
-                        ty: target,
-                        span: self.span,
-                        kind: ExprKind::Unsize { source: expr.to_ref() },
-                    };
-                }
-            }
+        // Now apply adjustments, if any, one step at a time.
+        let steps = adjustment_steps(cx, self);
+        for i in 0..steps.len() {
+            debug!("make_mirror: applying {:?}", steps[i]);
+            // Built-in derefs keep the lvalue, the first other step after
+            // this one reads it.
+            let read_later = steps[i + 1..].iter().any(|step| !is_builtin_deref(cx, self, step));
+            let (span, temp_lifetime) = step_span_and_scope(cx, self, &steps[i], read_later);
+            expr = apply_adjustment(cx, self, expr, steps[i], span, temp_lifetime);
         }
 
         // Next, wrap this up in the expr's scope.
@@ -201,6 +80,231 @@ impl<'tcx> Mirror<'tcx> for &'tcx hir::Expr {
     }
 }
 
+/// A single step of the adjustments typeck recorded for an expression.
+/// Each step is mirrored as an expression of its own, wrapping the one it
+/// adjusts, so that the derefs and borrows they introduce are explicit in
+/// the HAIR.
+#[derive(Copy, Clone, Debug)]
+enum AdjustmentStep<'tcx> {
+    ReifyFnPointer,
+    UnsafeFnPointer,
+    MutToConstPointer,
+    /// The `index`th autoderef, which may be overloaded.
+    Deref { index: u32 },
+    /// The autoref after `autoderefs` autoderefs.
+    Borrow { autoref: ty::adjustment::AutoRef<'tcx>, autoderefs: usize },
+    Unsize { target: Ty<'tcx> },
+}
+
+/// Returns the adjustments of `expr`, in the order they are applied.
+fn adjustment_steps<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
+                                    expr: &'tcx hir::Expr)
+                                    -> Vec<AdjustmentStep<'tcx>> {
+    match cx.tcx.tables.borrow().adjustments.get(&expr.id) {
+        None => vec![],
+        Some(&ty::adjustment::AdjustReifyFnPointer) => vec![AdjustmentStep::ReifyFnPointer],
+        Some(&ty::adjustment::AdjustUnsafeFnPointer) => vec![AdjustmentStep::UnsafeFnPointer],
+        Some(&ty::adjustment::AdjustMutToConstPointer) => {
+            vec![AdjustmentStep::MutToConstPointer]
+        }
+        Some(&ty::adjustment::AdjustDerefRef(ref adj)) => {
+            let mut steps: Vec<_> = (0..adj.autoderefs).map(|i| {
+                AdjustmentStep::Deref { index: i as u32 }
+            }).collect();
+            steps.extend(adj.autoref.map(|autoref| AdjustmentStep::Borrow {
+                autoref: autoref,
+                autoderefs: adj.autoderefs,
+            }));
+            steps.extend(adj.unsize.map(|target| AdjustmentStep::Unsize { target: target }));
+            steps
+        }
+    }
+}
+
+/// Whether `step`, an adjustment of `hir_expr`, is a built-in deref, whose
+/// result is an lvalue into the result of the step before.
+fn is_builtin_deref<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
+                                    hir_expr: &'tcx hir::Expr,
+                                    step: &AdjustmentStep<'tcx>)
+                                    -> bool {
+    match *step {
+        AdjustmentStep::Deref { index } => {
+            let method_key = ty::MethodCall::autoderef(hir_expr.id, index);
+            !cx.tcx.tables.borrow().method_map.contains_key(&method_key)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the span and temporary scope of the expression for `step`, an
+/// adjustment of `hir_expr`. The autoref of the receiver of a method call
+/// is taken for the call, and points at it. If the result of `step` is
+/// `read_later` by another step, its temporaries are not needed past
+/// `hir_expr`; otherwise they live as long as the temporaries of
+/// `hir_expr` would, as the result may be an lvalue into them.
+fn step_span_and_scope<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
+                                       hir_expr: &'tcx hir::Expr,
+                                       step: &AdjustmentStep<'tcx>,
+                                       read_later: bool)
+                                       -> (Span, Option<CodeExtent>) {
+    let temp_lifetime = cx.tcx.region_maps.temporary_scope(hir_expr.id);
+    let temp_lifetime = if read_later {
+        // There are no temporary scopes in constants.
+        temp_lifetime.map(|_| cx.tcx.region_maps.node_extent(hir_expr.id))
+    } else {
+        temp_lifetime
+    };
+    let span = match *step {
+        AdjustmentStep::Borrow { .. } => {
+            let parent = cx.tcx.map.get_parent_node(hir_expr.id);
+            match cx.tcx.map.find(parent) {
+                Some(map::NodeExpr(&hir::Expr {
+                    node: hir::ExprMethodCall(_, _, ref args), span, ..
+                })) if args[0].id == hir_expr.id => span,
+                _ => hir_expr.span,
+            }
+        }
+        _ => hir_expr.span,
+    };
+    (span, temp_lifetime)
+}
+
+/// Wraps `expr`, the mirror of `hir_expr` with the adjustments before
+/// `step` applied, in the expression for `step`, with the given `span`
+/// and temporary scope (see `step_span_and_scope`).
+fn apply_adjustment<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
+                                    hir_expr: &'tcx hir::Expr,
+                                    expr: Expr<'tcx>,
+                                    step: AdjustmentStep<'tcx>,
+                                    span: Span,
+                                    temp_lifetime: Option<CodeExtent>)
+                                    -> Expr<'tcx> {
+    let (ty, kind) = match step {
+        AdjustmentStep::ReifyFnPointer => {
+            (cx.tcx.expr_ty_adjusted(hir_expr), ExprKind::ReifyFnPointer { source: expr.to_ref() })
+        }
+        AdjustmentStep::UnsafeFnPointer => {
+            (cx.tcx.expr_ty_adjusted(hir_expr), ExprKind::UnsafeFnPointer { source: expr.to_ref() })
+        }
+        AdjustmentStep::MutToConstPointer => {
+            (cx.tcx.expr_ty_adjusted(hir_expr), ExprKind::Cast { source: expr.to_ref() })
+        }
+        AdjustmentStep::Deref { index } => {
+            let adjusted_ty =
+                expr.ty.adjust_for_autoderef(
+                    cx.tcx,
+                    hir_expr.id,
+                    span,
+                    index,
+                    |mc| cx.tcx.tables.borrow().method_map.get(&mc).map(|m| m.ty));
+            debug!("apply_adjustment: autoderef #{}, adjusted_ty={:?}", index, adjusted_ty);
+            let method_key = ty::MethodCall::autoderef(hir_expr.id, index);
+            let meth_ty =
+                cx.tcx.tables.borrow().method_map.get(&method_key).map(|m| m.ty);
+            let kind = if let Some(meth_ty) = meth_ty {
+                debug!("apply_adjustment: overloaded autoderef (meth_ty={:?})", meth_ty);
+
+                let ref_ty = match cx.tcx.no_late_bound_regions(&meth_ty.fn_ret()) {
+                    Some(ty::FnConverging(ref_ty)) => ref_ty,
+                    _ => span_bug!(expr.span, "autoderef returned bad type")
+                };
+                let (region, mutbl) = match ref_ty.sty {
+                    ty::TyRef(region, mt) => (region, mt.mutbl),
+                    _ => span_bug!(expr.span, "autoderef returned bad type")
+                };
+
+                let borrow = Expr {
+                    temp_lifetime: temp_lifetime,
+                    ty: cx.tcx.mk_ref(
+                        region, ty::TypeAndMut { ty: expr.ty, mutbl: mutbl }),
+                    span: span,
+                    kind: ExprKind::Borrow {
+                        region: *region,
+                        borrow_kind: to_borrow_kind(mutbl),
+                        arg: expr.to_ref()
+                    }
+                };
+
+                // `*Deref::deref(&expr)`, like `overloaded_lvalue` builds
+                // it, but in the scope of this step.
+                let mut fun = method_callee(cx, hir_expr, method_key);
+                fun.temp_lifetime = temp_lifetime;
+                fun.span = span;
+                let call = Expr {
+                    temp_lifetime: temp_lifetime,
+                    ty: ref_ty,
+                    span: span,
+                    kind: ExprKind::Call {
+                        ty: fun.ty,
+                        fun: fun.to_ref(),
+                        args: vec![borrow.to_ref()],
+                    }
+                };
+                ExprKind::Deref { arg: call.to_ref() }
+            } else {
+                debug!("apply_adjustment: built-in autoderef");
+                ExprKind::Deref { arg: expr.to_ref() }
+            };
+            (adjusted_ty, kind)
+        }
+        AdjustmentStep::Borrow { autoref, autoderefs } => {
+            let adjusted_ty = expr.ty.adjust_for_autoref(cx.tcx, Some(autoref));
+            let kind = match autoref {
+                ty::adjustment::AutoPtr(r, m) => {
+                    ExprKind::Borrow {
+                        region: *r,
+                        borrow_kind: to_borrow_kind(m),
+                        arg: expr.to_ref(),
+                    }
+                }
+                ty::adjustment::AutoUnsafe(m) if autoderefs > 0 => {
+                    // The autoderefs leave an lvalue, so take its address
+                    // directly. For `&lvalue`, skip the reference as well.
+                    let arg = match hir_expr.node {
+                        hir::ExprAddrOf(_, ref arg) if autoderefs == 1 &&
+                            !cx.tcx.tables.borrow().method_map.contains_key(
+                                &ty::MethodCall::autoderef(hir_expr.id, 0)) &&
+                            cx.tcx.expr_is_lval(arg) => arg.to_ref(),
+                        _ => expr.to_ref(),
+                    };
+                    ExprKind::AddressOf {
+                        mutability: to_mutability(m),
+                        arg: arg,
+                    }
+                }
+                ty::adjustment::AutoUnsafe(m) => {
+                    // Convert this to a suitable `&foo` and
+                    // then an unsafe coercion. Limit the region to be just this
+                    // expression.
+                    let region = ty::ReScope(cx.tcx.region_maps.node_extent(hir_expr.id));
+                    let region = cx.tcx.mk_region(region);
+                    let borrow = Expr {
+                        temp_lifetime: temp_lifetime,
+                        ty: cx.tcx.mk_ref(region, ty::TypeAndMut { ty: expr.ty, mutbl: m }),
+                        span: span,
+                        kind: ExprKind::Borrow {
+                            region: *region,
+                            borrow_kind: to_borrow_kind(m),
+                            arg: expr.to_ref(),
+                        },
+                    };
+                    ExprKind::Cast { source: borrow.to_ref() }
+                }
+            };
+            (adjusted_ty, kind)
+        }
+        AdjustmentStep::Unsize { target } => {
+            (target, ExprKind::Unsize { source: expr.to_ref() })
+        }
+    };
+    Expr {
+        temp_lifetime: temp_lifetime,
+        ty: ty,
+        span: span,
+        kind: kind,
+    }
+}
+
 fn make_mirror_unadjusted<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
                                           expr: &'tcx hir::Expr)
                                           -> Expr<'tcx> {
@@ -556,9 +660,14 @@ fn make_mirror_unadjusted<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
             ExprKind::Break { label: label.map(|_| loop_label(cx, expr)) },
         hir::ExprAgain(label) =>
             ExprKind::Continue { label: label.map(|_| loop_label(cx, expr)) },
-        hir::ExprMatch(ref discr, ref arms, _) =>
+        hir::ExprMatch(ref discr, ref arms, source) =>
             ExprKind::Match { discriminant: discr.to_ref(),
-                              arms: arms.iter().map(|a| convert_arm(cx, a)).collect() },
+                              arms: arms.iter().map(|a| convert_arm(cx, a)).collect(),
+                              loop_desugar: match source {
+                                  hir::MatchSource::ForLoopDesugar |
+                                  hir::MatchSource::WhileLetDesugar => true,
+                                  _ => false,
+                              } },
         hir::ExprIf(ref cond, ref then, ref otherwise) =>
             ExprKind::If { condition: cond.to_ref(),
                            then: block::to_expr_ref(cx, then),
@@ -648,6 +757,13 @@ fn to_borrow_kind(m: hir::Mutability) -> BorrowKind {
     }
 }
 
+fn to_mutability(m: hir::Mutability) -> Mutability {
+    match m {
+        hir::MutMutable => Mutability::Mut,
+        hir::MutImmutable => Mutability::Not,
+    }
+}
+
 fn convert_arm<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
                                arm: &'tcx hir::Arm) -> Arm<'tcx> {
     Arm {
@@ -15,6 +15,8 @@
  * work.
  */
 
+use std;
+
 use hair::*;
 use rustc::mir::repr::*;
 use rustc::mir::transform::MirSource;
@@ -27,11 +29,20 @@ use rustc::hir::def_id::DefId;
 use rustc::hir::intravisit::FnKind;
 use rustc::hir::map::blocks::FnLikeNode;
 use rustc::infer::InferCtxt;
+use rustc::middle::expr_use_visitor as euv;
+use rustc::middle::mem_categorization as mc;
+use rustc::middle::mem_categorization::Categorization;
+use rustc::util::nodemap::NodeSet;
+use rustc::session::config::OverflowChecksPolicy;
+use rustc::traits::Reveal;
+use rustc::ty::layout::{Layout, Primitive};
 use rustc::ty::subst::{Subst, Substs};
-use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use syntax::ast;
 use syntax::parse::token;
+use syntax_pos::Span;
 use rustc::hir;
-use rustc_const_math::{ConstInt, ConstUsize};
+use rustc_const_math::{ConstInt, ConstIsize, ConstUsize};
 use syntax::attr::AttrMetaMethods;
 
 #[derive(Copy, Clone)]
@@ -41,7 +52,24 @@ pub struct Cx<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
     constness: hir::Constness,
 
     /// True if this constant/function needs overflow checks.
-    check_overflow: bool
+    check_overflow: bool,
+
+    /// True if this function checks that raw pointers are aligned
+    /// when they are dereferenced.
+    check_alignment: bool,
+
+    /// True if the temporaries of match scrutinees that no binding can
+    /// borrow from are dropped when an arm is entered.
+    short_scrutinee_temps: bool,
+
+    /// True if the MIR built so far is dumped when building it panics.
+    dump_on_ice: bool,
+
+    /// True if building the MIR is made to panic with a bug, to test
+    /// `dump_on_ice`.
+    force_build_bug: bool,
+
+    src: MirSource,
 }
 
 impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
@@ -81,6 +109,14 @@ impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
             item.check_name("rustc_inherit_overflow_checks")
         });
 
+        // With -Z overflow-checks-policy=caller, generic functions are
+        // treated as if they had that attribute, as they are translated
+        // in the crates that instantiate them.
+        if infcx.tcx.sess.opts.debugging_opts.overflow_checks_policy ==
+           OverflowChecksPolicy::Caller {
+            check_overflow |= !infcx.parameter_environment.free_substs.types.is_empty();
+        }
+
         // Respect -Z force-overflow-checks=on and -C debug-assertions.
         check_overflow |= infcx.tcx.sess.opts.debugging_opts.force_overflow_checks
                .unwrap_or(infcx.tcx.sess.opts.debug_assertions);
@@ -88,11 +124,25 @@ impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
         // Constants and const fn's always need overflow checks.
         check_overflow |= constness == hir::Constness::Const;
 
+        // Misaligned dereferences are only checked with -C debug-assertions.
+        // Constants and const fn's cannot dereference raw pointers.
+        let check_alignment = infcx.tcx.sess.opts.debug_assertions &&
+                              constness == hir::Constness::NotConst;
+
+        let dump_on_ice = infcx.tcx.sess.opts.debugging_opts.dump_mir_on_ice ||
+                          attrs.iter().any(|item| item.check_name("rustc_mir_dump_on_ice"));
+        let force_build_bug = attrs.iter().any(|item| item.check_name("rustc_mir_build_bug"));
+
         Cx {
             tcx: infcx.tcx,
             infcx: infcx,
             constness: constness,
-            check_overflow: check_overflow
+            check_overflow: check_overflow,
+            check_alignment: check_alignment,
+            short_scrutinee_temps: infcx.tcx.sess.opts.debugging_opts.short_scrutinee_temps,
+            dump_on_ice: dump_on_ice,
+            force_build_bug: force_build_bug,
+            src: src,
         }
     }
 }
@@ -107,11 +157,16 @@ impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
         self.tcx.types.usize
     }
 
-    pub fn usize_literal(&mut self, value: u64) -> Literal<'tcx> {
-        match ConstUsize::new(value, self.tcx.sess.target.uint_type) {
-            Ok(val) => Literal::Value { value: ConstVal::Integral(ConstInt::Usize(val))},
-            Err(_) => bug!("usize literal out of range for target"),
-        }
+    /// Returns a `usize` literal of `value`, see `int_literal`.
+    pub fn usize_literal(&mut self, span: Span, value: u64) -> Literal<'tcx> {
+        let usize_ty = self.usize_ty();
+        self.int_literal(span, value, usize_ty)
+    }
+
+    /// Returns a literal of `value` of the integer type `ty`, see
+    /// `int_literal`.
+    pub fn int_literal(&mut self, span: Span, value: u64, ty: Ty<'tcx>) -> Literal<'tcx> {
+        int_literal(self.tcx, span, value, ty)
     }
 
     pub fn bool_ty(&mut self) -> Ty<'tcx> {
@@ -179,11 +234,20 @@ impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
     }
 
     pub fn needs_drop(&mut self, ty: Ty<'tcx>) -> bool {
-        let ty = self.tcx.lift_to_global(&ty).unwrap_or_else(|| {
-            bug!("MIR: Cx::needs_drop({}) got \
-                  type with inference types/regions", ty);
-        });
-        self.tcx.type_needs_drop_given_env(ty, &self.infcx.parameter_environment)
+        self.tcx.try_type_needs_drop_given_env(ty, &self.infcx.parameter_environment)
+            .unwrap_or_else(|| {
+                bug!("MIR: Cx::needs_drop({}) got \
+                      type with inference types/regions", ty);
+            })
+    }
+
+    /// Returns the local variables of the function with the given
+    /// declaration and body that are borrowed somewhere in it, including
+    /// through autoref, `ref` bindings and closure captures.
+    pub fn borrowed_vars(&self, decl: &hir::FnDecl, body: &hir::Block) -> NodeSet {
+        let mut delegate = BorrowedVars { vars: NodeSet() };
+        euv::ExprUseVisitor::new(&mut delegate, self.infcx).walk_fn(decl, body);
+        delegate.vars
     }
 
     pub fn tcx(&self) -> TyCtxt<'a, 'gcx, 'tcx> {
@@ -193,9 +257,216 @@ impl<'a, 'gcx, 'tcx> Cx<'a, 'gcx, 'tcx> {
     pub fn check_overflow(&self) -> bool {
         self.check_overflow
     }
+
+    pub fn check_alignment(&self) -> bool {
+        self.check_alignment
+    }
+
+    pub fn short_scrutinee_temps(&self) -> bool {
+        self.short_scrutinee_temps
+    }
+
+    pub fn dump_on_ice(&self) -> bool {
+        self.dump_on_ice
+    }
+
+    pub fn force_build_bug(&self) -> bool {
+        self.force_build_bug
+    }
+
+    pub fn src(&self) -> MirSource {
+        self.src
+    }
+
+    pub fn is_const(&self) -> bool {
+        self.constness == hir::Constness::Const
+    }
+
+    /// Returns the alignment of `ty` in bytes, if it is sized and its
+    /// layout does not depend on type parameters.
+    pub fn align_of(&mut self, ty: Ty<'tcx>) -> Option<u64> {
+        let tcx = self.tcx.global_tcx();
+        self.with_layout(ty, |layout| {
+            if layout.is_unsized() {
+                None
+            } else {
+                Some(layout.align(&tcx.data_layout).abi())
+            }
+        }).and_then(|align| align)
+    }
+
+    /// Whether `ty` is sized, with a size of zero, and its layout does not
+    /// depend on type parameters.
+    pub fn is_zero_sized(&mut self, ty: Ty<'tcx>) -> bool {
+        let tcx = self.tcx.global_tcx();
+        self.with_layout(ty, |layout| {
+            !layout.is_unsized() && layout.size(&tcx.data_layout).bytes() == 0
+        }).unwrap_or(false)
+    }
+
+    /// Returns how the variants of `ty`, an enum, are told apart in memory.
+    pub fn enum_repr(&mut self, ty: Ty<'tcx>) -> EnumRepr {
+        let tcx = self.tcx.global_tcx();
+        self.with_layout(ty, |layout| {
+            match *layout {
+                Layout::Univariant { .. } => EnumRepr::Univariant,
+                Layout::CEnum { discr, .. } |
+                Layout::General { discr, .. } => {
+                    EnumRepr::Tagged { size: Primitive::Int(discr).size(&tcx.data_layout).bytes() }
+                }
+                Layout::RawNullablePointer { nndiscr, .. } => {
+                    EnumRepr::Niche { nonnull_variant: nndiscr as usize, path: vec![0] }
+                }
+                Layout::StructWrappedNullablePointer { nndiscr, ref discrfield, .. } => {
+                    // The path starts with a 0 for GEP through a pointer.
+                    EnumRepr::Niche {
+                        nonnull_variant: nndiscr as usize,
+                        path: discrfield[1..].iter().map(|&i| i as usize).collect()
+                    }
+                }
+                _ => bug!("Cx::enum_repr({}): unexpected layout {:?}", ty, layout)
+            }
+        }).unwrap_or(EnumRepr::Unknown)
+    }
+
+    /// Returns the `nonnull_variant` of `ty`, an enum with a `Niche` repr,
+    /// along with the lvalue and the type of the field of `lvalue` that
+    /// tells the variants apart, if it can be reached through fields of
+    /// structs and tuples. The field is only valid to read if its type is
+    /// `Copy`, as `lvalue` is not known to hold a `nonnull_variant`.
+    pub fn niche_field(&mut self, lvalue: &Lvalue<'tcx>, ty: Ty<'tcx>)
+                       -> Option<(usize, Lvalue<'tcx>, Ty<'tcx>)> {
+        let (adt_def, substs) = match ty.sty {
+            ty::TyEnum(adt_def, substs) => (adt_def, substs),
+            _ => return None,
+        };
+        let (nonnull_variant, path) = match self.enum_repr(ty) {
+            EnumRepr::Niche { nonnull_variant, path } => (nonnull_variant, path),
+            _ => return None,
+        };
+        let mut field = lvalue.clone().elem(ProjectionElem::Downcast(adt_def, nonnull_variant));
+        let mut field_ty = ty;
+        for (depth, &index) in path.iter().enumerate() {
+            field_ty = match field_ty.sty {
+                ty::TyEnum(..) if depth == 0 => {
+                    adt_def.variants[nonnull_variant].fields[index].ty(self.tcx, substs)
+                }
+                ty::TyStruct(def, substs) => {
+                    def.struct_variant().fields[index].ty(self.tcx, substs)
+                }
+                ty::TyTuple(tys) => tys[index],
+                _ => return None,
+            };
+            // The types of fields are only known up to normalization here.
+            if field_ty.has_projection_types() {
+                return None;
+            }
+            field = field.elem(ProjectionElem::Field(Field::new(index), field_ty));
+        }
+        Some((nonnull_variant, field, field_ty))
+    }
+
+    /// Calls `f` with the layout of `ty`, if it does not depend on type
+    /// parameters.
+    fn with_layout<F, R>(&mut self, ty: Ty<'tcx>, f: F) -> Option<R>
+        where F: FnOnce(&Layout) -> R
+    {
+        if ty.needs_subst() {
+            return None;
+        }
+        let tcx = self.tcx.global_tcx();
+        let ty = match tcx.lift_to_global(&self.tcx.erase_regions(&ty)) {
+            Some(ty) => ty,
+            None => return None,
+        };
+        tcx.normalizing_infer_ctxt(Reveal::All).enter(|infcx| {
+            ty.layout(&infcx).ok().map(f)
+        })
+    }
 }
 
 mod block;
 mod expr;
 mod pattern;
 mod to_ref;
+
+/// Returns a literal of `value` of the integer type `ty`. If `value`
+/// does not fit in `ty` on the target, which for `isize` and `usize`
+/// may be 16 bits wide, an error is reported at `span` and the literal
+/// is zero instead.
+pub fn int_literal<'a, 'gcx, 'tcx>(tcx: TyCtxt<'a, 'gcx, 'tcx>,
+                                   span: Span,
+                                   value: u64,
+                                   ty: Ty<'tcx>)
+                                   -> Literal<'tcx> {
+    let (int_ty, uint_ty) = (tcx.sess.target.int_type, tcx.sess.target.uint_type);
+    let val = match ty.sty {
+        ty::TyInt(ast::IntTy::I8) if value <= std::i8::MAX as u64 => {
+            Some(ConstInt::I8(value as i8))
+        }
+        ty::TyInt(ast::IntTy::I16) if value <= std::i16::MAX as u64 => {
+            Some(ConstInt::I16(value as i16))
+        }
+        ty::TyInt(ast::IntTy::I32) if value <= std::i32::MAX as u64 => {
+            Some(ConstInt::I32(value as i32))
+        }
+        ty::TyInt(ast::IntTy::I64) if value <= std::i64::MAX as u64 => {
+            Some(ConstInt::I64(value as i64))
+        }
+        ty::TyInt(ast::IntTy::Is) if value <= std::i64::MAX as u64 => {
+            ConstIsize::new(value as i64, int_ty).ok().map(ConstInt::Isize)
+        }
+        ty::TyUint(ast::UintTy::U8) if value <= std::u8::MAX as u64 => {
+            Some(ConstInt::U8(value as u8))
+        }
+        ty::TyUint(ast::UintTy::U16) if value <= std::u16::MAX as u64 => {
+            Some(ConstInt::U16(value as u16))
+        }
+        ty::TyUint(ast::UintTy::U32) if value <= std::u32::MAX as u64 => {
+            Some(ConstInt::U32(value as u32))
+        }
+        ty::TyUint(ast::UintTy::U64) => Some(ConstInt::U64(value)),
+        ty::TyUint(ast::UintTy::Us) => {
+            ConstUsize::new(value, uint_ty).ok().map(ConstInt::Usize)
+        }
+        ty::TyInt(_) | ty::TyUint(_) => None,
+        _ => span_bug!(span, "Invalid type for int_literal: `{:?}`", ty)
+    };
+    match val {
+        Some(val) => Literal::Value { value: ConstVal::Integral(val) },
+        None => {
+            tcx.sess.span_err(span, &format!("the value {} does not fit in `{}` \
+                                              on the target", value, ty));
+            int_literal(tcx, span, 0, ty)
+        }
+    }
+}
+
+struct BorrowedVars {
+    vars: NodeSet,
+}
+
+impl<'tcx> euv::Delegate<'tcx> for BorrowedVars {
+    fn consume(&mut self, _: ast::NodeId, _: Span, _: mc::cmt, _: euv::ConsumeMode) {}
+    fn matched_pat(&mut self, _: &hir::Pat, _: mc::cmt, _: euv::MatchMode) {}
+    fn consume_pat(&mut self, _: &hir::Pat, _: mc::cmt, _: euv::ConsumeMode) {}
+    fn decl_without_init(&mut self, _: ast::NodeId, _: Span) {}
+    fn mutate(&mut self, _: ast::NodeId, _: Span, _: mc::cmt, _: euv::MutateMode) {}
+
+    fn borrow(&mut self, _: ast::NodeId, _: Span, cmt: mc::cmt, _: ty::Region,
+              _: ty::BorrowKind, _: euv::LoanCause) {
+        // Borrowing through a pointer does not borrow the variable holding it.
+        let mut cat = &cmt.cat;
+        loop {
+            match *cat {
+                Categorization::Local(id) => {
+                    self.vars.insert(id);
+                    return;
+                }
+                Categorization::Interior(ref base, _) |
+                Categorization::Downcast(ref base, _) => cat = &base.cat,
+                _ => return,
+            }
+        }
+    }
+}
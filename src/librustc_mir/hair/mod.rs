@@ -160,6 +160,9 @@ pub enum ExprKind<'tcx> {
     Match {
         discriminant: ExprRef<'tcx>,
         arms: Vec<Arm<'tcx>>,
+        /// whether this is a match of a desugared `for` or `while let`
+        /// loop, which is built once per iteration
+        loop_desugar: bool,
     },
     Block {
         body: &'tcx hir::Block,
@@ -194,6 +197,11 @@ pub enum ExprKind<'tcx> {
         borrow_kind: BorrowKind,
         arg: ExprRef<'tcx>,
     },
+    /// The address of the lvalue `arg` as a raw pointer.
+    AddressOf {
+        mutability: Mutability,
+        arg: ExprRef<'tcx>,
+    },
     Break {
         label: Option<CodeExtent>,
     },
@@ -340,6 +348,28 @@ pub struct FieldPattern<'tcx> {
     pub pattern: Pattern<'tcx>,
 }
 
+/// How the variants of an enum are told apart in memory, see
+/// `Cx::enum_repr`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnumRepr {
+    /// The layout of the enum depends on type parameters.
+    Unknown,
+
+    /// The enum has a single variant, and no discriminant.
+    Univariant,
+
+    /// The variant is given by a discriminant of `size` bytes, which is
+    /// the whole value for C-like enums.
+    Tagged { size: u64 },
+
+    /// The enum has two variants, and the value is a `nonnull_variant`
+    /// unless the field at `path` is zero. The path starts with a field
+    /// of `nonnull_variant`, and goes on through the fields of structs,
+    /// tuples and closures, the first element of arrays, and the halves
+    /// of fat pointers.
+    Niche { nonnull_variant: usize, path: Vec<usize> },
+}
+
 ///////////////////////////////////////////////////////////////////////////
 // The Mirror trait
 
@@ -0,0 +1,255 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A self-contained HTML rendering of MIR, written by `--emit mir-html`.
+//!
+//! Every body gets its own page holding the source it was built from, its
+//! locals and one collapsible element per basic block, and an index page
+//! links to all of them. Hovering over a local highlights all of its uses
+//! within the body, and every statement links back to the source line its
+//! span starts on.
+
+use dot;
+use rustc::hir::def_id::DefId;
+use rustc::mir::repr::*;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::transform::MirSource;
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_vec::Idx;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use syntax_pos::Span;
+
+const STYLE: &'static str = "
+body { font-family: monospace; }
+pre { margin: 0.5em 0; }
+.source { color: #555; background-color: #f4f4f4; }
+.block { margin-left: 2em; }
+.cleanup > summary { color: #999; }
+.stmt { margin-left: 2em; white-space: pre; }
+.span { color: #999; }
+.local.highlight { background-color: #ff8; }
+:target { background-color: #ff8; }
+";
+
+const SCRIPT: &'static str = "
+function highlight(event, on) {
+    var target = event.target;
+    if (!target.classList || !target.classList.contains('local')) {
+        return;
+    }
+    var selector = '.local[data-local=\"' + target.getAttribute('data-local') + '\"]';
+    var uses = document.querySelectorAll(selector);
+    for (var i = 0; i < uses.length; i++) {
+        uses[i].classList.toggle('highlight', on);
+    }
+}
+document.addEventListener('mouseover', function(e) { highlight(e, true); });
+document.addEventListener('mouseout', function(e) { highlight(e, false); });
+";
+
+/// Write an HTML page for each of the given MIRs and for each of the
+/// rvalues promoted out of them, next to the index page `path` linking to
+/// all of them. The page of the `n`th body is `path` with the extension
+/// `n.html`, e.g. `foo.mir.0.html` for `foo.mir.html`.
+pub fn write_mir_html<'a, 'tcx, I>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   iter: I,
+                                   mir_map: &MirMap<'tcx>,
+                                   path: &Path)
+                                   -> io::Result<()>
+    where I: Iterator<Item=DefId>
+{
+    let mut index = fs::File::create(path)?;
+    write_head(&format!("MIR for {}", tcx.crate_name), &mut index)?;
+    writeln!(index, "<ul>")?;
+
+    let mut n = 0;
+    for def_id in iter {
+        let mir = &mir_map.map[&def_id];
+        let id = tcx.map.as_local_node_id(def_id).unwrap();
+        let promoted = mir.promoted.iter_enumerated().map(|(i, promoted)| {
+            (MirSource::Promoted(id, i), promoted)
+        });
+        for (src, mir) in Some((MirSource::from_node(tcx, id), mir)).into_iter().chain(promoted) {
+            let mut title = tcx.item_path_str(tcx.map.local_def_id(src.item_id()));
+            if let MirSource::Promoted(_, i) = src {
+                title.push_str(&format!(" ({:?})", i));
+            }
+            let page = body_page(path, n);
+            let mut file = fs::File::create(&page)?;
+            write_head(&title, &mut file)?;
+            write_body(tcx, &title, mir, &mut file)?;
+            writeln!(file, "</body></html>")?;
+
+            let name = page.file_name().unwrap().to_string_lossy().into_owned();
+            writeln!(index, r#"<li><a href="{}">{}</a></li>"#,
+                     dot::escape_html(&name), dot::escape_html(&title))?;
+            n += 1;
+        }
+    }
+
+    writeln!(index, "</ul>")?;
+    writeln!(index, "</body></html>")
+}
+
+fn body_page(path: &Path, n: usize) -> PathBuf {
+    path.with_extension(format!("{}.html", n))
+}
+
+fn write_head(title: &str, w: &mut Write) -> io::Result<()> {
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, r#"<html><head><meta charset="utf-8">"#)?;
+    writeln!(w, "<title>{}</title>", dot::escape_html(title))?;
+    writeln!(w, "<style>{}</style>", STYLE)?;
+    writeln!(w, "<script>{}</script>", SCRIPT)?;
+    writeln!(w, "</head><body>")
+}
+
+fn write_body<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        title: &str,
+                        mir: &Mir<'tcx>,
+                        w: &mut Write)
+                        -> io::Result<()> {
+    writeln!(w, "<h2>{}</h2>", dot::escape_html(title))?;
+    write_source(tcx, mir.span, w)?;
+
+    writeln!(w, "<pre>")?;
+    write!(w, "fn(")?;
+    for (i, arg) in mir.arg_decls.iter_enumerated() {
+        if i.index() > 0 {
+            write!(w, ", ")?;
+        }
+        write!(w, "{}: {}", markup(&Lvalue::Arg(i)), markup(&arg.ty))?;
+    }
+    write!(w, ") -&gt; ")?;
+    match mir.return_ty {
+        ty::FnOutput::FnConverging(ty) => writeln!(w, "{}", markup(&ty))?,
+        ty::FnOutput::FnDiverging => writeln!(w, "!")?,
+    }
+    for (i, var) in mir.var_decls.iter_enumerated() {
+        let mut_str = if var.mutability == Mutability::Mut { "mut " } else { "" };
+        writeln!(w, "let {}{}: {}; // \"{}\"",
+                 mut_str,
+                 markup(&Lvalue::Var(i)),
+                 markup(&var.ty),
+                 dot::escape_html(&var.name.as_str()))?;
+    }
+    for (i, temp) in mir.temp_decls.iter_enumerated() {
+        writeln!(w, "let mut {}: {};",
+                 markup(&Lvalue::Temp(i)),
+                 markup(&temp.ty))?;
+    }
+    writeln!(w, "</pre>")?;
+
+    for (block, data) in mir.basic_blocks().iter_enumerated() {
+        let (class, note) = if data.is_cleanup {
+            ("block cleanup", " (cleanup)")
+        } else {
+            ("block", "")
+        };
+        writeln!(w, r#"<details open class="{}" id="{:?}">"#, class, block)?;
+        writeln!(w, "<summary>{:?}{}</summary>", block, note)?;
+
+        for statement in &data.statements {
+            write_line(tcx, &markup(statement), statement.source_info.span, w)?;
+        }
+
+        let terminator = data.terminator();
+        let mut head = String::new();
+        terminator.kind.fmt_head(&mut head).unwrap();
+        let mut text = linkify(&head);
+        let labels = terminator.kind.fmt_successor_labels();
+        let successors: Vec<_> = terminator.successors().iter().zip(labels).map(|(target, label)| {
+            let link = format!(r##"<a href="#{:?}">{:?}</a>"##, target, target);
+            if label.is_empty() {
+                link
+            } else {
+                format!("{}: {}", dot::escape_html(&label), link)
+            }
+        }).collect();
+        if !successors.is_empty() {
+            text.push_str(&format!(" -&gt; [{}]", successors.join(", ")));
+        }
+        write_line(tcx, &text, terminator.source_info.span, w)?;
+
+        writeln!(w, "</details>")?;
+    }
+    Ok(())
+}
+
+/// Write the source lines covered by `span`, each with an anchor that the
+/// statements of the body can link to.
+fn write_source<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          span: Span,
+                          w: &mut Write)
+                          -> io::Result<()> {
+    let lines = match tcx.sess.codemap().span_to_lines(span) {
+        Ok(lines) => lines,
+        Err(_) => return Ok(()),
+    };
+    writeln!(w, r#"<pre class="source">// {}"#, dot::escape_html(&lines.file.name))?;
+    for line in &lines.lines {
+        let text = lines.file.get_line(line.line_index).unwrap_or("");
+        writeln!(w, r#"<span id="l{}">{:4} {}</span>"#,
+                 line.line_index + 1, line.line_index + 1, dot::escape_html(text))?;
+    }
+    writeln!(w, "</pre>")
+}
+
+/// Write one statement or terminator (already rendered as HTML), followed
+/// by a link to the source line its span starts on.
+fn write_line<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        html: &str,
+                        span: Span,
+                        w: &mut Write)
+                        -> io::Result<()> {
+    let loc = tcx.sess.codemap().lookup_char_pos(span.lo);
+    writeln!(w, r##"<div class="stmt">{}; <a class="span" href="#l{}">// {}:{}</a></div>"##,
+             html, loc.line, loc.line, loc.col.0 + 1)
+}
+
+fn markup<T: Debug>(t: &T) -> String {
+    linkify(&format!("{:?}", t))
+}
+
+/// HTML-escape `text`, wrapping every mention of an argument, variable or
+/// temporary in an element tagged with the local's name so that its uses
+/// can be highlighted together.
+fn linkify(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if len == 0 {
+            out.push_str(&dot::escape_html(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let word = &rest[..len];
+        if is_local(word) {
+            out.push_str(&format!(r#"<span class="local" data-local="{}">{}</span>"#,
+                                  word, word));
+        } else {
+            out.push_str(&dot::escape_html(word));
+        }
+        rest = &rest[len..];
+    }
+    out
+}
+
+fn is_local(word: &str) -> bool {
+    ["arg", "var", "tmp"].iter().any(|prefix| {
+        word.len() > prefix.len() &&
+            word.starts_with(prefix) &&
+            word[prefix.len()..].chars().all(|c| c.is_digit(10))
+    })
+}
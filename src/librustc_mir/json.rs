@@ -0,0 +1,260 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A machine-readable export of MIR, written by `--emit mir-json`.
+//!
+//! The structure of each body (locals, scopes, basic blocks and their
+//! edges) is spelled out in JSON, while types, statements and terminators
+//! are recorded in the same textual syntax the pretty printer uses. This
+//! lets external tools walk the control-flow graph without linking against
+//! the compiler.
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::mir::repr::*;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::transform::MirSource;
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_vec::Idx;
+use rustc_serialize::json::as_pretty_json;
+use std::io::{self, Write};
+use syntax::ast::NodeId;
+use syntax_pos::{Span, DUMMY_SP};
+
+#[derive(RustcEncodable)]
+struct CrateJson {
+    crate_name: String,
+    bodies: Vec<BodyJson>,
+}
+
+#[derive(RustcEncodable)]
+struct BodyJson {
+    /// The item path of the function or constant this MIR belongs to.
+    name: String,
+    /// One of `fn`, `const`, `static`, `static mut` or `promoted`.
+    kind: String,
+    node_id: NodeId,
+    /// The index of a promoted rvalue within its parent body.
+    promoted: Option<usize>,
+    span: SpanJson,
+    return_ty: String,
+    locals: Vec<LocalJson>,
+    scopes: Vec<ScopeJson>,
+    blocks: Vec<BlockJson>,
+}
+
+#[derive(RustcEncodable)]
+struct LocalJson {
+    /// The lvalue as it is printed in statements, e.g. `var0` or `tmp1`.
+    name: String,
+    /// One of `arg`, `var` or `temp`.
+    kind: String,
+    ty: String,
+    mutable: bool,
+    /// The name given to the local in the source, if any.
+    debug_name: Option<String>,
+    scope: Option<usize>,
+    span: Option<SpanJson>,
+}
+
+#[derive(RustcEncodable)]
+struct ScopeJson {
+    index: usize,
+    parent: Option<usize>,
+    span: SpanJson,
+}
+
+#[derive(RustcEncodable)]
+struct BlockJson {
+    index: usize,
+    is_cleanup: bool,
+    statements: Vec<StatementJson>,
+    terminator: TerminatorJson,
+}
+
+#[derive(RustcEncodable)]
+struct StatementJson {
+    text: String,
+    scope: usize,
+    span: SpanJson,
+}
+
+#[derive(RustcEncodable)]
+struct TerminatorJson {
+    /// The terminator without its successor list, e.g. `drop(var0)`.
+    text: String,
+    successors: Vec<EdgeJson>,
+    scope: usize,
+    span: SpanJson,
+}
+
+#[derive(RustcEncodable)]
+struct EdgeJson {
+    /// The kind of edge, e.g. `return` or `unwind`; empty for plain gotos.
+    label: String,
+    target: usize,
+}
+
+#[derive(RustcEncodable)]
+struct SpanJson {
+    file_name: String,
+    /// 1-based.
+    line_start: usize,
+    line_end: usize,
+    /// 1-based, character offset.
+    column_start: usize,
+    column_end: usize,
+}
+
+/// Write a JSON document describing the given MIRs, including the MIR of
+/// the rvalues promoted out of them.
+pub fn write_mir_json<'a, 'tcx, I>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   iter: I,
+                                   mir_map: &MirMap<'tcx>,
+                                   w: &mut Write)
+                                   -> io::Result<()>
+    where I: Iterator<Item=DefId>
+{
+    let mut bodies = vec![];
+    for def_id in iter {
+        let mir = &mir_map.map[&def_id];
+        let id = tcx.map.as_local_node_id(def_id).unwrap();
+        bodies.push(body_json(tcx, MirSource::from_node(tcx, id), mir));
+        for (i, promoted) in mir.promoted.iter_enumerated() {
+            bodies.push(body_json(tcx, MirSource::Promoted(id, i), promoted));
+        }
+    }
+
+    let krate = CrateJson {
+        crate_name: tcx.crate_name.to_string(),
+        bodies: bodies,
+    };
+    writeln!(w, "{}", as_pretty_json(&krate))
+}
+
+fn body_json<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       src: MirSource,
+                       mir: &Mir<'tcx>)
+                       -> BodyJson {
+    let (kind, promoted) = match src {
+        MirSource::Fn(_) => ("fn", None),
+        MirSource::Const(_) => ("const", None),
+        MirSource::Static(_, hir::MutImmutable) => ("static", None),
+        MirSource::Static(_, hir::MutMutable) => ("static mut", None),
+        MirSource::Promoted(_, i) => ("promoted", Some(i.index())),
+    };
+    let node_id = src.item_id();
+
+    let return_ty = match mir.return_ty {
+        ty::FnOutput::FnConverging(ty) => format!("{:?}", ty),
+        ty::FnOutput::FnDiverging => "!".to_string(),
+    };
+
+    let mut locals = vec![];
+    for (i, arg) in mir.arg_decls.iter_enumerated() {
+        locals.push(LocalJson {
+            name: format!("{:?}", Lvalue::Arg(i)),
+            kind: "arg".to_string(),
+            ty: format!("{:?}", arg.ty),
+            mutable: false,
+            debug_name: Some(arg.debug_name.to_string()),
+            scope: None,
+            span: None,
+        });
+    }
+    for (i, var) in mir.var_decls.iter_enumerated() {
+        locals.push(LocalJson {
+            name: format!("{:?}", Lvalue::Var(i)),
+            kind: "var".to_string(),
+            ty: format!("{:?}", var.ty),
+            mutable: var.mutability == Mutability::Mut,
+            debug_name: Some(var.name.to_string()),
+            scope: Some(var.source_info.scope.index()),
+            span: Some(span_json(tcx, var.source_info.span)),
+        });
+    }
+    for (i, temp) in mir.temp_decls.iter_enumerated() {
+        locals.push(LocalJson {
+            name: format!("{:?}", Lvalue::Temp(i)),
+            kind: "temp".to_string(),
+            ty: format!("{:?}", temp.ty),
+            mutable: true,
+            debug_name: None,
+            scope: None,
+            span: if temp.span == DUMMY_SP { None } else { Some(span_json(tcx, temp.span)) },
+        });
+    }
+
+    let scopes = mir.visibility_scopes.iter_enumerated().map(|(scope, data)| {
+        ScopeJson {
+            index: scope.index(),
+            parent: data.parent_scope.map(|parent| parent.index()),
+            span: span_json(tcx, data.span),
+        }
+    }).collect();
+
+    let blocks = mir.basic_blocks().iter_enumerated().map(|(block, data)| {
+        let statements = data.statements.iter().map(|statement| {
+            StatementJson {
+                text: format!("{:?}", statement),
+                scope: statement.source_info.scope.index(),
+                span: span_json(tcx, statement.source_info.span),
+            }
+        }).collect();
+
+        let terminator = data.terminator();
+        let mut text = String::new();
+        terminator.kind.fmt_head(&mut text).unwrap();
+        let labels = terminator.kind.fmt_successor_labels();
+        let successors = terminator.successors().iter().zip(labels).map(|(target, label)| {
+            EdgeJson {
+                label: label.into_owned(),
+                target: target.index(),
+            }
+        }).collect();
+
+        BlockJson {
+            index: block.index(),
+            is_cleanup: data.is_cleanup,
+            statements: statements,
+            terminator: TerminatorJson {
+                text: text,
+                successors: successors,
+                scope: terminator.source_info.scope.index(),
+                span: span_json(tcx, terminator.source_info.span),
+            },
+        }
+    }).collect();
+
+    BodyJson {
+        name: tcx.item_path_str(tcx.map.local_def_id(node_id)),
+        kind: kind.to_string(),
+        node_id: node_id,
+        promoted: promoted,
+        span: span_json(tcx, mir.span),
+        return_ty: return_ty,
+        locals: locals,
+        scopes: scopes,
+        blocks: blocks,
+    }
+}
+
+fn span_json<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, span: Span) -> SpanJson {
+    let codemap = tcx.sess.codemap();
+    let start = codemap.lookup_char_pos(span.lo);
+    let end = codemap.lookup_char_pos(span.hi);
+    SpanJson {
+        file_name: start.file.name.clone(),
+        line_start: start.line,
+        line_end: end.line,
+        column_start: start.col.0 + 1,
+        column_end: end.col.0 + 1,
+    }
+}
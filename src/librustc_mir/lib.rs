@@ -41,12 +41,22 @@ extern crate syntax;
 extern crate syntax_pos;
 extern crate rustc_const_math;
 extern crate rustc_const_eval;
+extern crate serialize as rustc_serialize; // used by deriving
 
 pub mod diagnostics;
 
 pub mod build;
+pub mod callgraph;
+pub mod cost_model;
+mod diff;
 pub mod graphviz;
 mod hair;
+pub mod html;
+pub mod json;
 pub mod mir_map;
+pub mod patch;
 pub mod pretty;
+pub mod shim;
+pub mod stable_mir;
+pub mod test_support;
 pub mod transform;
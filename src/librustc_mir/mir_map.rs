@@ -23,6 +23,7 @@ use rustc::mir::repr::Mir;
 use rustc::mir::transform::MirSource;
 use rustc::mir::visit::MutVisitor;
 use pretty;
+use shim;
 use hair::cx::Cx;
 
 use rustc::mir::mir_map::MirMap;
@@ -30,6 +31,7 @@ use rustc::infer::InferCtxtBuilder;
 use rustc::traits::Reveal;
 use rustc::ty::{self, Ty, TyCtxt};
 use rustc::ty::subst::Substs;
+use rustc::util::nodemap::DefIdSet;
 use rustc::hir;
 use rustc::hir::intravisit::{self, FnKind, Visitor};
 use syntax::ast;
@@ -37,12 +39,18 @@ use syntax_pos::Span;
 
 use std::mem;
 
-pub fn build_mir_for_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MirMap<'tcx> {
+/// Build the MIR of every body in the crate, except for those of the
+/// items in `cached_items`, whose MIR is loaded from the incremental
+/// compilation cache once the MIR passes have run.
+pub fn build_mir_for_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                     cached_items: &DefIdSet)
+                                     -> MirMap<'tcx> {
     let mut map = MirMap::new(tcx.dep_graph.clone());
     {
         let mut dump = BuildMir {
             tcx: tcx,
             map: &mut map,
+            cached_items: cached_items,
         };
         tcx.visit_all_items_in_krate(DepNode::Mir, &mut dump);
     }
@@ -85,6 +93,7 @@ impl<'a, 'gcx: 'tcx, 'tcx> MutVisitor<'tcx> for GlobalizeMir<'a, 'gcx> {
 struct BuildMir<'a, 'tcx: 'a> {
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
     map: &'a mut MirMap<'tcx>,
+    cached_items: &'a DefIdSet,
 }
 
 /// Helper type of a temporary returned by BuildMir::cx(...).
@@ -156,6 +165,10 @@ impl<'a, 'gcx> BuildMir<'a, 'gcx> {
 impl<'a, 'tcx> Visitor<'tcx> for BuildMir<'a, 'tcx> {
     // Const and static items.
     fn visit_item(&mut self, item: &'tcx hir::Item) {
+        if self.cached_items.contains(&self.tcx.map.local_def_id(item.id)) {
+            return;
+        }
+
         match item.node {
             hir::ItemConst(_, ref expr) => {
                 self.cx(MirSource::Const(item.id)).build(|cx| {
@@ -232,6 +245,15 @@ impl<'a, 'tcx> Visitor<'tcx> for BuildMir<'a, 'tcx> {
             }
         };
 
+        // Derived methods that are mechanical are built directly.
+        if let Some(mir) = shim::derived_shim(self.tcx, id, fn_sig.output) {
+            pretty::dump_mir(self.tcx, "mir_map", &0, MirSource::Fn(id), &mir, None);
+            let def_id = self.tcx.map.local_def_id(id);
+            assert!(self.map.map.insert(def_id, mir).is_none());
+            intravisit::walk_fn(self, fk, decl, body, span, id);
+            return;
+        }
+
         let implicit_argument = if let FnKind::Closure(..) = fk {
             Some((closure_self_ty(self.tcx, id, body.id), None))
         } else {
@@ -248,7 +270,7 @@ impl<'a, 'tcx> Visitor<'tcx> for BuildMir<'a, 'tcx> {
 
         let arguments = implicit_argument.into_iter().chain(explicit_arguments);
         self.cx(MirSource::Fn(id)).build(|cx| {
-            build::construct_fn(cx, id, arguments, fn_sig.output, body)
+            build::construct_fn(cx, id, arguments, fn_sig.output, decl, body)
         });
 
         intravisit::walk_fn(self, fk, decl, body, span, id);
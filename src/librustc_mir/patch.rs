@@ -8,10 +8,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::gather_moves::Location;
 use rustc::ty::Ty;
 use rustc::mir::repr::*;
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax_pos::DUMMY_SP;
 
 /// This struct represents a patch to MIR, which can add
 /// new statements and basic blocks and patch over block
@@ -89,14 +89,14 @@ impl<'tcx> MirPatch<'tcx> {
         };
         Location {
             block: bb,
-            index: offset
+            statement_index: offset
         }
     }
 
     pub fn new_temp(&mut self, ty: Ty<'tcx>) -> Temp {
         let index = self.next_temp;
         self.next_temp += 1;
-        self.new_temps.push(TempDecl { ty: ty });
+        self.new_temps.push(TempDecl { ty: ty, span: DUMMY_SP });
         Temp::new(index as usize)
     }
 
@@ -149,12 +149,12 @@ impl<'tcx> MirPatch<'tcx> {
             }
             debug!("MirPatch: adding statement {:?} at loc {:?}+{}",
                    stmt, loc, delta);
-            loc.index += delta;
+            loc.statement_index += delta;
             let source_info = Self::source_info_for_index(
                 &mir[loc.block], loc
             );
             mir[loc.block].statements.insert(
-                loc.index, Statement {
+                loc.statement_index, Statement {
                     source_info: source_info,
                     kind: stmt
                 });
@@ -163,7 +163,7 @@ impl<'tcx> MirPatch<'tcx> {
     }
 
     pub fn source_info_for_index(data: &BasicBlockData, loc: Location) -> SourceInfo {
-        match data.statements.get(loc.index) {
+        match data.statements.get(loc.statement_index) {
             Some(stmt) => stmt.source_info,
             None => data.terminator().source_info
         }
@@ -177,3 +177,21 @@ impl<'tcx> MirPatch<'tcx> {
         Self::source_info_for_index(data, loc)
     }
 }
+
+/// The assignments that test whether the address `addr` is aligned to
+/// the power of two that `mask` is one less than, through the `usize`
+/// temporary `low_bits` into the `bool` temporary `aligned`. They are
+/// followed by an assert of `aligned` with `AssertMessage::Misaligned`.
+pub fn alignment_test<'tcx>(addr: Operand<'tcx>,
+                            mask: Operand<'tcx>,
+                            zero: Operand<'tcx>,
+                            low_bits: Lvalue<'tcx>,
+                            aligned: Lvalue<'tcx>)
+                            -> Vec<(Lvalue<'tcx>, Rvalue<'tcx>)> {
+    vec![
+        // low_bits = addr & (align - 1)
+        (low_bits.clone(), Rvalue::BinaryOp(BinOp::BitAnd, addr, mask)),
+        // aligned = low_bits == 0
+        (aligned, Rvalue::BinaryOp(BinOp::Eq, Operand::Consume(low_bits), zero)),
+    ]
+}
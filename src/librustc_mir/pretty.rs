@@ -8,11 +8,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use build::{Location, ScopeAuxiliaryVec, ScopeId};
+use build::{ScopeAuxiliaryVec, ScopeId};
+use graphviz::write_mir_fn_graphviz;
 use rustc::hir;
 use rustc::hir::def_id::DefId;
 use rustc::mir::repr::*;
 use rustc::mir::mir_map::MirMap;
+use rustc::mir::provenance::{self, BlockProvenance};
 use rustc::mir::transform::MirSource;
 use rustc::ty::{self, TyCtxt};
 use rustc_data_structures::fnv::FnvHashMap;
@@ -21,10 +23,13 @@ use std::fmt::Display;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{PathBuf, Path};
+use syntax_pos::{Span, DUMMY_SP};
 
 const INDENT: &'static str = "    ";
 /// Alignment for lining up comments following MIR statements
 const ALIGN: usize = 40;
+/// The number of characters of source code shown for a temp
+const EXCERPT_LEN: usize = 30;
 
 /// If the session is properly configured, dumps a human-readable
 /// representation of the mir into:
@@ -33,36 +38,88 @@ const ALIGN: usize = 40;
 /// rustc.node<node_id>.<pass_name>.<disambiguator>
 /// ```
 ///
+/// With `-Z dump-mir-graphviz`, a graphviz rendering of the control-flow
+/// graph is written next to it, with a `.dot` extension. When dumping
+/// around a pass, the `DumpMir` hook additionally writes a `.diff` file
+/// showing what the pass changed.
+///
 /// Output from this function is controlled by passing `-Z dump-mir=<filter>`,
 /// where `<filter>` takes the following forms:
 ///
 /// - `all` -- dump MIR for all fns, all passes, all everything
-/// - `substring1&substring2,...` -- `&`-separated list of substrings
-///   that can appear in the pass-name or the `item_path_str` for the given
-///   node-id. If any one of the substrings match, the data is dumped out.
+/// - `substring1&substring2&...` -- `&`-separated list of substrings that
+///   can appear in the pass-name or the `item_path_str` for the given
+///   node-id. The data is dumped out only if *all* of the substrings
+///   match, so `inline&my_fn` dumps `my_fn` before and after the
+///   `inline` pass only.
+/// - `filter1|filter2|...` -- `|`-separated list of the above; the data
+///   is dumped out if any one of the filters matches.
 pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           pass_name: &str,
                           disambiguator: &Display,
                           src: MirSource,
                           mir: &Mir<'tcx>,
                           auxiliary: Option<&ScopeAuxiliaryVec>) {
-    let filters = match tcx.sess.opts.debugging_opts.dump_mir {
+    if !dump_enabled(tcx, pass_name, src) {
+        return;
+    }
+    let mut file_path = match dump_path(tcx, pass_name, disambiguator, src, "mir") {
+        Some(file_path) => file_path,
         None => return,
-        Some(ref filters) => filters,
     };
+
     let node_id = src.item_id();
     let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
-    let is_matched =
-        filters.split("&")
-               .any(|filter| {
-                   filter == "all" ||
-                       pass_name.contains(filter) ||
-                       node_path.contains(filter)
-               });
-    if !is_matched {
-        return;
+    let _ = fs::File::create(&file_path).and_then(|mut file| {
+        try!(writeln!(file, "// MIR for `{}`", node_path));
+        try!(writeln!(file, "// node_id = {}", node_id));
+        try!(writeln!(file, "// pass_name = {}", pass_name));
+        try!(writeln!(file, "// disambiguator = {}", disambiguator));
+        try!(writeln!(file, ""));
+        try!(write_mir_fn(tcx, src, mir, &mut file, auxiliary));
+        Ok(())
+    });
+
+    if tcx.sess.opts.debugging_opts.dump_mir_graphviz {
+        file_path.set_extension("dot");
+        let _ = fs::File::create(&file_path).and_then(|mut file| {
+            write_mir_fn_graphviz(tcx, node_id, mir, &mut file)
+        });
     }
+}
+
+/// Whether the `-Z dump-mir` filter (see `dump_mir`) selects the MIR for
+/// `src` around the pass `pass_name`.
+pub fn dump_enabled<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                              pass_name: &str,
+                              src: MirSource)
+                              -> bool {
+    let filters = match tcx.sess.opts.debugging_opts.dump_mir {
+        None => return false,
+        Some(ref filters) => filters,
+    };
+    let node_path = tcx.item_path_str(tcx.map.local_def_id(src.item_id()));
+    filters.split("|")
+           .any(|or_filter| {
+               or_filter.split("&")
+                        .all(|and_filter| {
+                            and_filter == "all" ||
+                                pass_name.contains(and_filter) ||
+                                node_path.contains(and_filter)
+                        })
+           })
+}
 
+/// Returns the path of the dump file for `src` with the given extension,
+/// in the directory selected by `-Z dump-mir-dir`, creating that directory
+/// if necessary. Returns `None` (after reporting an error) if it could not
+/// be created.
+pub fn dump_path<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           pass_name: &str,
+                           disambiguator: &Display,
+                           src: MirSource,
+                           extension: &str)
+                           -> Option<PathBuf> {
     let promotion_id = match src {
         MirSource::Promoted(_, id) => format!("-{:?}", id),
         _ => String::new()
@@ -72,19 +129,49 @@ pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     if let Some(ref file_dir) = tcx.sess.opts.debugging_opts.dump_mir_dir {
         let p = Path::new(file_dir);
         file_path.push(p);
+        if let Err(e) = fs::create_dir_all(p) {
+            tcx.sess.err(&format!("could not create MIR dump directory `{}`: {}",
+                                  p.display(), e));
+            return None;
+        }
     };
-    let file_name = format!("rustc.node{}{}.{}.{}.mir",
-                            node_id, promotion_id, pass_name, disambiguator);
+    let file_name = format!("rustc.node{}{}.{}.{}.{}",
+                            src.item_id(), promotion_id, pass_name, disambiguator, extension);
     file_path.push(&file_name);
-    let _ = fs::File::create(&file_path).and_then(|mut file| {
-        try!(writeln!(file, "// MIR for `{}`", node_path));
-        try!(writeln!(file, "// node_id = {}", node_id));
-        try!(writeln!(file, "// pass_name = {}", pass_name));
-        try!(writeln!(file, "// disambiguator = {}", disambiguator));
-        try!(writeln!(file, ""));
-        try!(write_mir_fn(tcx, src, mir, &mut file, auxiliary));
-        Ok(())
-    });
+    Some(file_path)
+}
+
+/// A short excerpt of the source code at `span`, on one line, to show
+/// which expression a temp is for. `None` if there is no source for it.
+pub fn source_excerpt(tcx: TyCtxt, span: Span) -> Option<String> {
+    if span == DUMMY_SP {
+        return None;
+    }
+    let snippet = match tcx.sess.codemap().span_to_snippet(span) {
+        Ok(snippet) => snippet,
+        Err(_) => return None,
+    };
+    let excerpt = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if excerpt.chars().count() <= EXCERPT_LEN {
+        Some(excerpt)
+    } else {
+        Some(excerpt.chars().take(EXCERPT_LEN - 3).chain("...".chars()).collect())
+    }
+}
+
+/// Describes `lvalue` for diagnostics about MIR: the MIR of the lvalue,
+/// followed by the name of the variable or the expression of the temp it
+/// is based on, as those don't appear in the MIR.
+pub fn describe_lvalue(tcx: TyCtxt, mir: &Mir, lvalue: &Lvalue) -> String {
+    let origin = match *lvalue.base() {
+        Lvalue::Var(var) => Some(mir.var_decls[var].name.to_string()),
+        Lvalue::Temp(temp) => source_excerpt(tcx, mir.temp_decls[temp].span),
+        _ => None,
+    };
+    match origin {
+        Some(origin) => format!("`{:?}` (`{}`)", lvalue, origin),
+        None => format!("`{:?}`", lvalue),
+    }
 }
 
 /// Write out a human-readable textual representation for the given MIR.
@@ -151,9 +238,10 @@ pub fn write_mir_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                               auxiliary: Option<&ScopeAuxiliaryVec>)
                               -> io::Result<()> {
     let annotations = scope_entry_exit_annotations(auxiliary);
+    let origins = tcx.sess.mir_provenance.borrow();
     write_mir_intro(tcx, src, mir, w)?;
     for block in mir.basic_blocks().indices() {
-        write_basic_block(tcx, block, mir, w, &annotations)?;
+        write_basic_block(tcx, block, mir, w, &annotations, origins.block(src, block))?;
         if block.index() + 1 != mir.basic_blocks().len() {
             writeln!(w, "")?;
         }
@@ -168,15 +256,28 @@ fn write_basic_block(tcx: TyCtxt,
                      block: BasicBlock,
                      mir: &Mir,
                      w: &mut Write,
-                     annotations: &FnvHashMap<Location, Vec<Annotation>>)
+                     annotations: &FnvHashMap<Location, Vec<Annotation>>,
+                     origin: Option<&BlockProvenance>)
                      -> io::Result<()> {
     let data = &mir[block];
 
     // Basic block label at the top.
     writeln!(w, "{}{:?}: {{", INDENT, block)?;
 
+    // With `-Z mir-provenance`, where the block comes from.
+    if let Some(origin) = origin {
+        let span = tcx.sess.codemap().span_to_string(origin.span);
+        if origin.pass == provenance::BUILT {
+            writeln!(w, "{0}{0}// built as {1:?} at {2}", INDENT, origin.block, span)?;
+        } else {
+            writeln!(w, "{0}{0}// created as {1:?} by {2} at {3}",
+                     INDENT, origin.block, origin.pass, span)?;
+        }
+    }
+
     // List of statements in the middle.
     let mut current_location = Location { block: block, statement_index: 0 };
+    let mut source_line = None;
     for statement in &data.statements {
         if let Some(ref annotations) = annotations.get(&current_location) {
             for annotation in annotations.iter() {
@@ -191,6 +292,7 @@ fn write_basic_block(tcx: TyCtxt,
             }
         }
 
+        write_source_line(tcx, statement.source_info.span, &mut source_line, w)?;
         let indented_mir = format!("{0}{0}{1:?};", INDENT, statement);
         writeln!(w, "{0:1$} // {2}",
                  indented_mir,
@@ -201,6 +303,7 @@ fn write_basic_block(tcx: TyCtxt,
     }
 
     // Terminator at the bottom.
+    write_source_line(tcx, data.terminator().source_info.span, &mut source_line, w)?;
     let indented_terminator = format!("{0}{0}{1:?};", INDENT, data.terminator().kind);
     writeln!(w, "{0:1$} // {2}",
              indented_terminator,
@@ -210,6 +313,29 @@ fn write_basic_block(tcx: TyCtxt,
     writeln!(w, "{}}}", INDENT)
 }
 
+/// With `-Z mir-pretty-source`, write the source line `span` starts on as
+/// a comment, unless it is the line written last (`last_line`), so that
+/// each group of statements originating from one line is headed by it.
+fn write_source_line(tcx: TyCtxt,
+                     span: Span,
+                     last_line: &mut Option<(String, usize)>,
+                     w: &mut Write)
+                     -> io::Result<()> {
+    if !tcx.sess.opts.debugging_opts.mir_pretty_source || span == DUMMY_SP {
+        return Ok(());
+    }
+    let loc = tcx.sess.codemap().lookup_char_pos(span.lo);
+    let line = (loc.file.name.clone(), loc.line);
+    if last_line.as_ref() == Some(&line) {
+        return Ok(());
+    }
+    if let Some(text) = loc.file.get_line(loc.line - 1) {
+        writeln!(w, "{0}{0}// {1}:{2}: {3}", INDENT, loc.file.name, loc.line, text.trim())?;
+    }
+    *last_line = Some(line);
+    Ok(())
+}
+
 fn comment(tcx: TyCtxt, SourceInfo { span, scope }: SourceInfo) -> String {
     format!("scope {} at {}", scope.index(), tcx.sess.codemap().span_to_string(span))
 }
@@ -293,7 +419,7 @@ fn write_mir_intro<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
 
     write_scope_tree(tcx, mir, &scope_tree, w, ARGUMENT_VISIBILITY_SCOPE, 1)?;
 
-    write_mir_decls(mir, w)
+    write_mir_decls(tcx, mir, w)
 }
 
 fn write_mir_sig(tcx: TyCtxt, src: MirSource, mir: &Mir, w: &mut Write)
@@ -333,10 +459,14 @@ fn write_mir_sig(tcx: TyCtxt, src: MirSource, mir: &Mir, w: &mut Write)
     }
 }
 
-fn write_mir_decls(mir: &Mir, w: &mut Write) -> io::Result<()> {
-    // Compiler-introduced temporary types.
+fn write_mir_decls(tcx: TyCtxt, mir: &Mir, w: &mut Write) -> io::Result<()> {
+    // Compiler-introduced temporary types, and the expressions they are for.
     for (id, temp) in mir.temp_decls.iter_enumerated() {
-        writeln!(w, "{}let mut {:?}: {};", INDENT, id, temp.ty)?;
+        let decl = format!("{}let mut {:?}: {};", INDENT, id, temp.ty);
+        match source_excerpt(tcx, temp.span) {
+            Some(excerpt) => writeln!(w, "{0:1$} // `{2}`", decl, ALIGN, excerpt)?,
+            None => writeln!(w, "{}", decl)?,
+        }
     }
 
     // Wrote any declaration? Add an empty line before the first block is printed.
@@ -0,0 +1,929 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! MIR for functions that have no source of their own and are otherwise
+//! only synthesized by trans: drop glue, `Clone` for builtin types, the
+//! `Fn*` impls of function pointers and items, `call_once` of closures
+//! that are not `FnOnce` closures, and the shims through which methods
+//! are called on trait objects. `fn_trait_shim` finds the shim that a
+//! call of a `Fn*` trait method goes through, if any.
+//!
+//! These bodies are built directly, without going through HAIR. They
+//! take their arguments the same way the corresponding trans shims do and
+//! only contain constructs that passes already handle, so that anything
+//! consuming MIR can treat them like any other body. Types have to be
+//! fully monomorphic, with regions erased.
+//!
+//! Trans still translates drop glue, the `Fn*` adapters of functions and
+//! closures and the vtable shims on its own, rather than from these bodies.
+//! MIR trans only translates the bodies of the `MirMap`, which belong to
+//! items, while trans needs the shims for each instantiation it comes
+//! across, of types from any crate, in the middle of translating a caller.
+//! Until translated MIR can be looked up by instance rather than by item,
+//! these bodies are for the consumers of MIR that need to see through the
+//! shims, such as the call graph, and are built on demand with
+//! `build_shim`. Trans and these bodies follow the same calling
+//! conventions, so that either can be used for a call.
+//!
+//! The methods of some derived impls are also built this way, rather than
+//! from their expansion, as they are mechanical: see `derived_shim`. Their
+//! types are those of the impl, which may mention its type parameters.
+
+use hair::cx::int_literal;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map as hir_map;
+use rustc::middle::const_val::ConstVal;
+use rustc::middle::lang_items;
+use rustc::mir::repr::*;
+use rustc::ty::subst::{Subst, Substs, VecPerParamSpace};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_const_math::ConstInt;
+use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax::ast;
+use syntax::parse::token::{self, keywords};
+use syntax_pos::{Span, DUMMY_SP};
+
+#[derive(Copy, Clone, Debug)]
+pub enum Shim<'tcx> {
+    /// `drop_in_place::<T>`, taking a `*mut T`. The value is taken apart
+    /// one level deep: the `Drop` impl of `T` is called, if any, and then
+    /// each of its components is dropped with a `Drop` terminator.
+    DropGlue(Ty<'tcx>),
+
+    /// `<T as Clone>::clone` for a `Copy` type, a tuple, a closure or an
+    /// array, taking a `&T`.
+    Clone(Ty<'tcx>),
+
+    /// The `call`, `call_mut` or `call_once` method of the `Fn` trait of
+    /// the given kind, implemented for a function pointer or function item
+    /// type. It takes the function or a reference to it, and the arguments
+    /// as a tuple.
+    FnPtr(ty::ClosureKind, Ty<'tcx>),
+
+    /// `call_once` of a closure whose kind is `Fn` or `FnMut`, taking the
+    /// closure by value and the arguments as a tuple. The closure is
+    /// called through a reference to it and dropped afterwards.
+    ClosureOnce(DefId, ty::ClosureSubsts<'tcx>),
+
+    /// The trait method `def_id`, called through the vtable of the trait
+    /// object that `substs` has as `Self`.
+    Virtual(DefId, &'tcx Substs<'tcx>),
+
+    /// `<T as Default>::default` for a struct, as derived, given the
+    /// `Default` trait: each field is set to the `default()` of its type,
+    /// in order.
+    Default(DefId, Ty<'tcx>),
+
+    /// The `discriminant_value` intrinsic for `T`, taking a `&T`. It
+    /// returns the discriminant of the variant of an enum, sign-extended
+    /// to a `u64`, and 0 for any other type.
+    Discriminant(Ty<'tcx>),
+}
+
+/// Build the MIR of `shim`.
+pub fn build_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, shim: Shim<'tcx>) -> Mir<'tcx> {
+    debug!("build_shim({:?})", shim);
+    match shim {
+        Shim::DropGlue(ty) => build_drop_glue(tcx, ty),
+        Shim::Clone(ty) => build_clone_shim(tcx, ty),
+        Shim::FnPtr(kind, fn_ty) => build_fn_ptr_shim(tcx, kind, fn_ty),
+        Shim::ClosureOnce(def_id, substs) => build_closure_once_shim(tcx, def_id, substs),
+        Shim::Virtual(def_id, substs) => build_virtual_shim(tcx, def_id, substs),
+        Shim::Default(trait_def_id, ty) => build_default_shim(tcx, DUMMY_SP, trait_def_id, ty),
+        Shim::Discriminant(ty) => build_discriminant_shim(tcx, ty),
+    }
+}
+
+/// The shim through which the method `def_id` of one of the `Fn*` traits
+/// is called with `substs`, if trans would otherwise adapt the callee to
+/// that trait on its own: for function pointers and items, and for
+/// `call_once` of closures that are not `FnOnce` closures. Other calls of
+/// closures go directly to their body.
+pub fn fn_trait_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               def_id: DefId,
+                               substs: &'tcx Substs<'tcx>)
+                               -> Option<Shim<'tcx>> {
+    let kind = match tcx.trait_of_item(def_id).and_then(|t| tcx.lang_items.fn_trait_kind(t)) {
+        Some(kind) => kind,
+        None => return None,
+    };
+    let self_ty = match substs.self_ty() {
+        Some(self_ty) => self_ty,
+        None => return None,
+    };
+    match self_ty.sty {
+        ty::TyFnDef(..) | ty::TyFnPtr(_) => Some(Shim::FnPtr(kind, self_ty)),
+        ty::TyClosure(closure_def_id, closure_substs) => {
+            if kind == ty::ClosureKind::FnOnce &&
+               tcx.closure_kind(closure_def_id) != ty::ClosureKind::FnOnce {
+                Some(Shim::ClosureOnce(closure_def_id, closure_substs))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the MIR of the fn `id`, returning `output`, if it is a method
+/// of a derived impl that can be built as a shim: `Default::default` of a
+/// struct. Its types are not erased, so that it can be built in place of
+/// the body of the method and go through the MIR passes like one.
+pub fn derived_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                              id: ast::NodeId,
+                              output: ty::FnOutput<'tcx>)
+                              -> Option<Mir<'tcx>> {
+    if let Some(hir_map::NodeImplItem(_)) = tcx.map.find(id) {} else {
+        return None;
+    }
+    let impl_def_id = tcx.map.local_def_id(tcx.map.get_parent(id));
+    if !tcx.has_attr(impl_def_id, "automatically_derived") {
+        return None;
+    }
+    let trait_def_id = match tcx.impl_trait_ref(impl_def_id) {
+        Some(trait_ref) => trait_ref.def_id,
+        None => return None,
+    };
+    let is_default = tcx.lang_items.default_trait() == Some(trait_def_id);
+    match output {
+        ty::FnConverging(ty) if is_default => {
+            if let ty::TyStruct(..) = ty.sty {
+                return Some(build_default_shim(tcx, tcx.map.span(id), trait_def_id, ty));
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn build_drop_glue<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> Mir<'tcx> {
+    let mut builder = ShimBuilder::new(tcx, DUMMY_SP, vec![tcx.mk_mut_ptr(ty)], false);
+    let value = Lvalue::Arg(Arg::new(0)).deref();
+
+    let return_block = builder.return_unit_block();
+    let resume_block = builder.resume_block();
+    let entry = builder.open_drop(&value, ty, return_block, resume_block);
+    builder.terminate(START_BLOCK, TerminatorKind::Goto { target: entry });
+    builder.finish(ty::FnConverging(tcx.mk_nil()))
+}
+
+fn build_clone_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> Mir<'tcx> {
+    let ref_ty = tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), ty);
+    let mut builder = ShimBuilder::new(tcx, DUMMY_SP, vec![ref_ty], false);
+    let src = Lvalue::Arg(Arg::new(0)).deref();
+
+    if builder.is_copy(ty) {
+        let return_block = builder.return_block();
+        builder.push_assign(START_BLOCK, Lvalue::ReturnPointer,
+                            Rvalue::Use(Operand::Consume(src)));
+        builder.terminate(START_BLOCK, TerminatorKind::Goto { target: return_block });
+        return builder.finish(ty::FnConverging(ty));
+    }
+
+    match ty.sty {
+        ty::TyArray(elem_ty, len) => builder.clone_array(&src, elem_ty, len),
+        ty::TyTuple(tys) => {
+            builder.clone_fields(&src, tys, AggregateKind::Tuple)
+        }
+        ty::TyClosure(def_id, substs) => {
+            builder.clone_fields(&src, substs.upvar_tys, AggregateKind::Closure(def_id, substs))
+        }
+        _ => bug!("clone shim for non-builtin type `{:?}`", ty)
+    }
+    builder.finish(ty::FnConverging(ty))
+}
+
+fn build_fn_ptr_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               kind: ty::ClosureKind,
+                               fn_ty: Ty<'tcx>)
+                               -> Mir<'tcx> {
+    let sig = match fn_ty.sty {
+        ty::TyFnDef(_, _, f) | ty::TyFnPtr(f) => f.sig.clone(),
+        _ => bug!("fn pointer shim for non-fn type `{:?}`", fn_ty)
+    };
+    let sig = tcx.normalize_associated_type(&tcx.erase_late_bound_regions(&sig));
+
+    let self_ty = match kind {
+        ty::ClosureKind::Fn => tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), fn_ty),
+        ty::ClosureKind::FnMut => tcx.mk_mut_ref(tcx.mk_region(ty::ReErased), fn_ty),
+        ty::ClosureKind::FnOnce => fn_ty,
+    };
+    let args_ty = tcx.mk_tup(sig.inputs.clone());
+    let mut builder = ShimBuilder::new(tcx, DUMMY_SP, vec![self_ty, args_ty], true);
+
+    let func = match fn_ty.sty {
+        ty::TyFnDef(def_id, substs, _) => builder.item_operand(def_id, substs),
+        _ => {
+            let self_arg = Lvalue::Arg(Arg::new(0));
+            Operand::Consume(match kind {
+                ty::ClosureKind::Fn | ty::ClosureKind::FnMut => self_arg.deref(),
+                ty::ClosureKind::FnOnce => self_arg,
+            })
+        }
+    };
+    let args = sig.inputs.iter().enumerate().map(|(i, &ty)| {
+        Operand::Consume(Lvalue::Arg(Arg::new(1)).field(Field::new(i), ty))
+    }).collect();
+
+    builder.call_and_return(func, args, sig.output);
+    builder.finish(sig.output)
+}
+
+fn build_closure_once_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                     def_id: DefId,
+                                     substs: ty::ClosureSubsts<'tcx>)
+                                     -> Mir<'tcx> {
+    let closure_ty = tcx.mk_closure_from_closure_substs(def_id, substs);
+    let sig = tcx.erase_late_bound_regions(&tcx.closure_type(def_id, substs).sig);
+    let sig = tcx.normalize_associated_type(&sig);
+    // The signature of a closure takes its arguments as a tuple.
+    let args_ty = sig.inputs[0];
+    let span = tcx.map.span_if_local(def_id).unwrap_or(DUMMY_SP);
+    let mut builder = ShimBuilder::new(tcx, span, vec![closure_ty, args_ty], true);
+    let closure = Lvalue::Arg(Arg::new(0));
+
+    let region = tcx.mk_region(ty::ReErased);
+    let (lang_item, method, ref_ty, borrow_kind) = match tcx.closure_kind(def_id) {
+        ty::ClosureKind::Fn => {
+            (lang_items::FnTraitLangItem, "call",
+             tcx.mk_imm_ref(region, closure_ty), BorrowKind::Shared)
+        }
+        ty::ClosureKind::FnMut => {
+            (lang_items::FnMutTraitLangItem, "call_mut",
+             tcx.mk_mut_ref(region, closure_ty), BorrowKind::Mut)
+        }
+        ty::ClosureKind::FnOnce => {
+            bug!("call_once shim for `FnOnce` closure `{:?}`", closure_ty)
+        }
+    };
+    let fn_trait = tcx.lang_items.require(lang_item).unwrap_or_else(|e| tcx.sess.fatal(&e));
+    let func = builder.trait_method(fn_trait, method, closure_ty, vec![args_ty]);
+    let closure_ref = builder.new_temp(ref_ty);
+    builder.push_assign(START_BLOCK, closure_ref.clone(),
+                        Rvalue::Ref(region, borrow_kind, closure.clone()));
+
+    // The closure is dropped whether the call returns or unwinds.
+    let resume_block = builder.resume_block();
+    let destination = match sig.output {
+        ty::FnConverging(_) => {
+            let return_block = builder.return_block();
+            let drop = builder.drop_block(&closure, closure_ty, return_block,
+                                          Some(resume_block), false);
+            Some((Lvalue::ReturnPointer, drop))
+        }
+        ty::FnDiverging => None,
+    };
+    let cleanup = builder.drop_block(&closure, closure_ty, resume_block, None, true);
+    builder.terminate(START_BLOCK, TerminatorKind::Call {
+        func: func,
+        args: vec![Operand::Consume(closure_ref), Operand::Consume(Lvalue::Arg(Arg::new(1)))],
+        destination: destination,
+        cleanup: Some(cleanup)
+    });
+    builder.finish(sig.output)
+}
+
+fn build_virtual_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                def_id: DefId,
+                                substs: &'tcx Substs<'tcx>)
+                                -> Mir<'tcx> {
+    let method_ty = tcx.lookup_item_type(def_id).ty.subst(tcx, substs);
+    let sig = tcx.erase_late_bound_regions(method_ty.fn_sig());
+    let sig = tcx.normalize_associated_type(&sig);
+    let span = tcx.map.span_if_local(def_id).unwrap_or(DUMMY_SP);
+    let mut builder = ShimBuilder::new(tcx, span, sig.inputs.clone(), false);
+
+    let func = builder.item_operand(def_id, substs);
+    let args = (0..sig.inputs.len()).map(|i| {
+        Operand::Consume(Lvalue::Arg(Arg::new(i)))
+    }).collect();
+
+    builder.call_and_return(func, args, sig.output);
+    builder.finish(sig.output)
+}
+
+fn build_default_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                span: Span,
+                                default_trait: DefId,
+                                ty: Ty<'tcx>)
+                                -> Mir<'tcx> {
+    let mut builder = ShimBuilder::new(tcx, span, vec![], false);
+    let (adt, substs) = match ty.sty {
+        ty::TyStruct(adt, substs) => (adt, substs),
+        _ => bug!("default shim for non-struct type `{:?}`", ty)
+    };
+    let fields: Vec<_> = adt.struct_variant().fields.iter().map(|field| {
+        tcx.normalize_associated_type(&field.ty(tcx, substs))
+    }).collect();
+    builder.default_fields(default_trait, &fields, AggregateKind::Adt(adt, 0, substs));
+    builder.finish(ty::FnConverging(ty))
+}
+
+fn build_discriminant_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> Mir<'tcx> {
+    let ref_ty = tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), ty);
+    let mut builder = ShimBuilder::new(tcx, DUMMY_SP, vec![ref_ty], false);
+    let value = Lvalue::Arg(Arg::new(0)).deref();
+
+    let kind = match ty.sty {
+        ty::TyEnum(adt, _) => {
+            let targets = adt.variants.iter().map(|variant| {
+                builder.return_u64_block(variant.disr_val.to_u64_unchecked())
+            }).collect();
+            TerminatorKind::Switch {
+                discr: value,
+                adt_def: adt,
+                targets: targets
+            }
+        }
+        _ => TerminatorKind::Goto { target: builder.return_u64_block(0) }
+    };
+    builder.terminate(START_BLOCK, kind);
+    builder.finish(ty::FnConverging(tcx.types.u64))
+}
+
+/// Accumulates the blocks and locals of a shim. Blocks are started and
+/// terminated separately, so that they can jump to blocks created after
+/// them. `START_BLOCK` is created up front and has to be terminated by
+/// the caller.
+struct ShimBuilder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    param_env: ty::ParameterEnvironment<'tcx>,
+    source_info: SourceInfo,
+    basic_blocks: IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+    arg_decls: IndexVec<Arg, ArgDecl<'tcx>>,
+    temp_decls: IndexVec<Temp, TempDecl<'tcx>>,
+    resume_block: Option<BasicBlock>,
+}
+
+impl<'a, 'tcx> ShimBuilder<'a, 'tcx> {
+    /// If `spread` is true, the last argument is a tuple of the actual
+    /// arguments, as for the `rust-call` ABI.
+    fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+           span: Span,
+           arg_tys: Vec<Ty<'tcx>>,
+           spread: bool)
+           -> Self {
+        let num_args = arg_tys.len();
+        let arg_decls = arg_tys.into_iter().enumerate().map(|(i, ty)| ArgDecl {
+            ty: ty,
+            spread: spread && i + 1 == num_args,
+            debug_name: keywords::Invalid.name(),
+        }).collect();
+        let mut builder = ShimBuilder {
+            tcx: tcx,
+            param_env: tcx.empty_parameter_environment(),
+            source_info: SourceInfo {
+                span: span,
+                scope: ARGUMENT_VISIBILITY_SCOPE
+            },
+            basic_blocks: IndexVec::new(),
+            arg_decls: arg_decls,
+            temp_decls: IndexVec::new(),
+            resume_block: None,
+        };
+        assert_eq!(builder.start_block(false), START_BLOCK);
+        builder
+    }
+
+    fn finish(self, return_ty: ty::FnOutput<'tcx>) -> Mir<'tcx> {
+        let span = self.source_info.span;
+        Mir::new(self.basic_blocks,
+                 Some(VisibilityScopeData {
+                     span: span,
+                     parent_scope: None
+                 }).into_iter().collect(),
+                 IndexVec::new(),
+                 return_ty,
+                 IndexVec::new(),
+                 self.arg_decls,
+                 self.temp_decls,
+                 IndexVec::new(),
+                 span)
+    }
+
+    fn start_block(&mut self, is_cleanup: bool) -> BasicBlock {
+        self.basic_blocks.push(BasicBlockData {
+            statements: vec![],
+            terminator: None,
+            is_cleanup: is_cleanup
+        })
+    }
+
+    fn push_assign(&mut self, block: BasicBlock, lvalue: Lvalue<'tcx>, rvalue: Rvalue<'tcx>) {
+        let source_info = self.source_info;
+        self.basic_blocks[block].statements.push(Statement {
+            source_info: source_info,
+            kind: StatementKind::Assign(lvalue, rvalue)
+        });
+    }
+
+    fn terminate(&mut self, block: BasicBlock, kind: TerminatorKind<'tcx>) {
+        debug_assert!(self.basic_blocks[block].terminator.is_none(),
+                      "terminate: block {:?} already has a terminator", block);
+        self.basic_blocks[block].terminator = Some(Terminator {
+            source_info: self.source_info,
+            kind: kind
+        });
+    }
+
+    fn new_block(&mut self, is_cleanup: bool, kind: TerminatorKind<'tcx>) -> BasicBlock {
+        let block = self.start_block(is_cleanup);
+        self.terminate(block, kind);
+        block
+    }
+
+    fn new_temp(&mut self, ty: Ty<'tcx>) -> Lvalue<'tcx> {
+        Lvalue::Temp(self.temp_decls.push(TempDecl { ty: ty, span: self.source_info.span }))
+    }
+
+    fn return_block(&mut self) -> BasicBlock {
+        self.new_block(false, TerminatorKind::Return)
+    }
+
+    /// A block that stores `()` in the return pointer and returns.
+    fn return_unit_block(&mut self) -> BasicBlock {
+        let block = self.start_block(false);
+        self.push_assign(block, Lvalue::ReturnPointer,
+                         Rvalue::Aggregate(AggregateKind::Tuple, vec![]));
+        self.terminate(block, TerminatorKind::Return);
+        block
+    }
+
+    /// A block that stores the `u64` `value` in the return pointer and
+    /// returns.
+    fn return_u64_block(&mut self, value: u64) -> BasicBlock {
+        let block = self.start_block(false);
+        let value = Operand::Constant(Constant {
+            span: self.source_info.span,
+            ty: self.tcx.types.u64,
+            literal: Literal::Value { value: ConstVal::Integral(ConstInt::U64(value)) }
+        });
+        self.push_assign(block, Lvalue::ReturnPointer, Rvalue::Use(value));
+        self.terminate(block, TerminatorKind::Return);
+        block
+    }
+
+    fn resume_block(&mut self) -> BasicBlock {
+        if let Some(block) = self.resume_block {
+            return block;
+        }
+        let block = self.new_block(true, TerminatorKind::Resume);
+        self.resume_block = Some(block);
+        block
+    }
+
+    fn is_copy(&self, ty: Ty<'tcx>) -> bool {
+        !ty.moves_by_default(self.tcx, &self.param_env, self.source_info.span)
+    }
+
+    fn needs_drop(&self, ty: Ty<'tcx>) -> bool {
+        self.tcx.type_needs_drop_given_env(ty, &self.param_env)
+    }
+
+    fn usize_operand(&self, value: u64) -> Operand<'tcx> {
+        let span = self.source_info.span;
+        Operand::Constant(Constant {
+            span: span,
+            ty: self.tcx.types.usize,
+            literal: int_literal(self.tcx, span, value, self.tcx.types.usize)
+        })
+    }
+
+    fn item_operand(&self, def_id: DefId, substs: &'tcx Substs<'tcx>) -> Operand<'tcx> {
+        Operand::Constant(Constant {
+            span: self.source_info.span,
+            ty: self.tcx.lookup_item_type(def_id).ty.subst(self.tcx, substs),
+            literal: Literal::Item { def_id: def_id, substs: substs }
+        })
+    }
+
+    /// The method `name` of the trait `trait_def_id`, with `self_ty` as
+    /// `Self` and `types` as the type parameters of the trait.
+    fn trait_method(&self,
+                    trait_def_id: DefId,
+                    name: &str,
+                    self_ty: Ty<'tcx>,
+                    types: Vec<Ty<'tcx>>)
+                    -> Operand<'tcx> {
+        let name = token::intern(name);
+        let substs = self.tcx.mk_substs(Substs::new_trait(types, vec![], self_ty));
+        for trait_item in self.tcx.trait_items(trait_def_id).iter() {
+            if let ty::ImplOrTraitItem::MethodTraitItem(ref method) = *trait_item {
+                if method.name == name {
+                    return self.item_operand(method.def_id, substs);
+                }
+            }
+        }
+        bug!("found no method `{}` in `{:?}`", name, trait_def_id);
+    }
+
+    /// Terminate `START_BLOCK` with a call of `func` returning into the
+    /// return pointer, followed by a return if the call converges.
+    fn call_and_return(&mut self,
+                       func: Operand<'tcx>,
+                       args: Vec<Operand<'tcx>>,
+                       output: ty::FnOutput<'tcx>) {
+        let destination = match output {
+            ty::FnConverging(_) => Some((Lvalue::ReturnPointer, self.return_block())),
+            ty::FnDiverging => None,
+        };
+        let cleanup = Some(self.resume_block());
+        self.terminate(START_BLOCK, TerminatorKind::Call {
+            func: func,
+            args: args,
+            destination: destination,
+            cleanup: cleanup
+        });
+    }
+
+    /// Build the blocks `header`, `body` and `step` of a loop of `index`
+    /// up to `len`. The caller has to initialize `index` before jumping to
+    /// `header` and has to terminate `body`, normally with a jump to
+    /// `step`. The loop leaves to `exit`.
+    fn counted_loop(&mut self,
+                    index: &Lvalue<'tcx>,
+                    len: Operand<'tcx>,
+                    exit: BasicBlock,
+                    is_cleanup: bool)
+                    -> (BasicBlock, BasicBlock, BasicBlock) {
+        let tcx = self.tcx;
+        let cond = self.new_temp(tcx.types.bool);
+
+        let header = self.start_block(is_cleanup);
+        let body = self.start_block(is_cleanup);
+        let step = self.start_block(is_cleanup);
+
+        self.push_assign(header, cond.clone(),
+                         Rvalue::BinaryOp(BinOp::Lt, Operand::Consume(index.clone()), len));
+        self.terminate(header, TerminatorKind::If {
+            cond: Operand::Consume(cond),
+            targets: (body, exit)
+        });
+
+        let one = self.usize_operand(1);
+        self.push_assign(step, index.clone(),
+                         Rvalue::BinaryOp(BinOp::Add, Operand::Consume(index.clone()), one));
+        self.terminate(step, TerminatorKind::Goto { target: header });
+
+        (header, body, step)
+    }
+
+    /// A block that sets `index` to zero and jumps to `target`.
+    fn zero_index_block(&mut self, index: &Lvalue<'tcx>, target: BasicBlock, is_cleanup: bool)
+                        -> BasicBlock {
+        let block = self.start_block(is_cleanup);
+        let zero = self.usize_operand(0);
+        self.push_assign(block, index.clone(), Rvalue::Use(zero));
+        self.terminate(block, TerminatorKind::Goto { target: target });
+        block
+    }
+
+    /// A block that drops `lvalue` with a `Drop` terminator, or `succ` if
+    /// it does not need dropping.
+    fn drop_block(&mut self,
+                  lvalue: &Lvalue<'tcx>,
+                  ty: Ty<'tcx>,
+                  succ: BasicBlock,
+                  unwind: Option<BasicBlock>,
+                  is_cleanup: bool)
+                  -> BasicBlock {
+        if !self.needs_drop(ty) {
+            return succ;
+        }
+        self.new_block(is_cleanup, TerminatorKind::Drop {
+            location: lvalue.clone(),
+            target: succ,
+            unwind: unwind
+        })
+    }
+
+    /// Drop `fields` in order, continuing with `succ`. If one of the drops
+    /// unwinds, the remaining fields are still dropped on the way to
+    /// `unwind`.
+    fn drop_ladder(&mut self,
+                   fields: &[(Lvalue<'tcx>, Ty<'tcx>)],
+                   succ: BasicBlock,
+                   unwind: BasicBlock)
+                   -> BasicBlock {
+        let mut cleanup_succ = unwind;
+        let cleanups: Vec<_> = fields.iter().rev().map(|&(ref lvalue, ty)| {
+            cleanup_succ = self.drop_block(lvalue, ty, cleanup_succ, None, true);
+            cleanup_succ
+        }).collect();
+
+        let mut succ = succ;
+        let mut unwind = unwind;
+        for (i, &(ref lvalue, ty)) in fields.iter().rev().enumerate() {
+            succ = self.drop_block(lvalue, ty, succ, Some(unwind), false);
+            unwind = cleanups[i];
+        }
+        succ
+    }
+
+    /// Drop the contents of `lvalue`, of type `ty`, one level deep and
+    /// return the entry block.
+    fn open_drop(&mut self,
+                 lvalue: &Lvalue<'tcx>,
+                 ty: Ty<'tcx>,
+                 succ: BasicBlock,
+                 unwind: BasicBlock)
+                 -> BasicBlock {
+        if !self.needs_drop(ty) {
+            return succ;
+        }
+        match ty.sty {
+            ty::TyStruct(adt, substs) | ty::TyEnum(adt, substs) => {
+                if adt.has_dtor() {
+                    let contents_unwind = self.drop_adt_contents(lvalue, adt, substs,
+                                                                 unwind, unwind, true);
+                    let contents = self.drop_adt_contents(lvalue, adt, substs,
+                                                          succ, unwind, false);
+                    self.call_drop_impl(lvalue, ty, contents, contents_unwind)
+                } else {
+                    self.drop_adt_contents(lvalue, adt, substs, succ, unwind, false)
+                }
+            }
+            ty::TyTuple(tys) | ty::TyClosure(_, ty::ClosureSubsts { upvar_tys: tys, .. }) => {
+                let fields: Vec<_> = tys.iter().enumerate().map(|(i, &ty)| {
+                    (lvalue.clone().field(Field::new(i), ty), ty)
+                }).collect();
+                self.drop_ladder(&fields, succ, unwind)
+            }
+            ty::TyBox(content_ty) => {
+                let free = self.box_free_block(lvalue, content_ty, succ, false);
+                let free_unwind = self.box_free_block(lvalue, content_ty, unwind, true);
+                let content = lvalue.clone().deref();
+                self.drop_block(&content, content_ty, free, Some(free_unwind), false)
+            }
+            ty::TyArray(elem_ty, len) => {
+                let len = self.usize_operand(len as u64);
+                self.drop_elements(lvalue, elem_ty, len, succ, unwind)
+            }
+            ty::TySlice(elem_ty) => {
+                let usize_ty = self.tcx.types.usize;
+                let len = self.new_temp(usize_ty);
+                let entry = self.start_block(false);
+                self.push_assign(entry, len.clone(), Rvalue::Len(lvalue.clone()));
+                let elements = self.drop_elements(lvalue, elem_ty, Operand::Consume(len),
+                                                  succ, unwind);
+                self.terminate(entry, TerminatorKind::Goto { target: elements });
+                entry
+            }
+            // Trait objects are dropped through their vtable.
+            _ => self.drop_block(lvalue, ty, succ, Some(unwind), false)
+        }
+    }
+
+    /// Drop the fields of the struct or enum `lvalue`, switching on the
+    /// discriminant for enums with more than one variant.
+    fn drop_adt_contents(&mut self,
+                         lvalue: &Lvalue<'tcx>,
+                         adt: ty::AdtDef<'tcx>,
+                         substs: &'tcx Substs<'tcx>,
+                         succ: BasicBlock,
+                         unwind: BasicBlock,
+                         is_cleanup: bool)
+                         -> BasicBlock {
+        if adt.variants.len() == 1 {
+            return self.drop_variant(lvalue.clone(), adt, substs, 0, succ, unwind, is_cleanup);
+        }
+
+        let targets = (0..adt.variants.len()).map(|i| {
+            let variant = lvalue.clone().elem(ProjectionElem::Downcast(adt, i));
+            self.drop_variant(variant, adt, substs, i, succ, unwind, is_cleanup)
+        }).collect();
+        self.new_block(is_cleanup, TerminatorKind::Switch {
+            discr: lvalue.clone(),
+            adt_def: adt,
+            targets: targets
+        })
+    }
+
+    fn drop_variant(&mut self,
+                    base: Lvalue<'tcx>,
+                    adt: ty::AdtDef<'tcx>,
+                    substs: &'tcx Substs<'tcx>,
+                    variant_index: usize,
+                    succ: BasicBlock,
+                    unwind: BasicBlock,
+                    is_cleanup: bool)
+                    -> BasicBlock {
+        let tcx = self.tcx;
+        let variant = &adt.variants[variant_index];
+        let fields: Vec<_> = variant.fields.iter().enumerate().map(|(i, field)| {
+            let ty = tcx.normalize_associated_type(&field.ty(tcx, substs));
+            (base.clone().field(Field::new(i), ty), ty)
+        }).collect();
+
+        if is_cleanup {
+            let mut succ = succ;
+            for &(ref lvalue, ty) in fields.iter().rev() {
+                succ = self.drop_block(lvalue, ty, succ, None, true);
+            }
+            succ
+        } else {
+            self.drop_ladder(&fields, succ, unwind)
+        }
+    }
+
+    /// Call `<ty as Drop>::drop(&mut lvalue)`.
+    fn call_drop_impl(&mut self,
+                      lvalue: &Lvalue<'tcx>,
+                      ty: Ty<'tcx>,
+                      succ: BasicBlock,
+                      unwind: BasicBlock)
+                      -> BasicBlock {
+        let tcx = self.tcx;
+        let drop_trait = tcx.lang_items.require(lang_items::DropTraitLangItem)
+                            .unwrap_or_else(|e| tcx.sess.fatal(&e));
+        let drop_fn = self.trait_method(drop_trait, "drop", ty, vec![]);
+        let ref_ty = tcx.mk_mut_ref(tcx.mk_region(ty::ReErased), ty);
+        let self_ref = self.new_temp(ref_ty);
+        let unit_temp = self.new_temp(tcx.mk_nil());
+
+        let block = self.start_block(false);
+        let region = tcx.mk_region(ty::ReErased);
+        self.push_assign(block, self_ref.clone(),
+                         Rvalue::Ref(region, BorrowKind::Mut, lvalue.clone()));
+        self.terminate(block, TerminatorKind::Call {
+            func: drop_fn,
+            args: vec![Operand::Consume(self_ref)],
+            destination: Some((unit_temp, succ)),
+            cleanup: Some(unwind)
+        });
+        block
+    }
+
+    /// Free the allocation of the box `lvalue`, whose contents have already
+    /// been dropped.
+    fn box_free_block(&mut self,
+                      lvalue: &Lvalue<'tcx>,
+                      content_ty: Ty<'tcx>,
+                      target: BasicBlock,
+                      is_cleanup: bool)
+                      -> BasicBlock {
+        let tcx = self.tcx;
+        let free_fn = tcx.lang_items.require(lang_items::BoxFreeFnLangItem)
+                         .unwrap_or_else(|e| tcx.sess.fatal(&e));
+        let substs = tcx.mk_substs(Substs::new(
+            VecPerParamSpace::new(vec![], vec![], vec![content_ty]),
+            VecPerParamSpace::new(vec![], vec![], vec![])
+        ));
+        let func = self.item_operand(free_fn, substs);
+        let unit_temp = self.new_temp(tcx.mk_nil());
+        self.new_block(is_cleanup, TerminatorKind::Call {
+            func: func,
+            args: vec![Operand::Consume(lvalue.clone())],
+            destination: Some((unit_temp, target)),
+            cleanup: None
+        })
+    }
+
+    /// Drop the elements of the array or slice `lvalue` in a loop. If one
+    /// of them unwinds, a second loop drops the elements after it.
+    fn drop_elements(&mut self,
+                     lvalue: &Lvalue<'tcx>,
+                     elem_ty: Ty<'tcx>,
+                     len: Operand<'tcx>,
+                     succ: BasicBlock,
+                     unwind: BasicBlock)
+                     -> BasicBlock {
+        let usize_ty = self.tcx.types.usize;
+        let index = self.new_temp(usize_ty);
+        let elem = lvalue.clone().index(Operand::Consume(index.clone()));
+
+        let (header, body, step) = self.counted_loop(&index, len.clone(), succ, false);
+        let (_, cleanup_body, cleanup_step) = self.counted_loop(&index, len, unwind, true);
+        self.terminate(body, TerminatorKind::Drop {
+            location: elem.clone(),
+            target: step,
+            unwind: Some(cleanup_step)
+        });
+        self.terminate(cleanup_body, TerminatorKind::Drop {
+            location: elem,
+            target: cleanup_step,
+            unwind: None
+        });
+
+        self.zero_index_block(&index, header, false)
+    }
+
+    /// A block that calls `Clone::clone` on `src` and stores the result in
+    /// `dest`.
+    fn clone_call(&mut self,
+                  src: Lvalue<'tcx>,
+                  dest: Lvalue<'tcx>,
+                  ty: Ty<'tcx>,
+                  succ: BasicBlock,
+                  unwind: BasicBlock)
+                  -> BasicBlock {
+        let tcx = self.tcx;
+        let clone_trait = tcx.lang_items.require(lang_items::CloneTraitLangItem)
+                             .unwrap_or_else(|e| tcx.sess.fatal(&e));
+        let clone_fn = self.trait_method(clone_trait, "clone", ty, vec![]);
+        let ref_ty = tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), ty);
+        let src_ref = self.new_temp(ref_ty);
+
+        let block = self.start_block(false);
+        self.push_assign(block, src_ref.clone(),
+                         Rvalue::Ref(tcx.mk_region(ty::ReErased), BorrowKind::Shared, src));
+        self.terminate(block, TerminatorKind::Call {
+            func: clone_fn,
+            args: vec![Operand::Consume(src_ref)],
+            destination: Some((dest, succ)),
+            cleanup: Some(unwind)
+        });
+        block
+    }
+
+    /// Clone the fields of the tuple or closure `src` into temporaries
+    /// and build the result from them. If cloning a field unwinds, the
+    /// fields cloned so far are dropped.
+    fn clone_fields(&mut self, src: &Lvalue<'tcx>, tys: &[Ty<'tcx>], kind: AggregateKind<'tcx>) {
+        let return_block = self.return_block();
+        let mut unwind = self.resume_block();
+        let mut block = START_BLOCK;
+        let mut temps = vec![];
+        for (i, &ty) in tys.iter().enumerate() {
+            let temp = self.new_temp(ty);
+            let next = self.start_block(false);
+            let field = src.clone().field(Field::new(i), ty);
+            let call = self.clone_call(field, temp.clone(), ty, next, unwind);
+            self.terminate(block, TerminatorKind::Goto { target: call });
+            unwind = self.drop_block(&temp, ty, unwind, None, true);
+            temps.push(Operand::Consume(temp));
+            block = next;
+        }
+        self.push_assign(block, Lvalue::ReturnPointer, Rvalue::Aggregate(kind, temps));
+        self.terminate(block, TerminatorKind::Goto { target: return_block });
+    }
+
+    /// Call `Default::default` for each of `tys`, the types of the fields,
+    /// into temporaries and build the result from them. If one of the
+    /// calls unwinds, the fields built so far are dropped.
+    fn default_fields(&mut self,
+                      default_trait: DefId,
+                      tys: &[Ty<'tcx>],
+                      kind: AggregateKind<'tcx>) {
+        let return_block = self.return_block();
+        let mut unwind = self.resume_block();
+        let mut block = START_BLOCK;
+        let mut temps = vec![];
+        for &ty in tys {
+            let temp = self.new_temp(ty);
+            let next = self.start_block(false);
+            let default_fn = self.trait_method(default_trait, "default", ty, vec![]);
+            self.terminate(block, TerminatorKind::Call {
+                func: default_fn,
+                args: vec![],
+                destination: Some((temp.clone(), next)),
+                cleanup: Some(unwind)
+            });
+            unwind = self.drop_block(&temp, ty, unwind, None, true);
+            temps.push(Operand::Consume(temp));
+            block = next;
+        }
+        self.push_assign(block, Lvalue::ReturnPointer, Rvalue::Aggregate(kind, temps));
+        self.terminate(block, TerminatorKind::Goto { target: return_block });
+    }
+
+    /// Clone the elements of the array `src` in a loop, writing them into
+    /// the return pointer. If cloning an element unwinds, the elements
+    /// cloned so far are dropped in a second loop.
+    fn clone_array(&mut self, src: &Lvalue<'tcx>, elem_ty: Ty<'tcx>, len: usize) {
+        let tcx = self.tcx;
+        let return_block = self.return_block();
+        let resume_block = self.resume_block();
+        let index = self.new_temp(tcx.types.usize);
+        let cleanup_index = self.new_temp(tcx.types.usize);
+
+        // Drop `return[0..index]` when unwinding.
+        let (cleanup_header, cleanup_body, cleanup_step) =
+            self.counted_loop(&cleanup_index, Operand::Consume(index.clone()),
+                              resume_block, true);
+        let cleanup_elem = Lvalue::ReturnPointer.index(Operand::Consume(cleanup_index.clone()));
+        self.terminate(cleanup_body, TerminatorKind::Drop {
+            location: cleanup_elem,
+            target: cleanup_step,
+            unwind: None
+        });
+        let cleanup = self.zero_index_block(&cleanup_index, cleanup_header, true);
+
+        let len = self.usize_operand(len as u64);
+        let (header, body, step) = self.counted_loop(&index, len, return_block, false);
+        let src_elem = src.clone().index(Operand::Consume(index.clone()));
+        let dest_elem = Lvalue::ReturnPointer.index(Operand::Consume(index.clone()));
+        let call = self.clone_call(src_elem, dest_elem, elem_ty, step, cleanup);
+        self.terminate(body, TerminatorKind::Goto { target: call });
+
+        let entry = self.zero_index_block(&index, header, false);
+        self.terminate(START_BLOCK, TerminatorKind::Goto { target: entry });
+    }
+}
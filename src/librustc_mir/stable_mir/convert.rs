@@ -0,0 +1,514 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion from the internal MIR to the types of `stable_mir`.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr as mir;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::fnv::FnvHashMap;
+use rustc_data_structures::indexed_vec::Idx;
+use syntax_pos;
+
+use super::*;
+
+/// Converts MIR bodies on demand and maps the items and types they mention
+/// to opaque ids. Ids are handed out in the order items and types are first
+/// seen, and stay valid for as long as the `Tables` lives.
+pub struct Tables<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    items: Vec<DefId>,
+    item_ids: FnvHashMap<DefId, ItemId>,
+    types: Vec<Ty<'tcx>>,
+    ty_ids: FnvHashMap<Ty<'tcx>, TyId>,
+}
+
+impl<'a, 'tcx> Tables<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Tables<'a, 'tcx> {
+        Tables {
+            tcx: tcx,
+            items: vec![],
+            item_ids: FnvHashMap(),
+            types: vec![],
+            ty_ids: FnvHashMap(),
+        }
+    }
+
+    /// Converts the MIR of a function or constant defined in this crate,
+    /// if it has any.
+    pub fn local_body(&mut self, mir_map: &MirMap<'tcx>, def_id: DefId) -> Option<Body> {
+        mir_map.map.get(&def_id).map(|mir| self.body(mir))
+    }
+
+    /// Converts the MIR of an item defined in another crate, if its MIR
+    /// was encoded in the crate metadata.
+    pub fn extern_body(&mut self, def_id: DefId) -> Option<Body> {
+        let tcx = self.tcx;
+        tcx.sess.cstore.maybe_get_item_mir(tcx, def_id).map(|mir| self.body(&mir))
+    }
+
+    pub fn body(&mut self, mir: &mir::Mir<'tcx>) -> Body {
+        let return_ty = match mir.return_ty {
+            ty::FnOutput::FnConverging(ty) => Some(self.ty_id(ty)),
+            ty::FnOutput::FnDiverging => None,
+        };
+
+        let mut locals = vec![];
+        for arg in &mir.arg_decls {
+            locals.push(LocalDecl {
+                ty: self.ty_id(arg.ty),
+                mutable: false,
+                name: Some(arg.debug_name.to_string()),
+            });
+        }
+        for var in &mir.var_decls {
+            locals.push(LocalDecl {
+                ty: self.ty_id(var.ty),
+                mutable: var.mutability == mir::Mutability::Mut,
+                name: Some(var.name.to_string()),
+            });
+        }
+        for temp in &mir.temp_decls {
+            locals.push(LocalDecl {
+                ty: self.ty_id(temp.ty),
+                mutable: true,
+                name: None,
+            });
+        }
+
+        let blocks = mir.basic_blocks().iter().map(|data| {
+            BasicBlock {
+                statements: data.statements.iter().map(|statement| {
+                    self.statement(mir, statement)
+                }).collect(),
+                terminator: self.terminator(mir, data.terminator()),
+                is_cleanup: data.is_cleanup,
+            }
+        }).collect();
+
+        Body {
+            return_ty: return_ty,
+            locals: locals,
+            arg_count: mir.arg_decls.len(),
+            blocks: blocks,
+            promoted: mir.promoted.iter().map(|promoted| self.body(promoted)).collect(),
+            span: self.span(mir.span),
+        }
+    }
+
+    pub fn item_id(&mut self, def_id: DefId) -> ItemId {
+        if let Some(&id) = self.item_ids.get(&def_id) {
+            return id;
+        }
+        let id = ItemId(self.items.len() as u32);
+        self.items.push(def_id);
+        self.item_ids.insert(def_id, id);
+        id
+    }
+
+    pub fn def_id(&self, item: ItemId) -> DefId {
+        self.items[item.0 as usize]
+    }
+
+    /// The path of the item, e.g. `std::mem::swap`.
+    pub fn item_path(&self, item: ItemId) -> String {
+        self.tcx.item_path_str(self.def_id(item))
+    }
+
+    pub fn ty_id(&mut self, ty: Ty<'tcx>) -> TyId {
+        if let Some(&id) = self.ty_ids.get(&ty) {
+            return id;
+        }
+        let id = TyId(self.types.len() as u32);
+        self.types.push(ty);
+        self.ty_ids.insert(ty, id);
+        id
+    }
+
+    pub fn ty(&self, id: TyId) -> Ty<'tcx> {
+        self.types[id.0 as usize]
+    }
+
+    /// The type as the compiler would print it in a diagnostic.
+    pub fn ty_to_string(&self, id: TyId) -> String {
+        self.ty(id).to_string()
+    }
+
+    fn statement(&mut self, mir: &mir::Mir<'tcx>, statement: &mir::Statement<'tcx>)
+                 -> Statement {
+        let kind = match statement.kind {
+            mir::StatementKind::Assign(ref lvalue, ref rvalue) => {
+                StatementKind::Assign(self.place(mir, lvalue), self.rvalue(mir, rvalue))
+            }
+            mir::StatementKind::SetDiscriminant { ref lvalue, variant_index } => {
+                StatementKind::SetDiscriminant {
+                    place: self.place(mir, lvalue),
+                    variant_index: variant_index,
+                }
+            }
+            mir::StatementKind::Coverage { index, .. } => StatementKind::Coverage { index: index },
+            mir::StatementKind::SanitizerCheck { kind, ref lvalue } => {
+                let kind = match kind {
+                    mir::SanitizerCheckKind::Load => SanitizerCheckKind::Load,
+                    mir::SanitizerCheckKind::Store => SanitizerCheckKind::Store,
+                };
+                StatementKind::SanitizerCheck { kind: kind, place: self.place(mir, lvalue) }
+            }
+            mir::StatementKind::Subtype { ref lvalue, ty } => {
+                StatementKind::Subtype { place: self.place(mir, lvalue), ty: self.ty_id(ty) }
+            }
+            mir::StatementKind::Retag { kind, ref lvalue } => {
+                let kind = match kind {
+                    mir::RetagKind::FnEntry => RetagKind::FnEntry,
+                    mir::RetagKind::Ref => RetagKind::Ref,
+                    mir::RetagKind::Reborrow => RetagKind::Reborrow,
+                };
+                StatementKind::Retag { kind: kind, place: self.place(mir, lvalue) }
+            }
+            mir::StatementKind::Atomic { op, ordering, ref dest, ref ptr, ref value } => {
+                let op = match op {
+                    mir::AtomicOp::Load => AtomicOp::Load,
+                    mir::AtomicOp::Store => AtomicOp::Store,
+                    mir::AtomicOp::Xchg => AtomicOp::Xchg,
+                    mir::AtomicOp::Add => AtomicOp::Add,
+                    mir::AtomicOp::Sub => AtomicOp::Sub,
+                    mir::AtomicOp::And => AtomicOp::And,
+                    mir::AtomicOp::Nand => AtomicOp::Nand,
+                    mir::AtomicOp::Or => AtomicOp::Or,
+                    mir::AtomicOp::Xor => AtomicOp::Xor,
+                    mir::AtomicOp::Max => AtomicOp::Max,
+                    mir::AtomicOp::Min => AtomicOp::Min,
+                    mir::AtomicOp::UMax => AtomicOp::UMax,
+                    mir::AtomicOp::UMin => AtomicOp::UMin,
+                };
+                let ordering = match ordering {
+                    mir::AtomicOrdering::Relaxed => AtomicOrdering::Relaxed,
+                    mir::AtomicOrdering::Acquire => AtomicOrdering::Acquire,
+                    mir::AtomicOrdering::Release => AtomicOrdering::Release,
+                    mir::AtomicOrdering::AcqRel => AtomicOrdering::AcqRel,
+                    mir::AtomicOrdering::SeqCst => AtomicOrdering::SeqCst,
+                };
+                let dest = match *dest {
+                    Some(ref dest) => Some(self.place(mir, dest)),
+                    None => None,
+                };
+                let value = match *value {
+                    Some(ref value) => Some(self.operand(mir, value)),
+                    None => None,
+                };
+                StatementKind::Atomic {
+                    op: op,
+                    ordering: ordering,
+                    dest: dest,
+                    ptr: self.operand(mir, ptr),
+                    value: value,
+                }
+            }
+            mir::StatementKind::Probe { kind, function_id } => {
+                let kind = match kind {
+                    mir::ProbeKind::Entry => ProbeKind::Entry,
+                    mir::ProbeKind::Return => ProbeKind::Return,
+                    mir::ProbeKind::Unwind => ProbeKind::Unwind,
+                };
+                StatementKind::Probe { kind: kind, function_id: function_id }
+            }
+        };
+        Statement {
+            kind: kind,
+            span: self.span(statement.source_info.span),
+        }
+    }
+
+    fn terminator(&mut self, mir: &mir::Mir<'tcx>, terminator: &mir::Terminator<'tcx>)
+                  -> Terminator {
+        fn block(block: mir::BasicBlock) -> BasicBlockIdx {
+            block.index()
+        }
+
+        let kind = match terminator.kind {
+            mir::TerminatorKind::Goto { target } => {
+                TerminatorKind::Goto { target: block(target) }
+            }
+            mir::TerminatorKind::If { ref cond, targets: (then_target, else_target) } => {
+                TerminatorKind::If {
+                    cond: self.operand(mir, cond),
+                    then_target: block(then_target),
+                    else_target: block(else_target),
+                }
+            }
+            mir::TerminatorKind::Switch { ref discr, ref targets, .. } => {
+                TerminatorKind::Switch {
+                    discr: self.place(mir, discr),
+                    targets: targets.iter().cloned().map(block).collect(),
+                }
+            }
+            mir::TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => {
+                TerminatorKind::SwitchInt {
+                    discr: self.place(mir, discr),
+                    values: values.iter().map(const_val_to_string).collect(),
+                    targets: targets.iter().cloned().map(block).collect(),
+                }
+            }
+            mir::TerminatorKind::Resume => TerminatorKind::Resume,
+            mir::TerminatorKind::Abort => TerminatorKind::Abort,
+            mir::TerminatorKind::Return => TerminatorKind::Return,
+            mir::TerminatorKind::Unreachable => TerminatorKind::Unreachable,
+            mir::TerminatorKind::Drop { ref location, target, unwind } => {
+                TerminatorKind::Drop {
+                    place: self.place(mir, location),
+                    target: block(target),
+                    unwind: unwind.map(block),
+                }
+            }
+            mir::TerminatorKind::DropAndReplace { ref location, ref value, target, unwind } => {
+                TerminatorKind::DropAndReplace {
+                    place: self.place(mir, location),
+                    value: self.operand(mir, value),
+                    target: block(target),
+                    unwind: unwind.map(block),
+                }
+            }
+            mir::TerminatorKind::Call { ref func, ref args, ref destination, cleanup } => {
+                TerminatorKind::Call {
+                    func: self.operand(mir, func),
+                    args: args.iter().map(|arg| self.operand(mir, arg)).collect(),
+                    destination: destination.as_ref().map(|&(ref lvalue, target)| {
+                        (self.place(mir, lvalue), block(target))
+                    }),
+                    cleanup: cleanup.map(block),
+                }
+            }
+            mir::TerminatorKind::Assert { ref cond, expected, ref msg, target, cleanup } => {
+                let msg = match *msg {
+                    mir::AssertMessage::BoundsCheck { ref len, ref index } => {
+                        AssertMessage::BoundsCheck {
+                            len: self.operand(mir, len),
+                            index: self.operand(mir, index),
+                        }
+                    }
+                    mir::AssertMessage::Math { ref err, ref operands } => {
+                        AssertMessage::Math {
+                            description: err.description().to_string(),
+                            operands: operands.iter().map(|op| self.operand(mir, op)).collect(),
+                        }
+                    }
+                    mir::AssertMessage::Misaligned => AssertMessage::Misaligned,
+                    mir::AssertMessage::NullPointer => AssertMessage::NullPointer,
+                };
+                TerminatorKind::Assert {
+                    cond: self.operand(mir, cond),
+                    expected: expected,
+                    msg: msg,
+                    target: block(target),
+                    cleanup: cleanup.map(block),
+                }
+            }
+        };
+        Terminator {
+            kind: kind,
+            span: self.span(terminator.source_info.span),
+        }
+    }
+
+    fn rvalue(&mut self, mir: &mir::Mir<'tcx>, rvalue: &mir::Rvalue<'tcx>) -> Rvalue {
+        match *rvalue {
+            mir::Rvalue::Use(ref operand) => Rvalue::Use(self.operand(mir, operand)),
+            mir::Rvalue::Repeat(ref operand, ref count) => {
+                let count = count.value.as_u64(self.tcx.sess.target.uint_type);
+                Rvalue::Repeat(self.operand(mir, operand), count)
+            }
+            mir::Rvalue::Ref(_, kind, ref lvalue) => {
+                let kind = match kind {
+                    mir::BorrowKind::Shared => BorrowKind::Shared,
+                    mir::BorrowKind::Unique => BorrowKind::Unique,
+                    mir::BorrowKind::Mut => BorrowKind::Mut,
+                };
+                Rvalue::Ref(kind, self.place(mir, lvalue))
+            }
+            mir::Rvalue::RawPtr(mutbl, ref lvalue) => Rvalue::RawPtr {
+                mutable: mutbl == mir::Mutability::Mut,
+                place: self.place(mir, lvalue),
+            },
+            mir::Rvalue::Len(ref lvalue) => Rvalue::Len(self.place(mir, lvalue)),
+            mir::Rvalue::Cast(kind, ref operand, ty) => {
+                let kind = match kind {
+                    mir::CastKind::Misc => CastKind::Misc,
+                    mir::CastKind::PointerExposeAddress => CastKind::PointerExposeAddress,
+                    mir::CastKind::PointerFromExposedAddress => {
+                        CastKind::PointerFromExposedAddress
+                    }
+                    mir::CastKind::IntTruncate => CastKind::IntTruncate,
+                    mir::CastKind::ReifyFnPointer => CastKind::ReifyFnPointer,
+                    mir::CastKind::UnsafeFnPointer => CastKind::UnsafeFnPointer,
+                    mir::CastKind::Unsize => CastKind::Unsize,
+                };
+                Rvalue::Cast(kind, self.operand(mir, operand), self.ty_id(ty))
+            }
+            mir::Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                Rvalue::BinaryOp(bin_op(op), self.operand(mir, lhs), self.operand(mir, rhs))
+            }
+            mir::Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => {
+                Rvalue::CheckedBinaryOp(bin_op(op),
+                                        self.operand(mir, lhs),
+                                        self.operand(mir, rhs))
+            }
+            mir::Rvalue::UnaryOp(op, ref operand) => {
+                let op = match op {
+                    mir::UnOp::Not => UnOp::Not,
+                    mir::UnOp::Neg => UnOp::Neg,
+                };
+                Rvalue::UnaryOp(op, self.operand(mir, operand))
+            }
+            mir::Rvalue::Box(ty) => Rvalue::Box(self.ty_id(ty)),
+            mir::Rvalue::Aggregate(ref kind, ref operands) => {
+                let kind = match *kind {
+                    mir::AggregateKind::Vec => AggregateKind::Array,
+                    mir::AggregateKind::Tuple => AggregateKind::Tuple,
+                    mir::AggregateKind::Adt(adt_def, variant, _) => {
+                        AggregateKind::Adt(self.item_id(adt_def.did), variant)
+                    }
+                    mir::AggregateKind::Closure(def_id, _) => {
+                        AggregateKind::Closure(self.item_id(def_id))
+                    }
+                };
+                let operands = operands.iter().map(|operand| self.operand(mir, operand));
+                Rvalue::Aggregate(kind, operands.collect())
+            }
+            mir::Rvalue::Simd(op, ref operands) => {
+                let op = match op {
+                    mir::SimdOp::Extract => SimdOp::Extract,
+                    mir::SimdOp::Insert => SimdOp::Insert,
+                    mir::SimdOp::Shuffle(ty) => SimdOp::Shuffle(self.ty_id(ty)),
+                };
+                let operands = operands.iter().map(|operand| self.operand(mir, operand));
+                Rvalue::Simd(op, operands.collect())
+            }
+            mir::Rvalue::InlineAsm { ref outputs, ref inputs, .. } => {
+                Rvalue::InlineAsm {
+                    outputs: outputs.iter().map(|output| self.place(mir, output)).collect(),
+                    inputs: inputs.iter().map(|input| self.operand(mir, input)).collect(),
+                }
+            }
+        }
+    }
+
+    fn operand(&mut self, mir: &mir::Mir<'tcx>, operand: &mir::Operand<'tcx>) -> Operand {
+        match *operand {
+            mir::Operand::Consume(ref lvalue) => Operand::Consume(self.place(mir, lvalue)),
+            mir::Operand::Constant(ref constant) => {
+                let literal = match constant.literal {
+                    mir::Literal::Item { def_id, .. } => Literal::Item(self.item_id(def_id)),
+                    mir::Literal::Value { ref value } => {
+                        Literal::Value(const_val_to_string(value))
+                    }
+                    mir::Literal::Promoted { index } => Literal::Promoted(index.index()),
+                };
+                Operand::Constant(Constant {
+                    ty: self.ty_id(constant.ty),
+                    literal: literal,
+                })
+            }
+        }
+    }
+
+    fn place(&mut self, mir: &mir::Mir<'tcx>, lvalue: &mir::Lvalue<'tcx>) -> Place {
+        let (args, vars) = (mir.arg_decls.len(), mir.var_decls.len());
+        match *lvalue {
+            mir::Lvalue::ReturnPointer => Place {
+                base: PlaceBase::ReturnPlace,
+                projection: vec![],
+            },
+            mir::Lvalue::Arg(arg) => Place {
+                base: PlaceBase::Local(arg.index()),
+                projection: vec![],
+            },
+            mir::Lvalue::Var(var) => Place {
+                base: PlaceBase::Local(args + var.index()),
+                projection: vec![],
+            },
+            mir::Lvalue::Temp(temp) => Place {
+                base: PlaceBase::Local(args + vars + temp.index()),
+                projection: vec![],
+            },
+            mir::Lvalue::Static(def_id) => Place {
+                base: PlaceBase::Static(self.item_id(def_id)),
+                projection: vec![],
+            },
+            mir::Lvalue::Projection(ref proj) => {
+                let mut place = self.place(mir, &proj.base);
+                let elem = match proj.elem {
+                    mir::ProjectionElem::Deref => ProjectionElem::Deref,
+                    mir::ProjectionElem::Field(field, ty) => {
+                        ProjectionElem::Field(field.index(), self.ty_id(ty))
+                    }
+                    mir::ProjectionElem::Index(ref index) => {
+                        ProjectionElem::Index(self.operand(mir, index))
+                    }
+                    mir::ProjectionElem::ConstantIndex { offset, min_length, from_end } => {
+                        ProjectionElem::ConstantIndex {
+                            offset: offset,
+                            min_length: min_length,
+                            from_end: from_end,
+                        }
+                    }
+                    mir::ProjectionElem::Subslice { from, to } => {
+                        ProjectionElem::Subslice { from: from, to: to }
+                    }
+                    mir::ProjectionElem::Downcast(_, variant) => {
+                        ProjectionElem::Downcast(variant)
+                    }
+                };
+                place.projection.push(elem);
+                place
+            }
+        }
+    }
+
+    fn span(&self, span: syntax_pos::Span) -> Span {
+        let codemap = self.tcx.sess.codemap();
+        let start = codemap.lookup_char_pos(span.lo);
+        let end = codemap.lookup_char_pos(span.hi);
+        Span {
+            file_name: start.file.name.clone(),
+            line_start: start.line,
+            line_end: end.line,
+            column_start: start.col.0 + 1,
+            column_end: end.col.0 + 1,
+        }
+    }
+}
+
+fn bin_op(op: mir::BinOp) -> BinOp {
+    match op {
+        mir::BinOp::Add => BinOp::Add,
+        mir::BinOp::Sub => BinOp::Sub,
+        mir::BinOp::Mul => BinOp::Mul,
+        mir::BinOp::Div => BinOp::Div,
+        mir::BinOp::Rem => BinOp::Rem,
+        mir::BinOp::BitXor => BinOp::BitXor,
+        mir::BinOp::BitAnd => BinOp::BitAnd,
+        mir::BinOp::BitOr => BinOp::BitOr,
+        mir::BinOp::Shl => BinOp::Shl,
+        mir::BinOp::Shr => BinOp::Shr,
+        mir::BinOp::Eq => BinOp::Eq,
+        mir::BinOp::Lt => BinOp::Lt,
+        mir::BinOp::Le => BinOp::Le,
+        mir::BinOp::Ne => BinOp::Ne,
+        mir::BinOp::Ge => BinOp::Ge,
+        mir::BinOp::Gt => BinOp::Gt,
+    }
+}
+
+fn const_val_to_string(value: &::rustc::middle::const_val::ConstVal) -> String {
+    let mut s = String::new();
+    mir::fmt_const_val(&mut s, value).unwrap();
+    s
+}
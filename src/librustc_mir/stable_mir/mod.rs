@@ -0,0 +1,526 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A stable view of MIR for external tools such as verifiers and model
+//! checkers.
+//!
+//! The internal MIR data structures borrow from the type context and change
+//! whenever the compiler needs them to. The types in this module are owned,
+//! carry no lifetimes, and refer to items and types through opaque ids that
+//! a `Tables` hands out, so a tool written against them only has to follow
+//! `VERSION` rather than the compiler itself. Bodies are converted on demand
+//! with `Tables::body`; anything that is not modelled here (regions, the
+//! substitutions of an item) is simply left out.
+
+pub use self::convert::Tables;
+
+mod convert;
+
+/// Bumped at most once per release, if the types in this module changed
+/// since the previous release in a way that could break a tool using them.
+pub const VERSION: u32 = 1;
+
+/// An opaque reference to an item (a function, static, constant, ADT or
+/// closure), only meaningful to the `Tables` that created it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemId(u32);
+
+/// An opaque reference to a type, only meaningful to the `Tables` that
+/// created it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TyId(u32);
+
+/// The index of a local in `Body::locals`.
+pub type Local = usize;
+
+/// The index of a basic block in `Body::blocks`.
+pub type BasicBlockIdx = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub file_name: String,
+    /// 1-based.
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 1-based, character offset.
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Body {
+    /// `None` if the function diverges.
+    pub return_ty: Option<TyId>,
+    /// The arguments come first, followed by the user variables and then
+    /// the temporaries.
+    pub locals: Vec<LocalDecl>,
+    pub arg_count: usize,
+    /// The entry block is always the first one.
+    pub blocks: Vec<BasicBlock>,
+    /// The bodies of the rvalues promoted out of this one, referred to by
+    /// `Literal::Promoted`.
+    pub promoted: Vec<Body>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalDecl {
+    pub ty: TyId,
+    pub mutable: bool,
+    /// The name given to the local in the source, if any.
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BasicBlock {
+    pub statements: Vec<Statement>,
+    pub terminator: Terminator,
+    /// Whether the block is only reached while unwinding.
+    pub is_cleanup: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum StatementKind {
+    Assign(Place, Rvalue),
+    SetDiscriminant { place: Place, variant_index: usize },
+    /// Increment the `index`th coverage counter of the function.
+    Coverage { index: u32 },
+    /// Check the access to `place` with the sanitizer runtime.
+    SanitizerCheck { kind: SanitizerCheckKind, place: Place },
+    /// The value just written to `place` has type `ty`, a subtype of the
+    /// type of `place`.
+    Subtype { place: Place, ty: TyId },
+    /// The reference in `place` has just been created, or passed to the
+    /// function.
+    Retag { kind: RetagKind, place: Place },
+    /// An atomic access to the integer `ptr` points to. Loads and
+    /// read-modify-writes write the value read to `dest`; stores and
+    /// read-modify-writes use `value`.
+    Atomic {
+        op: AtomicOp,
+        ordering: AtomicOrdering,
+        dest: Option<Place>,
+        ptr: Operand,
+        value: Option<Operand>,
+    },
+    /// Call the probe function of `-Z mir-instrument-calls`.
+    Probe { kind: ProbeKind, function_id: u64 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AtomicOp {
+    Load,
+    Store,
+    Xchg,
+    Add,
+    Sub,
+    And,
+    Nand,
+    Or,
+    Xor,
+    Max,
+    Min,
+    UMax,
+    UMin,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AtomicOrdering {
+    Relaxed,
+    Acquire,
+    Release,
+    AcqRel,
+    SeqCst,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SanitizerCheckKind {
+    Load,
+    Store,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProbeKind {
+    Entry,
+    Return,
+    Unwind,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RetagKind {
+    FnEntry,
+    Ref,
+    Reborrow,
+}
+
+#[derive(Clone, Debug)]
+pub struct Terminator {
+    pub kind: TerminatorKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum TerminatorKind {
+    Goto { target: BasicBlockIdx },
+    If { cond: Operand, then_target: BasicBlockIdx, else_target: BasicBlockIdx },
+    /// Jump to `targets[i]` if `discr` holds the `i`th variant of its enum.
+    Switch { discr: Place, targets: Vec<BasicBlockIdx> },
+    /// Jump to `targets[i]` if `discr` equals `values[i]`, or to the last
+    /// target if it equals none of them. Values are written the way the
+    /// MIR pretty printer writes them.
+    SwitchInt { discr: Place, values: Vec<String>, targets: Vec<BasicBlockIdx> },
+    Resume,
+    /// Abort the process at the end of a landing pad instead of resuming.
+    Abort,
+    Return,
+    Unreachable,
+    Drop { place: Place, target: BasicBlockIdx, unwind: Option<BasicBlockIdx> },
+    DropAndReplace {
+        place: Place,
+        value: Operand,
+        target: BasicBlockIdx,
+        unwind: Option<BasicBlockIdx>,
+    },
+    Call {
+        func: Operand,
+        args: Vec<Operand>,
+        /// `None` if the call diverges.
+        destination: Option<(Place, BasicBlockIdx)>,
+        cleanup: Option<BasicBlockIdx>,
+    },
+    Assert {
+        cond: Operand,
+        expected: bool,
+        msg: AssertMessage,
+        target: BasicBlockIdx,
+        cleanup: Option<BasicBlockIdx>,
+    },
+}
+
+impl TerminatorKind {
+    pub fn successors(&self) -> Vec<BasicBlockIdx> {
+        use self::TerminatorKind::*;
+        match *self {
+            Goto { target } => vec![target],
+            If { then_target, else_target, .. } => vec![then_target, else_target],
+            Switch { ref targets, .. } |
+            SwitchInt { ref targets, .. } => targets.clone(),
+            Resume | Abort | Return | Unreachable => vec![],
+            Drop { target, unwind, .. } |
+            DropAndReplace { target, unwind, .. } |
+            Assert { target, cleanup: unwind, .. } => {
+                Some(target).into_iter().chain(unwind).collect()
+            }
+            Call { ref destination, cleanup, .. } => {
+                destination.iter().map(|&(_, target)| target).chain(cleanup).collect()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum AssertMessage {
+    BoundsCheck { len: Operand, index: Operand },
+    /// An arithmetic overflow or division by zero, described in words,
+    /// with the operands of the operation.
+    Math { description: String, operands: Vec<Operand> },
+    Misaligned,
+    NullPointer,
+}
+
+#[derive(Clone, Debug)]
+pub struct Place {
+    pub base: PlaceBase,
+    /// Applied to the base in order.
+    pub projection: Vec<ProjectionElem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaceBase {
+    /// Where the return value is stored.
+    ReturnPlace,
+    Local(Local),
+    Static(ItemId),
+}
+
+#[derive(Clone, Debug)]
+pub enum ProjectionElem {
+    Deref,
+    Field(usize, TyId),
+    Index(Operand),
+    ConstantIndex { offset: u32, min_length: u32, from_end: bool },
+    Subslice { from: u32, to: u32 },
+    /// Treat an enum as its `usize`th variant.
+    Downcast(usize),
+}
+
+#[derive(Clone, Debug)]
+pub enum Operand {
+    Consume(Place),
+    Constant(Constant),
+}
+
+#[derive(Clone, Debug)]
+pub struct Constant {
+    pub ty: TyId,
+    pub literal: Literal,
+}
+
+#[derive(Clone, Debug)]
+pub enum Literal {
+    /// A function or constant item.
+    Item(ItemId),
+    /// A value written the way the MIR pretty printer writes it.
+    Value(String),
+    /// An index into `Body::promoted`.
+    Promoted(usize),
+}
+
+#[derive(Clone, Debug)]
+pub enum Rvalue {
+    Use(Operand),
+    /// `[operand; count]`
+    Repeat(Operand, u64),
+    Ref(BorrowKind, Place),
+    /// The address of `place` as a raw pointer.
+    RawPtr { mutable: bool, place: Place },
+    Len(Place),
+    Cast(CastKind, Operand, TyId),
+    BinaryOp(BinOp, Operand, Operand),
+    /// Produces a tuple of the result and an overflow flag.
+    CheckedBinaryOp(BinOp, Operand, Operand),
+    UnaryOp(UnOp, Operand),
+    Box(TyId),
+    Aggregate(AggregateKind, Vec<Operand>),
+    Simd(SimdOp, Vec<Operand>),
+    InlineAsm { outputs: Vec<Place>, inputs: Vec<Operand> },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AggregateKind {
+    Array,
+    Tuple,
+    /// An ADT and the index of the variant being built.
+    Adt(ItemId, usize),
+    Closure(ItemId),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SimdOp {
+    Extract,
+    Insert,
+    /// A shuffle producing a vector of the given type.
+    Shuffle(TyId),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BorrowKind {
+    Shared,
+    /// Immutable but not aliasable; only used for closure captures.
+    Unique,
+    Mut,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CastKind {
+    Misc,
+    PointerExposeAddress,
+    PointerFromExposedAddress,
+    IntTruncate,
+    ReifyFnPointer,
+    UnsafeFnPointer,
+    Unsize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add, Sub, Mul, Div, Rem,
+    BitXor, BitAnd, BitOr, Shl, Shr,
+    Eq, Lt, Le, Ne, Ge, Gt,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnOp {
+    Not,
+    Neg,
+}
+
+/// Walks a `Body`. Every `visit_` method defaults to the matching `super_`
+/// method, which visits the components of its argument; override the
+/// former and call the latter to keep walking.
+pub trait Visitor {
+    fn visit_body(&mut self, body: &Body) {
+        self.super_body(body);
+    }
+
+    fn visit_basic_block(&mut self, block: BasicBlockIdx, data: &BasicBlock) {
+        self.super_basic_block(block, data);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        self.super_statement(statement);
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator) {
+        self.super_terminator(terminator);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue) {
+        self.super_rvalue(rvalue);
+    }
+
+    fn visit_operand(&mut self, operand: &Operand) {
+        self.super_operand(operand);
+    }
+
+    fn visit_place(&mut self, place: &Place) {
+        self.super_place(place);
+    }
+
+    fn visit_constant(&mut self, _constant: &Constant) {
+    }
+
+    fn super_body(&mut self, body: &Body) {
+        for (block, data) in body.blocks.iter().enumerate() {
+            self.visit_basic_block(block, data);
+        }
+    }
+
+    fn super_basic_block(&mut self, _block: BasicBlockIdx, data: &BasicBlock) {
+        for statement in &data.statements {
+            self.visit_statement(statement);
+        }
+        self.visit_terminator(&data.terminator);
+    }
+
+    fn super_statement(&mut self, statement: &Statement) {
+        match statement.kind {
+            StatementKind::Assign(ref place, ref rvalue) => {
+                self.visit_place(place);
+                self.visit_rvalue(rvalue);
+            }
+            StatementKind::SetDiscriminant { ref place, .. } => {
+                self.visit_place(place);
+            }
+            StatementKind::Coverage { .. } |
+            StatementKind::Probe { .. } => {}
+            StatementKind::SanitizerCheck { ref place, .. } |
+            StatementKind::Subtype { ref place, .. } |
+            StatementKind::Retag { ref place, .. } => {
+                self.visit_place(place);
+            }
+            StatementKind::Atomic { ref dest, ref ptr, ref value, .. } => {
+                self.visit_operand(ptr);
+                if let Some(ref value) = *value {
+                    self.visit_operand(value);
+                }
+                if let Some(ref dest) = *dest {
+                    self.visit_place(dest);
+                }
+            }
+        }
+    }
+
+    fn super_terminator(&mut self, terminator: &Terminator) {
+        match terminator.kind {
+            TerminatorKind::Goto { .. } |
+            TerminatorKind::Resume |
+            TerminatorKind::Abort |
+            TerminatorKind::Return |
+            TerminatorKind::Unreachable => {}
+            TerminatorKind::If { ref cond, .. } => self.visit_operand(cond),
+            TerminatorKind::Switch { ref discr, .. } |
+            TerminatorKind::SwitchInt { ref discr, .. } => self.visit_place(discr),
+            TerminatorKind::Drop { ref place, .. } => self.visit_place(place),
+            TerminatorKind::DropAndReplace { ref place, ref value, .. } => {
+                self.visit_place(place);
+                self.visit_operand(value);
+            }
+            TerminatorKind::Call { ref func, ref args, ref destination, .. } => {
+                self.visit_operand(func);
+                for arg in args {
+                    self.visit_operand(arg);
+                }
+                if let Some((ref place, _)) = *destination {
+                    self.visit_place(place);
+                }
+            }
+            TerminatorKind::Assert { ref cond, ref msg, .. } => {
+                self.visit_operand(cond);
+                match *msg {
+                    AssertMessage::BoundsCheck { ref len, ref index } => {
+                        self.visit_operand(len);
+                        self.visit_operand(index);
+                    }
+                    AssertMessage::Math { ref operands, .. } => {
+                        for operand in operands {
+                            self.visit_operand(operand);
+                        }
+                    }
+                    AssertMessage::Misaligned |
+                    AssertMessage::NullPointer => {}
+                }
+            }
+        }
+    }
+
+    fn super_rvalue(&mut self, rvalue: &Rvalue) {
+        match *rvalue {
+            Rvalue::Use(ref operand) |
+            Rvalue::Repeat(ref operand, _) |
+            Rvalue::Cast(_, ref operand, _) |
+            Rvalue::UnaryOp(_, ref operand) => self.visit_operand(operand),
+            Rvalue::Ref(_, ref place) |
+            Rvalue::RawPtr { ref place, .. } |
+            Rvalue::Len(ref place) => self.visit_place(place),
+            Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+            Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => {
+                self.visit_operand(lhs);
+                self.visit_operand(rhs);
+            }
+            Rvalue::Box(_) => {}
+            Rvalue::Aggregate(_, ref operands) |
+            Rvalue::Simd(_, ref operands) => {
+                for operand in operands {
+                    self.visit_operand(operand);
+                }
+            }
+            Rvalue::InlineAsm { ref outputs, ref inputs } => {
+                for output in outputs {
+                    self.visit_place(output);
+                }
+                for input in inputs {
+                    self.visit_operand(input);
+                }
+            }
+        }
+    }
+
+    fn super_operand(&mut self, operand: &Operand) {
+        match *operand {
+            Operand::Consume(ref place) => self.visit_place(place),
+            Operand::Constant(ref constant) => self.visit_constant(constant),
+        }
+    }
+
+    fn super_place(&mut self, place: &Place) {
+        for elem in &place.projection {
+            if let ProjectionElem::Index(ref index) = *elem {
+                self.visit_operand(index);
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `MirBuilder` builds small MIR bodies by hand, for tests of passes:
+//!
+//! ```rust,ignore
+//! let mut builder = MirBuilder::new(tcx.types.i32);
+//! let x = builder.var("x", tcx.types.i32);
+//! let exit = builder.block()
+//!                   .assign(Lvalue::ReturnPointer, Rvalue::Use(Operand::Consume(x.clone())))
+//!                   .ret();
+//! builder.start()
+//!        .assign(x, Rvalue::Use(constant(tcx.types.i32, value)))
+//!        .goto(exit);
+//! let mir = builder.finish();
+//! ```
+//!
+//! Blocks are numbered in the order they are created, starting with
+//! `START_BLOCK`, which `MirBuilder::new` creates. A block that jumps to a
+//! block created after it can reserve that block with `new_block`, and
+//! fill it in later with `at`. All statements and terminators have the
+//! same dummy span, in the argument scope.
+
+use rustc::mir::repr::*;
+use rustc::middle::const_val::ConstVal;
+use rustc::ty::{self, Ty};
+use rustc_data_structures::indexed_vec::IndexVec;
+use syntax::parse::token::{self, keywords};
+use syntax_pos::DUMMY_SP;
+
+pub struct MirBuilder<'tcx> {
+    source_info: SourceInfo,
+    basic_blocks: IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+    var_decls: IndexVec<Var, VarDecl<'tcx>>,
+    arg_decls: IndexVec<Arg, ArgDecl<'tcx>>,
+    temp_decls: IndexVec<Temp, TempDecl<'tcx>>,
+    return_ty: Ty<'tcx>,
+}
+
+impl<'tcx> MirBuilder<'tcx> {
+    /// Starts a body returning `return_ty`, with an empty start block.
+    pub fn new(return_ty: Ty<'tcx>) -> Self {
+        let mut builder = MirBuilder {
+            source_info: SourceInfo {
+                span: DUMMY_SP,
+                scope: ARGUMENT_VISIBILITY_SCOPE
+            },
+            basic_blocks: IndexVec::new(),
+            var_decls: IndexVec::new(),
+            arg_decls: IndexVec::new(),
+            temp_decls: IndexVec::new(),
+            return_ty: return_ty,
+        };
+        assert_eq!(builder.new_block(), START_BLOCK);
+        builder
+    }
+
+    pub fn arg(&mut self, ty: Ty<'tcx>) -> Lvalue<'tcx> {
+        Lvalue::Arg(self.arg_decls.push(ArgDecl {
+            ty: ty,
+            spread: false,
+            debug_name: keywords::Invalid.name(),
+        }))
+    }
+
+    pub fn var(&mut self, name: &str, ty: Ty<'tcx>) -> Lvalue<'tcx> {
+        let source_info = self.source_info;
+        Lvalue::Var(self.var_decls.push(VarDecl {
+            mutability: Mutability::Mut,
+            name: token::intern(name),
+            ty: ty,
+            source_info: source_info,
+        }))
+    }
+
+    pub fn temp(&mut self, ty: Ty<'tcx>) -> Lvalue<'tcx> {
+        Lvalue::Temp(self.temp_decls.push(TempDecl { ty: ty, span: self.source_info.span }))
+    }
+
+    /// Creates an empty block, to be filled in with `at`.
+    pub fn new_block(&mut self) -> BasicBlock {
+        self.basic_blocks.push(BasicBlockData::new(None))
+    }
+
+    /// Fills in the start block.
+    pub fn start<'a>(&'a mut self) -> BlockBuilder<'a, 'tcx> {
+        self.at(START_BLOCK)
+    }
+
+    /// Creates a block and fills it in.
+    pub fn block<'a>(&'a mut self) -> BlockBuilder<'a, 'tcx> {
+        let block = self.new_block();
+        self.at(block)
+    }
+
+    /// Fills in `block`, which must not have a terminator yet.
+    pub fn at<'a>(&'a mut self, block: BasicBlock) -> BlockBuilder<'a, 'tcx> {
+        assert!(self.basic_blocks[block].terminator.is_none(),
+                "MirBuilder: block {:?} already has a terminator", block);
+        BlockBuilder {
+            builder: self,
+            block: block,
+        }
+    }
+
+    /// Returns the body, once every block has a terminator.
+    pub fn finish(self) -> Mir<'tcx> {
+        for (block, data) in self.basic_blocks.iter_enumerated() {
+            assert!(data.terminator.is_some(),
+                    "MirBuilder: block {:?} has no terminator", block);
+        }
+        let span = self.source_info.span;
+        Mir::new(self.basic_blocks,
+                 Some(VisibilityScopeData {
+                     span: span,
+                     parent_scope: None
+                 }).into_iter().collect(),
+                 IndexVec::new(),
+                 ty::FnConverging(self.return_ty),
+                 self.var_decls,
+                 self.arg_decls,
+                 self.temp_decls,
+                 IndexVec::new(),
+                 span)
+    }
+}
+
+/// Adds statements to a block of a `MirBuilder`, until it is terminated.
+pub struct BlockBuilder<'a, 'tcx: 'a> {
+    builder: &'a mut MirBuilder<'tcx>,
+    block: BasicBlock,
+}
+
+impl<'a, 'tcx> BlockBuilder<'a, 'tcx> {
+    pub fn assign(self, lvalue: Lvalue<'tcx>, rvalue: Rvalue<'tcx>) -> Self {
+        self.statement(StatementKind::Assign(lvalue, rvalue))
+    }
+
+    pub fn statement(self, kind: StatementKind<'tcx>) -> Self {
+        let source_info = self.builder.source_info;
+        self.builder.basic_blocks[self.block].statements.push(Statement {
+            source_info: source_info,
+            kind: kind
+        });
+        self
+    }
+
+    /// Marks the block as a cleanup block.
+    pub fn cleanup(self) -> Self {
+        self.builder.basic_blocks[self.block].is_cleanup = true;
+        self
+    }
+
+    pub fn goto(self, target: BasicBlock) -> BasicBlock {
+        self.terminate(TerminatorKind::Goto { target: target })
+    }
+
+    pub fn branch(self, cond: Operand<'tcx>, then: BasicBlock, otherwise: BasicBlock)
+                  -> BasicBlock {
+        self.terminate(TerminatorKind::If { cond: cond, targets: (then, otherwise) })
+    }
+
+    pub fn ret(self) -> BasicBlock {
+        self.terminate(TerminatorKind::Return)
+    }
+
+    /// Ends the block with a terminator of `kind`, and returns the block.
+    pub fn terminate(self, kind: TerminatorKind<'tcx>) -> BasicBlock {
+        let source_info = self.builder.source_info;
+        self.builder.basic_blocks[self.block].terminator = Some(Terminator {
+            source_info: source_info,
+            kind: kind
+        });
+        self.block
+    }
+}
+
+/// The constant `value`, of type `ty`.
+pub fn constant<'tcx>(ty: Ty<'tcx>, value: ConstVal) -> Operand<'tcx> {
+    Operand::Constant(Constant {
+        span: DUMMY_SP,
+        ty: ty,
+        literal: Literal::Value { value: value }
+    })
+}
@@ -0,0 +1,93 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass makes unwinding out of a function marked with
+//! `#[unwind(aborts)]` abort the process, e.g. for `extern "C"` functions,
+//! which must not unwind into their foreign callers. The landing pads of
+//! the function end in `Abort` instead of `Resume`, and the terminators
+//! that could unwind without running a landing pad get one that aborts
+//! right away.
+//!
+//! It has to run after drop elaboration, which adds landing pads of its
+//! own.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use syntax::attr::{self, UnwindAttr};
+
+pub struct AbortUnwinding;
+
+impl<'tcx> MirPass<'tcx> for AbortUnwinding {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Without landing pads, nothing unwinds anyway.
+        if tcx.sess.no_landing_pads() {
+            return;
+        }
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return,
+        };
+        if attr::find_unwind_attr(None, tcx.map.attrs(id)) != Some(UnwindAttr::Aborts) {
+            return;
+        }
+
+        for data in mir.basic_blocks_mut() {
+            if let TerminatorKind::Resume = data.terminator().kind {
+                data.terminator_mut().kind = TerminatorKind::Abort;
+            }
+        }
+
+        // Unwinding out of a landing pad already aborts, as it means that
+        // a destructor panicked while panicking.
+        let unguarded: Vec<_> = mir.basic_blocks().iter_enumerated().filter(|&(_, data)| {
+            if data.is_cleanup {
+                return false;
+            }
+            match data.terminator().kind {
+                TerminatorKind::Call { cleanup: None, .. } |
+                TerminatorKind::Assert { cleanup: None, .. } |
+                TerminatorKind::DropAndReplace { unwind: None, .. } |
+                TerminatorKind::Drop { unwind: None, .. } => true,
+                _ => false,
+            }
+        }).map(|(block, _)| block).collect();
+        if unguarded.is_empty() {
+            return;
+        }
+
+        let source_info = SourceInfo {
+            span: mir.span,
+            scope: ARGUMENT_VISIBILITY_SCOPE,
+        };
+        let abort_block = mir.basic_blocks_mut().push(BasicBlockData {
+            statements: vec![],
+            terminator: Some(Terminator {
+                source_info: source_info,
+                kind: TerminatorKind::Abort,
+            }),
+            is_cleanup: true,
+        });
+        for block in unguarded {
+            match mir.basic_blocks_mut()[block].terminator_mut().kind {
+                TerminatorKind::Call { cleanup: ref mut unwind, .. } |
+                TerminatorKind::Assert { cleanup: ref mut unwind, .. } |
+                TerminatorKind::DropAndReplace { ref mut unwind, .. } |
+                TerminatorKind::Drop { ref mut unwind, .. } => {
+                    *unwind = Some(abort_block);
+                }
+                _ => bug!("{:?} cannot unwind", block),
+            }
+        }
+    }
+}
+
+impl Pass for AbortUnwinding {}
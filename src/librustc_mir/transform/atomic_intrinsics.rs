@@ -0,0 +1,160 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass replaces calls to the atomic load, store and
+//! read-modify-write intrinsics with `StatementKind::Atomic` statements,
+//! so that later passes see the ordering of each access instead of an
+//! opaque call.
+//!
+//! Only calls on concrete integer types are replaced, with the orderings
+//! that are valid for the operation; everything else, including
+//! compare-exchanges, fences and unordered accesses, is left to trans.
+//!
+//! It runs after drop elaboration, as borrowck does not know about atomic
+//! statements.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt, TypeFoldable};
+use syntax::abi::Abi;
+
+pub struct AtomicIntrinsics;
+
+impl<'tcx> MirPass<'tcx> for AtomicIntrinsics {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    _src: MirSource, mir: &mut Mir<'tcx>) {
+        let lowered: Vec<_> = {
+            let mir: &Mir<'tcx> = mir;
+            mir.basic_blocks().iter_enumerated().filter_map(|(bb, data)| {
+                atomic_statements(tcx, mir, &data.terminator().kind).map(|lowered| (bb, lowered))
+            }).collect()
+        };
+
+        for (bb, (kinds, target)) in lowered {
+            let data = &mut mir.basic_blocks_mut()[bb];
+            let source_info = data.terminator().source_info;
+            data.statements.extend(kinds.into_iter().map(|kind| Statement {
+                source_info: source_info,
+                kind: kind,
+            }));
+            // The cleanup block of the call is left unreachable, and
+            // removed by the next `SimplifyCfg`.
+            data.terminator_mut().kind = TerminatorKind::Goto { target: target };
+        }
+    }
+}
+
+impl Pass for AtomicIntrinsics {}
+
+/// Returns the statements and successor that replace `kind` if it is a
+/// valid call to one of the atomic intrinsics that have a statement.
+fn atomic_statements<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               mir: &Mir<'tcx>,
+                               kind: &TerminatorKind<'tcx>)
+                               -> Option<(Vec<StatementKind<'tcx>>, BasicBlock)> {
+    let (func, args, dest, target) = match *kind {
+        TerminatorKind::Call { ref func, ref args, destination: Some((ref dest, target)), .. } => {
+            (func, args, dest, target)
+        }
+        _ => return None,
+    };
+    let name = match func.ty(mir, tcx).sty {
+        ty::TyFnDef(def_id, _, f) if f.abi == Abi::RustIntrinsic => {
+            tcx.item_name(def_id).as_str()
+        }
+        _ => return None,
+    };
+    if !name.starts_with("atomic_") || args.is_empty() {
+        return None;
+    }
+
+    // The intrinsics are named "atomic_<operation>[_<ordering>]", and no
+    // ordering means SeqCst, as in trans.
+    let split: Vec<&str> = name.split('_').collect();
+    let ordering = match split.len() {
+        2 => AtomicOrdering::SeqCst,
+        3 => match split[2] {
+            "relaxed" => AtomicOrdering::Relaxed,
+            "acq" => AtomicOrdering::Acquire,
+            "rel" => AtomicOrdering::Release,
+            "acqrel" => AtomicOrdering::AcqRel,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let op = match split[1] {
+        "load" => AtomicOp::Load,
+        "store" => AtomicOp::Store,
+        "xchg" => AtomicOp::Xchg,
+        "xadd" => AtomicOp::Add,
+        "xsub" => AtomicOp::Sub,
+        "and" => AtomicOp::And,
+        "nand" => AtomicOp::Nand,
+        "or" => AtomicOp::Or,
+        "xor" => AtomicOp::Xor,
+        "max" => AtomicOp::Max,
+        "min" => AtomicOp::Min,
+        "umax" => AtomicOp::UMax,
+        "umin" => AtomicOp::UMin,
+        _ => return None,
+    };
+    // LLVM rejects loads with a release ordering and stores with an acquire
+    // one, which trans reports for the calls.
+    let valid = match (op, ordering) {
+        (AtomicOp::Load, AtomicOrdering::Release) |
+        (AtomicOp::Load, AtomicOrdering::AcqRel) |
+        (AtomicOp::Store, AtomicOrdering::Acquire) |
+        (AtomicOp::Store, AtomicOrdering::AcqRel) => false,
+        _ => true,
+    };
+    if !valid {
+        return None;
+    }
+
+    // The number and the types of the arguments are checked by typeck.
+    let ptr_ty = args[0].ty(mir, tcx);
+    if ptr_ty.needs_subst() {
+        return None;
+    }
+    match ptr_ty.sty {
+        ty::TyRawPtr(ty::TypeAndMut { ty, .. }) if ty.is_integral() => {}
+        _ => return None,
+    }
+
+    let ptr = args[0].clone();
+    let kinds = match op {
+        AtomicOp::Load => vec![StatementKind::Atomic {
+            op: op,
+            ordering: ordering,
+            dest: Some(dest.clone()),
+            ptr: ptr,
+            value: None,
+        }],
+        AtomicOp::Store => vec![
+            StatementKind::Atomic {
+                op: op,
+                ordering: ordering,
+                dest: None,
+                ptr: ptr,
+                value: Some(args[1].clone()),
+            },
+            StatementKind::Assign(dest.clone(),
+                                  Rvalue::Aggregate(AggregateKind::Tuple, vec![])),
+        ],
+        _ => vec![StatementKind::Atomic {
+            op: op,
+            ordering: ordering,
+            dest: Some(dest.clone()),
+            ptr: ptr,
+            value: Some(args[1].clone()),
+        }],
+    };
+    Some((kinds, target))
+}
@@ -0,0 +1,258 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass asserts that the raw pointers `Box`es are made from, e.g.
+//! by `Box::from_raw` or `transmute`, are non-null and aligned, for
+//! `-Z check-box-from-raw`. A call that returns a `Box<T>` and takes a
+//! `*const T` or `*mut T` is taken to make the box from that pointer.
+//!
+//! The pointer is checked as soon as the call returns, which is before
+//! the first dereference of the box, but only if the box, or one it is
+//! moved into, is dereferenced in the same body. Pointees whose layout
+//! depends on type parameters are not checked.
+//!
+//! The asserts have no cleanup, as the cleanup of the call would free
+//! the box: if they fail, the values that are still alive are leaked.
+//! They run after drop elaboration, which would add the box to that
+//! cleanup, and before `AbortUnwinding`, so that they abort in bodies
+//! that must not unwind.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::traits::Reveal;
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use hair::cx::int_literal;
+use patch::{alignment_test, MirPatch};
+
+pub struct CheckBoxFromRaw;
+
+/// A call that makes a box from the raw pointer it takes as `args[arg]`.
+struct Origin<'tcx> {
+    block: BasicBlock,
+    arg: usize,
+    ptr_ty: Ty<'tcx>,
+    align: u64,
+    dest: Lvalue<'tcx>,
+    target: BasicBlock,
+}
+
+impl<'tcx> MirPass<'tcx> for CheckBoxFromRaw {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Constants and statics cannot call `Box::from_raw`.
+        if let MirSource::Fn(_) = src {} else {
+            return;
+        }
+
+        let origins: Vec<_> = {
+            let mir: &Mir<'tcx> = mir;
+            let mut uses = BoxUses { moves: vec![], derefs: vec![] };
+            uses.visit_mir(mir);
+            mir.basic_blocks().iter_enumerated().filter_map(|(block, data)| {
+                if data.is_cleanup {
+                    return None;
+                }
+                box_origin(tcx, mir, block, &data.terminator().kind)
+            }).filter(|origin| uses.is_dereferenced(&origin.dest)).collect()
+        };
+        if origins.is_empty() {
+            return;
+        }
+
+        let usize_ty = tcx.types.usize;
+        let bool_ty = tcx.types.bool;
+
+        let mut patch = MirPatch::new(mir);
+        for origin in origins {
+            let source_info = mir[origin.block].terminator().source_info;
+            let assign = |lvalue: Lvalue<'tcx>, rvalue| Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(lvalue, rvalue),
+            };
+            let constant = |value: u64| Operand::Constant(Constant {
+                span: source_info.span,
+                ty: usize_ty,
+                literal: int_literal(tcx, source_info.span, value, usize_ty),
+            });
+            let assert = |cond: Lvalue<'tcx>, msg, target| Terminator {
+                source_info: source_info,
+                kind: TerminatorKind::Assert {
+                    cond: Operand::Consume(cond),
+                    expected: true,
+                    msg: msg,
+                    target: target,
+                    cleanup: None,
+                },
+            };
+
+            // Keep a copy of the pointer, which the call consumes.
+            let ptr = Lvalue::Temp(patch.new_temp(origin.ptr_ty));
+            let (func, mut args, cleanup) = match mir[origin.block].terminator().kind {
+                TerminatorKind::Call { ref func, ref args, cleanup, .. } => {
+                    (func.clone(), args.clone(), cleanup)
+                }
+                _ => bug!("{:?} is not a call", origin.block),
+            };
+            let call_loc = patch.terminator_loc(mir, origin.block);
+            patch.add_assign(call_loc, ptr.clone(), Rvalue::Use(args[origin.arg].clone()));
+            args[origin.arg] = Operand::Consume(ptr.clone());
+
+            let addr = Lvalue::Temp(patch.new_temp(usize_ty));
+            let mut target = origin.target;
+            if origin.align > 1 {
+                let low_bits = Lvalue::Temp(patch.new_temp(usize_ty));
+                let aligned = Lvalue::Temp(patch.new_temp(bool_ty));
+                let statements =
+                    alignment_test(Operand::Consume(addr.clone()), constant(origin.align - 1),
+                                   constant(0), low_bits, aligned.clone())
+                        .into_iter()
+                        .map(|(lvalue, rvalue)| assign(lvalue, rvalue))
+                        .collect();
+                target = patch.new_block(BasicBlockData {
+                    statements: statements,
+                    terminator: Some(assert(aligned, AssertMessage::Misaligned, target)),
+                    is_cleanup: false,
+                });
+            }
+            let nonnull = Lvalue::Temp(patch.new_temp(bool_ty));
+            let check = patch.new_block(BasicBlockData {
+                statements: vec![
+                    // addr = ptr as usize
+                    assign(addr.clone(), Rvalue::Cast(CastKind::PointerExposeAddress,
+                                                      Operand::Consume(ptr),
+                                                      usize_ty)),
+                    // nonnull = addr != 0
+                    assign(nonnull.clone(), Rvalue::BinaryOp(BinOp::Ne,
+                                                             Operand::Consume(addr),
+                                                             constant(0))),
+                ],
+                terminator: Some(assert(nonnull, AssertMessage::NullPointer, target)),
+                is_cleanup: false,
+            });
+
+            patch.patch_terminator(origin.block, TerminatorKind::Call {
+                func: func,
+                args: args,
+                destination: Some((origin.dest, check)),
+                cleanup: cleanup,
+            });
+        }
+        patch.apply(mir);
+    }
+}
+
+impl Pass for CheckBoxFromRaw {}
+
+/// Returns the origin of the box made by `kind`, if it is a call that
+/// makes one from a raw pointer to a pointee with a known layout.
+fn box_origin<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        mir: &Mir<'tcx>,
+                        block: BasicBlock,
+                        kind: &TerminatorKind<'tcx>)
+                        -> Option<Origin<'tcx>> {
+    let (args, dest, target) = match *kind {
+        TerminatorKind::Call { ref args, destination: Some((ref dest, target)), .. } => {
+            (args, dest, target)
+        }
+        _ => return None,
+    };
+    let pointee = match dest.ty(mir, tcx).to_ty(tcx).sty {
+        ty::TyBox(pointee) => pointee,
+        _ => return None,
+    };
+    let raw_ptr = args.iter().map(|arg| arg.ty(mir, tcx)).enumerate().find(|&(_, ty)| {
+        match ty.sty {
+            ty::TyRawPtr(mt) => mt.ty == pointee,
+            _ => false,
+        }
+    });
+    let (arg, ptr_ty) = match raw_ptr {
+        Some(raw_ptr) => raw_ptr,
+        None => return None,
+    };
+    if pointee.needs_subst() {
+        return None;
+    }
+    let align = match tcx.normalizing_infer_ctxt(Reveal::All).enter(|infcx| {
+        pointee.layout(&infcx).ok().and_then(|layout| {
+            if layout.is_unsized() {
+                None
+            } else {
+                Some(layout.align(&tcx.data_layout).abi())
+            }
+        })
+    }) {
+        Some(align) => align,
+        None => return None,
+    };
+    Some(Origin {
+        block: block,
+        arg: arg,
+        ptr_ty: ptr_ty,
+        align: align,
+        dest: dest.clone(),
+        target: target,
+    })
+}
+
+struct BoxUses<'tcx> {
+    /// The destination and source of every move of a whole lvalue.
+    moves: Vec<(Lvalue<'tcx>, Lvalue<'tcx>)>,
+    /// The lvalues that are dereferenced.
+    derefs: Vec<Lvalue<'tcx>>,
+}
+
+impl<'tcx> BoxUses<'tcx> {
+    /// Whether the box in `lvalue`, or one it is moved into, is dereferenced.
+    fn is_dereferenced(&self, lvalue: &Lvalue<'tcx>) -> bool {
+        let mut boxes = vec![lvalue.clone()];
+        let mut i = 0;
+        while i < boxes.len() {
+            if self.derefs.contains(&boxes[i]) {
+                return true;
+            }
+            for &(ref dest, ref src) in &self.moves {
+                if *src == boxes[i] && !boxes.contains(dest) {
+                    boxes.push(dest.clone());
+                }
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for BoxUses<'tcx> {
+    fn visit_statement(&mut self,
+                       block: BasicBlock,
+                       statement: &Statement<'tcx>,
+                       location: Location) {
+        if let StatementKind::Assign(ref dest, Rvalue::Use(Operand::Consume(ref src))) =
+                statement.kind {
+            self.moves.push((dest.clone(), src.clone()));
+        }
+        self.super_statement(block, statement, location);
+    }
+
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            if let ProjectionElem::Deref = proj.elem {
+                if !self.derefs.contains(&proj.base) {
+                    self.derefs.push(proj.base.clone());
+                }
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
@@ -24,22 +24,27 @@ impl<'tcx> MirPass<'tcx> for Deaggregator {
         let node_id = source.item_id();
         let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
         debug!("running on: {:?}", node_path);
-        // we only run when mir_opt_level > 1
+        // With -Z mir-deaggregate, every aggregate of a struct or enum
+        // variant is lowered, in every body, as the constant evaluator
+        // can build values field by field.
+        let all = tcx.sess.opts.debugging_opts.mir_deaggregate;
+
+        // otherwise we only run when mir_opt_level > 1
         match tcx.sess.opts.debugging_opts.mir_opt_level {
             Some(0) |
             Some(1) |
-            None => { return; },
+            None if !all => { return; },
             _ => {}
         };
 
         // Do not trigger on constants.  Could be revised in future
-        if let MirSource::Fn(_) = source {} else { return; }
+        if let MirSource::Fn(_) = source {} else if !all { return; }
         // In fact, we might not want to trigger in other cases.
         // Ex: when we could use SROA.  See issue #35259
 
         let mut curr: usize = 0;
         for bb in mir.basic_blocks_mut() {
-            let idx = match get_aggregate_statement_index(curr, &bb.statements) {
+            let idx = match get_aggregate_statement_index(curr, &bb.statements, all) {
                 Some(idx) => idx,
                 None => continue,
             };
@@ -50,7 +55,13 @@ impl<'tcx> MirPass<'tcx> for Deaggregator {
             let orig_stmt = bb.statements.pop().unwrap();
             let (lhs, rhs) = match orig_stmt.kind {
                 StatementKind::Assign(ref lhs, ref rhs) => (lhs, rhs),
-                StatementKind::SetDiscriminant{ .. } =>
+                StatementKind::SetDiscriminant{ .. } |
+                StatementKind::Coverage { .. } |
+                StatementKind::Probe { .. } |
+                StatementKind::SanitizerCheck { .. } |
+                StatementKind::Subtype { .. } |
+                StatementKind::Retag { .. } |
+                StatementKind::Atomic { .. } =>
                     span_bug!(src_info.span, "expected aggregate, not {:?}", orig_stmt.kind),
             };
             let (agg_kind, operands) = match rhs {
@@ -107,14 +118,25 @@ impl<'tcx> MirPass<'tcx> for Deaggregator {
     }
 }
 
+/// Returns the index of the first aggregate statement from `start` on
+/// that is lowered: only those of variants with named fields, unless `all`
+/// is set, which includes tuple and unit variants of enums, and tuple
+/// structs.
 fn get_aggregate_statement_index<'a, 'tcx, 'b>(start: usize,
-                                         statements: &Vec<Statement<'tcx>>)
+                                         statements: &Vec<Statement<'tcx>>,
+                                         all: bool)
                                          -> Option<usize> {
     for i in start..statements.len() {
         let ref statement = statements[i];
         let rhs = match statement.kind {
             StatementKind::Assign(_, ref rhs) => rhs,
-            StatementKind::SetDiscriminant{ .. } => continue,
+            StatementKind::SetDiscriminant{ .. } |
+            StatementKind::Coverage { .. } |
+            StatementKind::Probe { .. } |
+            StatementKind::SanitizerCheck { .. } |
+            StatementKind::Subtype { .. } |
+            StatementKind::Retag { .. } |
+            StatementKind::Atomic { .. } => continue,
         };
         let (kind, operands) = match rhs {
             &Rvalue::Aggregate(ref kind, ref operands) => (kind, operands),
@@ -124,14 +146,15 @@ fn get_aggregate_statement_index<'a, 'tcx, 'b>(start: usize,
             &AggregateKind::Adt(adt_def, variant, _) => (adt_def, variant),
             _ => continue,
         };
-        if operands.len() == 0 {
+        // Unit variants of enums still need their discriminant set.
+        if operands.len() == 0 && !(all && adt_def.variants.len() > 1) {
             // don't deaggregate ()
             continue;
         }
         debug!("getting variant {:?}", variant);
         debug!("for adt_def {:?}", adt_def);
         let variant_def = &adt_def.variants[variant];
-        if variant_def.kind == VariantKind::Struct {
+        if variant_def.kind == VariantKind::Struct || all {
             return Some(i);
         }
     };
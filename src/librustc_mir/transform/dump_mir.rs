@@ -11,10 +11,15 @@
 //! This pass just dumps MIR at a specified point.
 
 use std::fmt;
+use std::fs;
+use std::io::Write;
 
 use rustc::ty::TyCtxt;
 use rustc::mir::repr::*;
 use rustc::mir::transform::{Pass, MirPass, MirPassHook, MirSource};
+use rustc_data_structures::fnv::FnvHashMap;
+use syntax::ast::NodeId;
+use diff;
 use pretty;
 
 pub struct Marker<'a>(pub &'a str);
@@ -31,21 +36,31 @@ impl<'b> Pass for Marker<'b> {
 
 pub struct Disambiguator<'a> {
     pass: &'a Pass,
-    is_after: bool
+    title: &'static str,
 }
 
 impl<'a> fmt::Display for Disambiguator<'a> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        let title = if self.is_after { "after" } else { "before" };
         if let Some(fmt) = self.pass.disambiguator() {
-            write!(formatter, "{}-{}", fmt, title)
+            write!(formatter, "{}-{}", fmt, self.title)
         } else {
-            write!(formatter, "{}", title)
+            write!(formatter, "{}", self.title)
         }
     }
 }
 
-pub struct DumpMir;
+/// Dumps MIR before and after every pass, along with a diff of the two
+/// whenever the pass changed something.
+pub struct DumpMir {
+    /// The textual MIR of each body before the pass currently running.
+    before: FnvHashMap<(NodeId, Option<Promoted>), String>,
+}
+
+impl DumpMir {
+    pub fn new() -> DumpMir {
+        DumpMir { before: FnvHashMap() }
+    }
+}
 
 impl<'tcx> MirPassHook<'tcx> for DumpMir {
     fn on_mir_pass<'a>(
@@ -61,13 +76,45 @@ impl<'tcx> MirPassHook<'tcx> for DumpMir {
             pass.name(),
             &Disambiguator {
                 pass: pass,
-                is_after: is_after
+                title: if is_after { "after" } else { "before" },
             },
             src,
             mir,
             None
         );
+
+        if !pretty::dump_enabled(tcx, pass.name(), src) {
+            return;
+        }
+        let key = match src {
+            MirSource::Promoted(id, promoted) => (id, Some(promoted)),
+            _ => (src.item_id(), None),
+        };
+        let mut text = vec![];
+        if pretty::write_mir_fn(tcx, src, mir, &mut text, None).is_err() {
+            return;
+        }
+        let text = String::from_utf8(text).unwrap();
+        if !is_after {
+            self.before.insert(key, text);
+            return;
+        }
+
+        let before = match self.before.remove(&key) {
+            Some(before) => before,
+            None => return,
+        };
+        let diff = diff::unified_diff(&format!("before {}", pass.name()), &before,
+                                      &format!("after {}", pass.name()), &text);
+        if let Some(diff) = diff {
+            let disambiguator = Disambiguator { pass: pass, title: "changes" };
+            if let Some(path) = pretty::dump_path(tcx, pass.name(), &disambiguator, src, "diff") {
+                let _ = fs::File::create(&path).and_then(|mut file| {
+                    file.write_all(diff.as_bytes())
+                });
+            }
+        }
     }
 }
 
-impl<'b> Pass for DumpMir {}
+impl Pass for DumpMir {}
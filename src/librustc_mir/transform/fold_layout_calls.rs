@@ -0,0 +1,124 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass replaces calls to `mem::size_of`, `mem::align_of` and
+//! `mem::min_align_of`, and to the intrinsics behind them, with the size or
+//! alignment of their type parameter, as given by its layout.
+//!
+//! Only calls on types whose layout is known before monomorphization are
+//! replaced; the others, and those on types whose layout can't be computed,
+//! are left to trans.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::traits::Reveal;
+use rustc::ty::{self, TyCtxt, TypeFoldable};
+use rustc::ty::subst::FnSpace;
+use rustc_const_math::{ConstInt, ConstUsize};
+use syntax::abi::Abi;
+
+pub struct FoldLayoutCalls;
+
+#[derive(Copy, Clone)]
+enum LayoutQuery {
+    Size,
+    Align,
+    PrefAlign,
+}
+
+impl<'tcx> MirPass<'tcx> for FoldLayoutCalls {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    _src: MirSource, mir: &mut Mir<'tcx>) {
+        let folded: Vec<_> = {
+            let mir: &Mir<'tcx> = mir;
+            mir.basic_blocks().iter_enumerated().filter_map(|(bb, data)| {
+                layout_constant(tcx, mir, data.terminator()).map(|folded| (bb, folded))
+            }).collect()
+        };
+
+        for (bb, (dest, constant, target)) in folded {
+            let data = &mut mir.basic_blocks_mut()[bb];
+            let source_info = data.terminator().source_info;
+            data.statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(dest, Rvalue::Use(Operand::Constant(constant))),
+            });
+            // The cleanup block of the call is left unreachable, and
+            // removed by the next `SimplifyCfg`.
+            data.terminator_mut().kind = TerminatorKind::Goto { target: target };
+        }
+    }
+}
+
+impl Pass for FoldLayoutCalls {}
+
+/// Returns the destination, constant and successor that replace
+/// `terminator` if it is a call of one of the functions that return the
+/// size or alignment of a type with a known layout.
+fn layout_constant<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                             mir: &Mir<'tcx>,
+                             terminator: &Terminator<'tcx>)
+                             -> Option<(Lvalue<'tcx>, Constant<'tcx>, BasicBlock)> {
+    let (func, dest, target) = match terminator.kind {
+        TerminatorKind::Call { ref func, destination: Some((ref dest, target)), .. } => {
+            (func, dest, target)
+        }
+        _ => return None,
+    };
+    let (query, substs) = match func.ty(mir, tcx).sty {
+        ty::TyFnDef(def_id, substs, f) if f.abi == Abi::RustIntrinsic => {
+            let query = match &tcx.item_name(def_id).as_str()[..] {
+                "size_of" => LayoutQuery::Size,
+                "min_align_of" => LayoutQuery::Align,
+                "pref_align_of" => LayoutQuery::PrefAlign,
+                _ => return None,
+            };
+            (query, substs)
+        }
+        ty::TyFnDef(def_id, substs, _) => {
+            let query = match &tcx.absolute_item_path_str(def_id)[..] {
+                "core::mem::size_of" => LayoutQuery::Size,
+                "core::mem::align_of" | "core::mem::min_align_of" => LayoutQuery::Align,
+                _ => return None,
+            };
+            (query, substs)
+        }
+        _ => return None,
+    };
+    let ty = match substs.types.get_slice(FnSpace).first() {
+        Some(&ty) if !ty.needs_subst() => ty,
+        _ => return None,
+    };
+
+    let value = tcx.normalizing_infer_ctxt(Reveal::All).enter(|infcx| {
+        ty.layout(&infcx).ok().and_then(|layout| {
+            if layout.is_unsized() {
+                return None;
+            }
+            let dl = &tcx.data_layout;
+            Some(match query {
+                LayoutQuery::Size => layout.size(dl).bytes(),
+                LayoutQuery::Align => layout.align(dl).abi(),
+                LayoutQuery::PrefAlign => layout.align(dl).pref(),
+            })
+        })
+    });
+    let value = match value.map(|value| ConstUsize::new(value, tcx.sess.target.uint_type)) {
+        Some(Ok(value)) => value,
+        _ => return None,
+    };
+    let constant = Constant {
+        span: terminator.source_info.span,
+        ty: tcx.types.usize,
+        literal: Literal::Value { value: ConstVal::Integral(ConstInt::Usize(value)) },
+    };
+    Some((dest.clone(), constant, target))
+}
@@ -0,0 +1,84 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass inserts `Probe` statements for `-Z mir-instrument-calls`: one
+//! on entry to every function, one right before each of its returns, and
+//! one right before each place its cleanup code resumes unwinding. Trans
+//! lowers them to calls of the function the option names, passing an id of
+//! the enclosing function, so that tracing tools don't have to patch the
+//! binary.
+//!
+//! It has to run after drop elaboration, which does not expect any
+//! statements besides assignments.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::fnv::FnvHasher;
+use std::hash::Hasher;
+use syntax::attr;
+
+pub struct InstrumentCalls;
+
+impl<'tcx> MirPass<'tcx> for InstrumentCalls {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Constants and statics are evaluated at compile time, so they are
+        // never called.
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return,
+        };
+        // The probe function itself would call itself forever.
+        let symbol = tcx.sess.opts.debugging_opts.mir_instrument_calls.as_ref().unwrap();
+        let attrs = tcx.map.attrs(id);
+        let link_name = attr::first_attr_value_str_by_name(attrs, "export_name").or_else(|| {
+            if attr::contains_name(attrs, "no_mangle") {
+                Some(tcx.map.name(id).as_str())
+            } else {
+                None
+            }
+        });
+        if link_name.map_or(false, |name| name == &symbol[..]) {
+            return;
+        }
+
+        // The id is the same across compilations, as that of coverage
+        // counters.
+        let name = tcx.item_path_str(tcx.map.local_def_id(id));
+        let mut hasher = FnvHasher::default();
+        hasher.write(tcx.crate_name.as_bytes());
+        hasher.write(name.as_bytes());
+        let function_id = hasher.finish();
+
+        for data in mir.basic_blocks_mut() {
+            let kind = match data.terminator().kind {
+                TerminatorKind::Return => ProbeKind::Return,
+                TerminatorKind::Resume => ProbeKind::Unwind,
+                _ => continue,
+            };
+            let source_info = data.terminator().source_info;
+            data.statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Probe { kind: kind, function_id: function_id },
+            });
+        }
+        let source_info = SourceInfo {
+            span: mir.span,
+            scope: ARGUMENT_VISIBILITY_SCOPE
+        };
+        mir.basic_blocks_mut()[START_BLOCK].statements.insert(0, Statement {
+            source_info: source_info,
+            kind: StatementKind::Probe { kind: ProbeKind::Entry, function_id: function_id },
+        });
+    }
+}
+
+impl Pass for InstrumentCalls {}
@@ -0,0 +1,108 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass inserts a coverage counter at the entry of every basic block
+//! of a function that is not only reached while unwinding, and records the
+//! source region each counter covers in the session's coverage map, for
+//! `-Z instrument-coverage`. Trans lowers the counters to
+//! `llvm.instrprof.increment`.
+//!
+//! It has to run after drop elaboration, which does not expect any
+//! statements besides assignments.
+
+use rustc::mir::coverage::{CoverageRegion, FunctionCoverage};
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::fnv::FnvHasher;
+use std::hash::Hasher;
+use syntax_pos::Span;
+
+pub struct InstrumentCoverage;
+
+impl<'tcx> MirPass<'tcx> for InstrumentCoverage {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Constants and statics are evaluated at compile time, so there is
+        // nothing to count.
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return,
+        };
+        let name = tcx.item_path_str(tcx.map.local_def_id(id));
+        let mut hasher = FnvHasher::default();
+        hasher.write(tcx.crate_name.as_bytes());
+        hasher.write(name.as_bytes());
+        let function_hash = hasher.finish();
+
+        let body_span = mir.span;
+        let blocks: Vec<_> = mir.basic_blocks().iter_enumerated()
+                                .filter(|&(_, data)| !data.is_cleanup)
+                                .map(|(block, _)| block)
+                                .collect();
+        let num_counters = blocks.len() as u32;
+
+        let mut regions = vec![];
+        for (index, block) in blocks.into_iter().enumerate() {
+            let data = &mut mir.basic_blocks_mut()[block];
+            let source_info = data.statements.first()
+                                             .map_or(data.terminator().source_info,
+                                                     |statement| statement.source_info);
+            let span = block_span(data, body_span);
+            let lo = tcx.sess.codemap().lookup_char_pos(span.lo);
+            let hi = tcx.sess.codemap().lookup_char_pos(span.hi);
+            regions.push(CoverageRegion {
+                counter: index as u32,
+                file_name: lo.file.name.clone(),
+                line_start: lo.line,
+                line_end: hi.line,
+                column_start: lo.col.0 + 1,
+                column_end: hi.col.0 + 1,
+            });
+            data.statements.insert(0, Statement {
+                source_info: source_info,
+                kind: StatementKind::Coverage {
+                    function_hash: function_hash,
+                    num_counters: num_counters,
+                    index: index as u32,
+                },
+            });
+        }
+
+        tcx.sess.coverage_map.borrow_mut().functions.push(FunctionCoverage {
+            name: name,
+            function_hash: function_hash,
+            regions: regions,
+        });
+    }
+}
+
+/// The smallest span covering the statements and the terminator of `data`,
+/// ignoring those that come from outside of the body, e.g. from a macro
+/// definition.
+fn block_span(data: &BasicBlockData, body_span: Span) -> Span {
+    let terminator_span = data.terminator().source_info.span;
+    let spans = data.statements.iter()
+                               .map(|statement| statement.source_info.span)
+                               .chain(Some(terminator_span))
+                               .filter(|span| span.lo >= body_span.lo && span.hi <= body_span.hi);
+    spans.fold(None, |region: Option<Span>, span| {
+        Some(match region {
+            Some(region) => Span {
+                lo: if span.lo < region.lo { span.lo } else { region.lo },
+                hi: if span.hi > region.hi { span.hi } else { region.hi },
+                expn_id: region.expn_id,
+            },
+            None => span,
+        })
+    }).unwrap_or(terminator_span)
+}
+
+impl Pass for InstrumentCoverage {}
@@ -0,0 +1,100 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass inserts `Retag` statements for `-Z mir-retag`: one for every
+//! reference argument on entry to the function, and one for the
+//! destination of every borrow, right after it. They tell a checker of an
+//! aliasing model, such as an interpreter, where references come into
+//! existence; trans ignores them.
+//!
+//! It has to run after drop elaboration, which does not expect any
+//! statements besides assignments.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+
+pub struct InstrumentRetag;
+
+impl<'tcx> MirPass<'tcx> for InstrumentRetag {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Constants and statics have no arguments, and only borrow memory
+        // that is never written to.
+        if let MirSource::Fn(_) = src {} else {
+            return;
+        }
+
+        let mut borrows = vec![];
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                if let StatementKind::Assign(ref dest, Rvalue::Ref(_, _, ref lvalue)) =
+                    statement.kind {
+                    let kind = if is_reborrow(tcx, mir, lvalue) {
+                        RetagKind::Reborrow
+                    } else {
+                        RetagKind::Ref
+                    };
+                    let location = Location {
+                        block: block,
+                        statement_index: statement_index + 1
+                    };
+                    borrows.push((location, statement.source_info, kind, dest.clone()));
+                }
+            }
+        }
+        let args: Vec<_> = mir.arg_decls.iter_enumerated().filter(|&(_, decl)| {
+            if let ty::TyRef(..) = decl.ty.sty { true } else { false }
+        }).map(|(arg, _)| Lvalue::Arg(arg)).collect();
+
+        // Insert the retags of borrows back to front, so that the locations
+        // of the ones that are still to be inserted stay valid, and those of
+        // the arguments last, in front of everything else.
+        for (location, source_info, kind, lvalue) in borrows.into_iter().rev() {
+            mir.basic_blocks_mut()[location.block].statements.insert(
+                location.statement_index,
+                Statement {
+                    source_info: source_info,
+                    kind: StatementKind::Retag { kind: kind, lvalue: lvalue },
+                });
+        }
+        let source_info = SourceInfo {
+            span: mir.span,
+            scope: ARGUMENT_VISIBILITY_SCOPE
+        };
+        let entry = &mut mir.basic_blocks_mut()[START_BLOCK].statements;
+        for (i, lvalue) in args.into_iter().enumerate() {
+            entry.insert(i, Statement {
+                source_info: source_info,
+                kind: StatementKind::Retag { kind: RetagKind::FnEntry, lvalue: lvalue },
+            });
+        }
+    }
+}
+
+impl Pass for InstrumentRetag {}
+
+/// Whether borrowing `lvalue` borrows through a reference, i.e. whether one
+/// of its projections dereferences a reference.
+fn is_reborrow<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                         mir: &Mir<'tcx>,
+                         lvalue: &Lvalue<'tcx>)
+                         -> bool {
+    let mut current = lvalue;
+    while let Lvalue::Projection(ref proj) = *current {
+        if let ProjectionElem::Deref = proj.elem {
+            if let ty::TyRef(..) = proj.base.ty(mir, tcx).to_ty(tcx).sty {
+                return true;
+            }
+        }
+        current = &proj.base;
+    }
+    false
+}
@@ -0,0 +1,120 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass inserts a sanitizer check in front of every statement and
+//! terminator that loads from or stores to memory through a raw pointer,
+//! for `-Z sanitizer`. Trans lowers the checks to calls into the runtime
+//! of the chosen sanitizer.
+//!
+//! Accesses through references and to locals are not checked: the former
+//! are known to be valid, the latter are on the stack of the function.
+//!
+//! It has to run after drop elaboration, which does not expect any
+//! statements besides assignments.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, TyCtxt};
+
+pub struct InstrumentSanitizer;
+
+impl<'tcx> MirPass<'tcx> for InstrumentSanitizer {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        // Constants and statics cannot dereference raw pointers.
+        if let MirSource::Fn(_) = src {} else {
+            return;
+        }
+
+        let checks = {
+            let mir: &Mir<'tcx> = mir;
+            let mut collector = CollectAccesses {
+                tcx: tcx,
+                mir: mir,
+                checks: vec![],
+            };
+            collector.visit_mir(mir);
+            collector.checks
+        };
+
+        // Insert the checks back to front, so that the locations of the
+        // ones that are still to be inserted stay valid, and the checks of
+        // one statement end up in the order they were found in.
+        for (location, kind, lvalue) in checks.into_iter().rev() {
+            let data = &mut mir.basic_blocks_mut()[location.block];
+            let source_info = if location.statement_index < data.statements.len() {
+                data.statements[location.statement_index].source_info
+            } else {
+                data.terminator().source_info
+            };
+            data.statements.insert(location.statement_index, Statement {
+                source_info: source_info,
+                kind: StatementKind::SanitizerCheck { kind: kind, lvalue: lvalue },
+            });
+        }
+    }
+}
+
+impl Pass for InstrumentSanitizer {}
+
+struct CollectAccesses<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'a Mir<'tcx>,
+    checks: Vec<(Location, SanitizerCheckKind, Lvalue<'tcx>)>,
+}
+
+impl<'a, 'tcx> CollectAccesses<'a, 'tcx> {
+    /// Records a check of `lvalue` if it is accessed through a raw pointer,
+    /// and of every pointer loaded from memory to compute its address.
+    /// `kind` is `None` if `lvalue` itself is not accessed, e.g. because
+    /// it is only borrowed.
+    fn check_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    kind: Option<SanitizerCheckKind>,
+                    location: Location) {
+        let mut current = lvalue;
+        while let Lvalue::Projection(ref proj) = *current {
+            if let ProjectionElem::Deref = proj.elem {
+                let base_ty = proj.base.ty(self.mir, self.tcx).to_ty(self.tcx);
+                if let ty::TyRawPtr(_) = base_ty.sty {
+                    if let Some(kind) = kind {
+                        self.checks.push((location, kind, lvalue.clone()));
+                    }
+                }
+                self.check_lvalue(&proj.base, Some(SanitizerCheckKind::Load), location);
+                return;
+            }
+            current = &proj.base;
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for CollectAccesses<'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
+        let kind = match context {
+            LvalueContext::Store |
+            LvalueContext::Call => Some(SanitizerCheckKind::Store),
+            LvalueContext::Consume |
+            LvalueContext::Inspect |
+            LvalueContext::Drop => Some(SanitizerCheckKind::Load),
+            LvalueContext::Borrow { .. } |
+            LvalueContext::Slice { .. } |
+            LvalueContext::AddressOf |
+            LvalueContext::Projection => None,
+        };
+        // The projections are walked by `check_lvalue`, so this does not
+        // recurse into `super_lvalue`.
+        self.check_lvalue(lvalue, kind, location);
+    }
+}
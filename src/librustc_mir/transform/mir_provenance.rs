@@ -0,0 +1,46 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass hook keeps track of which pass created each basic block, for
+//! `-Z mir-provenance`. It has to run before `DumpMir`, so that the dumps
+//! after a pass show the blocks that pass created.
+
+use rustc::ty::TyCtxt;
+use rustc::mir::repr::*;
+use rustc::mir::provenance;
+use rustc::mir::transform::{Pass, MirPassHook, MirSource};
+
+pub struct TrackProvenance;
+
+impl<'tcx> MirPassHook<'tcx> for TrackProvenance {
+    fn on_mir_pass<'a>(
+        &mut self,
+        tcx: TyCtxt<'a, 'tcx, 'tcx>,
+        src: MirSource,
+        mir: &Mir<'tcx>,
+        pass: &Pass,
+        is_after: bool)
+    {
+        let mut table = tcx.sess.mir_provenance.borrow_mut();
+        if !is_after {
+            if !table.has_body(src) {
+                table.record(src, mir, provenance::BUILT);
+            }
+            return;
+        }
+        let name = match pass.disambiguator() {
+            Some(disambiguator) => format!("{}-{}", pass.name(), disambiguator),
+            None => pass.name().to_string(),
+        };
+        table.record(src, mir, &name);
+    }
+}
+
+impl Pass for TrackProvenance {}
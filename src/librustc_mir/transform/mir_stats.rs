@@ -0,0 +1,38 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass hook records the size of every MIR body before and after
+//! each pass, for `-Z mir-stats`.
+
+use rustc::ty::TyCtxt;
+use rustc::mir::repr::*;
+use rustc::mir::stats::MirSize;
+use rustc::mir::transform::{Pass, MirPassHook, MirSource};
+
+pub struct CollectStats;
+
+impl<'tcx> MirPassHook<'tcx> for CollectStats {
+    fn on_mir_pass<'a>(
+        &mut self,
+        tcx: TyCtxt<'a, 'tcx, 'tcx>,
+        _src: MirSource,
+        mir: &Mir<'tcx>,
+        pass: &Pass,
+        is_after: bool)
+    {
+        let name = match pass.disambiguator() {
+            Some(disambiguator) => format!("{}-{}", pass.name(), disambiguator),
+            None => pass.name().to_string(),
+        };
+        tcx.sess.mir_stats.borrow_mut().record(&name, is_after, MirSize::of(mir));
+    }
+}
+
+impl Pass for CollectStats {}
@@ -10,6 +10,9 @@
 
 pub mod simplify_branches;
 pub mod simplify_cfg;
+pub mod thread_switches;
+pub mod abort_unwinding;
+pub mod check_box_from_raw;
 pub mod erase_regions;
 pub mod no_landing_pads;
 pub mod type_check;
@@ -17,4 +20,14 @@ pub mod add_call_guards;
 pub mod promote_consts;
 pub mod qualify_consts;
 pub mod dump_mir;
+pub mod mir_stats;
+pub mod mir_provenance;
 pub mod deaggregator;
+pub mod instrument_coverage;
+pub mod instrument_calls;
+pub mod instrument_retag;
+pub mod instrument_sanitizer;
+pub mod simd_intrinsics;
+pub mod atomic_intrinsics;
+pub mod fold_layout_calls;
+pub mod unused_must_use;
@@ -28,8 +28,6 @@ use rustc::mir::traversal::ReversePostorder;
 use rustc::ty::{self, TyCtxt};
 use syntax_pos::Span;
 
-use build::Location;
-
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
 
 use std::mem;
@@ -77,13 +75,15 @@ pub enum Candidate {
 
 struct TempCollector {
     temps: IndexVec<Temp, TempState>,
-    location: Location,
     span: Span
 }
 
 impl<'tcx> Visitor<'tcx> for TempCollector {
-    fn visit_lvalue(&mut self, lvalue: &Lvalue<'tcx>, context: LvalueContext) {
-        self.super_lvalue(lvalue, context);
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
+        self.super_lvalue(lvalue, context, location);
         if let Lvalue::Temp(index) = *lvalue {
             // Ignore drops, if the temp gets promoted,
             // then it's constant and thus drop is noop.
@@ -97,7 +97,7 @@ impl<'tcx> Visitor<'tcx> for TempCollector {
                     LvalueContext::Store |
                     LvalueContext::Call => {
                         *temp = TempState::Defined {
-                            location: self.location,
+                            location: location,
                             uses: 0
                         };
                         return;
@@ -122,27 +122,11 @@ impl<'tcx> Visitor<'tcx> for TempCollector {
     fn visit_source_info(&mut self, source_info: &SourceInfo) {
         self.span = source_info.span;
     }
-
-    fn visit_statement(&mut self, bb: BasicBlock, statement: &Statement<'tcx>) {
-        assert_eq!(self.location.block, bb);
-        self.super_statement(bb, statement);
-        self.location.statement_index += 1;
-    }
-
-    fn visit_basic_block_data(&mut self, bb: BasicBlock, data: &BasicBlockData<'tcx>) {
-        self.location.statement_index = 0;
-        self.location.block = bb;
-        self.super_basic_block_data(bb, data);
-    }
 }
 
 pub fn collect_temps(mir: &Mir, rpo: &mut ReversePostorder) -> IndexVec<Temp, TempState> {
     let mut collector = TempCollector {
         temps: IndexVec::from_elem(TempState::Undefined, &mir.temp_decls),
-        location: Location {
-            block: START_BLOCK,
-            statement_index: 0
-        },
         span: mir.span
     };
     for (bb, data) in rpo {
@@ -221,9 +205,15 @@ impl<'a, 'tcx> Promoter<'a, 'tcx> {
             let statement = &mut self.source[bb].statements[stmt_idx];
             let mut rhs = match statement.kind {
                 StatementKind::Assign(_, ref mut rhs) => rhs,
-                StatementKind::SetDiscriminant{ .. } =>
+                StatementKind::SetDiscriminant{ .. } |
+                StatementKind::Coverage { .. } |
+                StatementKind::Probe { .. } |
+                StatementKind::SanitizerCheck { .. } |
+                StatementKind::Subtype { .. } |
+                StatementKind::Retag { .. } |
+                StatementKind::Atomic { .. } =>
                     span_bug!(statement.source_info.span,
-                              "cannot promote SetDiscriminant {:?}",
+                              "cannot promote {:?}",
                               statement),
             };
             if self.keep_original {
@@ -261,14 +251,16 @@ impl<'a, 'tcx> Promoter<'a, 'tcx> {
         };
 
         // Then, recurse for components in the Rvalue or Call.
+        let location = Location { block: bb, statement_index: stmt_idx };
         if stmt_idx < no_stmts {
-            self.visit_rvalue(rvalue.as_mut().unwrap());
+            self.visit_rvalue(rvalue.as_mut().unwrap(), location);
         } else {
-            self.visit_terminator_kind(bb, call.as_mut().unwrap());
+            self.visit_terminator_kind(bb, call.as_mut().unwrap(), location);
         }
 
         let new_temp = self.promoted.temp_decls.push(TempDecl {
-            ty: self.source.temp_decls[temp].ty
+            ty: self.source.temp_decls[temp].ty,
+            span: self.source.temp_decls[temp].span
         });
 
         // Inject the Rvalue or Call into the promoted MIR.
@@ -304,30 +296,41 @@ impl<'a, 'tcx> Promoter<'a, 'tcx> {
                 index: Promoted::new(self.source.promoted.len())
             }
         });
-        let mut rvalue = match candidate {
-            Candidate::Ref(Location { block: bb, statement_index: stmt_idx }) => {
-                let ref mut statement = self.source[bb].statements[stmt_idx];
+        let (mut rvalue, location) = match candidate {
+            Candidate::Ref(location) => {
+                let ref mut statement =
+                    self.source[location.block].statements[location.statement_index];
                 match statement.kind {
                     StatementKind::Assign(_, ref mut rvalue) => {
-                        mem::replace(rvalue, Rvalue::Use(new_operand))
+                        (mem::replace(rvalue, Rvalue::Use(new_operand)), location)
                     }
-                    StatementKind::SetDiscriminant{ .. } => {
+                    StatementKind::SetDiscriminant{ .. } |
+                    StatementKind::Coverage { .. } |
+                    StatementKind::Probe { .. } |
+                    StatementKind::SanitizerCheck { .. } |
+                    StatementKind::Subtype { .. } |
+                    StatementKind::Retag { .. } |
+                    StatementKind::Atomic { .. } => {
                         span_bug!(statement.source_info.span,
-                                  "cannot promote SetDiscriminant {:?}",
+                                  "cannot promote {:?}",
                                   statement);
                     }
                 }
             }
             Candidate::ShuffleIndices(bb) => {
+                let location = Location {
+                    block: bb,
+                    statement_index: self.source[bb].statements.len()
+                };
                 match self.source[bb].terminator_mut().kind {
                     TerminatorKind::Call { ref mut args, .. } => {
-                        Rvalue::Use(mem::replace(&mut args[2], new_operand))
+                        (Rvalue::Use(mem::replace(&mut args[2], new_operand)), location)
                     }
                     _ => bug!()
                 }
             }
         };
-        self.visit_rvalue(&mut rvalue);
+        self.visit_rvalue(&mut rvalue, location);
         self.assign(Lvalue::ReturnPointer, rvalue, span);
         self.source.promoted.push(self.promoted);
     }
@@ -335,11 +338,14 @@ impl<'a, 'tcx> Promoter<'a, 'tcx> {
 
 /// Replaces all temporaries with their promoted counterparts.
 impl<'a, 'tcx> MutVisitor<'tcx> for Promoter<'a, 'tcx> {
-    fn visit_lvalue(&mut self, lvalue: &mut Lvalue<'tcx>, context: LvalueContext) {
+    fn visit_lvalue(&mut self,
+                    lvalue: &mut Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
         if let Lvalue::Temp(ref mut temp) = *lvalue {
             *temp = self.promote_temp(*temp);
         }
-        self.super_lvalue(lvalue, context);
+        self.super_lvalue(lvalue, context, location);
     }
 }
 
@@ -356,6 +362,18 @@ pub fn promote_candidates<'a, 'tcx>(mir: &mut Mir<'tcx>,
                     StatementKind::Assign(ref dest, _) => dest,
                     StatementKind::SetDiscriminant{ .. } =>
                         panic!("cannot promote SetDiscriminant"),
+                    StatementKind::Coverage { .. } =>
+                        panic!("cannot promote Coverage"),
+                    StatementKind::Probe { .. } =>
+                        panic!("cannot promote Probe"),
+                    StatementKind::SanitizerCheck { .. } =>
+                        panic!("cannot promote SanitizerCheck"),
+                    StatementKind::Subtype { .. } =>
+                        panic!("cannot promote Subtype"),
+                    StatementKind::Retag { .. } =>
+                        panic!("cannot promote Retag"),
+                    StatementKind::Atomic { .. } =>
+                        panic!("cannot promote Atomic"),
                 };
                 if let Lvalue::Temp(index) = *dest {
                     if temps[index] == TempState::PromotedOut {
@@ -393,7 +411,7 @@ pub fn promote_candidates<'a, 'tcx>(mir: &mut Mir<'tcx>,
                 IndexVec::new(),
                 IndexVec::new(),
                 IndexVec::new(),
-                vec![],
+                IndexVec::new(),
                 span
             ),
             temps: &mut temps,
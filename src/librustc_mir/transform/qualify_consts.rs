@@ -23,7 +23,6 @@ use rustc::hir::intravisit::FnKind;
 use rustc::hir::map::blocks::FnLikeNode;
 use rustc::traits::{self, Reveal};
 use rustc::ty::{self, TyCtxt, Ty};
-use rustc::ty::cast::CastTy;
 use rustc::mir::repr::*;
 use rustc::mir::mir_map::MirMap;
 use rustc::mir::traversal::{self, ReversePostorder};
@@ -37,8 +36,6 @@ use syntax_pos::Span;
 use std::collections::hash_map::Entry;
 use std::fmt;
 
-use build::Location;
-
 use super::promote_consts::{self, Candidate, TempState};
 
 bitflags! {
@@ -147,7 +144,6 @@ struct Qualifier<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
     return_qualif: Option<Qualif>,
     qualif: Qualif,
     const_fn_arg_vars: BitVector,
-    location: Location,
     temp_promotion_state: IndexVec<Temp, TempState>,
     promotion_candidates: Vec<Candidate>
 }
@@ -178,10 +174,6 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
             return_qualif: None,
             qualif: Qualif::empty(),
             const_fn_arg_vars: BitVector::new(mir.var_decls.len()),
-            location: Location {
-                block: START_BLOCK,
-                statement_index: 0
-            },
             temp_promotion_state: temps,
             promotion_candidates: vec![]
         }
@@ -290,7 +282,7 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
     }
 
     /// Assign the current qualification to the given destination.
-    fn assign(&mut self, dest: &Lvalue<'tcx>) {
+    fn assign(&mut self, dest: &Lvalue<'tcx>, location: Location) {
         let qualif = self.qualif;
         let span = self.span;
         let store = |slot: &mut Option<Qualif>| {
@@ -328,7 +320,7 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
             // This must be an explicit assignment.
             _ => {
                 // Catch more errors in the destination.
-                self.visit_lvalue(dest, LvalueContext::Store);
+                self.visit_lvalue(dest, LvalueContext::Store, location);
                 self.statement_like();
             }
         }
@@ -364,6 +356,7 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
                 TerminatorKind::SwitchInt {..} |
                 TerminatorKind::DropAndReplace { .. } |
                 TerminatorKind::Resume |
+                TerminatorKind::Abort |
                 TerminatorKind::Unreachable => None,
 
                 TerminatorKind::Return => {
@@ -394,9 +387,13 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
 
                     // Make sure there are no extra unassigned variables.
                     self.qualif = Qualif::NOT_CONST;
+                    let location = Location {
+                        block: bb,
+                        statement_index: mir[bb].statements.len()
+                    };
                     for index in 0..mir.var_decls.len() {
                         if !self.const_fn_arg_vars.contains(index) {
-                            self.assign(&Lvalue::Var(Var::new(index)));
+                            self.assign(&Lvalue::Var(Var::new(index)), location);
                         }
                     }
 
@@ -436,13 +433,77 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
         }
         self.qualif
     }
+
+    /// Accumulates the effects of borrowing `lvalue`, or of taking its
+    /// address with a raw pointer, in `self.qualif`.
+    fn borrow(&mut self, kind: BorrowKind, lvalue: &Lvalue<'tcx>) {
+        // Static lvalues in consts would have errored already,
+        // only keep track of references to them here.
+        if self.qualif.intersects(Qualif::STATIC) {
+            self.qualif = self.qualif - Qualif::STATIC;
+            self.add(Qualif::STATIC_REF);
+        }
+
+        let ty = lvalue.ty(self.mir, self.tcx).to_ty(self.tcx);
+        if kind == BorrowKind::Mut {
+            // In theory, any zero-sized value could be borrowed
+            // mutably without consequences. However, only &mut []
+            // is allowed right now, and only in functions.
+            let allow = if self.mode == Mode::StaticMut {
+                // Inside a `static mut`, &mut [...] is also allowed.
+                match ty.sty {
+                    ty::TyArray(..) | ty::TySlice(_) => {
+                        // Mutating can expose drops, be conservative.
+                        self.add_type(ty);
+                        self.deny_drop();
+                        true
+                    }
+                    _ => false
+                }
+            } else if let ty::TyArray(_, 0) = ty.sty {
+                self.mode == Mode::Fn
+            } else {
+                false
+            };
+
+            if !allow {
+                self.add(Qualif::NOT_CONST);
+                if self.mode != Mode::Fn {
+                    struct_span_err!(self.tcx.sess,  self.span, E0017,
+                                     "references in {}s may only refer \
+                                      to immutable values", self.mode)
+                        .span_label(self.span, &format!("{}s require immutable values",
+                                                        self.mode))
+                        .emit();
+                }
+            }
+        } else {
+            // Constants cannot be borrowed if they contain interior mutability as
+            // it means that our "silent insertion of statics" could change
+            // initializer values (very bad).
+            if self.qualif.intersects(Qualif::MUTABLE_INTERIOR) {
+                // Replace MUTABLE_INTERIOR with NOT_CONST to avoid
+                // duplicate errors (from reborrowing, for example).
+                self.qualif = self.qualif - Qualif::MUTABLE_INTERIOR;
+                self.add(Qualif::NOT_CONST);
+                if self.mode != Mode::Fn {
+                    span_err!(self.tcx.sess, self.span, E0492,
+                              "cannot borrow a constant which contains \
+                               interior mutability, create a static instead");
+                }
+            }
+        }
+    }
 }
 
 /// Accumulates an Rvalue or Call's effects in self.qualif.
 /// For functions (constant or not), it also records
 /// candidates for promotion in promotion_candidates.
 impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
-    fn visit_lvalue(&mut self, lvalue: &Lvalue<'tcx>, context: LvalueContext) {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
         match *lvalue {
             Lvalue::Arg(_) => {
                 self.add(Qualif::FN_ARGUMENT);
@@ -474,7 +535,7 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
             }
             Lvalue::Projection(ref proj) => {
                 self.nest(|this| {
-                    this.super_lvalue(lvalue, context);
+                    this.super_lvalue(lvalue, context, location);
                     match proj.elem {
                         ProjectionElem::Deref => {
                             if !this.try_consume() {
@@ -520,11 +581,11 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
         }
     }
 
-    fn visit_operand(&mut self, operand: &Operand<'tcx>) {
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
         match *operand {
             Operand::Consume(_) => {
                 self.nest(|this| {
-                    this.super_operand(operand);
+                    this.super_operand(operand, location);
                     this.try_consume();
                 });
             }
@@ -563,15 +624,18 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
         }
     }
 
-    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>) {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
         // Recurse through operands and lvalues.
-        self.super_rvalue(rvalue);
+        self.super_rvalue(rvalue, location);
 
         match *rvalue {
             Rvalue::Use(_) |
             Rvalue::Repeat(..) |
             Rvalue::UnaryOp(..) |
             Rvalue::CheckedBinaryOp(..) |
+            Rvalue::Cast(CastKind::Misc, _, _) |
+            Rvalue::Cast(CastKind::PointerFromExposedAddress, _, _) |
+            Rvalue::Cast(CastKind::IntTruncate, _, _) |
             Rvalue::Cast(CastKind::ReifyFnPointer, _, _) |
             Rvalue::Cast(CastKind::UnsafeFnPointer, _, _) |
             Rvalue::Cast(CastKind::Unsize, _, _) => {}
@@ -583,65 +647,10 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
             }
 
             Rvalue::Ref(_, kind, ref lvalue) => {
-                // Static lvalues in consts would have errored already,
-                // only keep track of references to them here.
-                if self.qualif.intersects(Qualif::STATIC) {
-                    self.qualif = self.qualif - Qualif::STATIC;
-                    self.add(Qualif::STATIC_REF);
-                }
-
-                let ty = lvalue.ty(self.mir, self.tcx).to_ty(self.tcx);
-                if kind == BorrowKind::Mut {
-                    // In theory, any zero-sized value could be borrowed
-                    // mutably without consequences. However, only &mut []
-                    // is allowed right now, and only in functions.
-                    let allow = if self.mode == Mode::StaticMut {
-                        // Inside a `static mut`, &mut [...] is also allowed.
-                        match ty.sty {
-                            ty::TyArray(..) | ty::TySlice(_) => {
-                                // Mutating can expose drops, be conservative.
-                                self.add_type(ty);
-                                self.deny_drop();
-                                true
-                            }
-                            _ => false
-                        }
-                    } else if let ty::TyArray(_, 0) = ty.sty {
-                        self.mode == Mode::Fn
-                    } else {
-                        false
-                    };
-
-                    if !allow {
-                        self.add(Qualif::NOT_CONST);
-                        if self.mode != Mode::Fn {
-                            struct_span_err!(self.tcx.sess,  self.span, E0017,
-                                             "references in {}s may only refer \
-                                              to immutable values", self.mode)
-                                .span_label(self.span, &format!("{}s require immutable values",
-                                                                self.mode))
-                                .emit();
-                        }
-                    }
-                } else {
-                    // Constants cannot be borrowed if they contain interior mutability as
-                    // it means that our "silent insertion of statics" could change
-                    // initializer values (very bad).
-                    if self.qualif.intersects(Qualif::MUTABLE_INTERIOR) {
-                        // Replace MUTABLE_INTERIOR with NOT_CONST to avoid
-                        // duplicate errors (from reborrowing, for example).
-                        self.qualif = self.qualif - Qualif::MUTABLE_INTERIOR;
-                        self.add(Qualif::NOT_CONST);
-                        if self.mode != Mode::Fn {
-                            span_err!(self.tcx.sess, self.span, E0492,
-                                      "cannot borrow a constant which contains \
-                                       interior mutability, create a static instead");
-                        }
-                    }
-                }
+                self.borrow(kind, lvalue);
 
                 // We might have a candidate for promotion.
-                let candidate = Candidate::Ref(self.location);
+                let candidate = Candidate::Ref(location);
                 if self.mode == Mode::Fn || self.mode == Mode::ConstFn {
                     if !self.qualif.intersects(Qualif::NEVER_PROMOTE) {
                         // We can only promote direct borrows of temps.
@@ -652,21 +661,22 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                 }
             }
 
-            Rvalue::Cast(CastKind::Misc, ref operand, cast_ty) => {
-                let operand_ty = operand.ty(self.mir, self.tcx);
-                let cast_in = CastTy::from_ty(operand_ty).expect("bad input type for cast");
-                let cast_out = CastTy::from_ty(cast_ty).expect("bad output type for cast");
-                match (cast_in, cast_out) {
-                    (CastTy::Ptr(_), CastTy::Int(_)) |
-                    (CastTy::FnPtr, CastTy::Int(_)) => {
-                        self.add(Qualif::NOT_CONST);
-                        if self.mode != Mode::Fn {
-                            span_err!(self.tcx.sess, self.span, E0018,
-                                      "raw pointers cannot be cast to integers in {}s",
-                                      self.mode);
-                        }
-                    }
-                    _ => {}
+            Rvalue::RawPtr(mutbl, ref lvalue) => {
+                // Checked like a borrow, but never promoted: only lvalues
+                // have their address taken this way.
+                let kind = match mutbl {
+                    Mutability::Mut => BorrowKind::Mut,
+                    Mutability::Not => BorrowKind::Shared,
+                };
+                self.borrow(kind, lvalue);
+            }
+
+            Rvalue::Cast(CastKind::PointerExposeAddress, _, _) => {
+                self.add(Qualif::NOT_CONST);
+                if self.mode != Mode::Fn {
+                    span_err!(self.tcx.sess, self.span, E0018,
+                              "raw pointers cannot be cast to integers in {}s",
+                              self.mode);
                 }
             }
 
@@ -715,15 +725,19 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                 }
             }
 
+            Rvalue::Simd(..) |
             Rvalue::InlineAsm {..} => {
                 self.not_const();
             }
         }
     }
 
-    fn visit_terminator_kind(&mut self, bb: BasicBlock, kind: &TerminatorKind<'tcx>) {
+    fn visit_terminator_kind(&mut self,
+                             bb: BasicBlock,
+                             kind: &TerminatorKind<'tcx>,
+                             location: Location) {
         if let TerminatorKind::Call { ref func, ref args, ref destination, .. } = *kind {
-            self.visit_operand(func);
+            self.visit_operand(func, location);
 
             let fn_ty = func.ty(self.mir, self.tcx);
             let (is_shuffle, is_const_fn) = match fn_ty.sty {
@@ -737,7 +751,7 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
 
             for (i, arg) in args.iter().enumerate() {
                 self.nest(|this| {
-                    this.visit_operand(arg);
+                    this.visit_operand(arg, location);
                     if is_shuffle && i == 2 && this.mode == Mode::Fn {
                         let candidate = Candidate::ShuffleIndices(bb);
                         if !this.qualif.intersects(Qualif::NEVER_PROMOTE) {
@@ -815,16 +829,20 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                         self.deny_drop();
                     }
                 }
-                self.assign(dest);
+                self.assign(dest, location);
             }
         } else {
             // Qualify any operands inside other terminators.
-            self.super_terminator_kind(bb, kind);
+            self.super_terminator_kind(bb, kind, location);
         }
     }
 
-    fn visit_assign(&mut self, _: BasicBlock, dest: &Lvalue<'tcx>, rvalue: &Rvalue<'tcx>) {
-        self.visit_rvalue(rvalue);
+    fn visit_assign(&mut self,
+                    _: BasicBlock,
+                    dest: &Lvalue<'tcx>,
+                    rvalue: &Rvalue<'tcx>,
+                    location: Location) {
+        self.visit_rvalue(rvalue, location);
 
         // Check the allowed const fn argument forms.
         if let (Mode::ConstFn, &Lvalue::Var(index)) = (self.mode, dest) {
@@ -845,28 +863,29 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
             }
         }
 
-        self.assign(dest);
+        self.assign(dest, location);
     }
 
     fn visit_source_info(&mut self, source_info: &SourceInfo) {
         self.span = source_info.span;
     }
 
-    fn visit_statement(&mut self, bb: BasicBlock, statement: &Statement<'tcx>) {
-        assert_eq!(self.location.block, bb);
-        self.nest(|this| this.super_statement(bb, statement));
-        self.location.statement_index += 1;
-    }
-
-    fn visit_terminator(&mut self, bb: BasicBlock, terminator: &Terminator<'tcx>) {
-        assert_eq!(self.location.block, bb);
-        self.nest(|this| this.super_terminator(bb, terminator));
+    fn visit_statement(&mut self,
+                       bb: BasicBlock,
+                       statement: &Statement<'tcx>,
+                       location: Location) {
+        // Subtyping neither reads nor writes its lvalue.
+        if let StatementKind::Subtype { .. } = statement.kind {
+            return;
+        }
+        self.nest(|this| this.super_statement(bb, statement, location));
     }
 
-    fn visit_basic_block_data(&mut self, bb: BasicBlock, data: &BasicBlockData<'tcx>) {
-        self.location.statement_index = 0;
-        self.location.block = bb;
-        self.super_basic_block_data(bb, data);
+    fn visit_terminator(&mut self,
+                        bb: BasicBlock,
+                        terminator: &Terminator<'tcx>,
+                        location: Location) {
+        self.nest(|this| this.super_terminator(bb, terminator, location));
     }
 }
 
@@ -921,7 +940,8 @@ impl<'tcx> MirMapPass<'tcx> for QualifyAndPromoteConstants {
 
         // First, visit `const` items, potentially recursing, to get
         // accurate MUTABLE_INTERIOR and NEEDS_DROP qualifications.
-        let keys = map.map.keys();
+        let mut keys = map.map.keys();
+        keys.sort();
         for &def_id in &keys {
             let _task = tcx.dep_graph.in_task(DepNode::Mir(def_id));
             let id = tcx.map.as_local_node_id(def_id).unwrap();
@@ -0,0 +1,113 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass replaces calls to the `simd_extract`, `simd_insert` and
+//! `simd_shuffleN` platform intrinsics with `Rvalue::Simd` assignments,
+//! so that later passes do not have to treat them as opaque calls.
+//!
+//! Only calls on vector types that are known to be valid are replaced.
+//! Generic calls are left alone, as whether their types are vectors is
+//! only known after monomorphization, when trans reports the calls that
+//! turn out to be invalid.
+//!
+//! It has to run after constant promotion, which turns the indices of
+//! shuffles into constants.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use syntax::abi::Abi;
+
+pub struct SimdIntrinsics;
+
+impl<'tcx> MirPass<'tcx> for SimdIntrinsics {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    _src: MirSource, mir: &mut Mir<'tcx>) {
+        let lowered: Vec<_> = {
+            let mir: &Mir<'tcx> = mir;
+            mir.basic_blocks().iter_enumerated().filter_map(|(bb, data)| {
+                simd_rvalue(tcx, mir, &data.terminator().kind).map(|lowered| (bb, lowered))
+            }).collect()
+        };
+
+        for (bb, (dest, rvalue, target)) in lowered {
+            let data = &mut mir.basic_blocks_mut()[bb];
+            let source_info = data.terminator().source_info;
+            data.statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(dest, rvalue),
+            });
+            // The cleanup block of the call is left unreachable, and
+            // removed by the next `SimplifyCfg`.
+            data.terminator_mut().kind = TerminatorKind::Goto { target: target };
+        }
+    }
+}
+
+impl Pass for SimdIntrinsics {}
+
+/// Returns the destination, rvalue and successor that replace `kind` if it
+/// is a valid call to one of the SIMD intrinsics that have an rvalue.
+fn simd_rvalue<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                         mir: &Mir<'tcx>,
+                         kind: &TerminatorKind<'tcx>)
+                         -> Option<(Lvalue<'tcx>, Rvalue<'tcx>, BasicBlock)> {
+    let (func, args, dest, target) = match *kind {
+        TerminatorKind::Call { ref func, ref args, destination: Some((ref dest, target)), .. } => {
+            (func, args, dest, target)
+        }
+        _ => return None,
+    };
+    let name = match func.ty(mir, tcx).sty {
+        ty::TyFnDef(def_id, _, f) if f.abi == Abi::PlatformIntrinsic => {
+            tcx.item_name(def_id).as_str()
+        }
+        _ => return None,
+    };
+    if !name.starts_with("simd_") || args.is_empty() {
+        return None;
+    }
+
+    let arg_tys: Vec<Ty<'tcx>> = args.iter().map(|arg| arg.ty(mir, tcx)).collect();
+    let ret_ty = dest.ty(mir, tcx).to_ty(tcx);
+    if arg_tys.iter().chain(Some(&ret_ty)).any(|ty| ty.needs_subst()) {
+        return None;
+    }
+    let vector_ty = arg_tys[0];
+    if !vector_ty.is_simd() {
+        return None;
+    }
+    let elem_ty = vector_ty.simd_type(tcx);
+
+    // The number and the types of the other arguments are checked by typeck.
+    let op = match &name[..] {
+        "simd_extract" if ret_ty == elem_ty => SimdOp::Extract,
+        "simd_insert" if arg_tys[2] == elem_ty => SimdOp::Insert,
+        name if name.starts_with("simd_shuffle") => {
+            let n: usize = match name["simd_shuffle".len()..].parse() {
+                Ok(n) => n,
+                Err(_) => return None,
+            };
+            if !ret_ty.is_simd() ||
+               ret_ty.simd_size(tcx) != n ||
+               ret_ty.simd_type(tcx) != elem_ty {
+                return None;
+            }
+            // Promotion failed if the indices are not a constant, which
+            // has already been reported.
+            if let Operand::Consume(_) = args[2] {
+                return None;
+            }
+            SimdOp::Shuffle(ret_ty)
+        }
+        _ => return None,
+    };
+    Some((dest.clone(), Rvalue::Simd(op, args.clone()), target))
+}
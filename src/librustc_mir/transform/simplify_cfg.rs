@@ -116,6 +116,7 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
                 let mut inner_changed = true;
                 while inner_changed {
                     inner_changed = false;
+                    inner_changed |= self.prune_unreachable_branches(&mut terminator);
                     inner_changed |= self.simplify_branch(&mut terminator);
                     inner_changed |= self.merge_successor(&mut new_stmts, &mut terminator);
                     changed |= inner_changed;
@@ -190,6 +191,55 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
         true
     }
 
+    // Whether `bb` ends in `Unreachable`. Its statements, which can't
+    // diverge, are dead as well then.
+    fn is_unreachable(&self, bb: BasicBlock) -> bool {
+        match self.basic_blocks[bb].terminator {
+            Some(Terminator { kind: TerminatorKind::Unreachable, .. }) => true,
+            _ => false
+        }
+    }
+
+    // turn a branch whose successors are all unreachable but one into a
+    // goto to that one, and one whose successors are all unreachable, or
+    // that has none, e.g. a switch on an empty enum, into an unreachable
+    fn prune_unreachable_branches(&mut self, terminator: &mut Terminator<'tcx>) -> bool {
+        match terminator.kind {
+            TerminatorKind::If { .. } |
+            TerminatorKind::Switch { .. } |
+            TerminatorKind::SwitchInt { .. } => {},
+            _ => return false
+        };
+
+        let reachable = {
+            let successors = terminator.successors();
+            let mut reachable = successors.iter().filter(|&&s| !self.is_unreachable(s));
+            match (reachable.next(), reachable.next()) {
+                (None, _) => None,
+                (Some(&first), None) => Some(first),
+                (Some(&first), Some(&second)) => {
+                    if second != first || reachable.any(|&s| s != first) {
+                        return false
+                    }
+                    Some(first)
+                }
+            }
+        };
+
+        debug!("pruning unreachable successors of {:?}", terminator);
+        for &successor in terminator.successors().iter() {
+            self.pred_count[successor] -= 1;
+        }
+        terminator.kind = match reachable {
+            Some(target) => {
+                self.pred_count[target] += 1;
+                TerminatorKind::Goto { target: target }
+            }
+            None => TerminatorKind::Unreachable
+        };
+        true
+    }
+
     // turn a branch with all successors identical to a goto
     fn simplify_branch(&mut self, terminator: &mut Terminator<'tcx>) -> bool {
         match terminator.kind {
@@ -228,10 +278,11 @@ fn remove_dead_blocks(mir: &mut Mir) {
     let basic_blocks = mir.basic_blocks_mut();
 
     let num_blocks = basic_blocks.len();
-    let mut replacements : Vec<_> = (0..num_blocks).map(BasicBlock::new).collect();
+    let mut replacements: IndexVec<BasicBlock, _> =
+        (0..num_blocks).map(BasicBlock::new).collect();
     let mut used_blocks = 0;
     for alive_index in seen.iter() {
-        replacements[alive_index] = BasicBlock::new(used_blocks);
+        replacements[BasicBlock::new(alive_index)] = BasicBlock::new(used_blocks);
         if alive_index != used_blocks {
             // Swap the next alive block data with the current available slot. Since alive_index is
             // non-decreasing this is a valid operation.
@@ -243,7 +294,7 @@ fn remove_dead_blocks(mir: &mut Mir) {
 
     for block in basic_blocks {
         for target in block.terminator_mut().successors_mut() {
-            *target = replacements[target.index()];
+            *target = replacements[*target];
         }
     }
 }
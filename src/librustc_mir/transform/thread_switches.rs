@@ -0,0 +1,149 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass makes a block that ends by setting a local to a known value
+//! jump directly to the block a later switch on that local would branch
+//! to, when only empty blocks lie in between. State machines written as
+//! `loop { match state { ... } }`, whose arms set the next state, then go
+//! straight from one state to the next rather than through the `match` on
+//! every transition; the switch is left for the first entry into the loop.
+//!
+//! Only an assignment that is the last statement of its block is used, so
+//! nothing can change the local between it and the switch.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+
+use std::cmp::Ordering;
+
+pub struct ThreadSwitches;
+
+/// The value a block leaves in a local.
+#[derive(Copy, Clone)]
+enum KnownValue<'a> {
+    Constant(&'a ConstVal),
+    Variant(usize),
+}
+
+impl<'tcx> MirPass<'tcx> for ThreadSwitches {
+    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    _src: MirSource, mir: &mut Mir<'tcx>) {
+        // Blocks are only threaded through empty blocks, which are not
+        // threaded themselves, so all targets can be found up front.
+        let threaded: Vec<_> = {
+            let mir: &Mir<'tcx> = mir;
+            mir.basic_blocks().iter_enumerated().filter_map(|(bb, data)| {
+                threaded_target(mir, data).map(|target| (bb, target))
+            }).collect()
+        };
+
+        for (bb, target) in threaded {
+            debug!("ThreadSwitches: {:?} jumps to {:?}", bb, target);
+            mir.basic_blocks_mut()[bb].terminator_mut().kind =
+                TerminatorKind::Goto { target: target };
+        }
+    }
+}
+
+impl Pass for ThreadSwitches {}
+
+/// Returns the block `data` can jump to directly, if it ends by setting a
+/// local and going, through empty blocks only, to a switch on that local.
+fn threaded_target<'tcx>(mir: &Mir<'tcx>, data: &BasicBlockData<'tcx>) -> Option<BasicBlock> {
+    let mut target = match data.terminator().kind {
+        TerminatorKind::Goto { target } => target,
+        _ => return None,
+    };
+    let (lvalue, value) = match data.statements.last().map(|statement| &statement.kind) {
+        Some(&StatementKind::Assign(ref lvalue, ref rvalue)) => {
+            match *rvalue {
+                Rvalue::Use(Operand::Constant(Constant {
+                    literal: Literal::Value { ref value }, ..
+                })) => (lvalue, KnownValue::Constant(value)),
+                Rvalue::Aggregate(AggregateKind::Adt(_, variant, _), _) => {
+                    (lvalue, KnownValue::Variant(variant))
+                }
+                _ => return None,
+            }
+        }
+        Some(&StatementKind::SetDiscriminant { ref lvalue, variant_index }) => {
+            (lvalue, KnownValue::Variant(variant_index))
+        }
+        _ => return None,
+    };
+    // Writes through a pointer could change anything reached through one.
+    match *lvalue {
+        Lvalue::Var(_) | Lvalue::Temp(_) | Lvalue::Arg(_) => {}
+        _ => return None,
+    }
+
+    // Empty blocks can form a cycle, so don't follow more gotos than there
+    // are blocks.
+    for _ in 0..mir.basic_blocks().len() {
+        let data = &mir[target];
+        if !data.statements.is_empty() {
+            return None;
+        }
+        match data.terminator().kind {
+            TerminatorKind::Goto { target: next } => target = next,
+            TerminatorKind::If { cond: Operand::Consume(ref cond), targets } if cond == lvalue => {
+                return match value {
+                    KnownValue::Constant(&ConstVal::Bool(true)) => Some(targets.0),
+                    KnownValue::Constant(&ConstVal::Bool(false)) => Some(targets.1),
+                    _ => None,
+                };
+            }
+            TerminatorKind::Switch { ref discr, ref targets, .. } if discr == lvalue => {
+                return match value {
+                    KnownValue::Variant(variant) => targets.get(variant).cloned(),
+                    _ => None,
+                };
+            }
+            TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. }
+                if discr == lvalue => {
+                return match value {
+                    KnownValue::Constant(value) => switch_int_target(values, targets, value),
+                    _ => None,
+                };
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Returns the target a `SwitchInt` with `values` and `targets` branches to
+/// when its discriminant is `value`, if that is certain.
+fn switch_int_target(values: &[ConstVal],
+                     targets: &[BasicBlock],
+                     value: &ConstVal)
+                     -> Option<BasicBlock> {
+    for (i, candidate) in values.iter().enumerate() {
+        let equal = match (candidate, value) {
+            (&ConstVal::Integral(a), &ConstVal::Integral(b)) => {
+                match a.try_cmp(b) {
+                    Ok(ordering) => ordering == Ordering::Equal,
+                    Err(_) => return None,
+                }
+            }
+            (&ConstVal::Bool(a), &ConstVal::Bool(b)) => a == b,
+            (&ConstVal::Char(a), &ConstVal::Char(b)) => a == b,
+            _ => return None,
+        };
+        if equal {
+            return Some(targets[i]);
+        }
+    }
+    // The value is none of the values, which were all comparable with it,
+    // so the switch takes its otherwise branch.
+    targets.get(values.len()).cloned()
+}
@@ -19,6 +19,7 @@ use rustc::mir::repr::*;
 use rustc::mir::tcx::LvalueTy;
 use rustc::mir::transform::{MirPass, MirSource, Pass};
 use rustc::mir::visit::{self, Visitor};
+use std::cell::Cell;
 use std::fmt;
 use syntax_pos::{Span, DUMMY_SP};
 
@@ -29,7 +30,8 @@ macro_rules! span_mirbug {
         $context.tcx().sess.span_warn(
             $context.last_span,
             &format!("broken MIR ({:?}): {}", $elem, format!($($message)*))
-        )
+        );
+        $context.cx().reported_mirbug.set(true)
     })
 }
 
@@ -40,6 +42,7 @@ macro_rules! span_mirbug_and_err {
                 $context.last_span,
                 &format!("broken MIR ({:?}): {:?}", $elem, format!($($message)*))
             );
+            $context.cx().reported_mirbug.set(true);
             $context.error()
         }
     })
@@ -68,17 +71,20 @@ impl<'a, 'b, 'gcx, 'tcx> Visitor<'tcx> for TypeVerifier<'a, 'b, 'gcx, 'tcx> {
         }
     }
 
-    fn visit_lvalue(&mut self, lvalue: &Lvalue<'tcx>, _context: visit::LvalueContext) {
-        self.sanitize_lvalue(lvalue);
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    _context: visit::LvalueContext,
+                    location: Location) {
+        self.sanitize_lvalue(lvalue, location);
     }
 
-    fn visit_constant(&mut self, constant: &Constant<'tcx>) {
-        self.super_constant(constant);
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, location: Location) {
+        self.super_constant(constant, location);
         self.sanitize_type(constant, constant.ty);
     }
 
-    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>) {
-        self.super_rvalue(rvalue);
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        self.super_rvalue(rvalue, location);
         if let Some(ty) = rvalue.ty(self.mir, self.tcx()) {
             self.sanitize_type(rvalue, ty);
         }
@@ -118,6 +124,10 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
         self.cx.infcx.tcx
     }
 
+    fn cx(&self) -> &TypeChecker<'b, 'gcx, 'tcx> {
+        self.cx
+    }
+
     fn sanitize_type(&mut self, parent: &fmt::Debug, ty: Ty<'tcx>) -> Ty<'tcx> {
         if ty.needs_infer() || ty.has_escaping_regions() || ty.references_error() {
             span_mirbug_and_err!(self, parent, "bad type {:?}", ty)
@@ -126,7 +136,7 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
         }
     }
 
-    fn sanitize_lvalue(&mut self, lvalue: &Lvalue<'tcx>) -> LvalueTy<'tcx> {
+    fn sanitize_lvalue(&mut self, lvalue: &Lvalue<'tcx>, location: Location) -> LvalueTy<'tcx> {
         debug!("sanitize_lvalue: {:?}", lvalue);
         match *lvalue {
             Lvalue::Var(index) => LvalueTy::Ty { ty: self.mir.var_decls[index].ty },
@@ -145,14 +155,14 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
                 }
             }
             Lvalue::Projection(ref proj) => {
-                let base_ty = self.sanitize_lvalue(&proj.base);
+                let base_ty = self.sanitize_lvalue(&proj.base, location);
                 if let LvalueTy::Ty { ty } = base_ty {
                     if ty.references_error() {
                         assert!(self.errors_reported);
                         return LvalueTy::Ty { ty: self.tcx().types.err };
                     }
                 }
-                self.sanitize_projection(base_ty, &proj.elem, lvalue)
+                self.sanitize_projection(base_ty, &proj.elem, lvalue, location)
             }
         }
     }
@@ -160,7 +170,8 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
     fn sanitize_projection(&mut self,
                            base: LvalueTy<'tcx>,
                            pi: &LvalueElem<'tcx>,
-                           lvalue: &Lvalue<'tcx>)
+                           lvalue: &Lvalue<'tcx>,
+                           location: Location)
                            -> LvalueTy<'tcx> {
         debug!("sanitize_projection: {:?} {:?} {:?}", base, pi, lvalue);
         let tcx = self.tcx();
@@ -177,7 +188,7 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
                 }
             }
             ProjectionElem::Index(ref i) => {
-                self.visit_operand(i);
+                self.visit_operand(i, location);
                 let index_ty = i.ty(self.mir, tcx);
                 if index_ty != tcx.types.usize {
                     LvalueTy::Ty {
@@ -316,7 +327,9 @@ impl<'a, 'b, 'gcx, 'tcx> TypeVerifier<'a, 'b, 'gcx, 'tcx> {
 pub struct TypeChecker<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
     infcx: &'a InferCtxt<'a, 'gcx, 'tcx>,
     fulfillment_cx: traits::FulfillmentContext<'tcx>,
-    last_span: Span
+    last_span: Span,
+    /// Set once broken MIR has been reported.
+    reported_mirbug: Cell<bool>
 }
 
 impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
@@ -324,10 +337,15 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
         TypeChecker {
             infcx: infcx,
             fulfillment_cx: traits::FulfillmentContext::new(),
-            last_span: DUMMY_SP
+            last_span: DUMMY_SP,
+            reported_mirbug: Cell::new(false)
         }
     }
 
+    fn cx(&self) -> &Self {
+        self
+    }
+
     fn sub_types(&self, span: Span, sup: Ty<'tcx>, sub: Ty<'tcx>)
                  -> infer::UnitResult<'tcx>
     {
@@ -382,6 +400,48 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                                variant_index);
                 };
             }
+            StatementKind::Coverage { index, num_counters, .. } => {
+                if index >= num_counters {
+                    span_mirbug!(self, stmt, "coverage counter {} out of range", index);
+                }
+            }
+            // The lvalue itself has been checked by the `TypeVerifier`.
+            StatementKind::SanitizerCheck { .. } => {}
+            StatementKind::Probe { .. } => {}
+            StatementKind::Retag { ref lvalue, .. } => {
+                let lv_ty = lvalue.ty(mir, tcx).to_ty(tcx);
+                if let ty::TyRef(..) = lv_ty.sty {} else {
+                    span_mirbug!(self, stmt, "retag of non-reference {:?}: {:?}", lvalue, lv_ty);
+                }
+            }
+            StatementKind::Subtype { ref lvalue, ty } => {
+                let lv_ty = lvalue.ty(mir, tcx).to_ty(tcx);
+                if let Err(terr) = self.sub_types(self.last_span, ty, lv_ty) {
+                    span_mirbug!(self, stmt, "bad subtype ({:?}: {:?}): {:?}",
+                                 lv_ty, ty, terr);
+                }
+            }
+            StatementKind::Atomic { ref dest, ref ptr, ref value, .. } => {
+                let ptr_ty = ptr.ty(mir, tcx);
+                let pointee_ty = match ptr_ty.sty {
+                    ty::TyRawPtr(ty::TypeAndMut { ty, .. }) => ty,
+                    _ => {
+                        span_mirbug!(self, stmt, "atomic access through {:?}", ptr_ty);
+                        return;
+                    }
+                };
+                if !pointee_ty.is_integral() {
+                    span_mirbug!(self, stmt, "atomic access to {:?}", pointee_ty);
+                }
+                let dest_ty = dest.as_ref().map(|dest| dest.ty(mir, tcx).to_ty(tcx));
+                let value_ty = value.as_ref().map(|value| value.ty(mir, tcx));
+                for ty in dest_ty.into_iter().chain(value_ty) {
+                    if ty != pointee_ty {
+                        span_mirbug!(self, stmt, "bad atomic access ({:?} to {:?})",
+                                     ty, pointee_ty);
+                    }
+                }
+            }
         }
     }
 
@@ -393,6 +453,7 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
         match term.kind {
             TerminatorKind::Goto { .. } |
             TerminatorKind::Resume |
+            TerminatorKind::Abort |
             TerminatorKind::Return |
             TerminatorKind::Unreachable |
             TerminatorKind::Drop { .. } => {
@@ -473,13 +534,25 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                     span_mirbug!(self, term, "bad Assert ({:?}, not bool", cond_ty);
                 }
 
-                if let AssertMessage::BoundsCheck { ref len, ref index } = *msg {
-                    if len.ty(mir, tcx) != tcx.types.usize {
-                        span_mirbug!(self, len, "bounds-check length non-usize {:?}", len)
+                match *msg {
+                    AssertMessage::BoundsCheck { ref len, ref index } => {
+                        if len.ty(mir, tcx) != tcx.types.usize {
+                            span_mirbug!(self, len, "bounds-check length non-usize {:?}", len)
+                        }
+                        if index.ty(mir, tcx) != tcx.types.usize {
+                            span_mirbug!(self, index, "bounds-check index non-usize {:?}", index)
+                        }
                     }
-                    if index.ty(mir, tcx) != tcx.types.usize {
-                        span_mirbug!(self, index, "bounds-check index non-usize {:?}", index)
+                    AssertMessage::Math { ref operands, .. } => {
+                        for operand in operands {
+                            if !operand.ty(mir, tcx).is_integral() {
+                                span_mirbug!(self, operand,
+                                             "arithmetic operand non-integral {:?}", operand)
+                            }
+                        }
                     }
+                    AssertMessage::Misaligned |
+                    AssertMessage::NullPointer => {}
                 }
             }
         }
@@ -606,6 +679,11 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                     span_mirbug!(self, block, "resume on non-cleanup block!")
                 }
             }
+            TerminatorKind::Abort => {
+                if !is_cleanup {
+                    span_mirbug!(self, block, "abort on non-cleanup block!")
+                }
+            }
             TerminatorKind::Return => {
                 if is_cleanup {
                     span_mirbug!(self, block, "return on cleanup block")
@@ -712,19 +790,25 @@ impl<'tcx> MirPass<'tcx> for TypeckMir {
             return;
         }
         let param_env = ty::ParameterEnvironment::for_item(tcx, src.item_id());
-        tcx.infer_ctxt(None, Some(param_env), Reveal::NotSpecializable).enter(|infcx| {
-            let mut checker = TypeChecker::new(&infcx);
-            {
-                let mut verifier = TypeVerifier::new(&mut checker, mir);
-                verifier.visit_mir(mir);
-                if verifier.errors_reported {
-                    // don't do further checks to avoid ICEs
-                    return;
-                }
-            }
-            checker.typeck_mir(mir);
-            checker.verify_obligations(mir);
-        });
+        let reported_mirbug =
+            tcx.infer_ctxt(None, Some(param_env), Reveal::NotSpecializable).enter(|infcx| {
+                let mut checker = TypeChecker::new(&infcx);
+                {
+                    let mut verifier = TypeVerifier::new(&mut checker, mir);
+                    verifier.visit_mir(mir);
+                    if verifier.errors_reported {
+                        // don't do further checks to avoid ICEs
+                        return true;
+                    }
+                }
+                checker.typeck_mir(mir);
+                checker.verify_obligations(mir);
+                checker.reported_mirbug.get()
+            });
+        // The warnings would be lost if this MIR was cached.
+        if reported_mirbug {
+            tcx.sess.mir_diagnostic_nodes.borrow_mut().insert(src.item_id());
+        }
     }
 }
 
@@ -0,0 +1,183 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass reports values of `#[must_use]` types that are never used,
+//! because they are moved into a temporary aggregate that is dropped
+//! without being read, e.g. in `(tx.send(a), tx.send(b));`. The HIR lint
+//! only looks at the type of the whole statement, so it misses them, just
+//! like the ones built by a macro or taken out of a `Result` by `?`.
+//!
+//! Values that end up directly in a temporary which is never read are
+//! left to the HIR lint, as they are those of statements, or of `let _`,
+//! which is how results are ignored on purpose. Aggregates bound to `_`
+//! are left alone for the same reason.
+
+use rustc::hir::{self, intravisit};
+use rustc::hir::map::blocks::FnLikeNode;
+use rustc::lint::builtin::UNUSED_MUST_USE;
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::util::nodemap::FnvHashSet;
+use rustc_data_structures::indexed_vec::IndexVec;
+use syntax::attr::AttrMetaMethods;
+use syntax_pos::{Span, DUMMY_SP};
+
+pub struct UnusedMustUse;
+
+impl<'tcx> MirPass<'tcx> for UnusedMustUse {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return,
+        };
+
+        let mut uses = TempUses {
+            span: DUMMY_SP,
+            defs: IndexVec::from_elem(vec![], &mir.temp_decls),
+            reads: IndexVec::from_elem(0, &mir.temp_decls),
+            moved_into: IndexVec::from_elem(None, &mir.temp_decls),
+        };
+        uses.visit_mir(mir);
+
+        let mut ignored = IgnoredAggregates { spans: FnvHashSet() };
+        if let Some(fn_like) = FnLikeNode::from_node(tcx.map.get(id)) {
+            intravisit::walk_block(&mut ignored, fn_like.body());
+        }
+
+        for (temp, decl) in mir.temp_decls.iter_enumerated() {
+            let msg = match must_use_message(tcx, decl.ty) {
+                Some(msg) => msg,
+                None => continue,
+            };
+            let aggregate = match uses.discarded_into(temp) {
+                Some(aggregate) => aggregate,
+                None => continue,
+            };
+            if uses.defs[aggregate].iter().any(|span| ignored.spans.contains(span)) {
+                continue;
+            }
+            for &span in &uses.defs[temp] {
+                tcx.sess.add_lint(UNUSED_MUST_USE, id, span, msg.clone());
+            }
+        }
+    }
+}
+
+impl Pass for UnusedMustUse {}
+
+/// Returns the message of the lint for values of `ty`, if it is `#[must_use]`.
+fn must_use_message<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> Option<String> {
+    let def = match ty.sty {
+        ty::TyStruct(def, _) | ty::TyEnum(def, _) => def,
+        _ => return None,
+    };
+    tcx.get_attrs(def.did).iter().find(|attr| attr.check_name("must_use")).map(|attr| {
+        let mut msg = "unused result which must be used".to_string();
+        if let Some(s) = attr.value_str() {
+            msg.push_str(": ");
+            msg.push_str(&s);
+        }
+        msg
+    })
+}
+
+struct TempUses {
+    span: Span,
+    /// The spans of the statements and calls that store into each temp.
+    defs: IndexVec<Temp, Vec<Span>>,
+    /// How many times each temp is read, other than by being dropped or
+    /// moved into an aggregate.
+    reads: IndexVec<Temp, usize>,
+    /// The temp holding the aggregate each temp is moved into, if any.
+    moved_into: IndexVec<Temp, Option<Temp>>,
+}
+
+impl TempUses {
+    /// Returns the outermost aggregate `temp` is moved into, if neither
+    /// `temp` nor any of the aggregates containing it is ever read.
+    fn discarded_into(&self, temp: Temp) -> Option<Temp> {
+        let mut aggregate = None;
+        let mut current = temp;
+        loop {
+            if self.reads[current] > 0 {
+                return None;
+            }
+            match self.moved_into[current] {
+                Some(outer) => {
+                    aggregate = Some(outer);
+                    current = outer;
+                }
+                None => return aggregate,
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for TempUses {
+    fn visit_statement(&mut self,
+                       block: BasicBlock,
+                       statement: &Statement<'tcx>,
+                       location: Location) {
+        self.span = statement.source_info.span;
+        if let StatementKind::Assign(Lvalue::Temp(dest), Rvalue::Aggregate(_, ref operands)) =
+                statement.kind {
+            self.defs[dest].push(self.span);
+            for operand in operands {
+                match *operand {
+                    Operand::Consume(Lvalue::Temp(temp)) => self.moved_into[temp] = Some(dest),
+                    _ => self.visit_operand(operand, location),
+                }
+            }
+            return;
+        }
+        self.super_statement(block, statement, location);
+    }
+
+    fn visit_terminator(&mut self,
+                        block: BasicBlock,
+                        terminator: &Terminator<'tcx>,
+                        location: Location) {
+        self.span = terminator.source_info.span;
+        self.super_terminator(block, terminator, location);
+    }
+
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
+        if let Lvalue::Temp(temp) = *lvalue {
+            match context {
+                LvalueContext::Store | LvalueContext::Call => self.defs[temp].push(self.span),
+                LvalueContext::Drop => {}
+                _ => self.reads[temp] += 1,
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// Collects the spans of the initializers of `let _`.
+struct IgnoredAggregates {
+    spans: FnvHashSet<Span>,
+}
+
+impl<'v> intravisit::Visitor<'v> for IgnoredAggregates {
+    fn visit_local(&mut self, local: &'v hir::Local) {
+        if let hir::PatKind::Wild = local.pat.node {
+            if let Some(ref init) = local.init {
+                self.spans.insert(init.span);
+            }
+        }
+        intravisit::walk_local(self, local);
+    }
+}
@@ -85,8 +85,14 @@ pub fn from_fn_attrs(ccx: &CrateContext, attrs: &[ast::Attribute], llfn: ValueRe
         } else if attr.check_name("allocator") {
             Attribute::NoAlias.apply_llfn(
                 llvm::AttributePlace::ReturnValue(), llfn);
-        } else if attr.check_name("unwind") {
-            unwind(llfn, true);
         }
     }
+
+    // A malformed `#[unwind]` was already reported when checking attributes.
+    match find_unwind_attr(None, attrs) {
+        Some(UnwindAttr::Allowed) => unwind(llfn, true),
+        // The function's landing pads abort, so nothing unwinds out of it.
+        Some(UnwindAttr::Aborts) => unwind(llfn, false),
+        None => {}
+    }
 }
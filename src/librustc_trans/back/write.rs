@@ -664,7 +664,12 @@ pub fn run_passes(sess: &Session,
 
     // Figure out what we actually need to build.
 
-    let mut modules_config = ModuleConfig::new(tm, sess.opts.cg.passes.clone());
+    let mut module_passes = sess.opts.cg.passes.clone();
+    if sess.opts.debugging_opts.instrument_coverage {
+        // Lower the counters inserted by the MIR coverage instrumentation.
+        module_passes.push("instrprof".to_string());
+    }
+    let mut modules_config = ModuleConfig::new(tm, module_passes);
     let mut metadata_config = ModuleConfig::new(tm, vec!());
 
     modules_config.opt_level = Some(get_llvm_opt_level(sess.opts.optimize));
@@ -708,7 +713,7 @@ pub fn run_passes(sess: &Session,
                 modules_config.emit_obj = true;
                 metadata_config.emit_obj = true;
             },
-            OutputType::DepInfo => {}
+            OutputType::DepInfo | OutputType::MirJson | OutputType::MirHtml => {}
         }
     }
 
@@ -845,7 +850,9 @@ pub fn run_passes(sess: &Session,
                 copy_if_one_unit(OutputType::Object, true);
             }
             OutputType::Exe |
-            OutputType::DepInfo => {}
+            OutputType::DepInfo |
+            OutputType::MirJson |
+            OutputType::MirHtml => {}
         }
     }
     let user_wants_bitcode = user_wants_bitcode;
@@ -1854,6 +1854,9 @@ pub fn trans_closure<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
                                &arena);
 
     if fcx.mir.is_some() {
+        if ccx.sess().opts.debugging_opts.print_mono_mir.is_some() {
+            mir::print_mono_mir(ccx, instance, inlined_id, &fcx.mir());
+        }
         return mir::trans_mir(&fcx);
     }
 
@@ -50,6 +50,7 @@ use syntax::attr::{self, AttrMetaMethods};
 use syntax::parse::token;
 use syntax::ptr::P;
 use syntax_pos::Span;
+use errors::DiagnosticBuilder;
 
 pub type FnArgMap<'a> = Option<&'a NodeMap<ValueRef>>;
 
@@ -522,21 +523,62 @@ pub fn const_err<T>(cx: &CrateContext,
                     result: Result<T, ErrKind>,
                     trueconst: TrueConst)
                     -> Result<T, ConstEvalFailure> {
-    match (result, trueconst) {
-        (Ok(x), _) => Ok(x),
+    const_err_with_note(cx, span, result, trueconst, None)
+}
+
+/// Like `const_err`, with `note` added to the diagnostic, if any.
+pub fn const_err_with_note<T>(cx: &CrateContext,
+                              span: Span,
+                              result: Result<T, ErrKind>,
+                              trueconst: TrueConst,
+                              note: Option<&str>)
+                              -> Result<T, ConstEvalFailure> {
+    const_err_with_notes(cx, span, result, trueconst, note, |_| {})
+}
+
+/// Like `const_err_with_note`, with the notes added by `add_notes` after
+/// `note`.
+pub fn const_err_with_notes<T, F>(cx: &CrateContext,
+                                  span: Span,
+                                  result: Result<T, ErrKind>,
+                                  trueconst: TrueConst,
+                                  note: Option<&str>,
+                                  add_notes: F)
+                                  -> Result<T, ConstEvalFailure>
+    where F: FnOnce(&mut DiagnosticBuilder)
+{
+    let (err, mut diag) = match (result, trueconst) {
+        (Ok(x), _) => return Ok(x),
         (Err(err), TrueConst::Yes) => {
             let err = ConstEvalErr{ span: span, kind: err };
-            report_const_eval_err(cx.tcx(), &err, span, "expression").emit();
-            Err(Compiletime(err))
+            let diag = report_const_eval_err(cx.tcx(), &err, span, "expression");
+            (Compiletime(err), diag)
         },
         (Err(err), TrueConst::No) => {
             let err = ConstEvalErr{ span: span, kind: err };
             let mut diag = cx.tcx().sess.struct_span_warn(
                 span, "this expression will panic at run-time");
             note_const_eval_err(cx.tcx(), &err, span, "expression", &mut diag);
-            diag.emit();
-            Err(Runtime(err))
+            (Runtime(err), diag)
         },
+    };
+    if let Some(note) = note {
+        diag.note(note);
+    }
+    add_notes(&mut diag);
+    diag.emit();
+    Err(err)
+}
+
+/// A note giving the values of the operands of an arithmetic operation
+/// that fails, the left one first.
+pub fn math_operands_note(operands: &[ConstInt]) -> String {
+    match operands.len() {
+        1 => format!("the operand is `{}`", operands[0]),
+        _ => {
+            let operands: Vec<_> = operands.iter().map(|op| format!("`{}`", op)).collect();
+            format!("the operands are {}", operands.join(" and "))
+        }
     }
 }
 
@@ -124,6 +124,12 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 }
             }
 
+            mir::TerminatorKind::Abort => {
+                let trap = bcx.ccx().get_intrinsic(&("llvm.trap"));
+                bcx.call(trap, &[], cleanup_bundle);
+                bcx.unreachable();
+            }
+
             mir::TerminatorKind::Goto { target } => {
                 funclet_br(self, bcx, target);
             }
@@ -274,7 +280,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                     use rustc_const_math::ConstMathErr::Overflow;
                     use rustc_const_math::Op::Neg;
 
-                    if let mir::AssertMessage::Math(Overflow(Neg)) = *msg {
+                    if let mir::AssertMessage::Math { err: Overflow(Neg), .. } = *msg {
                         const_cond = Some(expected);
                     }
                 }
@@ -309,6 +315,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 let line = C_u32(bcx.ccx(), loc.line as u32);
 
                 // Put together the arguments to the panic entry point.
+                let mut const_note = None;
                 let (lang_item, args, const_err) = match *msg {
                     mir::AssertMessage::BoundsCheck { ref len, ref index } => {
                         let len = self.trans_operand(&mut bcx, len).immediate();
@@ -333,8 +340,29 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                          vec![file_line, index, len],
                          const_err)
                     }
-                    mir::AssertMessage::Math(ref err) => {
-                        let msg_str = token::intern_and_get_ident(err.description());
+                    mir::AssertMessage::Math { .. } |
+                    mir::AssertMessage::Misaligned |
+                    mir::AssertMessage::NullPointer => {
+                        let (msg_str, const_err) = match *msg {
+                            mir::AssertMessage::Math { ref err, ref operands } => {
+                                // The operands are only needed to describe an
+                                // operation that always fails.
+                                if const_cond == Some(!expected) {
+                                    const_note = operands.iter().map(|operand| {
+                                        let operand = self.trans_operand(&bcx, operand);
+                                        consts::to_const_int(operand.immediate(), operand.ty,
+                                                             bcx.tcx())
+                                    }).collect::<Option<Vec<_>>>()
+                                      .map(|values| consts::math_operands_note(&values));
+                                }
+                                (err.description(), Some(ErrKind::Math(err.clone())))
+                            }
+                            mir::AssertMessage::Misaligned => {
+                                ("misaligned pointer dereference", None)
+                            }
+                            _ => ("null pointer dereference", None),
+                        };
+                        let msg_str = token::intern_and_get_ident(msg_str);
                         let msg_str = C_str_slice(bcx.ccx(), msg_str);
                         let msg_file_line = C_struct(bcx.ccx(),
                                                      &[msg_str, filename, line],
@@ -346,7 +374,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                                                             "panic_loc");
                         (lang_items::PanicFnLangItem,
                          vec![msg_file_line],
-                         Some(ErrKind::Math(err.clone())))
+                         const_err)
                     }
                 };
 
@@ -354,9 +382,10 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 // is also constant, then we can produce a warning.
                 if const_cond == Some(!expected) {
                     if let Some(err) = const_err {
-                        let _ = consts::const_err(bcx.ccx(), span,
-                                                  Err::<(), _>(err),
-                                                  consts::TrueConst::No);
+                        let _ = consts::const_err_with_note(bcx.ccx(), span,
+                                                            Err::<(), _>(err),
+                                                            consts::TrueConst::No,
+                                                            const_note.as_ref().map(|s| &s[..]));
                     }
                 }
 
@@ -14,6 +14,7 @@ use rustc_const_eval::ErrKind;
 use rustc_const_math::ConstInt::*;
 use rustc_const_math::ConstFloat::*;
 use rustc_const_math::ConstMathErr;
+use rustc::hir;
 use rustc::hir::def_id::DefId;
 use rustc::infer::TransNormalize;
 use rustc::mir::repr as mir;
@@ -32,8 +33,10 @@ use consts::{self, ConstEvalFailure, TrueConst, to_const_int};
 use monomorphize::{self, Instance};
 use type_of;
 use type_::Type;
+use util::nodemap::FnvHashMap;
 use value::Value;
 
+use errors::DiagnosticBuilder;
 use syntax_pos::{Span, DUMMY_SP};
 
 use std::ptr;
@@ -194,6 +197,17 @@ impl<'tcx> ConstLvalue<'tcx> {
     }
 }
 
+/// A `const fn` call which is being evaluated, recorded so that
+/// errors deep inside nested calls can point back at every call site.
+#[derive(Copy, Clone)]
+struct ConstCallFrame {
+    /// The `const fn` being called.
+    def_id: DefId,
+
+    /// The span of the call, in the caller's body.
+    span: Span
+}
+
 /// Machinery for translating a constant's MIR to LLVM values.
 /// FIXME(eddyb) use miri and lower its allocations to LLVM.
 struct MirConstContext<'a, 'tcx: 'a> {
@@ -204,7 +218,15 @@ struct MirConstContext<'a, 'tcx: 'a> {
     substs: &'tcx Substs<'tcx>,
 
     /// Values of locals in a constant or const fn.
-    locals: IndexVec<mir::Local, Option<Const<'tcx>>>
+    locals: IndexVec<mir::Local, Option<Const<'tcx>>>,
+
+    /// Fields stored so far into locals whose aggregate was lowered to
+    /// one assignment per field, by the `Deaggregator` MIR pass.
+    fields: FnvHashMap<mir::Local, Vec<Option<ValueRef>>>,
+
+    /// The `const fn` calls that led to the evaluation of this MIR,
+    /// outermost first. Empty for the constant being translated.
+    call_stack: Vec<ConstCallFrame>
 }
 
 
@@ -212,13 +234,16 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
     fn new(ccx: &'a CrateContext<'a, 'tcx>,
            mir: &'a mir::Mir<'tcx>,
            substs: &'tcx Substs<'tcx>,
-           args: IndexVec<mir::Arg, Const<'tcx>>)
+           args: IndexVec<mir::Arg, Const<'tcx>>,
+           call_stack: Vec<ConstCallFrame>)
            -> MirConstContext<'a, 'tcx> {
         let mut context = MirConstContext {
             ccx: ccx,
             mir: mir,
             substs: substs,
             locals: (0..mir.count_locals()).map(|_| None).collect(),
+            fields: FnvHashMap(),
+            call_stack: call_stack
         };
         for (i, arg) in args.into_iter().enumerate() {
             let index = mir.local_index(&mir::Lvalue::Arg(mir::Arg::new(i))).unwrap();
@@ -229,7 +254,8 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
 
     fn trans_def(ccx: &'a CrateContext<'a, 'tcx>,
                  mut instance: Instance<'tcx>,
-                 args: IndexVec<mir::Arg, Const<'tcx>>)
+                 args: IndexVec<mir::Arg, Const<'tcx>>,
+                 call_stack: Vec<ConstCallFrame>)
                  -> Result<Const<'tcx>, ConstEvalFailure> {
         // Try to resolve associated constants.
         if instance.substs.self_ty().is_some() {
@@ -253,7 +279,28 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
         let mir = ccx.get_mir(instance.def).unwrap_or_else(|| {
             bug!("missing constant MIR for {}", instance)
         });
-        MirConstContext::new(ccx, &mir, instance.substs, args).trans()
+        MirConstContext::new(ccx, &mir, instance.substs, args, call_stack).trans()
+    }
+
+    /// Emit a compile-time error for `kind` at `span`, with `note`, if any,
+    /// followed by a note for every `const fn` call on the way here,
+    /// innermost first.
+    fn const_err<T>(&self, span: Span, kind: ErrKind, note: Option<&str>)
+                    -> Result<T, ConstEvalFailure> {
+        consts::const_err_with_notes(self.ccx, span, Err(kind), TrueConst::Yes, note,
+                                     |diag| self.note_call_stack(diag))
+    }
+
+    fn note_call_stack(&self, diag: &mut DiagnosticBuilder) {
+        let tcx = self.ccx.tcx();
+        for (i, frame) in self.call_stack.iter().rev().enumerate() {
+            let msg = if i == 0 {
+                format!("error occurred in call to `{}`", tcx.item_path_str(frame.def_id))
+            } else {
+                format!("inlined from call to `{}`", tcx.item_path_str(frame.def_id))
+            };
+            diag.span_note(frame.span, &msg);
+        }
     }
 
     fn monomorphize<T>(&self, value: &T) -> T
@@ -285,8 +332,44 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                             Err(err) => if failure.is_ok() { failure = Err(err); }
                         }
                     }
-                    mir::StatementKind::SetDiscriminant{ .. } => {
-                        span_bug!(span, "SetDiscriminant should not appear in constants?");
+                    mir::StatementKind::SetDiscriminant { ref lvalue, variant_index } => {
+                        // The fields are all stored by now, unless one of
+                        // them failed to evaluate.
+                        if failure.is_ok() {
+                            self.set_discriminant(lvalue, variant_index, span);
+                        }
+                    }
+                    mir::StatementKind::Coverage { .. } => {
+                        span_bug!(span, "Coverage should not appear in constants");
+                    }
+                    mir::StatementKind::SanitizerCheck { .. } => {
+                        span_bug!(span, "SanitizerCheck should not appear in constants");
+                    }
+                    mir::StatementKind::Retag { .. } => {
+                        span_bug!(span, "Retag should not appear in constants");
+                    }
+                    mir::StatementKind::Probe { .. } => {
+                        span_bug!(span, "Probe should not appear in constants");
+                    }
+                    mir::StatementKind::Subtype { .. } => {}
+                    mir::StatementKind::Atomic { op, ref dest, ref ptr, .. } => {
+                        // Constants are evaluated on a single thread, where
+                        // every ordering is sequentially consistent, making
+                        // atomic loads plain ones. The memory of constants
+                        // can't be written to, though.
+                        let loaded = match (op, dest, ptr) {
+                            (mir::AtomicOp::Load, &Some(ref dest),
+                             &mir::Operand::Consume(ref ptr)) => {
+                                self.const_lvalue(&ptr.clone().deref(), span)
+                                    .map(|pointee| (dest, pointee.to_const(span)))
+                            }
+                            _ => self.const_err(
+                                span, ErrKind::UnimplementedConstVal("atomic write"), None),
+                        };
+                        match loaded {
+                            Ok((dest, value)) => self.store(dest, value, span),
+                            Err(err) => if failure.is_ok() { failure = Err(err); }
+                        }
                     }
                 }
             }
@@ -308,6 +391,7 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                     let cond = self.const_operand(cond, span)?;
                     let cond_bool = common::const_to_uint(cond.llval) != 0;
                     if cond_bool != expected {
+                        let mut note = None;
                         let err = match *msg {
                             mir::AssertMessage::BoundsCheck { ref len, ref index } => {
                                 let len = self.const_operand(len, span)?;
@@ -317,14 +401,23 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                                     index: common::const_to_uint(index.llval)
                                 }
                             }
-                            mir::AssertMessage::Math(ref err) => {
+                            mir::AssertMessage::Math { ref err, ref operands } => {
+                                let mut values = Vec::with_capacity(operands.len());
+                                for operand in operands {
+                                    let operand = self.const_operand(operand, span)?;
+                                    values.push(to_const_int(operand.llval, operand.ty, tcx));
+                                }
+                                note = values.into_iter().collect::<Option<Vec<_>>>()
+                                             .map(|values| consts::math_operands_note(&values));
                                 ErrKind::Math(err.clone())
                             }
+                            mir::AssertMessage::Misaligned |
+                            mir::AssertMessage::NullPointer => {
+                                span_bug!(span, "pointer check in constant");
+                            }
                         };
-                        match consts::const_err(self.ccx, span, Err(err), TrueConst::Yes) {
-                            Ok(()) => {}
-                            Err(err) => if failure.is_ok() { failure = Err(err); }
-                        }
+                        let result = self.const_err(span, err, note.as_ref().map(|s| &s[..]));
+                        if failure.is_ok() { failure = result; }
                     }
                     target
                 }
@@ -348,7 +441,13 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                         }
                     }
                     if let Some((ref dest, target)) = *destination {
-                        match MirConstContext::trans_def(self.ccx, instance, const_args) {
+                        let mut call_stack = self.call_stack.clone();
+                        call_stack.push(ConstCallFrame {
+                            def_id: instance.def,
+                            span: span
+                        });
+                        match MirConstContext::trans_def(self.ccx, instance,
+                                                         const_args, call_stack) {
                             Ok(value) => self.store(dest, value, span),
                             Err(err) => if failure.is_ok() { failure = Err(err); }
                         }
@@ -365,11 +464,80 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
     fn store(&mut self, dest: &mir::Lvalue<'tcx>, value: Const<'tcx>, span: Span) {
         if let Some(index) = self.mir.local_index(dest) {
             self.locals[index] = Some(value);
+            return;
+        }
+
+        // Deaggregated structs and enum variants are stored one field at
+        // a time, into `(base.i)` or `((base as Variant).i)`.
+        if let mir::Lvalue::Projection(ref proj) = *dest {
+            if let mir::ProjectionElem::Field(field, _) = proj.elem {
+                let (base, variant) = match proj.base {
+                    mir::Lvalue::Projection(ref base) => match base.elem {
+                        mir::ProjectionElem::Downcast(_, variant) => (&base.base, variant),
+                        _ => (&proj.base, 0)
+                    },
+                    ref base => (base, 0)
+                };
+                if let Some(index) = self.mir.local_index(base) {
+                    let ty = self.local_ty(base);
+                    let adt_def = match ty.sty {
+                        ty::TyStruct(adt_def, _) | ty::TyEnum(adt_def, _) => adt_def,
+                        _ => span_bug!(span, "assignment to {:?} in constant", dest)
+                    };
+                    let field_count = adt_def.variants[variant].fields.len();
+                    {
+                        let fields = self.fields.entry(index)
+                                                .or_insert_with(|| vec![None; field_count]);
+                        fields[field.index()] = Some(value.llval);
+                    }
+                    // Enums with several variants are only complete once
+                    // their discriminant is set.
+                    if adt_def.variants.len() == 1 &&
+                       self.fields[&index].iter().all(|f| f.is_some()) {
+                        self.finish_aggregate(index, ty, 0, span);
+                    }
+                    return;
+                }
+            }
+        }
+        span_bug!(span, "assignment to {:?} in constant", dest);
+    }
+
+    fn set_discriminant(&mut self, lvalue: &mir::Lvalue<'tcx>, variant: usize, span: Span) {
+        if let Some(index) = self.mir.local_index(lvalue) {
+            let ty = self.local_ty(lvalue);
+            self.finish_aggregate(index, ty, variant, span);
         } else {
-            span_bug!(span, "assignment to {:?} in constant", dest);
+            span_bug!(span, "SetDiscriminant of {:?} in constant", lvalue);
         }
     }
 
+    /// Builds the value of `variant` of the local `index`, of type `ty`,
+    /// from the fields stored into it so far, which must be all of them.
+    fn finish_aggregate(&mut self, index: mir::Local, ty: Ty<'tcx>, variant: usize, span: Span) {
+        let fields = self.fields.remove(&index).unwrap_or(vec![]);
+        let fields = fields.into_iter().map(|field| field.unwrap_or_else(|| {
+            span_bug!(span, "field of {:?} not initialized in constant", ty)
+        })).collect::<Vec<_>>();
+        let adt_def = match ty.sty {
+            ty::TyStruct(adt_def, _) | ty::TyEnum(adt_def, _) => adt_def,
+            _ => span_bug!(span, "aggregate of {:?} in constant", ty)
+        };
+        if fields.len() != adt_def.variants[variant].fields.len() {
+            span_bug!(span, "field of {:?} not initialized in constant", ty);
+        }
+        let repr = adt::represent_type(self.ccx, ty);
+        let disr = Disr::from(adt_def.variants[variant].disr_val);
+        let val = adt::trans_const(self.ccx, &repr, disr, &fields);
+        self.locals[index] = Some(Const::new(val, ty));
+    }
+
+    fn local_ty(&self, lvalue: &mir::Lvalue<'tcx>) -> Ty<'tcx> {
+        let tcx = self.ccx.tcx();
+        let ty = lvalue.ty(self.mir, tcx);
+        self.monomorphize(&ty).to_ty(tcx)
+    }
+
     fn const_lvalue(&self, lvalue: &mir::Lvalue<'tcx>, span: Span)
                     -> Result<ConstLvalue<'tcx>, ConstEvalFailure> {
         let tcx = self.ccx.tcx();
@@ -482,11 +650,13 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
 
                         let substs = self.monomorphize(&substs);
                         let instance = Instance::new(def_id, substs);
-                        MirConstContext::trans_def(self.ccx, instance, IndexVec::new())
+                        MirConstContext::trans_def(self.ccx, instance, IndexVec::new(), vec![])
                     }
                     mir::Literal::Promoted { index } => {
+                        // Promoted rvalues are evaluated as part of the current frame.
                         let mir = &self.mir.promoted[index];
-                        MirConstContext::new(self.ccx, mir, self.substs, IndexVec::new()).trans()
+                        MirConstContext::new(self.ccx, mir, self.substs, IndexVec::new(),
+                                             self.call_stack.clone()).trans()
                     }
                     mir::Literal::Value { value } => {
                         Ok(Const::from_constval(self.ccx, value, ty))
@@ -614,6 +784,18 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                         assert_eq!(abi::FAT_PTR_EXTRA, 1);
                         C_struct(self.ccx, &[base, info], false)
                     }
+                    mir::CastKind::PointerExposeAddress => {
+                        let ll_t_out = type_of::immediate_type_of(self.ccx, cast_ty);
+                        unsafe { llvm::LLVMConstPtrToInt(operand.llval, ll_t_out.to_ref()) }
+                    }
+                    mir::CastKind::PointerFromExposedAddress => {
+                        let ll_t_out = type_of::immediate_type_of(self.ccx, cast_ty);
+                        unsafe { llvm::LLVMConstIntToPtr(operand.llval, ll_t_out.to_ref()) }
+                    }
+                    mir::CastKind::IntTruncate => {
+                        let ll_t_out = type_of::immediate_type_of(self.ccx, cast_ty);
+                        unsafe { llvm::LLVMConstTrunc(operand.llval, ll_t_out.to_ref()) }
+                    }
                     mir::CastKind::Misc if common::type_is_immediate(self.ccx, operand.ty) => {
                         debug_assert!(common::type_is_immediate(self.ccx, cast_ty));
                         let r_t_in = CastTy::from_ty(operand.ty).expect("bad input type for cast");
@@ -689,17 +871,29 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                 Const::new(val, cast_ty)
             }
 
-            mir::Rvalue::Ref(_, bk, ref lvalue) => {
+            mir::Rvalue::Ref(_, _, ref lvalue) |
+            mir::Rvalue::RawPtr(_, ref lvalue) => {
                 let tr_lvalue = self.const_lvalue(lvalue, span)?;
 
                 let ty = tr_lvalue.ty;
-                let ref_ty = tcx.mk_ref(tcx.mk_region(ty::ReErased),
-                    ty::TypeAndMut { ty: ty, mutbl: bk.to_mutbl_lossy() });
+                let (ref_ty, mutbl) = match *rvalue {
+                    mir::Rvalue::Ref(_, bk, _) => {
+                        let mutbl = bk.to_mutbl_lossy();
+                        (tcx.mk_ref(tcx.mk_region(ty::ReErased),
+                                    ty::TypeAndMut { ty: ty, mutbl: mutbl }),
+                         mutbl)
+                    }
+                    mir::Rvalue::RawPtr(mutbl, _) => {
+                        let mutbl = mutbl.to_hir();
+                        (tcx.mk_ptr(ty::TypeAndMut { ty: ty, mutbl: mutbl }), mutbl)
+                    }
+                    _ => bug!(),
+                };
 
                 let base = match tr_lvalue.base {
                     Base::Value(llval) => {
                         let align = type_of::align_of(self.ccx, ty);
-                        if bk == mir::BorrowKind::Mut {
+                        if mutbl == hir::MutMutable {
                             consts::addr_of_mut(self.ccx, llval, align, "ref_mut")
                         } else {
                             consts::addr_of(self.ccx, llval, align, "ref")
@@ -891,12 +1085,12 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
 
                 let substs = bcx.monomorphize(&substs);
                 let instance = Instance::new(def_id, substs);
-                MirConstContext::trans_def(bcx.ccx(), instance, IndexVec::new())
+                MirConstContext::trans_def(bcx.ccx(), instance, IndexVec::new(), vec![])
             }
             mir::Literal::Promoted { index } => {
                 let mir = &self.mir.promoted[index];
                 MirConstContext::new(bcx.ccx(), mir, bcx.fcx().param_substs,
-                                     IndexVec::new()).trans()
+                                     IndexVec::new(), vec![]).trans()
             }
             mir::Literal::Value { value } => {
                 Ok(Const::from_constval(bcx.ccx(), value, ty))
@@ -923,5 +1117,5 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
 pub fn trans_static_initializer(ccx: &CrateContext, def_id: DefId)
                                 -> Result<ValueRef, ConstEvalFailure> {
     let instance = Instance::mono(ccx.shared(), def_id);
-    MirConstContext::trans_def(ccx, instance, IndexVec::new()).map(|c| c.llval)
+    MirConstContext::trans_def(ccx, instance, IndexVec::new(), vec![]).map(|c| c.llval)
 }
@@ -14,16 +14,22 @@ use llvm::debuginfo::DIScope;
 use rustc::ty;
 use rustc::mir::repr as mir;
 use rustc::mir::tcx::LvalueTy;
+use rustc::mir::transform::MirSource;
+use rustc_mir::pretty;
 use session::config::FullDebugInfo;
 use base;
 use common::{self, Block, BlockAndBuilder, CrateContext, FunctionContext, C_null};
 use debuginfo::{self, declare_local, DebugLoc, VariableAccess, VariableKind};
 use machine;
+use monomorphize::{self, Instance};
+use trans_item;
 use type_of;
 
 use syntax_pos::DUMMY_SP;
+use syntax::ast;
 use syntax::parse::token::keywords;
 
+use std::io::{self, Write};
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -482,6 +488,41 @@ fn arg_local_refs<'bcx, 'tcx>(bcx: &BlockAndBuilder<'bcx, 'tcx>,
     }).collect()
 }
 
+/// Prints `mir`, the MIR of `instance`, with the substitutions of the
+/// instance applied, if it is the instance `-Z print-mono-mir` names.
+pub fn print_mono_mir<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                                instance: Instance<'tcx>,
+                                fn_id: ast::NodeId,
+                                mir: &mir::Mir<'tcx>) {
+    let tcx = ccx.tcx();
+    let name = trans_item::instance_to_string(tcx, instance);
+    match ccx.sess().opts.debugging_opts.print_mono_mir {
+        Some(ref wanted) if wanted.trim() == name => {}
+        _ => return,
+    }
+
+    let mir = monomorphize::apply_param_substs_to_mir(tcx, instance.substs, mir);
+    let stdout = io::stdout();
+    if let Err(e) = write_mono_mir(tcx, &name, fn_id, &mir, &mut stdout.lock()) {
+        ccx.sess().warn(&format!("could not print the MIR of `{}`: {}", name, e));
+    }
+}
+
+fn write_mono_mir<'a, 'tcx>(tcx: ty::TyCtxt<'a, 'tcx, 'tcx>,
+                            name: &str,
+                            fn_id: ast::NodeId,
+                            mir: &mir::Mir<'tcx>,
+                            w: &mut Write)
+                            -> io::Result<()> {
+    writeln!(w, "// MIR for `{}`", name)?;
+    pretty::write_mir_fn(tcx, MirSource::Fn(fn_id), mir, w, None)?;
+    for (i, promoted) in mir.promoted.iter_enumerated() {
+        writeln!(w, "")?;
+        pretty::write_mir_fn(tcx, MirSource::Promoted(fn_id, i), promoted, w, None)?;
+    }
+    Ok(())
+}
+
 mod analyze;
 mod block;
 mod constant;
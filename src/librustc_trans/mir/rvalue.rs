@@ -16,13 +16,17 @@ use rustc::mir::repr as mir;
 use asm;
 use base;
 use callee::Callee;
-use common::{self, val_ty, C_bool, C_null, C_uint, BlockAndBuilder, Result};
+use common::{self, val_ty, C_bool, C_i32, C_null, C_u8, C_uint, C_vector, BlockAndBuilder};
+use common::{CrateContext, Result};
 use datum::{Datum, Lvalue};
 use debuginfo::DebugLoc;
 use adt;
+use intrinsic;
+use libc;
 use machine;
 use type_of;
 use tvec;
+use type_::Type;
 use value::Value;
 use Disr;
 
@@ -97,6 +101,21 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
 
             mir::Rvalue::Repeat(ref elem, ref count) => {
                 let tr_elem = self.trans_operand(&bcx, elem);
+
+                // Arrays of a constant whose bytes are all the same, such
+                // as zeroes, are filled with a single `memset`.
+                if let OperandValue::Immediate(llval) = tr_elem.val {
+                    if let Some(byte) = splat_byte(bcx.ccx(), llval) {
+                        let ccx = bcx.ccx();
+                        let llty = type_of::type_of(ccx, dest.ty.to_ty(bcx.tcx()));
+                        let llptr = bcx.pointercast(dest.llval, Type::i8(ccx).ptr_to());
+                        let align = C_i32(ccx, type_of::align_of(ccx, tr_elem.ty) as i32);
+                        base::call_memset(&bcx, llptr, C_u8(ccx, byte),
+                                          machine::llsize_of(ccx, llty), align, false);
+                        return bcx;
+                    }
+                }
+
                 let size = count.value.as_u64(bcx.tcx().sess.target.uint_type);
                 let size = C_uint(bcx.ccx(), size);
                 let base = get_dataptr(&bcx, dest.llval);
@@ -244,6 +263,18 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                             }
                         }
                     }
+                    mir::CastKind::PointerExposeAddress => {
+                        let ll_t_out = type_of::immediate_type_of(bcx.ccx(), cast_ty);
+                        OperandValue::Immediate(bcx.ptrtoint(operand.immediate(), ll_t_out))
+                    }
+                    mir::CastKind::PointerFromExposedAddress => {
+                        let ll_t_out = type_of::immediate_type_of(bcx.ccx(), cast_ty);
+                        OperandValue::Immediate(bcx.inttoptr(operand.immediate(), ll_t_out))
+                    }
+                    mir::CastKind::IntTruncate => {
+                        let ll_t_out = type_of::immediate_type_of(bcx.ccx(), cast_ty);
+                        OperandValue::Immediate(bcx.trunc(operand.immediate(), ll_t_out))
+                    }
                     mir::CastKind::Misc if common::type_is_fat_ptr(bcx.tcx(), operand.ty) => {
                         let ll_cast_ty = type_of::immediate_type_of(bcx.ccx(), cast_ty);
                         let ll_from_ty = type_of::immediate_type_of(bcx.ccx(), operand.ty);
@@ -340,17 +371,24 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 (bcx, operand)
             }
 
-            mir::Rvalue::Ref(_, bk, ref lvalue) => {
+            mir::Rvalue::Ref(_, _, ref lvalue) |
+            mir::Rvalue::RawPtr(_, ref lvalue) => {
                 let tr_lvalue = self.trans_lvalue(&bcx, lvalue);
 
                 let ty = tr_lvalue.ty.to_ty(bcx.tcx());
-                let ref_ty = bcx.tcx().mk_ref(
-                    bcx.tcx().mk_region(ty::ReErased),
-                    ty::TypeAndMut { ty: ty, mutbl: bk.to_mutbl_lossy() }
-                );
+                let ref_ty = match *rvalue {
+                    mir::Rvalue::Ref(_, bk, _) => bcx.tcx().mk_ref(
+                        bcx.tcx().mk_region(ty::ReErased),
+                        ty::TypeAndMut { ty: ty, mutbl: bk.to_mutbl_lossy() }
+                    ),
+                    mir::Rvalue::RawPtr(mutbl, _) => bcx.tcx().mk_ptr(
+                        ty::TypeAndMut { ty: ty, mutbl: mutbl.to_hir() }
+                    ),
+                    _ => bug!(),
+                };
 
                 // Note: lvalues are indirect, so storing the `llval` into the
-                // destination effectively creates a reference.
+                // destination effectively creates a reference (or raw pointer).
                 let operand = if common::type_is_sized(bcx.tcx(), ty) {
                     OperandRef {
                         val: OperandValue::Immediate(tr_lvalue.llval),
@@ -468,6 +506,43 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 let operand = self.trans_operand(&bcx, operand);
                 (bcx, operand)
             }
+
+            mir::Rvalue::Simd(op, ref operands) => {
+                let vector = self.trans_operand(&bcx, &operands[0]);
+                let (llval, ty) = match op {
+                    mir::SimdOp::Extract => {
+                        let index = self.trans_operand(&bcx, &operands[1]);
+                        (bcx.extract_element(vector.immediate(), index.immediate()),
+                         vector.ty.simd_type(bcx.tcx()))
+                    }
+                    mir::SimdOp::Insert => {
+                        let index = self.trans_operand(&bcx, &operands[1]);
+                        let element = self.trans_operand(&bcx, &operands[2]);
+                        (bcx.insert_element(vector.immediate(),
+                                            element.immediate(),
+                                            index.immediate()),
+                         vector.ty)
+                    }
+                    mir::SimdOp::Shuffle(out_ty) => {
+                        let out_ty = bcx.monomorphize(&out_ty);
+                        let second = self.trans_operand(&bcx, &operands[1]);
+                        let llval = match operands[2] {
+                            mir::Operand::Constant(ref constant) => {
+                                self.trans_simd_shuffle(&bcx, vector, second, constant, out_ty)
+                            }
+                            mir::Operand::Consume(_) => {
+                                bug!("shuffle indices must be constant: {:?}", rvalue)
+                            }
+                        };
+                        (llval, out_ty)
+                    }
+                };
+                (bcx, OperandRef {
+                    val: OperandValue::Immediate(llval),
+                    ty: ty,
+                })
+            }
+
             mir::Rvalue::Repeat(..) |
             mir::Rvalue::Aggregate(..) |
             mir::Rvalue::InlineAsm { .. } => {
@@ -477,6 +552,48 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
         }
     }
 
+    fn trans_simd_shuffle(&mut self,
+                          bcx: &BlockAndBuilder<'bcx, 'tcx>,
+                          first: OperandRef<'tcx>,
+                          second: OperandRef<'tcx>,
+                          indices: &mir::Constant<'tcx>,
+                          out_ty: Ty<'tcx>)
+                          -> ValueRef
+    {
+        let out_len = out_ty.simd_size(bcx.tcx());
+        let total_len = first.ty.simd_size(bcx.tcx()) as u64 * 2;
+        let llindices = self.trans_constant(bcx, indices).llval;
+
+        // The indices are only known now, so they are checked here, like
+        // trans of the `simd_shuffleN` intrinsic does.
+        let error = |msg: String| {
+            let msg = format!("invalid monomorphization of `simd_shuffle{}` intrinsic: {}",
+                              out_len, msg);
+            intrinsic::span_invalid_monomorphization_error(bcx.sess(), indices.span, &msg);
+        };
+        let mask: Option<Vec<_>> = (0..out_len).map(|i| {
+            let val = common::const_get_elt(llindices, &[i as libc::c_uint]);
+            match common::const_to_opt_uint(val) {
+                None => {
+                    error(format!("shuffle index #{} is not a constant", i));
+                    None
+                }
+                Some(idx) if idx >= total_len => {
+                    error(format!("shuffle index #{} is out of bounds (limit {})",
+                                  i, total_len));
+                    None
+                }
+                Some(idx) => Some(C_i32(bcx.ccx(), idx as i32)),
+            }
+        }).collect();
+        match mask {
+            Some(mask) => {
+                bcx.shuffle_vector(first.immediate(), second.immediate(), C_vector(&mask))
+            }
+            None => C_null(type_of::type_of(bcx.ccx(), out_ty)),
+        }
+    }
+
     pub fn trans_scalar_binop(&mut self,
                               bcx: &BlockAndBuilder<'bcx, 'tcx>,
                               op: mir::BinOp,
@@ -610,12 +727,14 @@ pub fn rvalue_creates_operand<'bcx, 'tcx>(_mir: &mir::Mir<'tcx>,
                                           rvalue: &mir::Rvalue<'tcx>) -> bool {
     match *rvalue {
         mir::Rvalue::Ref(..) |
+        mir::Rvalue::RawPtr(..) |
         mir::Rvalue::Len(..) |
         mir::Rvalue::Cast(..) | // (*)
         mir::Rvalue::BinaryOp(..) |
         mir::Rvalue::CheckedBinaryOp(..) |
         mir::Rvalue::UnaryOp(..) |
         mir::Rvalue::Box(..) |
+        mir::Rvalue::Simd(..) |
         mir::Rvalue::Use(..) =>
             true,
         mir::Rvalue::Repeat(..) |
@@ -698,3 +817,27 @@ fn get_overflow_intrinsic(oop: OverflowOp, bcx: &BlockAndBuilder, ty: Ty) -> Val
 
     bcx.ccx().get_intrinsic(&name)
 }
+
+/// The byte that every byte of the constant `llval` is equal to, if any,
+/// so that an array of it can be filled with `memset`.
+fn splat_byte(ccx: &CrateContext, llval: ValueRef) -> Option<u8> {
+    unsafe {
+        if llvm::LLVMIsConstant(llval) == llvm::False || common::is_undef(llval) {
+            return None;
+        }
+        if llvm::LLVMIsNull(llval) != llvm::False {
+            return Some(0);
+        }
+    }
+    let value = match common::const_to_opt_uint(llval) {
+        Some(value) => value,
+        None => return None,
+    };
+    let size = machine::llsize_of_alloc(ccx, val_ty(llval));
+    let byte = value as u8;
+    if size <= 8 && (1..size).all(|i| (value >> (i * 8)) as u8 == byte) {
+        Some(byte)
+    } else {
+        None
+    }
+}
@@ -8,15 +8,26 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use libc::{c_char, c_uint};
+use llvm::{self, ValueRef};
 use rustc::mir::repr as mir;
+use rustc::session::config::Sanitizer;
 
-use common::{self, BlockAndBuilder};
+use common::{self, BlockAndBuilder, CrateContext, C_u32, C_u64, C_uint};
+use consts;
+use declare;
+use machine;
+use type_::Type;
+use type_of;
 
 use super::MirContext;
 use super::LocalRef;
+use super::operand::{OperandRef, OperandValue};
 use super::super::adt;
 use super::super::disr::Disr;
 
+use std::ffi::CStr;
+
 impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
     pub fn trans_statement(&mut self,
                            bcx: BlockAndBuilder<'bcx, 'tcx>,
@@ -71,6 +82,163 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 );
                 bcx
             }
+            mir::StatementKind::Coverage { function_hash, num_counters, index } => {
+                let ccx = bcx.ccx();
+                let name = coverage_name_var(ccx, bcx.fcx().llfn);
+                let increment = ccx.get_intrinsic(&("llvm.instrprof.increment"));
+                bcx.call(increment,
+                         &[name,
+                           C_u64(ccx, function_hash),
+                           C_u32(ccx, num_counters),
+                           C_u32(ccx, index)],
+                         None);
+                bcx
+            }
+            mir::StatementKind::SanitizerCheck { kind, ref lvalue } => {
+                let ty = self.monomorphized_lvalue_ty(lvalue);
+                // The size of an unsized lvalue is only known at runtime, and
+                // there is no memory to check for a zero-sized one.
+                if !common::type_is_sized(bcx.tcx(), ty) ||
+                   common::type_is_zero_size(bcx.ccx(), ty) {
+                    return bcx;
+                }
+                let ccx = bcx.ccx();
+                let name = match (ccx.sess().opts.debugging_opts.sanitizer, kind) {
+                    (Some(Sanitizer::Address), mir::SanitizerCheckKind::Load) => "__asan_loadN",
+                    (Some(Sanitizer::Address), mir::SanitizerCheckKind::Store) => "__asan_storeN",
+                    (Some(Sanitizer::Memory), mir::SanitizerCheckKind::Load) => {
+                        "__msan_check_mem_is_initialized"
+                    }
+                    // A store initializes the memory it writes to.
+                    (Some(Sanitizer::Memory), mir::SanitizerCheckKind::Store) => "__msan_unpoison",
+                    (None, _) => {
+                        span_bug!(statement.source_info.span,
+                                  "sanitizer check without -Z sanitizer")
+                    }
+                };
+                let check = sanitizer_fn(ccx, name);
+                let tr_lvalue = self.trans_lvalue(&bcx, lvalue);
+                let size = machine::llsize_of_alloc(ccx, type_of::type_of(ccx, ty));
+                bcx.call(check,
+                         &[bcx.pointercast(tr_lvalue.llval, Type::i8p(ccx)),
+                           C_uint(ccx, size)],
+                         None);
+                bcx
+            }
+            mir::StatementKind::Subtype { .. } |
+            mir::StatementKind::Retag { .. } => bcx,
+            mir::StatementKind::Probe { kind, function_id } => {
+                let ccx = bcx.ccx();
+                let probe = match ccx.sess().opts.debugging_opts.mir_instrument_calls {
+                    Some(ref symbol) => probe_fn(ccx, symbol),
+                    None => {
+                        span_bug!(statement.source_info.span,
+                                  "probe without -Z mir-instrument-calls")
+                    }
+                };
+                bcx.call(probe, &[C_u64(ccx, function_id), C_u32(ccx, kind.event())], None);
+                bcx
+            }
+            mir::StatementKind::Atomic { op, ordering, ref dest, ref ptr, ref value } => {
+                let order = match ordering {
+                    mir::AtomicOrdering::Relaxed => llvm::AtomicOrdering::Monotonic,
+                    mir::AtomicOrdering::Acquire => llvm::AtomicOrdering::Acquire,
+                    mir::AtomicOrdering::Release => llvm::AtomicOrdering::Release,
+                    mir::AtomicOrdering::AcqRel => llvm::AtomicOrdering::AcquireRelease,
+                    mir::AtomicOrdering::SeqCst => llvm::AtomicOrdering::SequentiallyConsistent,
+                };
+                let llptr = self.trans_operand(&bcx, ptr).immediate();
+                let llvalue = value.as_ref().map(|value| self.trans_operand(&bcx, value));
+                let llvalue = llvalue.map(|value| value.immediate());
+                let rmw_op = match op {
+                    mir::AtomicOp::Load => {
+                        let loaded = bcx.atomic_load(llptr, order);
+                        return self.store_atomic_result(bcx, dest, loaded);
+                    }
+                    mir::AtomicOp::Store => {
+                        bcx.atomic_store(llvalue.unwrap(), llptr, order);
+                        return bcx;
+                    }
+                    mir::AtomicOp::Xchg => llvm::AtomicXchg,
+                    mir::AtomicOp::Add => llvm::AtomicAdd,
+                    mir::AtomicOp::Sub => llvm::AtomicSub,
+                    mir::AtomicOp::And => llvm::AtomicAnd,
+                    mir::AtomicOp::Nand => llvm::AtomicNand,
+                    mir::AtomicOp::Or => llvm::AtomicOr,
+                    mir::AtomicOp::Xor => llvm::AtomicXor,
+                    mir::AtomicOp::Max => llvm::AtomicMax,
+                    mir::AtomicOp::Min => llvm::AtomicMin,
+                    mir::AtomicOp::UMax => llvm::AtomicUMax,
+                    mir::AtomicOp::UMin => llvm::AtomicUMin,
+                };
+                let old = bcx.atomic_rmw(rmw_op, llptr, llvalue.unwrap(), order);
+                self.store_atomic_result(bcx, dest, old)
+            }
+        }
+    }
+
+    /// Stores the value read by an atomic access into `dest`.
+    fn store_atomic_result(&mut self,
+                           bcx: BlockAndBuilder<'bcx, 'tcx>,
+                           dest: &Option<mir::Lvalue<'tcx>>,
+                           llval: ValueRef)
+                           -> BlockAndBuilder<'bcx, 'tcx> {
+        let dest = match *dest {
+            Some(ref dest) => dest,
+            None => return bcx,
+        };
+        let operand = OperandRef {
+            val: OperandValue::Immediate(llval),
+            ty: self.monomorphized_lvalue_ty(dest),
+        };
+        if let Some(index) = self.mir.local_index(dest) {
+            match self.locals[index] {
+                LocalRef::Lvalue(tr_dest) => self.store_operand(&bcx, tr_dest.llval, operand),
+                LocalRef::Operand(None) => self.locals[index] = LocalRef::Operand(Some(operand)),
+                LocalRef::Operand(Some(_)) => {
+                    bug!("operand {:?} already assigned", dest);
+                }
+            }
+        } else {
+            let tr_dest = self.trans_lvalue(&bcx, dest);
+            self.store_operand(&bcx, tr_dest.llval, operand);
         }
+        bcx
     }
 }
+
+/// The global holding the name of `llfn`, which identifies the function a
+/// counter of `llvm.instrprof.increment` belongs to. LLVM's instrumentation
+/// pass expects it to be named after the function with a `__profn_` prefix.
+fn coverage_name_var(ccx: &CrateContext, llfn: ValueRef) -> ValueRef {
+    let fn_name = unsafe {
+        CStr::from_ptr(llvm::LLVMGetValueName(llfn)).to_string_lossy().into_owned()
+    };
+    let var_name = format!("__profn_{}", fn_name);
+    let g = declare::get_defined_value(ccx, &var_name).unwrap_or_else(|| unsafe {
+        let init = llvm::LLVMConstStringInContext(ccx.llcx(),
+                                                  fn_name.as_ptr() as *const c_char,
+                                                  fn_name.len() as c_uint,
+                                                  llvm::True);
+        let g = declare::define_global(ccx, &var_name, common::val_ty(init)).unwrap();
+        llvm::LLVMSetInitializer(g, init);
+        llvm::LLVMSetGlobalConstant(g, llvm::True);
+        llvm::LLVMSetLinkage(g, llvm::PrivateLinkage);
+        g
+    });
+    consts::ptrcast(g, Type::i8p(ccx))
+}
+
+/// The probe function of `-Z mir-instrument-calls`, which takes the id of
+/// the function and the event.
+fn probe_fn(ccx: &CrateContext, symbol: &str) -> ValueRef {
+    let fn_ty = Type::func(&[Type::i64(ccx), Type::i32(ccx)], &Type::void(ccx));
+    declare::declare_cfn(ccx, symbol, fn_ty)
+}
+
+/// The runtime function `name` of the sanitizer, all of which take the
+/// address and the size of the memory that is accessed.
+fn sanitizer_fn(ccx: &CrateContext, name: &str) -> ValueRef {
+    let fn_ty = Type::func(&[Type::i8p(ccx), ccx.int_type()], &Type::void(ccx));
+    declare::declare_cfn(ccx, name, fn_ty)
+}
@@ -12,9 +12,12 @@ use llvm::ValueRef;
 use llvm;
 use rustc::hir::def_id::DefId;
 use rustc::infer::TransNormalize;
+use rustc::mir::repr::{Constant, Literal, Location, Mir};
+use rustc::mir::visit::MutVisitor;
 use rustc::ty::subst;
 use rustc::ty::subst::{Subst, Substs};
-use rustc::ty::{self, Ty, TypeFoldable, TyCtxt};
+use rustc::ty::{self, ClosureSubsts, Ty, TypeFoldable, TyCtxt};
+use rustc_const_eval::{eval_const_expr_partial, lookup_const_by_id, EvalHint};
 use attributes;
 use base::{push_ctxt};
 use base;
@@ -202,6 +205,60 @@ pub fn apply_param_substs<'a, 'tcx, T>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
 }
 
 
+/// Returns a copy of `mir`, and of its promoted constants, with
+/// `param_substs` applied to all its types, as trans sees them when it
+/// translates the instance, and the constant items it uses evaluated when
+/// the constant evaluator can. This is for `-Z print-mono-mir`.
+pub fn apply_param_substs_to_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                           param_substs: &Substs<'tcx>,
+                                           mir: &Mir<'tcx>)
+                                           -> Mir<'tcx>
+{
+    let mut mir = mir.clone();
+    let mut substitutor = MirSubstitutor { tcx: tcx, param_substs: param_substs };
+    substitutor.visit_mir(&mut mir);
+    for promoted in mir.promoted.iter_mut() {
+        substitutor.visit_mir(promoted);
+    }
+    mir
+}
+
+struct MirSubstitutor<'a, 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    param_substs: &'b Substs<'tcx>,
+}
+
+impl<'a, 'b, 'tcx> MutVisitor<'tcx> for MirSubstitutor<'a, 'b, 'tcx> {
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>) {
+        *ty = apply_param_substs(self.tcx, self.param_substs, ty);
+    }
+
+    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>) {
+        *substs = apply_param_substs(self.tcx, self.param_substs, substs);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &mut ClosureSubsts<'tcx>) {
+        *substs = apply_param_substs(self.tcx, self.param_substs, substs);
+    }
+
+    fn visit_constant(&mut self, constant: &mut Constant<'tcx>, location: Location) {
+        self.super_constant(constant, location);
+
+        let value = match constant.literal {
+            Literal::Item { def_id, substs } if !constant.ty.is_fn() => {
+                lookup_const_by_id(self.tcx, def_id, Some(substs)).and_then(|(expr, _)| {
+                    eval_const_expr_partial(self.tcx, expr, EvalHint::ExprTypeChecked, None)
+                        .ok()
+                })
+            }
+            _ => None,
+        };
+        if let Some(value) = value {
+            constant.literal = Literal::Value { value: value };
+        }
+    }
+}
+
 /// Returns the normalized type of a struct field
 pub fn field_ty<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           param_substs: &Substs<'tcx>,
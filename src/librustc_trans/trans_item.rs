@@ -608,6 +608,14 @@ pub fn def_id_to_string(tcx: TyCtxt, def_id: DefId) -> String {
     output
 }
 
+pub fn instance_to_string<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    instance: Instance<'tcx>)
+                                    -> String {
+    let mut output = String::new();
+    push_instance_as_string(tcx, instance, &mut output);
+    output
+}
+
 pub fn type_to_string<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                 ty: ty::Ty<'tcx>)
                                 -> String {
@@ -443,6 +443,41 @@ pub fn find_inline_attr(diagnostic: Option<&Handler>, attrs: &[Attribute]) -> In
     })
 }
 
+#[derive(Copy, Clone, PartialEq)]
+pub enum UnwindAttr {
+    /// `#[unwind]` or `#[unwind(allowed)]`: the function may unwind.
+    Allowed,
+    /// `#[unwind(aborts)]`: unwinding out of the function aborts the process.
+    Aborts,
+}
+
+/// Determine what `#[unwind]` attribute is present in `attrs`, if any.
+pub fn find_unwind_attr(diagnostic: Option<&Handler>, attrs: &[Attribute]) -> Option<UnwindAttr> {
+    attrs.iter().fold(None, |ua, attr| {
+        match attr.node.value.node {
+            MetaItemKind::Word(ref n) if n == "unwind" => {
+                mark_used(attr);
+                Some(UnwindAttr::Allowed)
+            }
+            MetaItemKind::List(ref n, ref items) if n == "unwind" => {
+                mark_used(attr);
+                if items.len() == 1 && contains_name(&items[..], "allowed") {
+                    Some(UnwindAttr::Allowed)
+                } else if items.len() == 1 && contains_name(&items[..], "aborts") {
+                    Some(UnwindAttr::Aborts)
+                } else {
+                    diagnostic.map(|d| {
+                        span_err!(d, attr.span, E0565,
+                                  "expected one of `allowed` or `aborts`");
+                    });
+                    ua
+                }
+            }
+            _ => ua,
+        }
+    })
+}
+
 /// True if `#[inline]` or `#[inline(always)]` is present in `attrs`.
 pub fn requests_inline(attrs: &[Attribute]) -> bool {
     match find_inline_attr(None, attrs) {
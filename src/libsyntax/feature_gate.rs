@@ -284,7 +284,11 @@ declare_features! (
 
     // Allows tuple structs and variants in more contexts,
     // Permits numeric fields in struct expressions and patterns.
-    (active, relaxed_adts, "1.12.0", Some(35626))
+    (active, relaxed_adts, "1.12.0", Some(35626)),
+
+    // Allows bindings after an `@` when they and the `@` binding all bind by
+    // reference, e.g. `ref a @ Some(ref b)`.
+    (active, bindings_after_at, "1.12.0", None)
 );
 
 declare_features! (
@@ -498,6 +502,16 @@ pub const KNOWN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeGat
                                                        is just used for rustc unit tests \
                                                        and will never be stable",
                                                       cfg_fn!(rustc_attrs))),
+    ("rustc_mir_reused", Whitelisted, Gated("rustc_attrs",
+                                            "this attribute \
+                                             is just used for rustc unit tests \
+                                             and will never be stable",
+                                            cfg_fn!(rustc_attrs))),
+    ("rustc_mir_rebuilt", Whitelisted, Gated("rustc_attrs",
+                                             "this attribute \
+                                              is just used for rustc unit tests \
+                                              and will never be stable",
+                                             cfg_fn!(rustc_attrs))),
     ("rustc_symbol_name", Whitelisted, Gated("rustc_attrs",
                                              "internal rustc attributes will never be stable",
                                              cfg_fn!(rustc_attrs))),
@@ -514,6 +528,16 @@ pub const KNOWN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeGat
                                       is just used for rustc unit tests \
                                       and will never be stable",
                                      cfg_fn!(rustc_attrs))),
+    ("rustc_mir_dump_on_ice", Whitelisted, Gated("rustc_attrs",
+                                                 "the `#[rustc_mir_dump_on_ice]` attribute \
+                                                  is just used for debugging rustc \
+                                                  and will never be stable",
+                                                 cfg_fn!(rustc_attrs))),
+    ("rustc_mir_build_bug", Whitelisted, Gated("rustc_attrs",
+                                               "the `#[rustc_mir_build_bug]` attribute \
+                                                is just used for rustc unit tests \
+                                                and will never be stable",
+                                               cfg_fn!(rustc_attrs))),
     ("rustc_no_mir", Whitelisted, Gated("rustc_attrs",
                                         "the `#[rustc_no_mir]` attribute \
                                          is just used to make tests pass \
@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes
+
+#![crate_type = "lib"]
+
+// CHECK-LABEL: @zeroes
+#[no_mangle]
+pub fn zeroes() -> [u8; 4096] {
+    // CHECK: call void @llvm.memset.{{.*}}(i8* {{.*}}, i8 0, i{{[0-9]+}} 4096
+    [0u8; 4096]
+}
+
+// CHECK-LABEL: @splat
+#[no_mangle]
+pub fn splat() -> [u32; 64] {
+    // CHECK: call void @llvm.memset.{{.*}}(i8* {{.*}}, i8 -1, i{{[0-9]+}} 256
+    [!0u32; 64]
+}
+
+// CHECK-LABEL: @no_splat
+#[no_mangle]
+pub fn no_splat() -> [u16; 64] {
+    // CHECK-NOT: call void @llvm.memset
+    [0x1234u16; 64]
+}
@@ -0,0 +1,42 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that a malformed `#[unwind]` is reported while checking attributes,
+// on functions, methods, trait methods and foreign functions alike, even
+// if nothing is translated.
+
+// compile-flags: -Z no-trans
+
+#![feature(unwind_attributes)]
+
+#[unwind(sometimes)] //~ ERROR E0565
+pub fn f() {}
+
+pub struct S;
+
+impl S {
+    #[unwind(allowed, aborts)] //~ ERROR E0565
+    pub fn g() {}
+}
+
+pub trait T {
+    #[unwind(never)] //~ ERROR E0565
+    fn h();
+
+    #[unwind()] //~ ERROR E0565
+    fn i() {}
+}
+
+extern {
+    #[unwind(abort)] //~ ERROR E0565
+    fn j();
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that a `ref mut` binding that overlaps another binding of the same
+// pattern is reported when building its MIR. The AST borrow checker still
+// runs afterwards and reports the conflicting borrows as well.
+
+#![feature(bindings_after_at)]
+
+fn main() {
+    let mut x = Some(1);
+    match x {
+        ref mut a @ Some(ref b) => {} //~ ERROR E0566
+                                      //~| ERROR E0502
+        None => {}
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that errors in nested `const fn` calls point at every call site.
+
+#![feature(const_fn)]
+#![allow(const_err)]
+
+const fn inner(x: u8) -> u8 {
+    x + 255 //~ ERROR constant evaluation error
+            //~| attempted to add with overflow
+}
+
+const fn outer(x: u8) -> u8 {
+    inner(x) //~ NOTE error occurred in call to `inner`
+}
+
+const X: u8 = outer(1); //~ NOTE inlined from call to `outer`
+
+fn main() {
+    let _ = X;
+}
@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that arithmetic errors in `const fn` calls report the values of
+// the operands, which the failed assertion carries.
+
+#![feature(const_fn)]
+#![allow(const_err)]
+
+const fn sub(x: u8, y: u8) -> u8 {
+    x - y //~ ERROR constant evaluation error
+          //~| attempted to subtract with overflow
+          //~| NOTE the operands are `3u8` and `4u8`
+}
+
+const fn neg(x: i8) -> i8 {
+    -x //~ ERROR constant evaluation error
+       //~| attempted to negate with overflow
+       //~| NOTE the operand is `-128i8`
+}
+
+const X: u8 = sub(3, 4); //~ NOTE error occurred in call to `sub`
+const Y: i8 = neg(-128); //~ NOTE error occurred in call to `neg`
+
+fn main() {
+    let _ = (X, Y);
+}
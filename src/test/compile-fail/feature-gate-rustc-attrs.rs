@@ -15,6 +15,8 @@
 #[rustc_variance] //~ ERROR the `#[rustc_variance]` attribute is just used for rustc unit tests and will never be stable
 #[rustc_error] //~ ERROR the `#[rustc_error]` attribute is just used for rustc unit tests and will never be stable
 #[rustc_move_fragments] //~ ERROR the `#[rustc_move_fragments]` attribute is just used for rustc unit tests and will never be stable
+#[rustc_mir_dump_on_ice] //~ ERROR the `#[rustc_mir_dump_on_ice]` attribute is just used for debugging rustc and will never be stable
+#[rustc_mir_build_bug] //~ ERROR the `#[rustc_mir_build_bug]` attribute is just used for rustc unit tests and will never be stable
 #[rustc_foo]
 //~^ ERROR unless otherwise specified, attributes with the prefix `rustc_` are reserved for internal compiler diagnostics
 
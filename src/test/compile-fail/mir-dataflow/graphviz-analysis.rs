@@ -0,0 +1,40 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that `borrowck_graphviz_analysis` must name one of the dataflow
+// analyses.
+
+#![feature(rustc_attrs)]
+
+#[rustc_mir_borrowck]
+#[rustc_mir(borrowck_graphviz_analysis="maybe_inits")]
+//~^ ERROR unknown analysis `maybe_inits` in borrowck_graphviz_analysis, expected one of
+fn unknown(x: u32) -> u32 {
+    x
+}
+
+#[rustc_mir_borrowck]
+#[rustc_mir(borrowck_graphviz_analysis)]
+//~^ ERROR borrowck_graphviz_analysis attribute requires one of `maybe_init`
+fn missing(x: u32) -> u32 {
+    x
+}
+
+#[rustc_mir_borrowck]
+#[rustc_mir(borrowck_graphviz_analysis="definite_init")]
+fn known(x: u32) -> u32 {
+    x
+}
+
+fn main() {
+    unknown(0);
+    missing(0);
+    known(0);
+}
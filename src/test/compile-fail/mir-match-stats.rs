@@ -0,0 +1,36 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-match-stats -Z mir-match-stats-threshold=10
+
+#![feature(rustc_attrs)]
+#![allow(dead_code)]
+
+fn small(b: bool) -> u8 {
+    match b { //~ NOTE match has 2 candidates, performs 1 tests
+        true => 1,
+        false => 0,
+    }
+}
+
+fn large(x: u8, y: u8) -> u8 {
+    match (x, y) { //~ WARNING match has 6 candidates
+    //~| NOTE more than the 10 blocks of -Z mir-match-stats-threshold
+        (0, 0) => 1,
+        (0, 1) | (1, 0) => 2,
+        (1, 1) => 3,
+        (2, _) => 4,
+        _ => 5,
+    }
+}
+
+#[rustc_error]
+fn main() { //~ ERROR compilation successful
+}
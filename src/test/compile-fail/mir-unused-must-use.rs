@@ -0,0 +1,51 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that results of `#[must_use]` types that are moved into an
+// aggregate that is never used are reported, once, from MIR.
+
+#![feature(question_mark)]
+#![deny(unused_must_use)]
+#![allow(dead_code)]
+
+#[must_use]
+enum MustUse { Test }
+
+#[must_use = "some message"]
+enum MustUseMsg { Test2 }
+
+fn foo<T>() -> T { panic!() }
+
+macro_rules! pair {
+    ($a:expr, $b:expr) => (($a, $b))
+}
+
+fn question_mark() -> Result<(), ()> {
+    (foo::<Result<MustUse, ()>>()?, 1); //~ ERROR: unused result which must be used
+    Ok(())
+}
+
+#[allow(unused_must_use)]
+fn allowed() {
+    (foo::<MustUse>(), 1);
+}
+
+fn main() {
+    foo::<MustUse>(); //~ ERROR: unused result which must be used
+    (foo::<MustUse>(), 1); //~ ERROR: unused result which must be used
+    ((foo::<MustUseMsg>(), 1), 2); //~ ERROR: unused result which must be used: some message
+    pair!(foo::<MustUse>(), foo::<Option<MustUse>>());
+    //~^ ERROR: unused result which must be used
+
+    let _ = (foo::<MustUse>(), 1);
+    let t = (foo::<MustUse>(), 1);
+    drop(t);
+    drop((foo::<MustUse>(), 1));
+}
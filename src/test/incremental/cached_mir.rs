@@ -0,0 +1,82 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the MIR of items that did not change is loaded from the
+// cache in the second revision with its def-ids and spans intact: the
+// closure and the enum have to be found again, and the overflow check
+// in `add` has to report the line it is on. The MIR of the items that
+// did change has to be built again.
+
+// revisions: rpass1 rpass2
+// compile-flags: -C debug-assertions
+
+#![feature(rustc_attrs)]
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+static PANIC_LINE: AtomicUsize = ATOMIC_USIZE_INIT;
+
+pub enum Shape {
+    Circle(u32),
+    Square { side: u32 },
+}
+
+#[rustc_mir_reused(cfg="rpass2")]
+pub fn areas(shapes: &[Shape]) -> Vec<u32> {
+    shapes.iter().map(|shape| match *shape {
+        Shape::Circle(r) => 3 * r * r,
+        Shape::Square { side } => side * side,
+    }).collect()
+}
+
+#[rustc_mir_reused(cfg="rpass2")]
+pub fn add(a: u8, b: u8) -> u8 {
+    a + b
+}
+
+#[rustc_mir_reused(cfg="rpass2")]
+pub fn add_line() -> u32 {
+    line!() - 5
+}
+
+#[rustc_mir_rebuilt(cfg="rpass2")]
+pub fn changed() -> u32 {
+    #[cfg(rpass1)]
+    let x = 1;
+    #[cfg(rpass2)]
+    let x = 2;
+    x
+}
+
+#[rustc_mir_rebuilt(cfg="rpass2")]
+fn main() {
+    #[cfg(rpass1)]
+    let shapes = [Shape::Circle(1)];
+    #[cfg(rpass2)]
+    let shapes = [Shape::Circle(1), Shape::Square { side: 2 }];
+
+    #[cfg(rpass1)]
+    assert_eq!(areas(&shapes), [3]);
+    #[cfg(rpass2)]
+    assert_eq!(areas(&shapes), [3, 4]);
+
+    #[cfg(rpass1)]
+    assert_eq!(changed(), 1);
+    #[cfg(rpass2)]
+    assert_eq!(changed(), 2);
+
+    panic::set_hook(Box::new(|info| {
+        let line = info.location().unwrap().line();
+        PANIC_LINE.store(line as usize, Ordering::SeqCst);
+    }));
+    assert!(panic::catch_unwind(|| add(200, 100)).is_err());
+    assert_eq!(PANIC_LINE.load(Ordering::SeqCst), add_line() as usize);
+}
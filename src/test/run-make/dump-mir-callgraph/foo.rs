@@ -0,0 +1,35 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        double(self.0) / 2 * self.0
+    }
+}
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn total(shape: &Shape) -> u32 {
+    shape.area()
+}
+
+fn main() {
+    total(&Square(3));
+    let add = |x| double(x) + 1;
+    add(2);
+}
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(rustc_attrs)]
+
+#[must_use]
+pub struct Token;
+
+pub fn token() -> Token {
+    Token
+}
+
+#[rustc_mir_rebuilt(cfg="second")]
+pub fn discard() {
+    (token(), 1);
+}
+
+#[rustc_mir_reused(cfg="second")]
+pub fn keep() -> Token {
+    token()
+}
+
+fn main() {
+    discard();
+    let _ = keep();
+}
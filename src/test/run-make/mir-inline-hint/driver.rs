@@ -0,0 +1,77 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Prints the inline hint score recorded in the metadata of each function
+// of the `hints` crate, as `main.rs` would see it.
+
+#![feature(rustc_private)]
+
+extern crate getopts;
+extern crate rustc;
+extern crate rustc_driver;
+
+use rustc::hir::def::Def;
+use rustc::middle::cstore::DefLike;
+use rustc::mir::stats::INLINE_HINT_THRESHOLD;
+use rustc::session::Session;
+use rustc_driver::{CompilerCalls, Compilation};
+use rustc_driver::driver::CompileController;
+use std::path::PathBuf;
+
+struct HintCalls;
+
+impl<'a> CompilerCalls<'a> for HintCalls {
+    fn build_controller(&mut self,
+                        _: &Session,
+                        _: &getopts::Matches)
+                        -> CompileController<'a> {
+        let mut cc = CompileController::basic();
+        cc.after_analysis.stop = Compilation::Stop;
+        cc.after_analysis.callback = Box::new(|state| {
+            let cstore = &state.session.cstore;
+            for cnum in cstore.crates() {
+                if &cstore.crate_name(cnum)[..] != "hints" {
+                    continue;
+                }
+                for child in cstore.crate_top_level_items(cnum) {
+                    if let DefLike::DlDef(Def::Fn(def_id)) = child.def {
+                        let hint = match cstore.item_mir_inline_hint(def_id) {
+                            Some(score) if score > INLINE_HINT_THRESHOLD => "too large",
+                            Some(_) => "inlinable",
+                            None => "no hint",
+                        };
+                        println!("{}: {}", child.name, hint);
+                    }
+                }
+            }
+        });
+        cc
+    }
+}
+
+fn main() {
+    // The arguments are those of `$(RUSTC)`: the rustc path, after the
+    // environment it is run in, and then the flags it is run with.
+    let args: Vec<String> = std::env::args().collect();
+    let mut sysroot = PathBuf::from(&args[2]);
+    sysroot.pop();
+    sysroot.pop();
+
+    let mut rustc_args = vec!["_".to_string(),
+                              "main.rs".to_string(),
+                              "--sysroot".to_string(),
+                              sysroot.to_str().unwrap().to_string()];
+    rustc_args.extend(args[3..].iter().cloned());
+
+    let (result, _) = rustc_driver::run_compiler(&rustc_args, &mut HintCalls);
+    if let Err(n) = result {
+        panic!("Error {}", n);
+    }
+}
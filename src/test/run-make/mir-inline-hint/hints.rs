@@ -0,0 +1,30 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "rlib"]
+
+#[inline]
+pub fn small(x: u32) -> u32 {
+    x + 1
+}
+
+macro_rules! twice {
+    ($e:expr) => { { $e; $e } }
+}
+
+#[inline]
+pub fn big(mut x: u32) -> u32 {
+    twice!(twice!(twice!(twice!(twice!(twice!(x = small(x)))))));
+    x
+}
+
+pub fn plain(x: u32) -> u32 {
+    x
+}
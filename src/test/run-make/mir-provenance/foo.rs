@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn f(x: u32) -> u32 {
+    x
+}
+
+// Both calls return to the same block and unwind to the drop of `s`, so
+// `AddCallGuards` has to break the edges they return along.
+fn pick(c: bool, s: String) -> u32 {
+    let r = if c { f(1) } else { f(2) };
+    drop(s);
+    r
+}
+
+fn main() {
+    pick(true, String::new());
+}
@@ -0,0 +1,31 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+enum Void {}
+
+fn unwrap(r: Result<u32, Void>) -> u32 {
+    match r {
+        Ok(n) => n,
+        Err(e) => match e {},
+    }
+}
+
+fn either(c: bool, v: Void) -> u32 {
+    if c {
+        1
+    } else {
+        match v {}
+    }
+}
+
+fn main() {
+    assert_eq!(unwrap(Ok(7)), 7);
+    let _ = either;
+}
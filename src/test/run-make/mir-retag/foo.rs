@@ -0,0 +1,19 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn first(v: &mut (u32, u32)) -> &mut u32 {
+    &mut v.0
+}
+
+fn main() {
+    let mut pair = (1, 2);
+    *first(&mut pair) += 10;
+    assert_eq!(pair, (11, 2));
+}
@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn small(x: u32) -> u32 {
+    x
+}
+
+fn big(x: u32) -> u32 {
+    let mut v = Vec::new();
+    for i in 0..x {
+        v.push(i * 2 + 1);
+        v.push(i * 3 + 2);
+        v.push(i * 5 + 3);
+        v.push(i * 7 + 4);
+    }
+    let mut sum = 0;
+    for n in &v {
+        if *n % 2 == 0 {
+            sum += *n;
+        } else {
+            sum -= *n / 2;
+        }
+    }
+    sum
+}
+
+fn main() {
+    small(1);
+    big(3);
+}
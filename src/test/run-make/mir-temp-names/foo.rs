@@ -0,0 +1,21 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn square(x: u32) -> u32 {
+    x * x
+}
+
+fn sum_squared(a: u32, b: u32) -> u32 {
+    square(a + b) + 1
+}
+
+fn main() {
+    assert_eq!(sum_squared(1, 2), 10);
+}
@@ -0,0 +1,26 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+fn may_panic(n: u32) -> u32 {
+    if n == 0 { panic!() }
+    n
+}
+
+pub fn drops_on_unwind(v: Vec<u32>) -> usize {
+    let s = String::new();
+    may_panic(v.len() as u32);
+    s.len() + v.len()
+}
+
+pub fn no_cleanup(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(associated_consts)]
+
+trait Scale {
+    const FACTOR: u32;
+}
+
+struct Triple;
+
+impl Scale for Triple {
+    const FACTOR: u32 = 3;
+}
+
+fn scale<S: Scale>(x: u32) -> u32 {
+    x * S::FACTOR
+}
+
+fn wrap<T>(x: T) -> Option<T> {
+    Some(x)
+}
+
+fn main() {
+    assert_eq!(scale::<Triple>(2), 6);
+    assert_eq!(wrap(1u8), Some(1));
+}
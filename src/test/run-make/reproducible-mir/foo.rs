@@ -0,0 +1,47 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+const LIMITS: &'static [u32] = &[1, 2, 3];
+
+static NAMES: [&'static str; 2] = ["a", "b"];
+
+struct Shape {
+    sides: Vec<u32>,
+}
+
+impl Shape {
+    fn perimeter(&self) -> u32 {
+        self.sides.iter().fold(0, |acc, side| acc + side)
+    }
+}
+
+fn promoted() -> (&'static u32, &'static [u32; 2]) {
+    (&7, &[4, 5])
+}
+
+fn drop_flags(cond: bool) -> Option<Shape> {
+    let a = Shape { sides: vec![1, 2] };
+    let b = Shape { sides: vec![3] };
+    let c = Shape { sides: vec![] };
+    if cond {
+        drop(a);
+        Some(b)
+    } else {
+        drop(b);
+        drop(c);
+        None
+    }
+}
+
+fn main() {
+    let shape = drop_flags(LIMITS.len() > 2).unwrap();
+    let scale = |x: u32| x * *promoted().0;
+    println!("{} {} {}", NAMES[0], scale(shape.perimeter()), promoted().1[1]);
+}
@@ -0,0 +1,78 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+extern crate syntax_pos;
+
+use rustc::mir::transform::{self, MirPass, MirSource};
+use rustc::mir::repr::*;
+use rustc::ty::TyCtxt;
+use rustc_plugin::Registry;
+use syntax_pos::Span;
+
+struct Pass;
+
+impl transform::Pass for Pass {}
+
+// Checks the spans of the adjustments of the receiver of `v.len()` in
+// `count`: `v` is autoderefed twice, from `Rc<Vec<u8>>` to `[u8]`, with a
+// call to `Deref::deref` each, and then borrowed for the call.
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return
+        };
+        if tcx.item_name(tcx.map.local_def_id(id)).as_str() != "count" {
+            return;
+        }
+        let snippet = |span: Span| tcx.sess.codemap().span_to_snippet(span).unwrap();
+
+        // Each autoderef points at the expression it derefs.
+        let deref_spans: Vec<_> = mir.basic_blocks().iter().filter_map(|data| {
+            let terminator = data.terminator();
+            match terminator.kind {
+                TerminatorKind::Call {
+                    func: Operand::Constant(Constant {
+                        literal: Literal::Item { def_id, .. }, ..
+                    }), ..
+                } if tcx.item_name(def_id).as_str() == "deref" => {
+                    Some(snippet(terminator.source_info.span))
+                }
+                _ => None
+            }
+        }).collect();
+        assert_eq!(deref_spans, ["v", "v"]);
+
+        // The borrows of the autoderefs point at `v` too, while the autoref
+        // of the receiver points at the call it is taken for.
+        let borrow_spans: Vec<_> = mir.basic_blocks().iter().flat_map(|data| {
+            data.statements.iter().filter_map(|statement| match statement.kind {
+                StatementKind::Assign(_, Rvalue::Ref(..)) => {
+                    Some(snippet(statement.source_info.span))
+                }
+                _ => None
+            })
+        }).collect();
+        assert_eq!(borrow_spans, ["v", "v", "v.len()"]);
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass(box Pass);
+}
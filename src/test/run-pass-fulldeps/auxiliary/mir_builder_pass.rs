@@ -0,0 +1,76 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+extern crate rustc_mir;
+extern crate syntax;
+
+use rustc::mir::transform::{self, MirPass, MirSource};
+use rustc::mir::repr::*;
+use rustc::ty::TyCtxt;
+use rustc::middle::const_val::ConstVal;
+use rustc_mir::test_support::{MirBuilder, constant};
+use rustc_mir::transform::simplify_cfg::SimplifyCfg;
+use rustc_mir::transform::thread_switches::ThreadSwitches;
+use rustc_plugin::Registry;
+
+struct Pass;
+
+impl transform::Pass for Pass {}
+
+// Runs passes on bodies built with `MirBuilder`, next to each body of the
+// crate, and checks what they did with them.
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, _: &mut Mir<'tcx>) {
+        // Gotos through empty blocks are merged away.
+        let mut builder = MirBuilder::new(tcx.types.bool);
+        let x = builder.var("x", tcx.types.bool);
+        let exit = builder.block()
+                          .assign(Lvalue::ReturnPointer, Rvalue::Use(Operand::Consume(x.clone())))
+                          .ret();
+        let middle = builder.block().goto(exit);
+        builder.start()
+               .assign(x, Rvalue::Use(constant(tcx.types.bool, ConstVal::Bool(true))))
+               .goto(middle);
+        let mut mir = builder.finish();
+        SimplifyCfg::new("mir_builder_pass").run_pass(tcx, src, &mut mir);
+        assert_eq!(mir.basic_blocks().len(), 1);
+        assert_eq!(mir[START_BLOCK].statements.len(), 2);
+
+        // A jump to a test of a known value goes straight to its target.
+        let mut builder = MirBuilder::new(tcx.mk_nil());
+        let x = builder.var("x", tcx.types.bool);
+        let test = builder.new_block();
+        let then = builder.block().ret();
+        let otherwise = builder.block().ret();
+        builder.start()
+               .assign(x.clone(), Rvalue::Use(constant(tcx.types.bool, ConstVal::Bool(false))))
+               .goto(test);
+        builder.at(test).branch(Operand::Consume(x), then, otherwise);
+        let mut mir = builder.finish();
+        ThreadSwitches.run_pass(tcx, src, &mut mir);
+        match mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Goto { target } => assert_eq!(target, otherwise),
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass(box Pass);
+}
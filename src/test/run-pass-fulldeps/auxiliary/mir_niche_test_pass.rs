@@ -0,0 +1,64 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+
+use rustc::mir::transform::{self, MirPass, MirSource};
+use rustc::mir::repr::*;
+use rustc::session::config::OptLevel;
+use rustc::ty::TyCtxt;
+use rustc_plugin::Registry;
+
+struct Pass;
+
+impl transform::Pass for Pass {}
+
+// Checks how the match of `simple` tells `Some` and `None` apart: by
+// switching on the discriminant when optimizing for size, and by comparing
+// the reference with zero otherwise.
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return
+        };
+        if tcx.item_name(tcx.map.local_def_id(id)).as_str() != "simple" {
+            return;
+        }
+        let switches = mir.basic_blocks().iter().any(|data| match data.terminator().kind {
+            TerminatorKind::Switch { .. } => true,
+            _ => false
+        });
+        let compares = mir.basic_blocks().iter().flat_map(|data| &data.statements).any(|stmt| {
+            match stmt.kind {
+                StatementKind::Assign(_, Rvalue::BinaryOp(BinOp::Eq, _, _)) => true,
+                _ => false
+            }
+        });
+        let for_size = match tcx.sess.opts.optimize {
+            OptLevel::Size | OptLevel::SizeMin => true,
+            _ => false
+        };
+        assert_eq!(switches, for_size);
+        assert_eq!(compares, !for_size);
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass(box Pass);
+}
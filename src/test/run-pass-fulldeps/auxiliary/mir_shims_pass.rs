@@ -0,0 +1,242 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+extern crate rustc_mir;
+extern crate rustc_const_math;
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::transform::{self, MirPass, MirSource};
+use rustc::mir::repr::*;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::subst::{self, Substs};
+use rustc_const_math::ConstInt;
+use rustc_mir::shim::{self, Shim};
+use rustc_plugin::Registry;
+
+struct Pass;
+
+impl transform::Pass for Pass {}
+
+// The callees of the calls in `mir`.
+fn callees<'tcx>(mir: &Mir<'tcx>) -> Vec<(DefId, &'tcx Substs<'tcx>)> {
+    mir.basic_blocks().iter().filter_map(|data| match data.terminator().kind {
+        TerminatorKind::Call {
+            func: Operand::Constant(Constant {
+                literal: Literal::Item { def_id, substs }, ..
+            }), ..
+        } => Some((def_id, substs)),
+        _ => None
+    }).collect()
+}
+
+fn callee<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>, name: &str)
+                    -> (DefId, &'tcx Substs<'tcx>) {
+    callees(mir).into_iter().find(|&(def_id, _)| tcx.item_name(def_id).as_str() == name)
+                .unwrap_or_else(|| bug!("no call to `{}`", name))
+}
+
+// The value `block` of a shim returns.
+fn returned_u64<'tcx>(mir: &Mir<'tcx>, block: BasicBlock) -> u64 {
+    match mir[block].statements[0].kind {
+        StatementKind::Assign(Lvalue::ReturnPointer, Rvalue::Use(Operand::Constant(Constant {
+            literal: Literal::Value { value: ConstVal::Integral(ConstInt::U64(value)) }, ..
+        }))) => value,
+        ref kind => bug!("unexpected statement {:?}", kind)
+    }
+}
+
+// Builds shims for the calls and types found in `main`, and checks that
+// they do what trans does for them.
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return
+        };
+        if tcx.item_name(tcx.map.local_def_id(id)).as_str() != "main" {
+            return;
+        }
+        let var_ty = |name: &str| {
+            mir.var_decls.iter().find(|decl| decl.name.as_str() == name)
+               .unwrap_or_else(|| bug!("no variable `{}`", name)).ty
+        };
+
+        // A call through a trait object calls the method of the vtable,
+        // and resumes unwinding if it unwinds.
+        let (area, substs) = callee(tcx, mir, "area");
+        match substs.self_ty().unwrap().sty {
+            ty::TyTrait(_) => {}
+            ref sty => bug!("`area` called on {:?}", sty)
+        }
+        let shim_mir = shim::build_shim(tcx, Shim::Virtual(area, substs));
+        assert_eq!(shim_mir.arg_decls.len(), 1);
+        match shim_mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Call {
+                func: Operand::Constant(Constant {
+                    literal: Literal::Item { def_id, .. }, ..
+                }),
+                ref args,
+                destination: Some(_),
+                cleanup: Some(cleanup)
+            } => {
+                assert_eq!(def_id, area);
+                assert_eq!(args.len(), 1);
+                assert!(shim_mir[cleanup].is_cleanup);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind)
+        }
+
+        // The `FnOnce::call_once` shim of the function passed to `apply`.
+        let applied = |is_closure: bool| {
+            callees(mir).into_iter().filter_map(|(def_id, substs)| {
+                if tcx.item_name(def_id).as_str() != "apply" {
+                    return None;
+                }
+                let ty = substs.types.get_slice(subst::FnSpace)[0];
+                match ty.sty {
+                    ty::TyClosure(..) if is_closure => Some(ty),
+                    ty::TyFnDef(..) if !is_closure => Some(ty),
+                    _ => None
+                }
+            }).next().unwrap()
+        };
+        let fn_once = tcx.lang_items.fn_once_trait().unwrap();
+        let call_once = tcx.trait_items(fn_once).iter().filter_map(|item| match *item {
+            ty::MethodTraitItem(ref method) => Some(method.def_id),
+            _ => None
+        }).next().unwrap();
+        let call_once_shim = |ty: Ty<'tcx>| {
+            let substs = Substs::new_trait(vec![tcx.mk_tup(vec![tcx.types.u32])], vec![], ty);
+            shim::fn_trait_shim(tcx, call_once, tcx.mk_substs(substs))
+        };
+
+        // `FnOnce::call_once` of a function item calls the function with
+        // the fields of the argument tuple.
+        let fn_ty = applied(false);
+        let shim = call_once_shim(fn_ty);
+        match shim {
+            Some(Shim::FnPtr(ty::ClosureKind::FnOnce, ty)) => assert_eq!(ty, fn_ty),
+            ref shim => bug!("unexpected shim {:?}", shim)
+        }
+        let shim_mir = shim::build_shim(tcx, shim.unwrap());
+        assert_eq!(shim_mir.arg_decls.len(), 2);
+        match shim_mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Call {
+                func: Operand::Constant(Constant {
+                    literal: Literal::Item { def_id, .. }, ..
+                }),
+                ref args, ..
+            } => {
+                assert_eq!(tcx.item_name(def_id).as_str(), "double");
+                assert_eq!(args.len(), 1);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind)
+        }
+
+        // `FnOnce::call_once` of a `Fn` closure calls `Fn::call` with a
+        // reference to the closure, and drops the closure afterwards,
+        // whether the call returns or unwinds.
+        let closure_ty = applied(true);
+        let shim = call_once_shim(closure_ty);
+        match shim {
+            Some(Shim::ClosureOnce(..)) => {}
+            ref shim => bug!("unexpected shim {:?}", shim)
+        }
+        let shim_mir = shim::build_shim(tcx, shim.unwrap());
+        assert_eq!(shim_mir.arg_decls.iter().next().unwrap().ty, closure_ty);
+        let fn_trait = tcx.lang_items.fn_trait().unwrap();
+        let is_drop = |block: BasicBlock| match shim_mir[block].terminator().kind {
+            TerminatorKind::Drop { .. } => true,
+            _ => false
+        };
+        match shim_mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Call {
+                func: Operand::Constant(Constant {
+                    literal: Literal::Item { def_id, .. }, ..
+                }),
+                destination: Some((_, target)),
+                cleanup: Some(cleanup), ..
+            } => {
+                assert_eq!(tcx.trait_of_item(def_id), Some(fn_trait));
+                assert!(is_drop(target));
+                assert!(is_drop(cleanup));
+                assert!(shim_mir[cleanup].is_cleanup);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind)
+        }
+
+        // Drop glue drops the fields of a value, and the `Vec` of a
+        // `String` in turn.
+        let string_ty = var_ty("name");
+        let shim_mir = shim::build_shim(tcx, Shim::DropGlue(string_ty));
+        assert_eq!(shim_mir.arg_decls.iter().next().unwrap().ty, tcx.mk_mut_ptr(string_ty));
+        assert!(shim_mir.basic_blocks().iter().any(|data| match data.terminator().kind {
+            TerminatorKind::Drop { .. } => true,
+            _ => false
+        }));
+
+        // A `Copy` type is cloned by copying it.
+        let shim_mir = shim::build_shim(tcx, Shim::Clone(tcx.types.u32));
+        match shim_mir[START_BLOCK].statements[0].kind {
+            StatementKind::Assign(Lvalue::ReturnPointer,
+                                  Rvalue::Use(Operand::Consume(Lvalue::Projection(_)))) => {}
+            ref kind => bug!("unexpected statement {:?}", kind)
+        }
+
+        // A tuple is cloned field by field, with `Clone::clone`.
+        let tuple_ty = tcx.mk_tup(vec![tcx.types.u32, string_ty]);
+        let shim_mir = shim::build_shim(tcx, Shim::Clone(tuple_ty));
+        let clone_trait = tcx.lang_items.clone_trait().unwrap();
+        assert!(callees(&shim_mir).iter().any(|&(def_id, _)| {
+            tcx.trait_of_item(def_id) == Some(clone_trait)
+        }));
+        assert!(shim_mir.basic_blocks().iter().flat_map(|data| &data.statements).any(|stmt| {
+            match stmt.kind {
+                StatementKind::Assign(Lvalue::ReturnPointer,
+                                      Rvalue::Aggregate(AggregateKind::Tuple, ref fields)) => {
+                    fields.len() == 2
+                }
+                _ => false
+            }
+        }));
+
+        // The discriminant of an enum is returned sign-extended, and that
+        // of any other type is 0.
+        let shim_mir = shim::build_shim(tcx, Shim::Discriminant(var_ty("size")));
+        match shim_mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Switch { ref targets, .. } => {
+                let values: Vec<_> = targets.iter().map(|&target| {
+                    returned_u64(&shim_mir, target)
+                }).collect();
+                assert_eq!(values, [!0, 10]);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind)
+        }
+        let shim_mir = shim::build_shim(tcx, Shim::Discriminant(tcx.types.u32));
+        match shim_mir[START_BLOCK].terminator().kind {
+            TerminatorKind::Goto { target } => assert_eq!(returned_u64(&shim_mir, target), 0),
+            ref kind => bug!("unexpected terminator {:?}", kind)
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass(box Pass);
+}
@@ -0,0 +1,311 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+extern crate rustc_const_math;
+extern crate rustc_mir;
+extern crate syntax;
+
+use rustc::mir::transform::{self, MirPass, MirSource};
+use rustc::mir::repr::*;
+use rustc::ty::{self, TyCtxt};
+use rustc::ty::subst::Substs;
+use rustc::middle::const_val::ConstVal;
+use rustc_const_math::ConstInt;
+use rustc_mir::stable_mir::{self, Tables};
+use rustc_mir::test_support::{MirBuilder, constant};
+use rustc_plugin::Registry;
+
+struct Pass;
+
+impl transform::Pass for Pass {}
+
+// Builds a body with one statement of each kind in its start block and one
+// terminator of each kind, one per block, when run on `switch` in the test
+// crate, and checks what they are converted to.
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        let def_id = match src {
+            MirSource::Fn(id) => tcx.map.local_def_id(id),
+            _ => return,
+        };
+        if tcx.item_name(def_id).as_str() != "switch" {
+            return;
+        }
+        let enum_ty = mir.arg_decls.iter().next().unwrap().ty;
+        let adt_def = match enum_ty.sty {
+            ty::TyEnum(adt_def, _) => adt_def,
+            ref sty => bug!("unexpected argument type {:?}", sty),
+        };
+        let (u32_ty, bool_ty) = (tcx.types.u32, tcx.types.bool);
+        let one = || constant(u32_ty, ConstVal::Integral(ConstInt::U32(1)));
+
+        let mut builder = MirBuilder::new(tcx.mk_nil());
+        let x = builder.var("x", u32_ty);
+        let b = builder.var("b", bool_ty);
+        let e = builder.var("e", enum_ty);
+        let p = builder.var("p", tcx.mk_mut_ptr(u32_ty));
+        let blocks: Vec<_> = (0..11).map(|_| builder.new_block()).collect();
+        let cleanup = Some(blocks[9]);
+        builder.start()
+               .assign(x.clone(), Rvalue::Use(one()))
+               .statement(StatementKind::SetDiscriminant { lvalue: e.clone(), variant_index: 1 })
+               .statement(StatementKind::Coverage {
+                   function_hash: 0,
+                   num_counters: 1,
+                   index: 0
+               })
+               .statement(StatementKind::SanitizerCheck {
+                   kind: SanitizerCheckKind::Load,
+                   lvalue: p.clone().deref()
+               })
+               .statement(StatementKind::Subtype { lvalue: x.clone(), ty: u32_ty })
+               .statement(StatementKind::Retag { kind: RetagKind::FnEntry, lvalue: p.clone() })
+               .statement(StatementKind::Atomic {
+                   op: AtomicOp::Add,
+                   ordering: AtomicOrdering::SeqCst,
+                   dest: Some(x.clone()),
+                   ptr: Operand::Consume(p.clone()),
+                   value: Some(one())
+               })
+               .statement(StatementKind::Probe { kind: ProbeKind::Entry, function_id: 7 })
+               .goto(blocks[0]);
+        builder.at(blocks[0]).branch(constant(bool_ty, ConstVal::Bool(true)),
+                                     blocks[1], blocks[2]);
+        builder.at(blocks[1]).terminate(TerminatorKind::Switch {
+            discr: e.clone(),
+            adt_def: adt_def,
+            targets: vec![blocks[2], blocks[3]]
+        });
+        builder.at(blocks[2]).terminate(TerminatorKind::SwitchInt {
+            discr: b.clone(),
+            switch_ty: bool_ty,
+            values: vec![ConstVal::Bool(true)],
+            targets: vec![blocks[3], blocks[4]]
+        });
+        builder.at(blocks[3]).terminate(TerminatorKind::Drop {
+            location: e.clone(),
+            target: blocks[4],
+            unwind: cleanup
+        });
+        builder.at(blocks[4]).terminate(TerminatorKind::DropAndReplace {
+            location: x.clone(),
+            value: one(),
+            target: blocks[5],
+            unwind: cleanup
+        });
+        builder.at(blocks[5]).terminate(TerminatorKind::Call {
+            func: Operand::Constant(Constant {
+                span: mir.span,
+                ty: tcx.lookup_item_type(def_id).ty,
+                literal: Literal::Item {
+                    def_id: def_id,
+                    substs: tcx.mk_substs(Substs::empty())
+                }
+            }),
+            args: vec![Operand::Consume(e.clone())],
+            destination: Some((Lvalue::ReturnPointer, blocks[6])),
+            cleanup: cleanup
+        });
+        builder.at(blocks[6]).terminate(TerminatorKind::Assert {
+            cond: Operand::Consume(b.clone()),
+            expected: true,
+            msg: AssertMessage::BoundsCheck {
+                len: Operand::Consume(x.clone()),
+                index: one()
+            },
+            target: blocks[7],
+            cleanup: cleanup
+        });
+        builder.at(blocks[7]).ret();
+        builder.at(blocks[8]).terminate(TerminatorKind::Unreachable);
+        builder.at(blocks[9]).cleanup().terminate(TerminatorKind::Resume);
+        builder.at(blocks[10]).cleanup().terminate(TerminatorKind::Abort);
+        let mir = builder.finish();
+
+        let mut tables = Tables::new(tcx);
+        let body = tables.body(&mir);
+        // There are no arguments, so the variables come first.
+        let local = |index| stable_mir::Place {
+            base: stable_mir::PlaceBase::Local(index),
+            projection: vec![],
+        };
+        let (x, b, e, p) = (local(0), local(1), local(2), local(3));
+        assert_eq!(body.locals.len(), 4);
+        assert_eq!(body.blocks.len(), 12);
+
+        let statements = &body.blocks[0].statements;
+        assert_eq!(statements.len(), 8);
+        match statements[0].kind {
+            stable_mir::StatementKind::Assign(ref place, stable_mir::Rvalue::Use(_)) => {
+                assert_eq!(place.base, x.base);
+            }
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[1].kind {
+            stable_mir::StatementKind::SetDiscriminant { ref place, variant_index: 1 } => {
+                assert_eq!(place.base, e.base);
+            }
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[2].kind {
+            stable_mir::StatementKind::Coverage { index: 0 } => {}
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[3].kind {
+            stable_mir::StatementKind::SanitizerCheck {
+                kind: stable_mir::SanitizerCheckKind::Load,
+                ref place
+            } => {
+                assert_eq!(place.base, p.base);
+                assert_eq!(place.projection.len(), 1);
+                match place.projection[0] {
+                    stable_mir::ProjectionElem::Deref => {}
+                    ref elem => bug!("unexpected projection {:?}", elem),
+                }
+            }
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[4].kind {
+            stable_mir::StatementKind::Subtype { ref place, ty } => {
+                assert_eq!(place.base, x.base);
+                assert_eq!(tables.ty_to_string(ty), "u32");
+            }
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[5].kind {
+            stable_mir::StatementKind::Retag {
+                kind: stable_mir::RetagKind::FnEntry,
+                ref place
+            } => assert_eq!(place.base, p.base),
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[6].kind {
+            stable_mir::StatementKind::Atomic {
+                op: stable_mir::AtomicOp::Add,
+                ordering: stable_mir::AtomicOrdering::SeqCst,
+                dest: Some(ref dest),
+                ptr: stable_mir::Operand::Consume(ref ptr),
+                value: Some(_)
+            } => {
+                assert_eq!(dest.base, x.base);
+                assert_eq!(ptr.base, p.base);
+            }
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+        match statements[7].kind {
+            stable_mir::StatementKind::Probe {
+                kind: stable_mir::ProbeKind::Entry,
+                function_id: 7
+            } => {}
+            ref kind => bug!("unexpected statement {:?}", kind),
+        }
+
+        // Block `i` of the body is `blocks[i - 1]`.
+        let terminators: Vec<_> = body.blocks.iter().map(|block| &block.terminator.kind)
+                                                    .collect();
+        match *terminators[0] {
+            stable_mir::TerminatorKind::Goto { target: 1 } => {}
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[1] {
+            stable_mir::TerminatorKind::If {
+                cond: stable_mir::Operand::Constant(_),
+                then_target: 2,
+                else_target: 3
+            } => {}
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[2] {
+            stable_mir::TerminatorKind::Switch { ref discr, ref targets } => {
+                assert_eq!(discr.base, e.base);
+                assert_eq!(*targets, [3, 4]);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[3] {
+            stable_mir::TerminatorKind::SwitchInt { ref discr, ref values, ref targets } => {
+                assert_eq!(discr.base, b.base);
+                assert_eq!(*values, ["true"]);
+                assert_eq!(*targets, [4, 5]);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[4] {
+            stable_mir::TerminatorKind::Drop { ref place, target: 5, unwind: Some(10) } => {
+                assert_eq!(place.base, e.base);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[5] {
+            stable_mir::TerminatorKind::DropAndReplace {
+                ref place,
+                value: stable_mir::Operand::Constant(_),
+                target: 6,
+                unwind: Some(10)
+            } => assert_eq!(place.base, x.base),
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[6] {
+            stable_mir::TerminatorKind::Call {
+                func: stable_mir::Operand::Constant(stable_mir::Constant {
+                    literal: stable_mir::Literal::Item(item),
+                    ..
+                }),
+                ref args,
+                destination: Some((ref destination, 7)),
+                cleanup: Some(10)
+            } => {
+                assert_eq!(tables.def_id(item), def_id);
+                assert_eq!(args.len(), 1);
+                assert_eq!(destination.base, stable_mir::PlaceBase::ReturnPlace);
+            }
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[7] {
+            stable_mir::TerminatorKind::Assert {
+                cond: stable_mir::Operand::Consume(ref cond),
+                expected: true,
+                msg: stable_mir::AssertMessage::BoundsCheck { .. },
+                target: 8,
+                cleanup: Some(10)
+            } => assert_eq!(cond.base, b.base),
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[8] {
+            stable_mir::TerminatorKind::Return => {}
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[9] {
+            stable_mir::TerminatorKind::Unreachable => {}
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[10] {
+            stable_mir::TerminatorKind::Resume => assert!(body.blocks[10].is_cleanup),
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+        match *terminators[11] {
+            stable_mir::TerminatorKind::Abort => assert!(body.blocks[11].is_cleanup),
+            ref kind => bug!("unexpected terminator {:?}", kind),
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass(box Pass);
+}
@@ -0,0 +1,28 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_adjustment_spans_pass.rs
+// ignore-stage1
+
+// Test that the MIR of each adjustment step points at the source it comes
+// from (see the plugin).
+
+#![feature(plugin)]
+#![plugin(mir_adjustment_spans_pass)]
+
+use std::rc::Rc;
+
+fn count(v: Rc<Vec<u8>>) -> usize {
+    v.len()
+}
+
+fn main() {
+    assert_eq!(count(Rc::new(vec![1, 2, 3])), 3);
+}
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_needs_drop_pass.rs
+// ignore-stage1
+
+// Test that whether a type parameter is dropped depends on the bounds of
+// the function it belongs to, even if the same parameter was asked about
+// in another function before (see the plugin).
+
+#![feature(plugin)]
+#![plugin(mir_needs_drop_pass)]
+
+fn copied<T: Copy>(x: T) -> T {
+    let y = x;
+    y
+}
+
+fn dropped<T>(x: T) {
+    let _y = x;
+}
+
+fn main() {
+    copied(1);
+    dropped(String::new());
+}
@@ -0,0 +1,31 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_niche_test_pass.rs
+// ignore-stage1
+// compile-flags: -C opt-level=s
+
+// Test how matches on enums with a niche are built when optimizing
+// for size (see the plugin).
+
+#![feature(plugin)]
+#![plugin(mir_niche_test_pass)]
+
+fn simple(x: Option<&u8>) -> u8 {
+    match x {
+        Some(&v) => v,
+        None => 0,
+    }
+}
+
+fn main() {
+    assert_eq!(simple(Some(&1)), 1);
+    assert_eq!(simple(None), 0);
+}
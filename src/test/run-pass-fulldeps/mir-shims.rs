@@ -0,0 +1,49 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_shims_pass.rs
+// ignore-stage1
+
+// Test the shape of the shims built for the calls and types of `main` (see
+// the plugin).
+
+#![feature(plugin)]
+#![plugin(mir_shims_pass)]
+
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self) -> u32 { self.0 * self.0 }
+}
+
+enum Size {
+    Small = -1,
+    Large = 10,
+}
+
+fn double(x: u32) -> u32 { x * 2 }
+
+fn apply<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 { f(x) }
+
+fn main() {
+    let shape: &Shape = &Square(3);
+    assert_eq!(shape.area(), 9);
+    assert_eq!(apply(double, 2), 4);
+    let suffix = String::from("!");
+    assert_eq!(apply(move |x| x + suffix.len() as u32, 2), 3);
+    let name = String::from("shim");
+    assert_eq!(name.len(), 4);
+    let size = Size::Large;
+    assert!(match size { Size::Small => false, Size::Large => true });
+}
@@ -0,0 +1,36 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:stable_mir_pass.rs
+// ignore-stage1
+
+// Test that every kind of statement and terminator is converted to its
+// `stable_mir` counterpart (see the plugin).
+
+#![feature(plugin)]
+#![plugin(stable_mir_pass)]
+
+pub enum E {
+    A,
+    B,
+}
+
+// The plugin takes the type of `e` as the enum of a `Switch`.
+pub fn switch(e: E) {
+    match e {
+        E::A => {}
+        E::B => {}
+    }
+}
+
+fn main() {
+    switch(E::A);
+    switch(E::B);
+}
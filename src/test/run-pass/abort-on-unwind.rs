@@ -0,0 +1,70 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests that a panic in a function marked with `#[unwind(aborts)]` runs the
+// destructors of the function and then aborts instead of unwinding out of it.
+
+// ignore-emscripten no processes
+
+#![feature(unwind_attributes)]
+
+use std::env;
+use std::io::{self, Write};
+use std::process::Command;
+
+struct Noise;
+
+impl Drop for Noise {
+    fn drop(&mut self) {
+        let _ = writeln!(io::stderr(), "dropped");
+    }
+}
+
+struct Unreachable;
+
+impl Drop for Unreachable {
+    fn drop(&mut self) {
+        let _ = writeln!(io::stderr(), "unwound into the caller");
+    }
+}
+
+#[unwind(aborts)]
+extern "C" fn panic_with_drop() {
+    let _noise = Noise;
+    panic!("test");
+}
+
+#[unwind(aborts)]
+extern "C" fn panic_without_drop() {
+    panic!("test");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 1 {
+        let _unreachable = Unreachable;
+        match &*args[1] {
+            "with-drop" => panic_with_drop(),
+            "without-drop" => panic_without_drop(),
+            _ => unreachable!(),
+        }
+        return;
+    }
+
+    for &(arg, dropped) in &[("with-drop", true), ("without-drop", false)] {
+        let output = Command::new(&args[0]).arg(arg).output().unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(!output.status.success());
+        // Unwinding into `main` would exit with 101 instead of aborting.
+        assert!(output.status.code() != Some(101));
+        assert_eq!(stderr.contains("dropped"), dropped);
+        assert!(!stderr.contains("unwound into the caller"));
+    }
+}
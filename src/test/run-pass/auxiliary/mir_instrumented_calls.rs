@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-instrument-calls=aux_trace_probe
+
+#![crate_type = "rlib"]
+
+use std::ops::Add;
+
+static mut EVENTS: u32 = 0;
+
+#[no_mangle]
+pub extern "C" fn aux_trace_probe(_function_id: u64, _event: u32) {
+    unsafe {
+        EVENTS += 1;
+    }
+}
+
+pub fn events() -> u32 {
+    unsafe { EVENTS }
+}
+
+pub fn double<T: Copy + Add<Output=T>>(x: T) -> T {
+    x + x
+}
+
+#[inline]
+pub fn triple(x: u32) -> u32 {
+    x * 3
+}
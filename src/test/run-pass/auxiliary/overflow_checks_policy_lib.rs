@@ -0,0 +1,21 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C debug-assertions=no -Z overflow-checks-policy=caller
+
+#![crate_type = "rlib"]
+
+pub fn len_plus<T>(items: &[T], extra: usize) -> usize {
+    items.len() + extra
+}
+
+pub fn add(a: u8, b: u8) -> u8 {
+    a + b
+}
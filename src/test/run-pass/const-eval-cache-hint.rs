@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that cached constant values are keyed by the hint they were
+// evaluated with: a constant referenced both from a type, before type
+// checking, and from type-checked expressions is evaluated for each.
+
+const LEN: usize = 1 + 2;
+const DOUBLE_LEN: usize = LEN * 2;
+
+struct Buf {
+    data: [u8; LEN],
+    more: [u8; DOUBLE_LEN],
+}
+
+fn main() {
+    let buf = Buf { data: [0; LEN], more: [1; DOUBLE_LEN] };
+    assert_eq!(buf.data.len(), LEN);
+    assert_eq!(buf.more.len(), DOUBLE_LEN);
+    match buf.more.len() {
+        LEN => panic!(),
+        DOUBLE_LEN => {}
+        _ => panic!(),
+    }
+    assert_eq!(DOUBLE_LEN, 6);
+}
@@ -0,0 +1,46 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that cached constant values are keyed by their substitutions:
+// the same associated constant referenced through different impls must
+// not reuse a value computed for another impl.
+
+#![feature(associated_consts)]
+
+trait Size {
+    const SIZE: usize;
+}
+
+struct A;
+struct B;
+
+impl Size for A {
+    const SIZE: usize = 3;
+}
+
+impl Size for B {
+    const SIZE: usize = 5;
+}
+
+const A_TWICE: usize = <A as Size>::SIZE + <A as Size>::SIZE;
+const B_TWICE: usize = <B as Size>::SIZE + <B as Size>::SIZE;
+
+fn main() {
+    assert_eq!(A_TWICE, 6);
+    assert_eq!(B_TWICE, 10);
+    let a: [u8; <A as Size>::SIZE] = [0; 3];
+    let b: [u8; <B as Size>::SIZE] = [0; 5];
+    assert_eq!(a.len() + b.len(), 8);
+    match 5 {
+        <A as Size>::SIZE => panic!(),
+        <B as Size>::SIZE => {}
+        _ => panic!(),
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Array lengths are computed by the compile-time constant evaluator, so
+// these check that its floating-point arithmetic is correctly rounded.
+
+const ROUNDING: usize = ((0.1f64 + 0.2 - 0.3) * 1e17) as usize;
+const SUBNORMAL: usize = (4.9406564584124654e-324f64 * 0.5 == 0.0) as usize;
+const SINGLE: usize = ((16777216.0f32 + 1.0) - 16777216.0) as usize;
+const NAN: usize = (0.0f64 / 0.0 != 0.0 / 0.0) as usize;
+
+fn main() {
+    let rounding: [u8; ROUNDING] = [0; 5];
+    let subnormal: [u8; SUBNORMAL] = [0; 1];
+    let single: [u8; SINGLE] = [];
+    let nan: [u8; NAN] = [0; 1];
+    assert_eq!(rounding.len() + subnormal.len() + single.len() + nan.len(), 7);
+}
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(rustc_attrs)]
+#![feature(box_syntax, rustc_attrs)]
 
 use std::cell::{Cell, RefCell};
 use std::panic;
@@ -115,6 +115,76 @@ fn assignment1(a: &Allocator, c0: bool) {
     _v = _w;
 }
 
+#[rustc_mir]
+fn shared_cleanup(a: &Allocator, c: bool) {
+    let _x = a.alloc();
+    let _y = (a.alloc(), {
+        let _z = a.alloc();
+        if c { a.alloc() } else { a.alloc() }
+    }, a.alloc());
+    let _v = a.alloc();
+}
+
+#[rustc_mir]
+fn extended_temporary(a: &Allocator) {
+    let _x = a.alloc();
+    {
+        let _z = a.alloc();
+        let _r = &a.alloc();
+        a.alloc();
+        let _v = a.alloc();
+    }
+}
+
+#[rustc_mir]
+fn boxed(a: &Allocator) {
+    let _x = a.alloc();
+    let _b = box (a.alloc(), {
+        let _z = a.alloc();
+        a.alloc()
+    });
+    let _v = a.alloc();
+}
+
+#[rustc_mir]
+fn for_loop(a: &Allocator) {
+    let ptrs = vec![a.alloc(), a.alloc(), a.alloc()];
+    for p in ptrs {
+        let _q = a.alloc();
+        drop(p);
+    }
+}
+
+#[rustc_mir]
+fn for_loop_break(a: &Allocator) {
+    let ptrs = vec![a.alloc(), a.alloc(), a.alloc()];
+    for (i, _p) in ptrs.into_iter().enumerate() {
+        if i == 1 {
+            break;
+        }
+        let _q = a.alloc();
+    }
+}
+
+#[rustc_mir]
+fn for_loop_return(a: &Allocator) {
+    let ptrs = vec![a.alloc(), a.alloc(), a.alloc()];
+    for (i, _p) in ptrs.into_iter().enumerate() {
+        if i == 1 {
+            return;
+        }
+        let _q = a.alloc();
+    }
+}
+
+#[rustc_mir]
+fn while_let(a: &Allocator) {
+    let mut ptrs = vec![Err(a.alloc()), Ok(a.alloc()), Ok(a.alloc())];
+    while let Some(Ok(_p)) = ptrs.pop() {
+        let _q = a.alloc();
+    }
+}
+
 fn run_test<F>(mut f: F)
     where F: FnMut(&Allocator)
 {
@@ -153,4 +223,13 @@ fn main() {
 
     run_test(|a| assignment1(a, false));
     run_test(|a| assignment1(a, true));
+
+    run_test(|a| shared_cleanup(a, false));
+    run_test(|a| shared_cleanup(a, true));
+    run_test(|a| extended_temporary(a));
+    run_test(|a| boxed(a));
+    run_test(|a| for_loop(a));
+    run_test(|a| for_loop_break(a));
+    run_test(|a| for_loop_return(a));
+    run_test(|a| while_let(a));
 }
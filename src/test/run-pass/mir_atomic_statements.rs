@@ -0,0 +1,62 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the atomic intrinsics that MIR represents as statements
+// behave the same whether they are called on concrete integers, where
+// they are statements, or on generic ones, where they stay calls.
+
+#![feature(core_intrinsics)]
+
+use std::intrinsics::*;
+
+unsafe fn generic_xadd<T>(dst: *mut T, src: T) -> T {
+    atomic_xadd(dst, src)
+}
+
+unsafe fn generic_load<T>(src: *const T) -> T {
+    atomic_load_acq(src)
+}
+
+fn main() {
+    unsafe {
+        let mut x = 1u32;
+        assert_eq!(atomic_load(&x), 1);
+        assert_eq!(atomic_load_acq(&x), 1);
+        assert_eq!(atomic_load_relaxed(&x), 1);
+
+        atomic_store(&mut x, 2);
+        assert_eq!(x, 2);
+        atomic_store_rel(&mut x, 3);
+        assert_eq!(x, 3);
+        atomic_store_relaxed(&mut x, 4);
+        assert_eq!(x, 4);
+
+        assert_eq!(atomic_xadd(&mut x, 1), 4);
+        assert_eq!(atomic_xadd_acqrel(&mut x, 1), 5);
+        assert_eq!(atomic_xsub_rel(&mut x, 2), 6);
+        assert_eq!(atomic_xchg_acq(&mut x, 10), 4);
+        assert_eq!(atomic_and_relaxed(&mut x, 6), 10);
+        assert_eq!(atomic_or(&mut x, 1), 2);
+        assert_eq!(atomic_xor(&mut x, 7), 3);
+        assert_eq!(atomic_umax(&mut x, 9), 4);
+        assert_eq!(atomic_umin(&mut x, 5), 9);
+        assert_eq!(x, 5);
+
+        let mut y = -1isize;
+        assert_eq!(atomic_max(&mut y, -5), -1);
+        assert_eq!(atomic_min(&mut y, -5), -1);
+        assert_eq!(atomic_nand(&mut y, 0), -5);
+        assert_eq!(y, -1);
+
+        let mut z = 7usize;
+        assert_eq!(generic_xadd(&mut z, 3), 7);
+        assert_eq!(generic_load(&z), 10);
+    }
+}
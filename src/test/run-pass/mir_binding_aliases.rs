@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that by-reference bindings are allowed after an `@`, and that
+// `ref mut` bindings of disjoint fields of a value, behind a box, are not
+// reported to alias each other.
+
+#![feature(bindings_after_at, box_patterns)]
+
+fn at_bindings(x: &Option<(u32, u32)>) -> u32 {
+    match *x {
+        ref whole @ Some((ref a, _)) => whole.map_or(0, |(_, b)| b) + *a,
+        None => 0,
+    }
+}
+
+fn swap_fields(x: &mut Box<(u32, (u32, u32))>) {
+    match *x {
+        box (ref mut a, (_, ref mut b)) => ::std::mem::swap(a, b),
+    }
+}
+
+fn main() {
+    assert_eq!(at_bindings(&Some((1, 2))), 3);
+    assert_eq!(at_bindings(&None), 0);
+
+    let mut pair = Box::new((1, (2, 3)));
+    swap_fields(&mut pair);
+    assert_eq!(*pair, (3, (2, 1)));
+}
@@ -0,0 +1,57 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that calls assigned to a field of a variable see the old value
+// of the variable, whether they return into the field directly or, as
+// the variable is passed to them or borrowed, into a temporary.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Big {
+    a: [u64; 8],
+}
+
+#[derive(Copy, Clone)]
+struct Holder {
+    big: Big,
+    n: u64,
+}
+
+fn reversed(b: Big) -> Big {
+    Big { a: [b.a[7], b.a[6], b.a[5], b.a[4], b.a[3], b.a[2], b.a[1], b.a[0]] }
+}
+
+fn reversed_of(h: Holder) -> Big {
+    reversed(h.big)
+}
+
+fn sum(h: *const Holder) -> u64 {
+    unsafe { (*h).big.a.iter().fold((*h).n, |acc, x| acc + x) }
+}
+
+fn new_big() -> Big {
+    Big { a: [0, 1, 2, 3, 4, 5, 6, 7] }
+}
+
+fn main() {
+    let mut h = Holder { big: Big { a: [0; 8] }, n: 1 };
+
+    h.big = new_big();
+    assert_eq!(h.big, Big { a: [0, 1, 2, 3, 4, 5, 6, 7] });
+
+    h.big = reversed(h.big);
+    assert_eq!(h.big, Big { a: [7, 6, 5, 4, 3, 2, 1, 0] });
+
+    h.big = reversed_of(h);
+    assert_eq!(h.big, Big { a: [0, 1, 2, 3, 4, 5, 6, 7] });
+
+    let p = &h as *const Holder;
+    h.n = sum(p);
+    assert_eq!(h.n, 29);
+}
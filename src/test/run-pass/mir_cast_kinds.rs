@@ -0,0 +1,55 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that casts between pointers and integers, and truncating integer
+// casts, which have cast kinds of their own in MIR, behave like any
+// other cast, both at runtime and in constants.
+
+const NULL: *const u8 = 0 as *const u8;
+const TRUNCATED: u8 = 0x1234u16 as u8;
+const SIGNED: i8 = -0x101i32 as i8;
+
+fn addr<T>(ptr: *const T) -> usize {
+    ptr as usize
+}
+
+fn from_addr<T>(addr: usize) -> *const T {
+    addr as *const T
+}
+
+fn truncate(x: u64) -> (u32, u16, u8, i8) {
+    (x as u32, x as u16, x as u8, x as i8)
+}
+
+fn five() -> u32 { 5 }
+
+fn main() {
+    let x = 7u32;
+    let p = &x as *const u32;
+    let q: *const u32 = from_addr(addr(p));
+    assert_eq!(q, p);
+    assert_eq!(unsafe { *q }, 7);
+
+    assert!(NULL.is_null());
+    assert_eq!(addr(NULL), 0);
+    assert_eq!(TRUNCATED, 0x34);
+    assert_eq!(SIGNED, -1);
+
+    assert_eq!(truncate(0x0123_4567_abcd_ef01), (0xabcd_ef01, 0xef01, 0x01, 1));
+    assert_eq!(truncate(0xff), (0xff, 0xff, 0xff, -1));
+    assert_eq!(1000usize as u8, 232);
+    assert_eq!(-1isize as u8, 255);
+    assert_eq!(0x1_0000_0001u64 as usize as u8, 1);
+
+    let f = five as fn() -> u32;
+    let f_addr = f as usize;
+    assert!(f_addr != 0);
+    assert_eq!(f(), 5);
+}
@@ -0,0 +1,53 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that boxes made from null or misaligned raw pointers panic
+// before they are dereferenced with -Z check-box-from-raw, while those
+// made from valid pointers, or never dereferenced, do not.
+
+// compile-flags: -Z check-box-from-raw
+
+use std::mem;
+use std::panic;
+use std::ptr;
+
+fn from_raw(ptr: *mut u32) -> u32 {
+    let b = unsafe { Box::from_raw(ptr) };
+    let value = *b;
+    mem::forget(b);
+    value
+}
+
+fn transmuted(ptr: *mut u32) -> u32 {
+    let b: Box<u32> = unsafe { mem::transmute(ptr) };
+    let moved = b;
+    let value = *moved;
+    mem::forget(moved);
+    value
+}
+
+fn forgotten(ptr: *mut u32) {
+    mem::forget(unsafe { Box::from_raw(ptr) });
+}
+
+fn main() {
+    let valid = Box::into_raw(Box::new(7u32));
+    assert_eq!(from_raw(valid), 7);
+    assert_eq!(transmuted(valid), 7);
+    drop(unsafe { Box::from_raw(valid) });
+
+    let misaligned = (&[0u32; 2] as *const u32 as usize + 1) as *mut u32;
+    assert!(panic::catch_unwind(|| from_raw(ptr::null_mut())).is_err());
+    assert!(panic::catch_unwind(|| from_raw(misaligned)).is_err());
+    assert!(panic::catch_unwind(|| transmuted(ptr::null_mut())).is_err());
+    assert!(panic::catch_unwind(|| transmuted(misaligned)).is_err());
+
+    forgotten(ptr::null_mut());
+}
@@ -0,0 +1,64 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that `Fn` and `FnMut` closures called through `FnOnce::call_once`
+// run once and drop what they captured once, whether the call returns or
+// unwinds.
+
+use std::cell::Cell;
+use std::panic;
+
+thread_local!(static DROPS: Cell<u32> = Cell::new(0));
+
+fn take_drops() -> u32 {
+    DROPS.with(|drops| {
+        let value = drops.get();
+        drops.set(0);
+        value
+    })
+}
+
+struct Noisy(u32);
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        DROPS.with(|drops| drops.set(drops.get() + 1));
+    }
+}
+
+fn call_once<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+fn main() {
+    let noisy = Noisy(1);
+    let by_ref = move |x| x + noisy.0;
+    assert_eq!(call_once(by_ref, 2), 3);
+    assert_eq!(take_drops(), 1);
+
+    let mut noisy = Noisy(1);
+    let by_mut = move |x| {
+        noisy.0 += x;
+        noisy.0
+    };
+    assert_eq!(call_once(by_mut, 2), 3);
+    assert_eq!(take_drops(), 1);
+
+    let noisy = Noisy(1);
+    let panics = move |x| {
+        if x > noisy.0 {
+            panic!("too big");
+        }
+        x
+    };
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| call_once(panics, 2)));
+    assert!(result.is_err());
+    assert_eq!(take_drops(), 1);
+}
@@ -0,0 +1,73 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-deaggregate
+
+// Test that structs and enum variants built one field at a time, followed
+// by a `SetDiscriminant` for enums, have the same values as aggregates,
+// both at runtime and in constants.
+
+#![feature(const_fn)]
+
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Point(i32, i32),
+    Rect { w: u8, h: u16 },
+}
+
+#[derive(Debug, PartialEq)]
+enum Single {
+    Only(u64, bool),
+}
+
+#[derive(Debug, PartialEq)]
+struct Pair(u8, Shape);
+
+#[derive(Debug, PartialEq)]
+struct Named {
+    a: u32,
+    b: Single,
+}
+
+const EMPTY: Shape = Shape::Empty;
+const POINT: Shape = Shape::Point(-1, 2);
+const RECT: Shape = Shape::Rect { w: 3, h: 400 };
+const PAIR: Pair = Pair(5, Shape::Point(6, 7));
+static NAMED: Named = Named { a: 8, b: Single::Only(9, true) };
+
+const fn rect(w: u8, h: u16) -> Shape {
+    Shape::Rect { w: w, h: h }
+}
+
+const FROM_FN: Shape = rect(10, 11);
+
+fn point(x: i32, y: i32) -> Shape {
+    Shape::Point(x, y)
+}
+
+fn main() {
+    assert_eq!(EMPTY, Shape::Empty);
+    assert_eq!(POINT, point(-1, 2));
+    assert_eq!(RECT, Shape::Rect { w: 3, h: 400 });
+    assert_eq!(PAIR, Pair(5, point(6, 7)));
+    assert_eq!(NAMED, Named { a: 8, b: Single::Only(9, true) });
+    assert_eq!(FROM_FN, rect(10, 11));
+
+    let shapes = [Shape::Empty, point(1, 2), rect(3, 4)];
+    match shapes[1] {
+        Shape::Point(x, y) => assert_eq!((x, y), (1, 2)),
+        _ => panic!("expected a point"),
+    }
+    match shapes[2] {
+        Shape::Rect { w, h } => assert_eq!((w, h), (3, 4)),
+        _ => panic!("expected a rect"),
+    }
+}
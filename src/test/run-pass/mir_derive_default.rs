@@ -0,0 +1,102 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that derived `Default` impls, whose MIR is built directly rather
+// than from their expansion, default every field in order and drop the
+// fields built so far if one of them panics.
+
+use std::cell::Cell;
+use std::panic;
+
+thread_local!(static LOG: Cell<u32> = Cell::new(0));
+
+fn log(n: u32) {
+    LOG.with(|log| log.set(log.get() * 10 + n));
+}
+
+fn take_log() -> u32 {
+    LOG.with(|log| {
+        let value = log.get();
+        log.set(0);
+        value
+    })
+}
+
+struct Noisy(u32);
+
+impl Default for Noisy {
+    fn default() -> Noisy {
+        log(1);
+        Noisy(1)
+    }
+}
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        log(2);
+    }
+}
+
+struct Panics;
+
+impl Default for Panics {
+    fn default() -> Panics {
+        log(3);
+        panic!("default")
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Named {
+    a: u32,
+    b: String,
+    c: Option<u8>,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Tuple(i64, bool);
+
+#[derive(Default, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Default, Debug, PartialEq)]
+struct Generic<T> {
+    value: T,
+    values: Vec<T>,
+}
+
+#[derive(Default)]
+struct Ordered {
+    first: Noisy,
+    second: Noisy,
+}
+
+#[derive(Default)]
+struct Unwinds {
+    first: Noisy,
+    second: Noisy,
+    third: Panics,
+}
+
+fn main() {
+    assert_eq!(Named::default(), Named { a: 0, b: String::new(), c: None });
+    assert_eq!(Tuple::default(), Tuple(0, false));
+    assert_eq!(Unit::default(), Unit);
+    assert_eq!(Generic::<u8>::default(), Generic { value: 0, values: vec![] });
+
+    {
+        let ordered = Ordered::default();
+        assert_eq!(ordered.first.0 + ordered.second.0, 2);
+    }
+    assert_eq!(take_log(), 1122);
+
+    assert!(panic::catch_unwind(|| { Unwinds::default(); }).is_err());
+    assert_eq!(take_log(), 11322);
+}
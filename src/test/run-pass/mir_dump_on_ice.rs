@@ -0,0 +1,46 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z dump-mir-on-ice
+
+// Test that MIR built while watching for panics, to dump it on an ICE, is
+// the same as otherwise.
+
+#![feature(rustc_attrs)]
+
+const LIMIT: u32 = 10;
+
+#[rustc_mir_dump_on_ice]
+fn sum_below(n: u32) -> u32 {
+    let mut total = 0;
+    for i in 0..n {
+        if i == LIMIT {
+            break;
+        }
+        total += i;
+    }
+    total
+}
+
+fn describe(x: Option<&str>) -> String {
+    match x {
+        Some(s) => format!("some {}", s),
+        None => "none".to_string(),
+    }
+}
+
+fn main() {
+    assert_eq!(sum_below(5), 10);
+    assert_eq!(sum_below(20), 45);
+    assert_eq!(describe(Some("thing")), "some thing");
+    assert_eq!(describe(None), "none");
+    let add = |a: u32, b: u32| a + b;
+    assert_eq!(add(LIMIT, 1), 11);
+}
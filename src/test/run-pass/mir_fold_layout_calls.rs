@@ -0,0 +1,65 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the sizes and alignments MIR folds from the layout of concrete
+// types are those trans computes for generic ones, where the calls stay.
+
+#![feature(core_intrinsics)]
+
+use std::intrinsics;
+use std::mem;
+
+#[allow(dead_code)]
+struct Packed {
+    a: u8,
+    b: u32,
+    c: u16,
+}
+
+#[allow(dead_code)]
+enum Shape {
+    Circle(f64),
+    Square(u8),
+    Empty,
+}
+
+fn generic_layout<T>() -> (usize, usize, usize, usize) {
+    (mem::size_of::<T>(),
+     mem::align_of::<T>(),
+     unsafe { intrinsics::min_align_of::<T>() },
+     unsafe { intrinsics::pref_align_of::<T>() })
+}
+
+macro_rules! check_layout {
+    ($ty:ty) => {
+        assert_eq!((mem::size_of::<$ty>(),
+                    mem::align_of::<$ty>(),
+                    unsafe { intrinsics::min_align_of::<$ty>() },
+                    unsafe { intrinsics::pref_align_of::<$ty>() }),
+                   generic_layout::<$ty>());
+    }
+}
+
+fn main() {
+    check_layout!(());
+    check_layout!(u8);
+    check_layout!(u64);
+    check_layout!(f64);
+    check_layout!([u16; 7]);
+    check_layout!((u8, u32));
+    check_layout!(&str);
+    check_layout!(Box<[u8]>);
+    check_layout!(Option<Box<u8>>);
+    check_layout!(Packed);
+    check_layout!(Shape);
+    check_layout!(Option<Shape>);
+
+    assert_eq!(mem::size_of::<u32>(), 4);
+}
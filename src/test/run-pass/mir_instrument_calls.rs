@@ -0,0 +1,51 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-instrument-calls=trace_probe
+
+// Test that -Z mir-instrument-calls calls the probe function on entry to
+// every function, and when it returns or unwinds, but not in the probe
+// function itself.
+
+use std::panic;
+
+static mut EVENTS: [u32; 3] = [0; 3];
+static mut LAST_ID: u64 = 0;
+
+#[no_mangle]
+pub extern "C" fn trace_probe(function_id: u64, event: u32) {
+    unsafe {
+        if event < 3 {
+            EVENTS[event as usize] += 1;
+        }
+        LAST_ID = function_id;
+    }
+}
+
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+fn main() {
+    let (entries, returns, unwinds) = unsafe { (EVENTS[0], EVENTS[1], EVENTS[2]) };
+    assert_eq!((entries, returns, unwinds), (1, 0, 0));
+    let main_id = unsafe { LAST_ID };
+
+    assert_eq!(add(1, 2), 3);
+    let add_id = unsafe { LAST_ID };
+    assert!(add_id != main_id);
+    assert_eq!(unsafe { (EVENTS[0], EVENTS[1], EVENTS[2]) }, (2, 1, 0));
+
+    assert!(panic::catch_unwind(|| {
+        let _s = String::from("dropped while unwinding");
+        panic!("unwind")
+    }).is_err());
+    assert_eq!(unsafe { (EVENTS[0], EVENTS[1], EVENTS[2]) }, (3, 1, 1));
+}
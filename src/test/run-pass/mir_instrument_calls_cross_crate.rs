@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_instrumented_calls.rs
+
+// Test that the generic and inline functions of a crate built with
+// -Z mir-instrument-calls are translated without probes by a crate built
+// without it, while its other functions still call the probe.
+
+extern crate mir_instrumented_calls as upstream;
+
+fn main() {
+    // `events` reads the count between its own entry and return probes.
+    let before = upstream::events();
+    assert_eq!(upstream::double(2u32), 4);
+    assert_eq!(upstream::triple(2), 6);
+    assert_eq!(upstream::events(), before + 2);
+}
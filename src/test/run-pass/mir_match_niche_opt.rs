@@ -0,0 +1,108 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -O
+
+// Test that matches on enums whose variant is given by a non-null field,
+// which compare that field with zero, pick the right variants when
+// optimizing, where the comparison of a reference with zero must not be
+// folded away.
+
+#![feature(nonzero)]
+
+extern crate core;
+
+use core::nonzero::NonZero;
+
+enum First<'a> {
+    Ref(&'a u8),
+    Nothing,
+}
+
+#[inline(never)]
+fn simple(x: Option<&u8>) -> u8 {
+    match x {
+        Some(&v) => v,
+        None => 0,
+    }
+}
+
+#[inline(never)]
+fn first(x: First) -> u8 {
+    match x {
+        First::Ref(&v) => v,
+        First::Nothing => 0,
+    }
+}
+
+#[inline(never)]
+fn nested(x: Result<(u8, &u8), ()>) -> u8 {
+    match x {
+        Ok((a, &b)) => a + b,
+        Err(()) => 0,
+    }
+}
+
+#[inline(never)]
+fn function(x: Option<fn() -> u8>) -> u8 {
+    match x {
+        Some(f) => f(),
+        None => 0,
+    }
+}
+
+#[inline(never)]
+fn vector(x: Option<Vec<u8>>) -> usize {
+    match x {
+        Some(v) => v.len(),
+        None => 0,
+    }
+}
+
+#[inline(never)]
+fn boxed(x: Option<Box<u8>>) -> u8 {
+    match x {
+        Some(b) => *b,
+        None => 0,
+    }
+}
+
+#[inline(never)]
+fn non_zero(x: Option<NonZero<u32>>) -> u32 {
+    match x {
+        Some(n) => *n,
+        None => 0,
+    }
+}
+
+fn three() -> u8 {
+    3
+}
+
+fn main() {
+    let one = 1;
+    let two = 2;
+
+    assert_eq!(simple(Some(&one)), 1);
+    assert_eq!(simple(None), 0);
+    assert_eq!(first(First::Ref(&two)), 2);
+    assert_eq!(first(First::Nothing), 0);
+    assert_eq!(nested(Ok((1, &two))), 3);
+    assert_eq!(nested(Err(())), 0);
+    assert_eq!(function(Some(three)), 3);
+    assert_eq!(function(None), 0);
+    assert_eq!(vector(Some(vec![1, 2, 3, 4])), 4);
+    assert_eq!(vector(Some(vec![])), 0);
+    assert_eq!(vector(None), 0);
+    assert_eq!(boxed(Some(Box::new(5))), 5);
+    assert_eq!(boxed(None), 0);
+    assert_eq!(non_zero(Some(unsafe { NonZero::new(6) })), 6);
+    assert_eq!(non_zero(None), 0);
+}
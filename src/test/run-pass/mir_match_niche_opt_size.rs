@@ -0,0 +1,48 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C opt-level=s
+
+// Test that matches on enums whose variant is given by a non-null field
+// pick the right variants when optimizing for size, where they switch on
+// the discriminant instead of comparing that field with zero.
+
+fn simple(x: Option<&u8>) -> u8 {
+    match x {
+        Some(&v) => v,
+        None => 0,
+    }
+}
+
+fn nested(x: Result<(u8, &u8), ()>) -> u8 {
+    match x {
+        Ok((a, &b)) => a + b,
+        Err(()) => 0,
+    }
+}
+
+fn vector(x: Option<Vec<u8>>) -> usize {
+    match x {
+        Some(v) => v.len(),
+        None => 0,
+    }
+}
+
+fn main() {
+    let one = 1;
+    let two = 2;
+
+    assert_eq!(simple(Some(&one)), 1);
+    assert_eq!(simple(None), 0);
+    assert_eq!(nested(Ok((1, &two))), 3);
+    assert_eq!(nested(Err(())), 0);
+    assert_eq!(vector(Some(vec![1, 2, 3, 4])), 4);
+    assert_eq!(vector(None), 0);
+}
@@ -0,0 +1,56 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that dereferencing a misaligned raw pointer panics with
+// debug assertions, while aligned and byte pointers do not, and neither
+// does only taking the address of what a misaligned pointer points to.
+
+// compile-flags: -C debug-assertions
+
+use std::panic;
+
+fn read(ptr: *const u32) -> u32 {
+    unsafe { *ptr }
+}
+
+fn write(ptr: *mut u32, value: u32) {
+    unsafe { *ptr = value; }
+}
+
+struct Pair {
+    _first: u32,
+    second: u32,
+}
+
+fn address(ptr: *const u32) -> usize {
+    unsafe { &*ptr as *const u32 as usize }
+}
+
+fn field_address(ptr: *const Pair) -> usize {
+    unsafe { &(*ptr).second as *const u32 as usize }
+}
+
+fn main() {
+    let mut buf = [0u32; 2];
+    let aligned = buf.as_mut_ptr();
+    let misaligned = unsafe { (aligned as *mut u8).offset(1) as *mut u32 };
+
+    write(aligned, 0x01020304);
+    assert_eq!(read(aligned), 0x01020304);
+    let second_byte = if cfg!(target_endian = "little") { 0x03 } else { 0x02 };
+    assert_eq!(unsafe { *(misaligned as *const u8) }, second_byte);
+
+    assert_eq!(address(misaligned), misaligned as usize);
+    assert_eq!(field_address(misaligned as *const Pair), misaligned as usize + 4);
+
+    assert!(panic::catch_unwind(|| read(misaligned)).is_err());
+    assert!(panic::catch_unwind(|| write(misaligned, 0)).is_err());
+    assert_eq!(buf[0], 0x01020304);
+}
@@ -0,0 +1,57 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests that raw pointers to lvalues, which are taken without going
+// through a reference, point to the lvalue itself.
+
+use std::mem;
+use std::ptr;
+
+#[repr(packed)]
+struct Packed {
+    a: u8,
+    b: u32,
+}
+
+fn read_b(p: &Packed) -> u32 {
+    let b = &p.b as *const u32 as *const u8;
+    let mut bytes = [0u8; 4];
+    unsafe {
+        ptr::copy_nonoverlapping(b, bytes.as_mut_ptr(), 4);
+        mem::transmute(bytes)
+    }
+}
+
+fn write_b(p: &mut Packed, value: u32) {
+    let b: *mut u32 = &mut p.b;
+    let bytes: [u8; 4] = unsafe { mem::transmute(value) };
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), b as *mut u8, 4);
+    }
+}
+
+fn main() {
+    let mut p = Packed { a: 1, b: 0 };
+    write_b(&mut p, 0x12345678);
+    assert_eq!(read_b(&p), 0x12345678);
+    assert_eq!(p.a, 1);
+
+    let base = &p as *const Packed as usize;
+    assert_eq!(&p.b as *const u32 as usize, base + 1);
+
+    let array = [1u8, 2, 3];
+    let slice = &array as *const [u8];
+    assert_eq!(unsafe { (*slice).len() }, 3);
+    assert_eq!(slice as *const u8, &array[0] as *const u8);
+
+    // Rvalues still have their address taken through a reference.
+    let five = &5 as *const i32;
+    assert_eq!(unsafe { *five }, 5);
+}
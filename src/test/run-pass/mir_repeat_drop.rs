@@ -0,0 +1,44 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that an element that is not `Copy`, which can only be repeated
+// zero times or once, is dropped exactly once either way, and that
+// arrays of repeated constants have the right contents.
+
+use std::cell::Cell;
+
+struct Noisy<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Noisy<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    {
+        let _none: [Noisy; 0] = [Noisy(&drops); 0];
+        assert_eq!(drops.get(), 1);
+    }
+    assert_eq!(drops.get(), 1);
+    {
+        let _one: [Noisy; 1] = [Noisy(&drops); 1];
+        assert_eq!(drops.get(), 1);
+    }
+    assert_eq!(drops.get(), 2);
+
+    assert!([0u8; 4096].iter().all(|&b| b == 0));
+    assert!([!0u32; 64].iter().all(|&x| x == !0));
+    assert!([0x1234u16; 64].iter().all(|&x| x == 0x1234));
+    assert!([-1i64; 8].iter().all(|&x| x == -1));
+    assert!([0.0f64; 16].iter().all(|&x| x == 0.0));
+    assert!([true; 3].iter().all(|&x| x));
+}
@@ -0,0 +1,78 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z short-scrutinee-temps
+
+// Test that with -Z short-scrutinee-temps the temporaries of a match
+// scrutinee are dropped when an arm is entered if the arms cannot borrow
+// from them, and at the end of the statement otherwise.
+
+use std::cell::Cell;
+
+thread_local!(static LOG: Cell<u32> = Cell::new(0));
+
+fn log(n: u32) {
+    LOG.with(|log| log.set(log.get() * 10 + n));
+}
+
+fn take_log() -> u32 {
+    LOG.with(|log| {
+        let value = log.get();
+        log.set(0);
+        value
+    })
+}
+
+struct Noisy(u32);
+
+impl Noisy {
+    fn value(&self) -> u32 {
+        self.0
+    }
+
+    fn get(&self) -> &u32 {
+        &self.0
+    }
+}
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        log(9);
+    }
+}
+
+fn main() {
+    match Noisy(1).value() {
+        1 => log(1),
+        _ => log(2),
+    }
+    assert_eq!(take_log(), 91);
+
+    match Noisy(2).value() {
+        n if n > 5 => log(n),
+        n => log(n + 1),
+    }
+    assert_eq!(take_log(), 93);
+
+    match Noisy(4) {
+        Noisy(n) => log(n),
+    }
+    assert_eq!(take_log(), 94);
+
+    match Noisy(5).value() {
+        ref n => log(*n),
+    }
+    assert_eq!(take_log(), 59);
+
+    match Noisy(6).get() {
+        n => log(*n),
+    }
+    assert_eq!(take_log(), 69);
+}
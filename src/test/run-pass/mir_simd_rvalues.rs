@@ -0,0 +1,63 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the SIMD intrinsics that MIR represents as rvalues behave
+// the same whether they are called on concrete vectors, where they are
+// rvalues, or on generic ones, where they stay calls.
+
+#![feature(repr_simd, platform_intrinsics)]
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[allow(non_camel_case_types)]
+struct f32x4(f32, f32, f32, f32);
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[allow(non_camel_case_types)]
+struct f32x2(f32, f32);
+
+extern "platform-intrinsic" {
+    fn simd_insert<T, E>(x: T, idx: u32, y: E) -> T;
+    fn simd_extract<T, E>(x: T, idx: u32) -> E;
+    fn simd_shuffle2<T, U>(x: T, y: T, idx: [u32; 2]) -> U;
+    fn simd_shuffle4<T, U>(x: T, y: T, idx: [u32; 4]) -> U;
+}
+
+const REVERSE: [u32; 4] = [3, 2, 1, 0];
+
+fn concrete(x: f32x4, y: f32x4, i: u32) -> (f32, f32x4, f32x4, f32x2) {
+    unsafe {
+        (simd_extract(x, i),
+         simd_insert(x, i, 10.0),
+         simd_shuffle4(x, y, REVERSE),
+         simd_shuffle2(x, y, [1, 6]))
+    }
+}
+
+fn generic<T: Copy, E>(x: T, y: T, i: u32, e: E) -> (E, T, T) {
+    unsafe {
+        (simd_extract(x, i),
+         simd_insert(x, i, e),
+         simd_shuffle4(x, y, REVERSE))
+    }
+}
+
+fn main() {
+    let x = f32x4(0.0, 1.0, 2.0, 3.0);
+    let y = f32x4(4.0, 5.0, 6.0, 7.0);
+
+    let (element, inserted, reversed, picked) = concrete(x, y, 2);
+    assert_eq!(element, 2.0);
+    assert_eq!(inserted, f32x4(0.0, 1.0, 10.0, 3.0));
+    assert_eq!(reversed, f32x4(3.0, 2.0, 1.0, 0.0));
+    assert_eq!(picked, f32x2(1.0, 6.0));
+
+    assert_eq!(generic(x, y, 2, 10.0f32), (element, inserted, reversed));
+}
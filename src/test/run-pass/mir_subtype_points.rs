@@ -0,0 +1,61 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that values coerced by subtyping when they are written to the
+// return pointer, variables and their fields build and run, as MIR
+// records where the coercions happen.
+
+struct Holder<'a> {
+    r: &'a u32,
+    f: fn(&'static u32) -> u32,
+}
+
+static FORTY_TWO: u32 = 42;
+
+const STATIC_REF: &'static u32 = &FORTY_TWO;
+
+fn read(r: &u32) -> u32 {
+    *r
+}
+
+fn forty_two() -> &'static u32 {
+    &FORTY_TWO
+}
+
+fn shorten<'a>(r: &'static u32) -> &'a u32 {
+    r
+}
+
+fn shorten_call<'a>() -> &'a u32 {
+    forty_two()
+}
+
+fn holder<'a>(local: &'a u32) -> Holder<'a> {
+    let mut h = Holder { r: local, f: read };
+    h.r = forty_two();
+    h.r = STATIC_REF;
+    h
+}
+
+fn main() {
+    let local = 7;
+    let mut r: &u32 = &local;
+    assert_eq!(*r, 7);
+    r = shorten(&FORTY_TWO);
+    assert_eq!(*r, 42);
+    r = shorten_call();
+    assert_eq!(*r, 42);
+
+    let h = holder(&local);
+    assert_eq!((h.f)(h.r), 42);
+
+    let f: fn(&'static u32) -> u32 = read;
+    assert_eq!(f(STATIC_REF), 42);
+}
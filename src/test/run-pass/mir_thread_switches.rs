@@ -0,0 +1,82 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that state machines written as `loop { match state { ... } }` run
+// the same when the arms jump straight to the next state, including when
+// the state is changed again after it is set.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum State {
+    Start,
+    Count(u32),
+    Skip,
+    Done(u32),
+}
+
+fn run_enum(limit: u32) -> (u32, Vec<State>) {
+    let mut trace = vec![];
+    let mut state = State::Start;
+    loop {
+        trace.push(state);
+        match state {
+            State::Start => state = State::Count(0),
+            State::Count(n) if n >= limit => state = State::Done(n),
+            State::Count(n) if n % 2 == 1 => state = State::Skip,
+            State::Count(n) => state = State::Count(n + 1),
+            State::Skip => {
+                state = State::Count(0);
+                skip(&mut state, limit);
+            }
+            State::Done(n) => return (n, trace),
+        }
+    }
+}
+
+fn skip(state: &mut State, limit: u32) {
+    *state = State::Count(limit);
+}
+
+fn run_int(steps: u32) -> u32 {
+    let mut state = 0u8;
+    let mut visits = 0;
+    loop {
+        visits += 1;
+        match state {
+            0 => state = 1,
+            1 => state = if visits < steps { 2 } else { 3 },
+            2 => state = 1,
+            _ => return visits,
+        }
+    }
+}
+
+fn run_bool(steps: u32) -> u32 {
+    let mut even = true;
+    let mut flips = 0;
+    while flips < steps {
+        if even {
+            even = false;
+        } else {
+            even = true;
+        }
+        flips += 1;
+    }
+    if even { flips } else { flips + 100 }
+}
+
+fn main() {
+    assert_eq!(run_enum(0), (0, vec![State::Start, State::Count(0), State::Done(0)]));
+    assert_eq!(run_enum(3), (3, vec![State::Start, State::Count(0), State::Count(1),
+                                     State::Skip, State::Count(3), State::Done(3)]));
+    assert_eq!(run_int(1), 3);
+    assert_eq!(run_int(6), 7);
+    assert_eq!(run_bool(3), 103);
+    assert_eq!(run_bool(4), 4);
+}
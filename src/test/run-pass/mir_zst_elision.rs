@@ -0,0 +1,87 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that values of zero-sized types, whose assignments MIR construction
+// leaves out, can still be used, and that those needing drop are dropped
+// exactly once.
+
+#![feature(const_fn)]
+
+use std::marker::PhantomData;
+
+static mut DROPS: u32 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Empty;
+
+struct Dropped;
+
+impl Drop for Dropped {
+    fn drop(&mut self) {
+        unsafe { DROPS += 1; }
+    }
+}
+
+const UNIT: () = ();
+const fn empty() -> Empty { Empty }
+static EMPTY: Empty = Empty;
+
+fn pass<T>(t: T) -> T {
+    t
+}
+
+fn units() -> ((), Empty, [u32; 0]) {
+    let a = ();
+    let b = Empty;
+    let c: [u32; 0] = [];
+    let d = a;
+    let _e = if b == Empty { d } else { () };
+    (pass(a), pass(b), pass(c))
+}
+
+fn phantom() -> PhantomData<u32> {
+    let p = PhantomData;
+    let q = p;
+    pass(q)
+}
+
+fn dropped(keep: bool) -> Option<Dropped> {
+    let d = Dropped;
+    let e = d;
+    if keep { Some(e) } else { None }
+}
+
+fn main() {
+    let (unit, empty_val, array) = units();
+    assert_eq!(unit, ());
+    assert_eq!(empty_val, Empty);
+    assert_eq!(array.len(), 0);
+    assert_eq!(phantom(), PhantomData);
+
+    let f = || Empty;
+    assert_eq!(pass(f)(), Empty);
+
+    assert_eq!(UNIT, ());
+    assert_eq!(empty(), Empty);
+    assert_eq!(EMPTY, Empty);
+
+    let kept = dropped(true);
+    assert_eq!(unsafe { DROPS }, 0);
+    drop(kept);
+    assert_eq!(unsafe { DROPS }, 1);
+    dropped(false);
+    assert_eq!(unsafe { DROPS }, 2);
+    {
+        let _d = Dropped;
+        let _ = Dropped;
+        assert_eq!(unsafe { DROPS }, 3);
+    }
+    assert_eq!(unsafe { DROPS }, 4);
+}
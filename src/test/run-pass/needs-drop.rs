@@ -0,0 +1,56 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that types need to be dropped when something in them has a
+// destructor, unless their layout holds no value, and that asking about
+// a type parameter gives the answer for the type it stands for.
+
+#![feature(core_intrinsics)]
+
+use std::cell::Cell;
+use std::intrinsics::needs_drop;
+
+enum Void {}
+
+struct Noisy<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Noisy<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn generic<T>() -> bool {
+    unsafe { needs_drop::<T>() }
+}
+
+fn main() {
+    unsafe {
+        assert!(needs_drop::<String>());
+        assert!(needs_drop::<(u32, String)>());
+        assert!(!needs_drop::<u32>());
+        assert!(!needs_drop::<&String>());
+        assert!(needs_drop::<[String; 1]>());
+        assert!(!needs_drop::<[String; 0]>());
+        assert!(!needs_drop::<Void>());
+    }
+    assert!(generic::<String>());
+    assert!(!generic::<u32>());
+    assert!(!generic::<[String; 0]>());
+
+    // Nothing is dropped for an empty array, but its elements are dropped
+    // otherwise.
+    let drops = Cell::new(0);
+    {
+        let _empty: [Noisy; 0] = [];
+        let _one = [Noisy(&drops)];
+    }
+    assert_eq!(drops.get(), 1);
+}
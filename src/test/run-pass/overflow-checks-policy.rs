@@ -0,0 +1,28 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that with -Z overflow-checks-policy=caller, the generic functions
+// of a crate built without overflow checks have them when they are
+// instantiated in a crate with overflow checks, while its other
+// functions do not.
+
+// aux-build:overflow_checks_policy_lib.rs
+// compile-flags: -C debug-assertions
+
+extern crate overflow_checks_policy_lib as lib;
+
+use std::panic;
+use std::usize;
+
+fn main() {
+    assert_eq!(lib::add(200, 100), 44);
+    assert_eq!(lib::len_plus(&[1, 2], 3), 5);
+    assert!(panic::catch_unwind(|| lib::len_plus(&[1, 2], usize::MAX)).is_err());
+}